@@ -0,0 +1,112 @@
+
+// Generates the constants of the `glyphs` module from the data files of the `glyphs` directory and records the commit
+// the crate is built from for the `build_info` module
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+
+const GLYPHS_DIR: &str = "glyphs";
+
+fn parse_number(value: &str) -> Option<usize> {
+    match value.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn firmware_module(firmware: &str, data: &str) -> String {
+    let mut description = None;
+    let mut glyphs = vec![];
+    let mut counts = vec![];
+    for (line_index, line) in data.lines().enumerate() {
+        if let Some(comment) = line.strip_prefix('#') {
+            description.get_or_insert_with(|| comment.trim().to_owned());
+            continue;
+        }
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [name, value] if name.chars().all(|char| char.is_ascii_uppercase() || char.is_ascii_digit() || char == '_') =>
+                match parse_number(value) {
+                    Some(value) if name.ends_with("_COUNT") => counts.push((name.to_string(), value)),
+                    Some(value) => glyphs.push((name.to_string(), value)),
+                    None => panic!("{GLYPHS_DIR}/{firmware}.txt line {}: invalid number `{value}`", line_index + 1),
+                },
+            _ => panic!("{GLYPHS_DIR}/{firmware}.txt line {}: expected `<NAME> <index>`", line_index + 1),
+        }
+    }
+
+    // the counts are not glyphs, they give the ranges of glyphs with the `<NAME>_START` index of the same name
+    let ranges: Vec<(&str, &str)> = counts.iter().map(|(count_name, _)| {
+        let range_name = count_name.strip_suffix("_COUNT").unwrap();
+        match glyphs.iter().any(|(name, _)| *name == format!("{range_name}_START")) {
+            true => (range_name, count_name.as_str()),
+            false => panic!("{GLYPHS_DIR}/{firmware}.txt: `{count_name}` has no matching `{range_name}_START` index"),
+        }
+    }).collect();
+
+    let mut module = String::new();
+    writeln!(module, "pub mod {firmware} {{").unwrap();
+    if let Some(description) = description {
+        writeln!(module, "    //! {description}").unwrap();
+    }
+    for (name, value) in &glyphs {
+        writeln!(module, "    pub const {name}: usize = {value:#04X};").unwrap();
+    }
+    for ((range_name, count_name), (_, count)) in ranges.iter().zip(&counts) {
+        writeln!(module, "    /// Number of glyphs of the `{range_name}` range starting at `{range_name}_START`").unwrap();
+        writeln!(module, "    pub const {count_name}: usize = {count};").unwrap();
+    }
+    writeln!(module, "    /// Every glyph of the firmware with its name, in the order of the data file").unwrap();
+    writeln!(module, "    pub const ALL: &[(&str, usize)] = &[").unwrap();
+    for (name, _) in &glyphs {
+        writeln!(module, "        (\"{name}\", {name}),").unwrap();
+    }
+    writeln!(module, "    ];").unwrap();
+    writeln!(module, "    /// Ranges of glyphs given by a `<NAME>_START` index and a `<NAME>_COUNT` with their name").unwrap();
+    writeln!(module, "    pub const RANGES: &[(&str, core::ops::Range<usize>)] = &[").unwrap();
+    for (range_name, count_name) in &ranges {
+        writeln!(module, "        (\"{range_name}\", {range_name}_START..{range_name}_START + {count_name}),").unwrap();
+    }
+    writeln!(module, "    ];\n}}").unwrap();
+    module
+}
+
+// only when building from a git checkout with git installed
+fn record_git_hash() {
+    if ! Path::new(".git/HEAD").exists() {
+        return;
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    match Command::new("git").args(["rev-parse", "--short", "HEAD"]).output() {
+        Ok(output) if output.status.success() =>
+            println!("cargo:rustc-env=HD_FPV_OSD_FONT_TOOL_GIT_HASH={}", String::from_utf8_lossy(&output.stdout).trim()),
+        _ => {},
+    }
+}
+
+fn main() {
+    record_git_hash();
+    println!("cargo:rerun-if-changed={GLYPHS_DIR}");
+    let mut data_files: Vec<_> = fs::read_dir(GLYPHS_DIR).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect();
+    data_files.sort();
+
+    let mut code = String::new();
+    for path in data_files {
+        let firmware = path.file_stem().unwrap().to_string_lossy().into_owned();
+        println!("cargo:rerun-if-changed={}", path.to_string_lossy());
+        code.push_str(&firmware_module(&firmware, &fs::read_to_string(&path).unwrap()));
+    }
+    fs::write(Path::new(&env::var("OUT_DIR").unwrap()).join("glyphs.rs"), code).unwrap();
+}
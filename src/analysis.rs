@@ -0,0 +1,9 @@
+
+pub mod compare;
+pub mod coverage;
+pub mod dust;
+pub mod index_offset;
+pub mod inventory;
+pub mod kind_guess;
+pub mod palette;
+pub mod similarity;
\ No newline at end of file
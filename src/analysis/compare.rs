@@ -0,0 +1,114 @@
+
+//! Tile by tile comparison of two collections
+//!
+//! The exact mode reports tiles whose pixels are not identical, the fuzzy mode ignores channel differences up to a
+//! tolerance like the ones left by lossy re-encoding or color quantization. A tile missing from one of the collections
+//! is compared to an empty tile.
+
+use image::{GenericImage, ImageBuffer, Rgba};
+
+use crate::osd::tile::{Tile, container::uniq_tile_kind::{TileKindError, UniqTileKind}};
+
+
+pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+const SIDE_BY_SIDE_GAP: u32 = 2;
+const DIFFERENCE_COLOR: Rgba<u8> = Rgba([255, 0, 255, 255]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareMode {
+    Exact,
+    /// pixels whose channels all differ by at most `tolerance` are considered the same
+    Fuzzy { tolerance: u8 },
+}
+
+impl CompareMode {
+
+    fn tolerance(&self) -> u8 {
+        match self {
+            Self::Exact => 0,
+            Self::Fuzzy { tolerance } => *tolerance,
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileDifference {
+    pub index: usize,
+    pub differing_pixels: usize,
+    /// largest difference of a channel of the differing pixels
+    pub max_channel_delta: u8,
+}
+
+fn channel_delta(first: &Rgba<u8>, second: &Rgba<u8>) -> u8 {
+    first.0.iter().zip(second.0.iter()).map(|(first, second)| first.abs_diff(*second)).max().unwrap()
+}
+
+/// Compares two tiles of the same kind, `None` if they are the same according to the mode
+pub fn compare_tile(index: usize, first: &Tile, second: &Tile, mode: CompareMode) -> Option<TileDifference> {
+    let tolerance = mode.tolerance();
+    let mut differing_pixels = 0;
+    let mut max_channel_delta = 0;
+    for (first_pixel, second_pixel) in first.pixels().zip(second.pixels()) {
+        let delta = channel_delta(first_pixel, second_pixel);
+        if delta > tolerance {
+            differing_pixels += 1;
+            max_channel_delta = max_channel_delta.max(delta);
+        }
+    }
+    (differing_pixels > 0).then_some(TileDifference { index, differing_pixels, max_channel_delta })
+}
+
+/// Differing tiles of two collections of the same tile kind, ordered by index
+pub fn compare(first: &[Tile], second: &[Tile], mode: CompareMode) -> Result<Vec<TileDifference>, TileKindError> {
+    let tile_kind = [first, second].concat().tile_kind()?;
+    let empty_tile = Tile::new(tile_kind);
+    let differences = (0..first.len().max(second.len()))
+        .filter_map(|index| compare_tile(index, first.get(index).unwrap_or(&empty_tile), second.get(index).unwrap_or(&empty_tile), mode))
+        .collect();
+    Ok(differences)
+}
+
+/// Image of the first tile, the second tile and the pixels which differ highlighted in magenta, side by side
+pub fn side_by_side_image(first: &Tile, second: &Tile, mode: CompareMode) -> Image {
+    let (width, height) = first.dimensions();
+    let mut image = Image::new(3 * width + 2 * SIDE_BY_SIDE_GAP, height);
+    image.copy_from(first.image(), 0, 0).unwrap();
+    image.copy_from(second.image(), width + SIDE_BY_SIDE_GAP, 0).unwrap();
+    let tolerance = mode.tolerance();
+    for (x, y, first_pixel) in first.enumerate_pixels() {
+        if channel_delta(first_pixel, second.get_pixel(x, y)) > tolerance {
+            image.put_pixel(2 * (width + SIDE_BY_SIDE_GAP) + x, y, DIFFERENCE_COLOR);
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{compare, side_by_side_image, CompareMode};
+
+    #[test]
+    fn compare_collections() {
+        let first = vec![Tile::new(TileKind::HD); 3];
+        let mut second = first.clone();
+        second[0].put_pixel(1, 1, Rgba([0, 0, 0, 4]));
+        second[2].put_pixel(2, 2, Rgba([255, 255, 255, 255]));
+        second.push(Tile::new(TileKind::HD));
+
+        let differences = compare(&first, &second, CompareMode::Exact).unwrap();
+        assert_eq!(differences.iter().map(|difference| difference.index).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(differences[1].max_channel_delta, 255);
+        assert_eq!(compare(&first, &second, CompareMode::Fuzzy { tolerance: 8 }).unwrap().len(), 1);
+        assert!(compare(&first, &[Tile::new(TileKind::SD)], CompareMode::Exact).is_err());
+
+        let image = side_by_side_image(&first[2], &second[2], CompareMode::Exact);
+        assert_eq!(image.dimensions(), (3 * 24 + 4, 36));
+        assert_eq!(*image.get_pixel(2 * 26 + 2, 2), Rgba([255, 0, 255, 255]));
+    }
+}
@@ -0,0 +1,58 @@
+
+use crate::osd::tile::Tile;
+
+
+/// Differences between the populated slots of a collection and the ones of the reference it is derived from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// indices of the tiles populated in the reference which are empty or missing in the collection
+    pub missing: Vec<usize>,
+    /// indices of the tiles populated in the collection which are empty or missing in the reference
+    pub added: Vec<usize>,
+}
+
+impl Coverage {
+    pub fn has_regressions(&self) -> bool {
+        ! self.missing.is_empty()
+    }
+}
+
+fn is_populated(tiles: &[Tile], index: usize) -> bool {
+    tiles.get(index).is_some_and(|tile| ! tile.is_empty())
+}
+
+pub fn compare_coverage(reference: &[Tile], tiles: &[Tile]) -> Coverage {
+    let mut coverage = Coverage::default();
+    for index in 0..reference.len().max(tiles.len()) {
+        match (is_populated(reference, index), is_populated(tiles, index)) {
+            (true, false) => coverage.missing.push(index),
+            (false, true) => coverage.added.push(index),
+            _ => {},
+        }
+    }
+    coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::compare_coverage;
+
+    #[test]
+    fn missing_and_added_tiles() {
+        let mut reference = vec![Tile::new(TileKind::SD); 4];
+        reference[1].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        reference[3].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let mut tiles = vec![Tile::new(TileKind::SD); 6];
+        tiles[1].put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        tiles[5].put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+
+        let coverage = compare_coverage(&reference, &tiles);
+        assert_eq!(coverage.missing, vec![3]);
+        assert_eq!(coverage.added, vec![5]);
+        assert!(coverage.has_regressions());
+    }
+}
@@ -0,0 +1,95 @@
+
+use image::Rgba;
+
+use crate::osd::tile::Tile;
+
+
+/// Group of stray opaque pixels found in a tile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dust {
+    pub tile_index: usize,
+    /// coordinates of the pixels of the group
+    pub pixels: Vec<(u32, u32)>,
+}
+
+// groups of 8-connected pixels which are not fully transparent
+fn opaque_pixel_groups(tile: &Tile) -> Vec<Vec<(u32, u32)>> {
+    let (width, height) = tile.dimensions();
+    let mut visited = vec![false; (width * height) as usize];
+    let mut groups = vec![];
+    for (x, y, pixel) in tile.enumerate_pixels() {
+        if pixel.0[3] == 0 || visited[(y * width + x) as usize] {
+            continue;
+        }
+        visited[(y * width + x) as usize] = true;
+        let mut group = vec![];
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            group.push((x, y));
+            for neighbour_y in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                for neighbour_x in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                    let index = (neighbour_y * width + neighbour_x) as usize;
+                    if ! visited[index] && tile.get_pixel(neighbour_x, neighbour_y).0[3] != 0 {
+                        visited[index] = true;
+                        stack.push((neighbour_x, neighbour_y));
+                    }
+                }
+            }
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+/// Finds the groups of at most `max_size` opaque pixels touching the border of their tile
+///
+/// These are typically left by slicing a grid image taken from a screenshot with the neighbouring tiles bleeding over
+/// the edges. Small groups away from the border are kept since they can be legitimate parts of a glyph like a dot.
+pub fn find_dust(tiles: &[Tile], max_size: usize) -> Vec<Dust> {
+    let mut dust = vec![];
+    for (tile_index, tile) in tiles.iter().enumerate() {
+        let (width, height) = tile.dimensions();
+        let touches_border = |(x, y): &(u32, u32)| *x == 0 || *y == 0 || *x == width - 1 || *y == height - 1;
+        for pixels in opaque_pixel_groups(tile) {
+            if pixels.len() <= max_size && pixels.iter().any(touches_border) {
+                dust.push(Dust { tile_index, pixels });
+            }
+        }
+    }
+    dust
+}
+
+/// Makes the dust found by [`find_dust`] fully transparent and returns what has been removed
+pub fn remove_dust(tiles: &mut [Tile], max_size: usize) -> Vec<Dust> {
+    let dust = find_dust(tiles, max_size);
+    for Dust { tile_index, pixels } in &dust {
+        for (x, y) in pixels {
+            tiles[*tile_index].put_pixel(*x, *y, Rgba([0, 0, 0, 0]));
+        }
+    }
+    dust
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::remove_dust;
+
+    #[test]
+    fn border_pixels_are_removed() {
+        let mut tile = Tile::new(TileKind::SD);
+        let opaque = Rgba([255, 255, 255, 255]);
+        tile.put_pixel(0, 0, opaque);
+        tile.put_pixel(6, 6, opaque);
+        let mut tiles = vec![tile];
+
+        let dust = remove_dust(&mut tiles, 2);
+        assert_eq!(dust.len(), 1);
+        assert_eq!(dust[0].pixels, vec![(0, 0)]);
+        assert_eq!(tiles[0].get_pixel(0, 0).0[3], 0);
+        assert_eq!(*tiles[0].get_pixel(6, 6), opaque);
+    }
+}
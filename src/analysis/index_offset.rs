@@ -0,0 +1,103 @@
+
+use super::similarity::PerceptualHash;
+
+use crate::osd::tile::Tile;
+
+
+/// Uniform shift of the indices of a collection relative to a reference: the tile at `index` in the reference is at
+/// `index + offset` in the collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexOffset {
+    pub offset: isize,
+    /// number of non-empty reference tiles looking like the tile at the shifted index
+    pub matching_tiles: usize,
+    /// number of non-empty reference tiles having a tile at the shifted index
+    pub compared_tiles: usize,
+}
+
+fn shifted_index(index: usize, offset: isize) -> Option<usize> {
+    let shifted = index as isize + offset;
+    if shifted < 0 { None } else { Some(shifted as usize) }
+}
+
+fn score(reference: &[(usize, PerceptualHash)], hashes: &[Option<PerceptualHash>], offset: isize, max_distance: u32) -> IndexOffset {
+    let mut index_offset = IndexOffset { offset, matching_tiles: 0, compared_tiles: 0 };
+    for (index, reference_hash) in reference {
+        if let Some(Some(hash)) = shifted_index(*index, offset).and_then(|shifted| hashes.get(shifted)) {
+            index_offset.compared_tiles += 1;
+            if reference_hash.distance(hash) <= max_distance {
+                index_offset.matching_tiles += 1;
+            }
+        }
+    }
+    index_offset
+}
+
+/// Finds the offset in `-max_offset..=max_offset` for which the most tiles of the collection look like the reference
+/// ones, ties are won by the smallest offsets
+pub fn detect_index_offset(reference: &[Tile], tiles: &[Tile], max_offset: usize, max_distance: u32) -> IndexOffset {
+    let reference_hashes: Vec<(usize, PerceptualHash)> = reference.iter().enumerate()
+        .filter(|(_, tile)| ! tile.is_empty())
+        .map(|(index, tile)| (index, PerceptualHash::of(tile)))
+        .collect();
+    let hashes: Vec<Option<PerceptualHash>> = tiles.iter()
+        .map(|tile| if tile.is_empty() { None } else { Some(PerceptualHash::of(tile)) })
+        .collect();
+    let max_offset = max_offset as isize;
+    let mut offsets: Vec<isize> = (-max_offset..=max_offset).collect();
+    offsets.sort_by_key(|offset| offset.abs());
+    offsets.into_iter()
+        .map(|offset| score(&reference_hashes, &hashes, offset, max_distance))
+        .fold(None, |best: Option<IndexOffset>, candidate| match best {
+            Some(best) if best.matching_tiles >= candidate.matching_tiles => Some(best),
+            _ => Some(candidate),
+        })
+        .unwrap()
+}
+
+/// Moves the tiles back to the indices of the reference, the tiles shifted out are dropped and the freed slots are empty
+pub fn undo_index_offset(tiles: &[Tile], offset: isize) -> Vec<Tile> {
+    (0..tiles.len())
+        .map(|index| match shifted_index(index, offset).and_then(|shifted| tiles.get(shifted)) {
+            Some(tile) => tile.clone(),
+            None => Tile::new(tiles[index].kind()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{detect_index_offset, undo_index_offset};
+
+    // vertical stripes drawn from the bits of `seed + 1` so that every glyph looks different
+    fn glyph(seed: u32) -> Tile {
+        let mut tile = Tile::new(TileKind::SD);
+        for y in 0..54 {
+            for x in 0..35 {
+                if (seed + 1) & (1 << (x / 7)) != 0 {
+                    tile.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                }
+            }
+        }
+        tile
+    }
+
+    #[test]
+    fn detect_and_undo() {
+        let reference: Vec<Tile> = (0..20).map(glyph).collect();
+        let mut tiles = vec![Tile::new(TileKind::SD); 3];
+        tiles.extend(reference[..17].iter().cloned());
+
+        let index_offset = detect_index_offset(&reference, &tiles, 8, 0);
+        assert_eq!(index_offset.offset, 3);
+        assert_eq!(index_offset.matching_tiles, index_offset.compared_tiles);
+
+        let repaired = undo_index_offset(&tiles, index_offset.offset);
+        assert!(repaired[..17].iter().zip(&reference).all(|(repaired, tile)| repaired.as_raw() == tile.as_raw()));
+        assert!(repaired[17].is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+
+//! Overview of the content of a collection: kind and number of tiles, which tiles are empty and a checksum of each tile
+//!
+//! The trailing empty tiles are the empty tiles following the last non-empty one, they are usually padding added to
+//! reach the tile count of a font profile and can be dropped with `convert --repage`.
+
+use serde::{Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::osd::tile::{Kind as TileKind, Tile, container::symbol::Symbol};
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TileInfo {
+    pub index: usize,
+    pub empty: bool,
+    /// SHA-256 of the raw RGBA pixels of the tile
+    pub sha256: String,
+}
+
+/// Tiles of a symbol of a symbol directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SymbolSpan {
+    pub start_tile_index: usize,
+    pub span: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Inventory {
+    /// none for a collection without tiles
+    #[serde(serialize_with = "serialize_tile_kind")]
+    pub tile_kind: Option<TileKind>,
+    pub tile_count: usize,
+    pub non_empty_tile_count: usize,
+    pub trailing_empty_tile_count: usize,
+    /// only known for symbol directories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbols: Option<Vec<SymbolSpan>>,
+    pub tiles: Vec<TileInfo>,
+}
+
+fn serialize_tile_kind<S: Serializer>(tile_kind: &Option<TileKind>, serializer: S) -> Result<S::Ok, S::Error> {
+    match tile_kind {
+        Some(tile_kind) => serializer.collect_str(tile_kind),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn tile_sha256(tile: &Tile) -> String {
+    Sha256::digest(tile.as_raw()).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+impl Inventory {
+
+    pub fn of_tiles(tiles: &[Tile]) -> Self {
+        let tiles_info: Vec<TileInfo> = tiles.iter().enumerate()
+            .map(|(index, tile)| TileInfo { index, empty: tile.is_empty(), sha256: tile_sha256(tile) })
+            .collect();
+        let non_empty_tile_count = tiles_info.iter().filter(|tile_info| ! tile_info.empty).count();
+        let trailing_empty_tile_count = tiles_info.iter().rev().take_while(|tile_info| tile_info.empty).count();
+        Self {
+            tile_kind: tiles.first().map(Tile::kind),
+            tile_count: tiles.len(),
+            non_empty_tile_count,
+            trailing_empty_tile_count,
+            symbols: None,
+            tiles: tiles_info,
+        }
+    }
+
+    pub fn of_symbols(symbols: &[Symbol]) -> Self {
+        let mut start_tile_index = 0;
+        let spans = symbols.iter().map(|symbol| {
+            let span = SymbolSpan { start_tile_index, span: symbol.span() };
+            start_tile_index += symbol.span();
+            span
+        }).collect();
+        let tiles: Vec<Tile> = symbols.iter().flat_map(|symbol| symbol.tiles().iter().cloned()).collect();
+        Self { symbols: Some(spans), ..Self::of_tiles(&tiles) }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile, container::symbol::Symbol};
+
+    use super::{Inventory, SymbolSpan};
+
+    #[test]
+    fn inventory() {
+        let mut tiles = vec![Tile::new(TileKind::SD); 5];
+        tiles[1].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        tiles[2].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+
+        let inventory = Inventory::of_tiles(&tiles);
+        assert_eq!(inventory.tile_kind, Some(TileKind::SD));
+        assert_eq!((inventory.tile_count, inventory.non_empty_tile_count, inventory.trailing_empty_tile_count), (5, 2, 2));
+        assert_eq!(inventory.tiles[1].sha256, inventory.tiles[2].sha256);
+        assert_ne!(inventory.tiles[0].sha256, inventory.tiles[1].sha256);
+        assert!(inventory.to_json().unwrap().contains("\"tile_kind\": \"SD\""));
+
+        let symbols = vec![Symbol::new(TileKind::SD), Symbol::try_from(tiles[1..3].to_vec()).unwrap()];
+        let inventory = Inventory::of_symbols(&symbols);
+        assert_eq!(inventory.symbols, Some(vec![SymbolSpan { start_tile_index: 0, span: 1 }, SymbolSpan { start_tile_index: 1, span: 2 }]));
+        assert_eq!(inventory.trailing_empty_tile_count, 0);
+    }
+}
@@ -0,0 +1,104 @@
+
+//! Guessing the kind of the tiles of raw RGBA data of unknown shape
+//!
+//! The size of raw data only identifies the tile kind when it is a whole number of tiles of a single kind: truncated
+//! dumps and collections of some tile counts, e.g. 4 SD tiles and 9 HD tiles, have sizes which are multiples of the
+//! tile sizes of several kinds. Each kind whose tiles fit in the data is a candidate, scored by how much the pixels
+//! change from one row to the next: decoding rows with the wrong width shears the glyphs so that consecutive rows stop
+//! matching.
+
+use std::cmp::Ordering;
+
+use strum::IntoEnumIterator;
+
+use crate::osd::tile::Kind as TileKind;
+
+
+/// How well raw RGBA data decodes as tiles of a kind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KindGuess {
+    pub tile_kind: TileKind,
+    pub tile_count: usize,
+    /// bytes left once the whole tiles are decoded, 0 for plausible data
+    pub trailing_bytes: usize,
+    /// mean absolute difference of the channels of vertically adjacent pixels within the non-empty tiles, the lower the
+    /// more likely
+    pub discontinuity: f64,
+}
+
+fn discontinuity(bytes: &[u8], tile_kind: TileKind) -> f64 {
+    let row_bytes = tile_kind.dimensions().width as usize * 4;
+    let mut difference_sum = 0u64;
+    let mut compared_count = 0u64;
+    for tile in bytes.chunks_exact(tile_kind.raw_rgba_size_bytes()) {
+        if tile.iter().all(|byte| *byte == 0) {
+            continue;
+        }
+        for (row, next_row) in tile.chunks_exact(row_bytes).zip(tile.chunks_exact(row_bytes).skip(1)) {
+            difference_sum += row.iter().zip(next_row).map(|(channel, next_channel)| channel.abs_diff(*next_channel) as u64).sum::<u64>();
+            compared_count += row_bytes as u64;
+        }
+    }
+    match compared_count {
+        0 => 0.0,
+        compared_count => difference_sum as f64 / compared_count as f64,
+    }
+}
+
+/// Scores each tile kind at least one tile of which fits in the data, most likely first
+///
+/// Kinds decoding the data as a whole number of tiles always come before the others.
+pub fn rank_kinds(bytes: &[u8]) -> Vec<KindGuess> {
+    let mut guesses: Vec<KindGuess> = TileKind::iter()
+        .filter(|tile_kind| bytes.len() >= tile_kind.raw_rgba_size_bytes())
+        .map(|tile_kind| KindGuess {
+            tile_kind,
+            tile_count: bytes.len() / tile_kind.raw_rgba_size_bytes(),
+            trailing_bytes: bytes.len() % tile_kind.raw_rgba_size_bytes(),
+            discontinuity: discontinuity(bytes, tile_kind),
+        })
+        .collect();
+    guesses.sort_by(|guess, other| (guess.trailing_bytes != 0).cmp(&(other.trailing_bytes != 0))
+        .then(guess.discontinuity.partial_cmp(&other.discontinuity).unwrap_or(Ordering::Equal)));
+    guesses
+}
+
+/// Most likely kind of the tiles of raw RGBA data, `None` if the data is smaller than a tile of any kind
+pub fn guess_kind(bytes: &[u8]) -> Option<KindGuess> {
+    rank_kinds(bytes).into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::Kind as TileKind;
+
+    use super::{guess_kind, rank_kinds};
+
+    // tiles of vertical stripes, perfectly continuous from one row to the next
+    fn striped_tiles(tile_kind: TileKind, tile_count: usize) -> Vec<u8> {
+        let dimensions = tile_kind.dimensions();
+        (0..tile_count * (dimensions.width * dimensions.height) as usize)
+            .flat_map(|pixel| match pixel % dimensions.width as usize % 7 < 3 {
+                true => [255, 255, 255, 255],
+                false => [0, 0, 0, 255],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ambiguous_sizes() {
+        // 4 SD tiles have the size of 9 HD tiles
+        for (tile_kind, tile_count) in [(TileKind::SD, 4), (TileKind::HD, 9)] {
+            let guesses = rank_kinds(&striped_tiles(tile_kind, tile_count));
+            assert_eq!(guesses.len(), 2);
+            assert!(guesses.iter().all(|guess| guess.trailing_bytes == 0));
+            assert_eq!((guesses[0].tile_kind, guesses[0].tile_count), (tile_kind, tile_count));
+            assert_eq!(guesses[0].discontinuity, 0.0);
+        }
+
+        let mut bytes = striped_tiles(TileKind::HD, 2);
+        bytes.extend([0; 4]);
+        assert_eq!(guess_kind(&bytes).unwrap().trailing_bytes, 4);
+        assert!(guess_kind(&bytes[..100]).is_none());
+    }
+}
@@ -0,0 +1,148 @@
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use image::Rgba;
+use thiserror::Error;
+
+use crate::background::parse_color;
+use crate::osd::tile::Tile;
+
+
+#[derive(Debug, Error)]
+pub enum LoadPaletteError {
+    #[error("failed to read palette file: {0}")]
+    ReadError(std::io::Error),
+    #[error("invalid color `{color}` on line {line} of palette file {file_path}: expected `#RRGGBB` or `#RRGGBBAA`")]
+    InvalidColor { file_path: PathBuf, line: usize, color: String },
+}
+
+#[derive(Debug, Error)]
+#[error("tile {tile_index} pixel ({x}, {y}) has color {color} which is not part of the palette")]
+pub struct PaletteViolationError {
+    pub tile_index: usize,
+    pub x: u32,
+    pub y: u32,
+    pub color: Color,
+}
+
+/// RGBA color displayed as `#RRGGBBAA`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color(pub Rgba<u8>);
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [red, green, blue, alpha] = self.0.0;
+        write!(f, "#{red:02X}{green:02X}{blue:02X}{alpha:02X}")
+    }
+}
+
+impl FromStr for Color {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_color(s).map(Self).ok_or(())
+    }
+}
+
+/// What to do with pixels whose color is not part of the palette, fully transparent pixels are always accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PaletteMode {
+    /// fail on the first pixel not matching the palette
+    Error,
+    /// replace the color of the pixel by the nearest color of the palette
+    Remap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette(Vec<Color>);
+
+impl Palette {
+
+    /// Loads a palette file: one color per line, empty lines and lines starting with `#` followed by a space are ignored
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadPaletteError> {
+        let content = fs_err::read_to_string(&path).map_err(LoadPaletteError::ReadError)?;
+        let mut colors = vec![];
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("# ") || line == "#" {
+                continue;
+            }
+            let color = line.split_whitespace().next().unwrap();
+            colors.push(color.parse().map_err(|_| LoadPaletteError::InvalidColor {
+                file_path: path.as_ref().to_path_buf(),
+                line: line_index + 1,
+                color: color.to_owned(),
+            })?);
+        }
+        Ok(Self(colors))
+    }
+
+    pub fn colors(&self) -> &[Color] {
+        &self.0
+    }
+
+    fn nearest(&self, color: Rgba<u8>) -> Option<Color> {
+        let distance = |other: &Color| color.0.iter().zip(other.0.0.iter())
+            .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+            .sum::<i32>();
+        self.0.iter().copied().min_by_key(distance)
+    }
+
+    /// Checks or remaps the pixels of the tiles, returns the number of remapped pixels
+    pub fn enforce(&self, tiles: &mut [Tile], mode: PaletteMode) -> Result<usize, PaletteViolationError> {
+        let mut remapped_count = 0;
+        for (tile_index, tile) in tiles.iter_mut().enumerate() {
+            for (x, y, pixel) in tile.enumerate_pixels_mut() {
+                if pixel.0[3] == 0 || self.0.contains(&Color(*pixel)) {
+                    continue;
+                }
+                match (mode, self.nearest(*pixel)) {
+                    (PaletteMode::Remap, Some(nearest)) => {
+                        *pixel = nearest.0;
+                        remapped_count += 1;
+                    },
+                    _ => return Err(PaletteViolationError { tile_index, x, y, color: Color(*pixel) }),
+                }
+            }
+        }
+        Ok(remapped_count)
+    }
+
+}
+
+/// Colors used by the non-transparent pixels of the tiles and their number of pixels, most used first
+pub fn extract_colors(tiles: &[Tile]) -> Vec<(Color, usize)> {
+    let mut counts: HashMap<Color, usize> = HashMap::new();
+    for pixel in tiles.iter().flat_map(|tile| tile.pixels()) {
+        if pixel.0[3] != 0 {
+            *counts.entry(Color(*pixel)).or_default() += 1;
+        }
+    }
+    let mut colors: Vec<(Color, usize)> = counts.into_iter().collect();
+    colors.sort_by(|(color_a, count_a), (color_b, count_b)| count_b.cmp(count_a).then(color_a.0.0.cmp(&color_b.0.0)));
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{Color, Palette, PaletteMode, extract_colors};
+
+    #[test]
+    fn remap() {
+        let mut tiles = vec![Tile::new(TileKind::SD)];
+        tiles[0].put_pixel(0, 0, Rgba([250, 250, 250, 255]));
+        tiles[0].put_pixel(1, 0, Rgba([0, 0, 0, 255]));
+        let palette = Palette(vec!["#FFFFFF".parse().unwrap(), "#000000FF".parse().unwrap()]);
+        assert!(palette.enforce(&mut tiles.clone(), PaletteMode::Error).is_err());
+        assert_eq!(palette.enforce(&mut tiles, PaletteMode::Remap).unwrap(), 1);
+        assert_eq!(extract_colors(&tiles), vec![(Color(Rgba([0, 0, 0, 255])), 1), (Color(Rgba([255, 255, 255, 255])), 1)]);
+    }
+}
@@ -0,0 +1,179 @@
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use image::{codecs::png::PngEncoder, ColorType, ImageEncoder, ImageError};
+
+use crate::osd::tile::Tile;
+
+
+const HASH_GRID_SIZE: u32 = 8;
+
+/// Average hash of a tile: 64 bits telling which cells of an 8x8 grid are brighter than the tile average
+///
+/// Pixels are weighted by their alpha so that what is compared is what is visible on the goggles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PerceptualHash(u64);
+
+impl PerceptualHash {
+
+    pub fn of(tile: &Tile) -> Self {
+        let (width, height) = tile.dimensions();
+        let mut cells = [0u64; (HASH_GRID_SIZE * HASH_GRID_SIZE) as usize];
+        for cell_y in 0..HASH_GRID_SIZE {
+            for cell_x in 0..HASH_GRID_SIZE {
+                let x_range = cell_x * width / HASH_GRID_SIZE..(cell_x + 1) * width / HASH_GRID_SIZE;
+                let y_range = cell_y * height / HASH_GRID_SIZE..(cell_y + 1) * height / HASH_GRID_SIZE;
+                let pixel_count = (x_range.len() * y_range.len()) as u64;
+                let mut sum = 0u64;
+                for y in y_range {
+                    for x in x_range.clone() {
+                        let [red, green, blue, alpha] = tile.get_pixel(x, y).0;
+                        let luma = 299 * red as u64 + 587 * green as u64 + 114 * blue as u64;
+                        sum += luma * alpha as u64 / 255;
+                    }
+                }
+                cells[(cell_y * HASH_GRID_SIZE + cell_x) as usize] = sum / pixel_count;
+            }
+        }
+        let average = cells.iter().sum::<u64>() / cells.len() as u64;
+        Self(cells.iter().enumerate().fold(0, |hash, (index, cell)| if *cell > average { hash | 1 << index } else { hash }))
+    }
+
+    /// Number of differing bits, 0 meaning the tiles look the same
+    pub fn distance(&self, other: &Self) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+
+}
+
+/// Group of tiles which look alike
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster {
+    pub tile_indices: Vec<usize>,
+    /// largest hash distance between two tiles of the cluster
+    pub max_distance: u32,
+    /// all the tiles of the cluster are pixel for pixel the same
+    pub identical: bool,
+}
+
+fn find_root(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[index] = root;
+    root
+}
+
+/// Groups the non-empty tiles whose hashes are at most `max_distance` apart, tiles looking unique are not reported
+pub fn find_clusters(tiles: &[Tile], max_distance: u32) -> Vec<Cluster> {
+    let hashes: Vec<(usize, PerceptualHash)> = tiles.iter().enumerate()
+        .filter(|(_, tile)| ! tile.is_empty())
+        .map(|(index, tile)| (index, PerceptualHash::of(tile)))
+        .collect();
+
+    let mut parents: Vec<usize> = (0..tiles.len()).collect();
+    for (position, (index, hash)) in hashes.iter().enumerate() {
+        for (other_index, other_hash) in &hashes[position + 1..] {
+            if tiles[*index].kind() == tiles[*other_index].kind() && hash.distance(other_hash) <= max_distance {
+                let root = find_root(&mut parents, *index);
+                let other_root = find_root(&mut parents, *other_index);
+                parents[other_root.max(root)] = other_root.min(root);
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<(usize, PerceptualHash)>> = vec![];
+    let mut cluster_positions = HashMap::new();
+    for (index, hash) in &hashes {
+        let root = find_root(&mut parents, *index);
+        let position = *cluster_positions.entry(root).or_insert_with(|| { clusters.push(vec![]); clusters.len() - 1 });
+        clusters[position].push((*index, *hash));
+    }
+
+    clusters.into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let max_distance = members.iter()
+                .flat_map(|(_, hash)| members.iter().map(move |(_, other_hash)| hash.distance(other_hash)))
+                .max().unwrap_or(0);
+            let first_tile = &tiles[members[0].0];
+            let identical = members.iter().all(|(index, _)| tiles[*index].as_raw() == first_tile.as_raw());
+            Cluster { tile_indices: members.into_iter().map(|(index, _)| index).collect(), max_distance, identical }
+        })
+        .collect()
+}
+
+fn tile_data_uri(tile: &Tile) -> Result<String, ImageError> {
+    let mut png = vec![];
+    PngEncoder::new(&mut png).write_image(tile.as_raw(), tile.width(), tile.height(), ColorType::Rgba8)?;
+    Ok(format!("data:image/png;base64,{}", BASE64.encode(&png)))
+}
+
+/// Self-contained HTML page showing the tiles of each cluster side by side
+pub fn html_report(tiles: &[Tile], clusters: &[Cluster]) -> Result<String, ImageError> {
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Tile similarity report</title>\n<style>\n",
+        "body { font-family: sans-serif; background: #202020; color: #e0e0e0; }\n",
+        ".cluster { margin: 1em 0; padding: 0.5em; border: 1px solid #505050; }\n",
+        ".near { border-color: #d08020; }\n",
+        "figure { display: inline-block; margin: 0.5em; text-align: center; }\n",
+        "img { image-rendering: pixelated; height: 108px; background: #606060; }\n",
+        "</style>\n</head>\n<body>\n<h1>Tile similarity report</h1>\n",
+    ));
+    writeln!(html, "<p>{} tiles, {} clusters of similar tiles</p>", tiles.len(), clusters.len()).unwrap();
+    for cluster in clusters {
+        let (class, description) = if cluster.identical {
+            ("cluster", "identical tiles".to_owned())
+        } else {
+            ("cluster near", format!("near-duplicates, distance up to {}", cluster.max_distance))
+        };
+        writeln!(html, "<div class=\"{class}\">\n<h2>{description}</h2>").unwrap();
+        for index in &cluster.tile_indices {
+            writeln!(html, "<figure><img src=\"{}\"><figcaption>{index}</figcaption></figure>", tile_data_uri(&tiles[*index])?).unwrap();
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{find_clusters, tile_data_uri};
+
+    fn tile_with_block(x_start: u32, x_end: u32) -> Tile {
+        let mut tile = Tile::new(TileKind::HD);
+        for y in 5..30 {
+            for x in x_start..x_end {
+                tile.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+            }
+        }
+        tile
+    }
+
+    #[test]
+    fn clusters() {
+        let mut near_duplicate = tile_with_block(4, 12);
+        near_duplicate.put_pixel(4, 5, Rgba([0, 0, 0, 255]));
+        let tiles = vec![tile_with_block(4, 12), Tile::new(TileKind::HD), tile_with_block(14, 22), tile_with_block(4, 12), near_duplicate, Tile::new(TileKind::HD)];
+        let clusters = find_clusters(&tiles, 0);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].tile_indices, vec![0, 3, 4]);
+        assert!(! clusters[0].identical);
+    }
+
+    #[test]
+    fn data_uri() {
+        let uri = tile_data_uri(&tile_with_block(4, 12)).unwrap();
+        let png = BASE64.decode(uri.strip_prefix("data:image/png;base64,").unwrap()).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
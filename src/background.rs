@@ -0,0 +1,111 @@
+
+use std::str::FromStr;
+
+use image::{ImageBuffer, Rgba};
+use thiserror::Error;
+
+
+pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+const DEFAULT_CHECKERBOARD_SQUARE_SIZE: u32 = 6;
+const CHECKERBOARD_COLORS: [Rgba<u8>; 2] = [Rgba([0x60, 0x60, 0x60, 255]), Rgba([0x90, 0x90, 0x90, 255])];
+
+#[derive(Debug, Error)]
+#[error("invalid background `{0}`: expected a color like `#202020` or `checkerboard[:square size]`")]
+pub struct InvalidBackgroundError(String);
+
+/// Opaque background composited under images meant to be looked at so that transparent pixels are visible
+///
+/// Compositing is only applied to the generated image and never to the tiles themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Color(Rgba<u8>),
+    Checkerboard { square_size: u32 },
+}
+
+impl Background {
+
+    fn pixel_at(&self, x: u32, y: u32) -> Rgba<u8> {
+        match self {
+            Background::Color(color) => *color,
+            Background::Checkerboard { square_size } => CHECKERBOARD_COLORS[((x / square_size + y / square_size) % 2) as usize],
+        }
+    }
+
+    /// Returns an opaque copy of the image blended over the background
+    pub fn composite(&self, image: &Image) -> Image {
+        let mut output = Image::new(image.width(), image.height());
+        for (x, y, pixel) in image.enumerate_pixels() {
+            output.put_pixel(x, y, blend(*pixel, self.pixel_at(x, y)));
+        }
+        output
+    }
+
+}
+
+fn blend(foreground: Rgba<u8>, background: Rgba<u8>) -> Rgba<u8> {
+    let alpha = foreground.0[3] as u32;
+    let channel = |index: usize| ((foreground.0[index] as u32 * alpha + background.0[index] as u32 * (255 - alpha) + 127) / 255) as u8;
+    Rgba([channel(0), channel(1), channel(2), 255])
+}
+
+/// `#RRGGBB` or `#RRGGBBAA` color, the `#` is optional and the color is opaque when the alpha is not given
+pub(crate) fn parse_color(s: &str) -> Option<Rgba<u8>> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if ! (hex.len() == 6 || hex.len() == 8) || ! hex.chars().all(|char| char.is_ascii_hexdigit()) {
+        return None;
+    }
+    let component = |index: usize| hex.get(index * 2..index * 2 + 2).map_or(255, |hex| u8::from_str_radix(hex, 16).unwrap());
+    Some(Rgba([component(0), component(1), component(2), component(3)]))
+}
+
+/// Color parsed by [`parse_color`] which has to be opaque
+pub(crate) fn parse_opaque_color(s: &str) -> Option<Rgba<u8>> {
+    parse_color(s).filter(|color| color.0[3] == 255)
+}
+
+impl FromStr for Background {
+    type Err = InvalidBackgroundError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidBackgroundError(s.to_owned());
+        if s == "checkerboard" {
+            return Ok(Self::Checkerboard { square_size: DEFAULT_CHECKERBOARD_SQUARE_SIZE });
+        }
+        if let Some(square_size) = s.strip_prefix("checkerboard:") {
+            let square_size = square_size.parse().map_err(|_| invalid())?;
+            if square_size == 0 {
+                return Err(invalid());
+            }
+            return Ok(Self::Checkerboard { square_size });
+        }
+        parse_opaque_color(s).map(Self::Color).ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::{Background, Image};
+
+    #[test]
+    fn parse() {
+        assert_eq!("#202020".parse::<Background>().unwrap(), Background::Color(Rgba([0x20, 0x20, 0x20, 255])));
+        assert_eq!("a0B1c2".parse::<Background>().unwrap(), Background::Color(Rgba([0xA0, 0xB1, 0xC2, 255])));
+        assert_eq!("checkerboard:4".parse::<Background>().unwrap(), Background::Checkerboard { square_size: 4 });
+        assert_eq!("#202020FF".parse::<Background>().unwrap(), Background::Color(Rgba([0x20, 0x20, 0x20, 255])));
+        assert!("#20202080".parse::<Background>().is_err());
+        assert!("#2020".parse::<Background>().is_err());
+        assert!("checkerboard:0".parse::<Background>().is_err());
+    }
+
+    #[test]
+    fn composite() {
+        let mut image = Image::new(2, 1);
+        image.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+        let composited = Background::Color(Rgba([0x20, 0x20, 0x20, 255])).composite(&image);
+        assert_eq!(*composited.get_pixel(0, 0), Rgba([0x20, 0x20, 0x20, 255]));
+        assert_eq!(*composited.get_pixel(1, 0), Rgba([255, 255, 255, 255]));
+    }
+}
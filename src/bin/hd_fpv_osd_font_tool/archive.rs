@@ -0,0 +1,149 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::conversion_report::{Artifact, ConversionReport};
+use hd_fpv_osd_font_tool::osd::archive::{self, ArchiveWriter, ExtractedArchive};
+use thiserror::Error;
+
+
+const PREFIX: &str = "zip:";
+
+/// Content of the archive a `convert` or `convert-set` argument refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Content {
+    Collection,
+    Set,
+}
+
+#[derive(Debug, Error)]
+pub enum InvalidArchiveArgError {
+    #[error("the archive path must have the `.{}` extension", archive::EXTENSION)]
+    InvalidExtension,
+    #[error("the collection in the archive must be given: zip:<archive>.zip:<collection specification>")]
+    MissingSpec,
+    #[error("specifications in archives must be `<prefix>:<relative path>`: {0}")]
+    InvalidSpec(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ConvertArchiveError {
+    #[error("invalid `from` argument: {0}")]
+    FromArg(InvalidArchiveArgError),
+    #[error("invalid `to` argument: {0}")]
+    ToArg(InvalidArchiveArgError),
+}
+
+// `zip:<archive>.zip[:<specification>]`, the archive path ends at the extension so that it can contain `:`
+struct ArchiveArg<'a> {
+    archive_path: &'a str,
+    spec: Option<&'a str>,
+}
+
+pub(crate) fn is_archive_arg(input: &str) -> bool {
+    input.starts_with(PREFIX)
+}
+
+fn identify_archive_arg(input: &str) -> Result<Option<ArchiveArg<'_>>, InvalidArchiveArgError> {
+    let arg = match input.strip_prefix(PREFIX) {
+        Some(arg) => arg,
+        None => return Ok(None),
+    };
+    let extension = format!(".{}", archive::EXTENSION);
+    if arg.ends_with(&extension) {
+        return Ok(Some(ArchiveArg { archive_path: arg, spec: None }));
+    }
+    match arg.find(&format!("{extension}:")) {
+        Some(index) => {
+            let (archive_path, spec) = arg.split_at(index + extension.len());
+            Ok(Some(ArchiveArg { archive_path, spec: Some(&spec[1..]) }))
+        },
+        None => Err(InvalidArchiveArgError::InvalidExtension),
+    }
+}
+
+// specifications starting with a single relative path are rooted in the directory the archive content is in
+fn rooted_spec(spec: &str, dir: &Path) -> Result<String, InvalidArchiveArgError> {
+    let (prefix, path) = spec.split_once(':').ok_or_else(|| InvalidArchiveArgError::InvalidSpec(spec.to_owned()))?;
+    if prefix.contains('{') || Path::new(path).is_absolute() {
+        return Err(InvalidArchiveArgError::InvalidSpec(spec.to_owned()));
+    }
+    Ok(format!("{prefix}:{}", dir.join(path).to_string_lossy()))
+}
+
+// sets default to the normalized bin files, or to the normalized grids for sources without bin files
+fn content_spec(arg: &ArchiveArg, dir: &Path, content: Content, is_source: bool) -> Result<String, InvalidArchiveArgError> {
+    let spec = match (arg.spec, content) {
+        (Some(spec), _) => spec,
+        (None, Content::Collection) => return Err(InvalidArchiveArgError::MissingSpec),
+        (None, Content::Set) if is_source && ! archive::contains_bin_files_norm(dir, &None) => "tilesetgridsnorm:.",
+        (None, Content::Set) => "djibinsetnorm:.",
+    };
+    rooted_spec(spec, dir)
+}
+
+/// Runs `load` with the specification of the content of the archive `from` refers to once extracted
+pub(crate) fn with_extracted_source<T, F>(from: &str, content: Content, load: F) -> anyhow::Result<T>
+where
+    F: FnOnce(&str) -> anyhow::Result<T>,
+{
+    match identify_archive_arg(from).map_err(ConvertArchiveError::FromArg)? {
+        Some(arg) => {
+            let extracted = ExtractedArchive::extract(arg.archive_path)?;
+            load(&content_spec(&arg, &extracted.path(), content, true).map_err(ConvertArchiveError::FromArg)?)
+        },
+        None => load(from),
+    }
+}
+
+/// Runs a conversion of which the source and/or the destination are archives: the source archive is extracted and the
+/// destination archive written from the outputs once the conversion succeeded
+pub(crate) fn convert_with_archives<F>(from: &str, to: &str, content: Content, convert: F) -> anyhow::Result<ConversionReport>
+where
+    F: FnOnce(&str, &str) -> anyhow::Result<ConversionReport>,
+{
+    let from_arg = identify_archive_arg(from).map_err(ConvertArchiveError::FromArg)?;
+    let to_arg = identify_archive_arg(to).map_err(ConvertArchiveError::ToArg)?;
+
+    let extracted = from_arg.as_ref().map(|arg| ExtractedArchive::extract(arg.archive_path)).transpose()?;
+    let content_from = match (&from_arg, &extracted) {
+        (Some(arg), Some(extracted)) => content_spec(arg, &extracted.path(), content, true).map_err(ConvertArchiveError::FromArg)?,
+        _ => from.to_owned(),
+    };
+    let writer = to_arg.as_ref().map(|arg| ArchiveWriter::create(arg.archive_path)).transpose()?;
+    let content_to = match (&to_arg, &writer) {
+        (Some(arg), Some(writer)) => content_spec(arg, &writer.path(), content, false).map_err(ConvertArchiveError::ToArg)?,
+        _ => to.to_owned(),
+    };
+
+    let mut report = convert(&content_from, &content_to)?;
+    report.source = from.to_owned();
+    report.destination = to.to_owned();
+    if let (Some(arg), Some(writer)) = (to_arg, writer) {
+        writer.finish()?;
+        log::info!("wrote archive {}", arg.archive_path);
+        report.artifacts = vec![Artifact::of_file(arg.archive_path)?];
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{identify_archive_arg, rooted_spec};
+
+    #[test]
+    fn archive_args() {
+        let arg = identify_archive_arg("zip:C:\\fonts\\ardu.zip:tiledir:tiles:ppm").unwrap().unwrap();
+        assert_eq!(arg.archive_path, "C:\\fonts\\ardu.zip");
+        assert_eq!(arg.spec, Some("tiledir:tiles:ppm"));
+        assert_eq!(identify_archive_arg("zip:ardu.zip").unwrap().unwrap().spec, None);
+        assert!(identify_archive_arg("zip:ardu.tar:djibin:font.bin").is_err());
+        assert!(identify_archive_arg("djibin:font.bin").unwrap().is_none());
+
+        let dir = Path::new("content");
+        assert_eq!(rooted_spec("djibinsetnorm:.:ardu", dir).unwrap(), format!("djibinsetnorm:{}", Path::new("content").join(".:ardu").to_string_lossy()));
+        assert!(rooted_spec("djibinset{sd=a.bin}", dir).is_err());
+        assert!(rooted_spec("tilegrid", dir).is_err());
+    }
+}
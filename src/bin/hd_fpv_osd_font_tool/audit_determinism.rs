@@ -0,0 +1,83 @@
+
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::collection_format::{SpecFormat, TILE_COLLECTION_FORMATS};
+use hd_fpv_osd_font_tool::conversion::ConversionOptions;
+use hd_fpv_osd_font_tool::determinism::{self, Stage};
+use hd_fpv_osd_font_tool::image_encode::ImageEncodeOptions;
+use hd_fpv_osd_font_tool::workspace::Workspace;
+
+use crate::convert::convert_command;
+
+
+// the outputs of both conversions are written to the same path so that the paths they record are identical
+const OUTPUT_DIR_NAME: &str = "output";
+const FIRST_OUTPUT_DIR_NAME: &str = "first";
+
+fn audited_formats(prefixes: &[String], symbol_specs_file: &Path) -> anyhow::Result<Vec<SpecFormat>> {
+    if prefixes.is_empty() {
+        // symbol directories can only be written with the specs of the symbols
+        return Ok(TILE_COLLECTION_FORMATS.iter().copied().filter(|format| format.prefix != "symdir" || symbol_specs_file.exists()).collect());
+    }
+    prefixes.iter().map(|prefix| {
+        TILE_COLLECTION_FORMATS.iter().copied().find(|format| format.prefix == prefix).ok_or_else(|| anyhow!("unknown collection format `{prefix}`"))
+    }).collect()
+}
+
+// the second conversion starts in a different second so that the recorded dates differ
+fn wait_for_next_second() {
+    let subsec_nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.subsec_nanos()).unwrap_or(0);
+    thread::sleep(Duration::from_nanos(u64::from(1_000_000_000 - subsec_nanos)));
+}
+
+fn convert_to_formats(source: &str, output_dir: &Path, formats: &[SpecFormat], options: &ConversionOptions) -> anyhow::Result<()> {
+    fs_err::create_dir(output_dir)?;
+    for format in formats {
+        convert_command(source, &format.spec(output_dir.join(format.prefix)), options)?;
+    }
+    Ok(())
+}
+
+pub fn audit_determinism_command(source: &str, prefixes: &[String], symbol_specs_file: &Path, image_encode: &ImageEncodeOptions, bin_metadata: bool,
+        write_summary: bool) -> anyhow::Result<()> {
+    let formats = audited_formats(prefixes, symbol_specs_file)?;
+    let options = ConversionOptions {
+        symbol_specs_file: Some(symbol_specs_file.to_path_buf()),
+        image_encode: image_encode.clone(),
+        bin_metadata_source: bin_metadata.then(|| source.to_owned()),
+        write_summary,
+        ..Default::default()
+    };
+
+    let mut findings = determinism::encoder_findings(image_encode);
+    if symbol_specs_file.exists() {
+        findings.extend(determinism::symbol_specs_findings(symbol_specs_file)?);
+    }
+
+    let workspace = Workspace::create_in(std::env::temp_dir())?;
+    let output_dir = workspace.path().join(OUTPUT_DIR_NAME);
+    let first_output_dir = workspace.path().join(FIRST_OUTPUT_DIR_NAME);
+    convert_to_formats(source, &output_dir, &formats, &options)?;
+    fs_err::rename(&output_dir, &first_output_dir)?;
+    wait_for_next_second();
+    convert_to_formats(source, &output_dir, &formats, &options)?;
+    let differences = determinism::compare_dirs(&first_output_dir, &output_dir)?;
+
+    for finding in &findings {
+        println!("{finding}");
+    }
+    for difference in &differences {
+        println!("{}: {difference}", difference.stage());
+    }
+
+    let output_difference_count = differences.iter().filter(|difference| difference.stage() == Stage::Output).count();
+    log::info!("converted {source} twice to {} formats: {} files differ, {} of them by their timestamps only", formats.len(),
+        differences.len(), differences.len() - output_difference_count);
+    match differences.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow!("the outputs of the two conversions differ")),
+    }
+}
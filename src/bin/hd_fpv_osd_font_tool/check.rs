@@ -0,0 +1,56 @@
+
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::check::{self, LintConfig, Target};
+use hd_fpv_osd_font_tool::osd::bin_file::metadata::Metadata as BinFileMetadata;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+// prints the provenance recorded in the metadata sidecar file of a bin file and verifies its checksum
+fn check_bin_metadata(bin_file_path: &str) -> anyhow::Result<()> {
+    let metadata = match BinFileMetadata::load_sidecar(bin_file_path)? {
+        Some(metadata) => metadata,
+        None => return Ok(()),
+    };
+    println!("created by version {} of the tool from {}", metadata.tool_version, metadata.source.as_deref().unwrap_or("an unknown source"));
+    if let Some(ident) = &metadata.ident {
+        println!("ident: {ident}");
+    }
+    if ! metadata.matches_file(bin_file_path)? {
+        return Err(anyhow!("{bin_file_path} has been modified since its metadata sidecar file has been written: checksum mismatch"));
+    }
+    Ok(())
+}
+
+pub fn check_command(collection: &str, target: Target, ident: &Option<String>, lint_config: &Option<PathBuf>) -> anyhow::Result<()> {
+    // load as many tiles as any target supports so that extra tiles are reported instead of silently ignored
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    if let Some(bin_file_path) = collection.strip_prefix("djibin:") {
+        check_bin_metadata(bin_file_path)?;
+    }
+    let ident = ident.as_deref();
+    let lint_config = match lint_config {
+        Some(lint_config_file) => LintConfig::load_file(lint_config_file)?,
+        None => LintConfig::default(),
+    };
+    let report = check::check_with_config(&tiles, target, &ident, &lint_config);
+
+    for issue in &report.issues {
+        println!("{issue}");
+    }
+
+    if ! report.output_files.is_empty() {
+        println!("files produced for {target}:");
+        for file_name in &report.output_files {
+            println!("    {}", file_name.to_string_lossy());
+        }
+    }
+
+    if report.has_errors() {
+        return Err(anyhow!("{collection} cannot be used on {target}: {} error(s)", report.error_count()));
+    }
+    Ok(())
+}
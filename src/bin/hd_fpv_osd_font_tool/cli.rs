@@ -1,12 +1,13 @@
 
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use getset::{CopyGetters, Getters};
-use hd_fpv_osd_font_tool::log_level::LogLevel;
+use hd_fpv_osd_font_tool::man_pages::PageFormat;
+use hd_fpv_osd_font_tool::{analysis::palette::PaletteMode, osd::bin_file::pages::PaddingPolicy, background::Background, check::Target, image_encode::{ColorProfile, PngFilter}, log_level::LogLevel, scale::ScaleFilter, terminal_graphics::Protocol, osd::{font_profile::FontProfile, preview::{ComparisonMode, ScreenGrid}, tile::{container::{region::Region, set_dir_layout::SetDirLayout, symbol::spec_header}, Kind as TileKind, digit_style::DigitStyle, index::parse_index, alpha_mode::AlphaMode, grid::pages::GridLayout, file_format::PngCompression, transform::{Shift, Transform}}}};
 
 
-#[derive(Parser, CopyGetters)]
+#[derive(Parser, CopyGetters, Getters)]
 #[clap(author, version, about, long_about = None)]
 pub struct Cli {
 
@@ -15,6 +16,30 @@ pub struct Cli {
     #[getset(get_copy = "pub")]
     log_level: LogLevel,
 
+    /// only output errors, overrides --log-level
+    #[clap(short, long, global = true)]
+    #[getset(get_copy = "pub")]
+    quiet: bool,
+
+    /// print why each loaded file has been detected as holding SD or HD tiles: the file size or image dimensions and
+    /// what each tile kind expects
+    #[clap(long, global = true)]
+    #[getset(get_copy = "pub")]
+    explain_detection: bool,
+
+    /// config file holding the conversion presets, defaults to `hd_fpv_osd_font_tool/config.yaml` in the user config
+    /// directory
+    #[clap(long, global = true)]
+    #[getset(get = "pub")]
+    config: Option<PathBuf>,
+
+    /// reports the releases newer than this version and the format changes they bring, nothing is downloaded but the
+    /// list of releases
+    #[cfg(feature = "http")]
+    #[clap(long, global = true)]
+    #[getset(get_copy = "pub")]
+    check_updates: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 
@@ -27,9 +52,14 @@ pub enum Commands {
     /// Valid collection specifications are:{n}
     ///     * djibin:path       raw RGBA file{n}
     ///     * avatar:path       Avatar tile collection image file{n}
+    ///     * wtfos:path        msp-osd (WTFOS) font image file{n}
+    ///     * mcm:path[:kind]   MAX7456 analog OSD character memory file, `sd` (default) or `hd` tiles when loading{n}
     ///     * tilegrid:path     grid of tiles image{n}
-    ///     * tiledir:path      directory with each tile in a separate file{n}
+    ///     * tiledir:path[:ext] directory with each tile in a separate file{n}
     ///     * symdir:path       directory with each symbol in a separate file{n}
+    ///     * atlas:path        power of two texture atlas image with a `.json` UV map file next to it{n}
+    ///     * screenshot:path:geometry screenshot or photo of a font test screen, source only{n}
+    ///     * zip:path.zip:spec collection of the specification within a zip archive, e.g. zip:font.zip:djibin:font_hd.bin{n}
     ///
     /// Bin files normalized names{n}
     ///     Generic bin files (no ident):{n}
@@ -41,7 +71,10 @@ pub enum Commands {
     ///
     /// Tile directory (tiledir){n}
     ///     A tile directory is a directory representing a collection of tiles with each tile in a separate file. Each file{n}
-    ///     is named from the index of the tile 0 padded to 3 digits and with the png extensions e.g. 011.png
+    ///     is named from the index of the tile 0 padded to 3 digits and with the png extensions e.g. 011.png{n}
+    ///     The optional extension selects another tile file format:{n}
+    ///     - rgba: raw RGBA bytes without header, e.g. 011.rgba{n}
+    ///     - ppm: binary PPM image, the alpha channel is lost and loaded tiles are fully opaque
     ///
     /// Symbol directory (symdir){n}
     ///     A symbol is a small sub-collection of tiles representing a full symbol (symbol spanning across several tiles).{n}
@@ -50,18 +83,193 @@ pub enum Commands {
     ///     - symbols spanning a single tile: index of the symbol 0 padded to 3 digits and with png extension e.g. 011.png{n}
     ///     - other symbols: index of the first tile and index of the last tile 0 padded to 3 digits and separated by `-` e.g. 030-032.png
     ///
+    /// msp-osd font file (wtfos){n}
+    ///     PNG image of a single kind of tiles used by msp-osd on WTFOS rooted DJI goggles. The 4 pages of 256 tiles are{n}
+    ///     placed side by side, each page being a column of tiles. Trailing empty pages are dropped when loading
+    ///
+    /// MAX7456 character memory file (mcm){n}
+    ///     Analog OSD font of Betaflight and INAV. The 12x18 pixels black, white and transparent characters are scaled 3{n}
+    ///     times to SD tiles or 2 times to HD tiles when loading and tiles are scaled down to characters when saving.{n}
+    ///     Other colors are made black or white from their luminance
+    ///
+    /// Texture atlas (atlas){n}
+    ///     The non-empty tiles are packed in a power of two square PNG image for GPU based OSD overlay renderers. The{n}
+    ///     JSON file with the same name lists the pixel position and texture coordinates of each packed tile index.
+    ///
+    /// Screenshot (screenshot){n}
+    ///     Some goggles can display the whole font as a test screen. An approximate font is sampled out of a screenshot{n}
+    ///     or a photo of it for analysis, told where the visible grid is: `<sd|hd>,<columns>x<rows>,<left>,<top>,<right>,<bottom>`{n}
+    ///     with the bounding box of the grid in pixels e.g. `screenshot:dvr.png:hd,16x16,12,8,1900,1072`. Giving the 8{n}
+    ///     coordinates of the 4 corners of the grid clockwise from the top left one instead of the bounding box corrects{n}
+    ///     the perspective of photos taken at an angle on a best-effort basis.
+    ///
+    /// Presets{n}
+    ///     Options used together often can be stored under a name in the `presets` section of the config file and used{n}
+    ///     with --preset, the options given on the command line take precedence. Keys are the long option names and the{n}
+    ///     collections can be stored with the `from` and `to` keys, e.g.:{n}
+    ///     presets:{n}
+    ///       release-dji:{n}
+    ///         palette: dji.palette{n}
+    ///         palette-mode: remap{n}
+    ///         bin-metadata: true{n}
+    ///         check: dji-wtfos
+    ///
     /// Example: extracting the tiles from a bin file to individual files in the `tiles` directory:{n}
     ///     `convert bin:font.bin tiledir:tiles`
     Convert {
 
+        /// use the options of a preset from the config file
+        #[clap(long)]
+        preset: Option<String>,
+
         #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
         symbol_specs_file: PathBuf,
 
-        /// source collection in the form of a tile collection specification, see above
-        from: String,
+        /// font profile defining the maximum number of tiles loaded from tile/symbol directories
+        #[clap(short = 'p', long, value_enum, default_value_t = FontProfile::DJI_2PAGE)]
+        font_profile: FontProfile,
 
-        /// destination collection in the form of a tile collection specification, see above
-        to: String
+        /// composite grid images over a background to make transparent pixels visible: a color like `#202020` or
+        /// `checkerboard[:square size]`. The generated grids are meant for viewing and cannot be converted back losslessly
+        #[clap(short, long)]
+        background: Option<Background>,
+
+        /// pad with empty tiles or truncate the collection to this number of tiles before saving, e.g. 256 or 512
+        #[clap(short, long)]
+        repage: Option<usize>,
+
+        /// when saving to a tilegrid also write an HTML file next to the image with an image map showing the index
+        /// and symbol name of the hovered tile, symbol names are taken from the symbol specifications file if it exists
+        #[clap(long)]
+        html_image_map: bool,
+
+        /// when saving to a tilegrid outline the tiles of each symbol of the symbol specifications file in a color of
+        /// their own and write the symbol names over them. The annotated grids are meant for viewing and cannot be
+        /// converted back losslessly
+        #[clap(long)]
+        annotate_symbols: bool,
+
+        /// when saving to a tilegrid only keep the rows containing non-empty tiles, the number of tiles is recorded in
+        /// the image so that the grid gets its original size back when loaded
+        #[clap(long)]
+        crop: bool,
+
+        /// number of images a tilegrid is split into when loading or saving it, the second page is named after the
+        /// first with `_2` appended to the file stem, e.g. `grid.png` and `grid_2.png`
+        #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+        pages: u8,
+
+        /// `<columns>x<rows>` shape of each tilegrid page, pages are padded with empty tiles to this shape when saving,
+        /// defaults to 16x32 for a single page and 16x16 for two pages
+        #[clap(long, value_name = "COLUMNSxROWS")]
+        layout: Option<GridLayout>,
+
+        /// how transparency is expressed in the imported grid images and tile/symbol directories: `alpha` uses the alpha
+        /// channel, `color-key[:color]` makes the pixels of the color (magenta by default) transparent as in legacy
+        /// analog OSD art
+        #[clap(long, default_value_t = AlphaMode::Alpha)]
+        alpha_mode: AlphaMode,
+
+        /// rotate or flip every tile, quarter turns keep the dimensions of the tiles and clip what does not fit,
+        /// `snap-alpha` makes the semi-transparent pixels fully transparent or opaque as suggested by `check`
+        #[clap(long, value_enum)]
+        transform: Option<Transform>,
+
+        /// file listing tiles to generate from other tiles with a transform, e.g. mirrored arrows, with one
+        /// `<source index> <destination index> <transform>` mapping per line
+        #[clap(long)]
+        transform_map: Option<PathBuf>,
+
+        /// YAML file mapping destination indices to source indices giving the output a different index arrangement than
+        /// the source, applied before the other tile operations so that their indices refer to the new arrangement
+        #[clap(long)]
+        order: Option<PathBuf>,
+
+        /// move the content of a range of tiles by (dx, dy) pixels, e.g. `0x30-0x39:0,-1` moves the digits one pixel
+        /// up, a warning is emitted when content is moved past the edges of a tile, can be repeated
+        #[clap(long = "shift", value_name = "RANGE:DX,DY")]
+        shifts: Vec<Shift>,
+
+        /// palette file the colors of the tiles must belong to, see the `palette` command
+        #[clap(long)]
+        palette: Option<PathBuf>,
+
+        /// what to do with the pixels whose color is not part of the palette
+        #[clap(long, value_enum, default_value_t = PaletteMode::Error)]
+        palette_mode: PaletteMode,
+
+        /// remove the groups of at most this many stray opaque pixels touching the border of the tiles, see the `dust`
+        /// command
+        #[clap(long, value_name = "MAX_SIZE")]
+        remove_dust: Option<usize>,
+
+        /// when saving to bin files also write a `<bin file>.meta.yaml` sidecar file next to each of them recording the
+        /// tool version, source, ident and checksum of the file, it is verified by `check`
+        #[clap(long)]
+        bin_metadata: bool,
+
+        /// also write a summary file next to the output recording the source and its checksum, the tool version, the
+        /// date, the command line arguments and the symbol specs file, it is printed by `inspect`
+        #[clap(long)]
+        write_summary: bool,
+
+        /// write the report of the conversion listing the files written with their size and checksum, the warnings and
+        /// the duration as JSON to this file, `-` for the standard output
+        #[clap(long)]
+        report: Option<PathBuf>,
+
+        /// when the source tile directory contains both SD and HD tiles convert each kind to its own destination named
+        /// with a `_sd` or `_hd` suffix, e.g. `font_sd.bin`, instead of failing, each tile keeps its index
+        #[clap(long)]
+        split_kinds: bool,
+
+        /// when converting a PNG tile directory to a PNG tile directory or a symbol directory without modifying the
+        /// tiles, copy the files of the tiles and of the single tile symbols byte for byte instead of re-encoding them,
+        /// the PNG encoding options are then ignored for these files
+        #[clap(long)]
+        copy_unmodified: bool,
+
+        /// number of times the file operations failing with a transient IO error, e.g. on network filesystems or SD
+        /// card readers, are attempted again, the delay between two attempts doubles from 100ms up to 5s
+        #[clap(long, default_value_t = 0)]
+        retries: u32,
+
+        /// compression of the PNG files written, `best` produces the smallest files but is the slowest
+        #[clap(long, value_enum, default_value_t = PngCompression::Default)]
+        png_compression: PngCompression,
+
+        /// filter applied to the rows of the PNG files written before compressing them, `adaptive` usually produces the
+        /// smallest files, pinning a filter makes the output independent of the encoder heuristics
+        #[clap(long, value_enum, default_value_t = PngFilter::Adaptive)]
+        png_filter: PngFilter,
+
+        /// color space information written in the PNG files: `srgb` tags them as sRGB so that color managed editors do not
+        /// shift the glyph colors, `source` copies the ICC profile of the source images and `strip` writes none
+        #[clap(long, value_enum, default_value_t = ColorProfile::Srgb)]
+        color_profile: ColorProfile,
+
+        /// also write copies of the grid, avatar, tile directory or symbol directory output scaled by these factors,
+        /// e.g. `2,3`, named with a `@<factor>x` suffix like `grid@2x.png`
+        #[clap(long, value_delimiter = ',')]
+        scaled_variants: Vec<u32>,
+
+        /// filter used to generate the scaled variants and to scale the tiles to another kind
+        #[clap(long, value_enum, default_value_t = ScaleFilter::Nearest)]
+        scale_filter: ScaleFilter,
+
+        /// scale the tiles to SD (36x54) or HD (24x36) tiles, e.g. to make a HD font out of a SD one
+        #[clap(long, value_enum)]
+        scale_to: Option<TileKindArg>,
+
+        /// check the converted collection can be used on this target system, see the `check` command
+        #[clap(long, value_enum)]
+        check: Option<Target>,
+
+        /// source collection in the form of a tile collection specification, see above, can be given by the preset
+        from: Option<String>,
+
+        /// destination collection in the form of a tile collection specification, see above, can be given by the preset
+        to: Option<String>
     },
 
     /// Converts between tile collection set formats
@@ -74,7 +282,31 @@ pub enum Commands {
     ///     * tilesetgrids:sd_path:hd_path  grids of tiles image forming a SD/HD set{n}
     ///     * tilesetgridsnorm:path:ident   grid of tiles image set with normalized names{n}
     ///     * tilesetdir:path               directory with SD and HD tiles in the corresponding directory{n}
-    ///     * symsetdir:path                directory with SD and HD symbols in the corresponding directory
+    ///     * symsetdir:path                directory with SD and HD symbols in the corresponding directory{n}
+    ///     * wsltool:path                  walksnail-osd-tool font folder{n}
+    ///     * wtfos:path:ident              msp-osd (WTFOS) font files with normalized names{n}
+    ///     * avatarset:sd_path:hd_path     Walksnail Avatar font files forming a SD/HD set{n}
+    ///     * avatarsetnorm:path:ident      Walksnail Avatar font files with normalized names{n}
+    ///     * zip:path.zip[:spec]           set of the specification within a zip archive
+    ///
+    /// The parameters can also be named, in which case they can be given in any order and the optional ones omitted:{n}
+    ///     * djibinset{sd=path,sd2=path,hd=path,hd2=path}{n}
+    ///     * djibinsetnorm{dir=path,ident=ident}{n}
+    ///     * tilesetgrids{sd=path,hd=path}{n}
+    ///     * tilesetgridsnorm{dir=path,ident=ident}{n}
+    ///     * tilesetdir{dir=path}{n}
+    ///     * symsetdir{dir=path}{n}
+    ///     * wsltool{dir=path}{n}
+    ///     * wtfos{dir=path,ident=ident}{n}
+    ///     * avatarset{sd=path,hd=path}{n}
+    ///     * avatarsetnorm{dir=path,ident=ident}{n}
+    /// Paths given this way can contain `:` but not `,`
+    ///
+    /// Zip archives (zip){n}
+    ///     The specification within the archive has a single path relative to the root of the archive, named parameters{n}
+    ///     cannot be used. Without specification the normalized bin files at the root of the archive are read, or its{n}
+    ///     normalized grid images if it has no bin files, and the normalized bin files are written.{n}
+    ///     The archive is written once the conversion succeeded, replacing the existing one
     ///
     /// Bin files normalized names (binsetnorm){n}
     ///     Generic bin files (no ident):{n}
@@ -93,11 +325,30 @@ pub enum Commands {
     ///         SD: grid_<ident>.png{n}
     ///         HD: grid_<ident>_hd.png
     ///
+    /// When converting between normalized sets the ident of the source is kept if the destination does not specify one
+    ///
     /// Tile/symbol sets directory (tilesetdir / symsetdir){n}
     ///     A directory with the SD tiles in the SD subdirectory and HD tiles in the HD subdirectory{n}
     ///     When saving to a symsetdir the symbol specifications file can be specified with the -s/--symbols-specs-file argument.{n}
+    ///     Other layouts can be selected with --set-dir-layout: dirs:<SD dir>,<HD dir> for other subdirectories, possibly nested,{n}
+    ///     or prefix:<SD prefix>,<HD prefix> for both kinds in the directory with prefixed file names, e.g. prefix:sd_,hd_.{n}
+    ///     When loading the layout is detected if not specified.{n}
     ///     If `path/indent` is not provided will read the files from the current directory without ident
     ///
+    /// walksnail-osd-tool font folder (wsltool){n}
+    ///     A directory with the SD tiles in font_sd.png and the HD tiles in font_hd.png, each a single column of tiles.{n}
+    ///     When saving font_hd_2x.png with the HD tiles scaled to 48 pixels wide is also written
+    ///
+    /// msp-osd font files normalized names (wtfos){n}
+    ///     Generic font files (no ident): SD: font.png, HD: font_hd.png{n}
+    ///     With ident: SD: font_<ident>.png, HD: font_<ident>_hd.png{n}
+    ///     Each file has the 4 pages of 256 tiles of its kind side by side
+    ///
+    /// Walksnail Avatar font files normalized names (avatarsetnorm){n}
+    ///     Generic font files (no ident): SD: font_36.png, HD: font_24.png{n}
+    ///     With ident: SD: font_<ident>_36.png, HD: font_<ident>_24.png{n}
+    ///     Each file is a single column of the 256 tiles of its kind
+    ///
     /// Example: extracting the tiles from a bin file set with normalized name and no ident from the `font_files` directory{n}
     ///          to individual files. SD tiles in the `tiles/SD` directory and HD tiles in the `tiles/HD` directory:{n}
     ///     `convert-set binsetnorm:font_files tiledir:tiles`
@@ -106,6 +357,69 @@ pub enum Commands {
         #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
         symbol_specs_file: PathBuf,
 
+        /// font profile defining the maximum number of tiles loaded from tile/symbol directories
+        #[clap(short = 'p', long, value_enum, default_value_t = FontProfile::DJI_2PAGE)]
+        font_profile: FontProfile,
+
+        /// composite grid images over a background to make transparent pixels visible: a color like `#202020` or
+        /// `checkerboard[:square size]`. The generated grids are meant for viewing and cannot be converted back losslessly
+        #[clap(short, long)]
+        background: Option<Background>,
+
+        /// how transparency is expressed in the imported grid images and tile/symbol directories: `alpha` uses the alpha
+        /// channel, `color-key[:color]` makes the pixels of the color (magenta by default) transparent as in legacy
+        /// analog OSD art
+        #[clap(long, default_value_t = AlphaMode::Alpha)]
+        alpha_mode: AlphaMode,
+
+        /// load SD/HD sources given in the wrong order by swapping them back instead of failing
+        #[clap(long)]
+        auto_swap: bool,
+
+        /// load a djibinsetnorm source even if the files of one of the tile kinds are missing, only the available tile
+        /// kind is then converted
+        #[clap(long)]
+        allow_partial: bool,
+
+        /// number of times the reads of the bin file and msp-osd font sources failing with a transient IO error are
+        /// attempted again, the delay between two attempts doubles from 100ms up to 5s
+        #[clap(long, default_value_t = 0)]
+        retries: u32,
+
+        /// when saving to bin files also write a `<bin file>.meta.yaml` sidecar file next to each of them recording the
+        /// tool version, source, ident and checksum of the file, it is verified by `check`
+        #[clap(long)]
+        bin_metadata: bool,
+
+        /// also write a summary file next to the output recording the source and its checksum, the tool version, the
+        /// date, the command line arguments and the symbol specs file, it is printed by `inspect`
+        #[clap(long)]
+        write_summary: bool,
+
+        /// write the report of the conversion listing the files written with their size and checksum, the warnings and
+        /// the duration as JSON to this file, `-` for the standard output
+        #[clap(long)]
+        report: Option<PathBuf>,
+
+        /// layout of the tilesetdir and symsetdir directories: dirs:<SD dir>,<HD dir> or prefix:<SD prefix>,<HD prefix>,
+        /// detected when loading and dirs:SD,HD when saving if not specified
+        #[clap(long)]
+        set_dir_layout: Option<SetDirLayout>,
+
+        /// compression of the PNG files written, `best` produces the smallest files but is the slowest
+        #[clap(long, value_enum, default_value_t = PngCompression::Default)]
+        png_compression: PngCompression,
+
+        /// filter applied to the rows of the PNG files written before compressing them, `adaptive` usually produces the
+        /// smallest files, pinning a filter makes the output independent of the encoder heuristics
+        #[clap(long, value_enum, default_value_t = PngFilter::Adaptive)]
+        png_filter: PngFilter,
+
+        /// color space information written in the PNG files: `srgb` tags them as sRGB so that color managed editors do not
+        /// shift the glyph colors, `source` copies the ICC profile of the source images and `strip` writes none
+        #[clap(long, value_enum, default_value_t = ColorProfile::Srgb)]
+        color_profile: ColorProfile,
+
         /// source collection in the form of a tile collection specification, see above
         from: String,
 
@@ -113,13 +427,753 @@ pub enum Commands {
         to: String
     },
 
+    /// Checks that a tile collection can be used on a target system
+    ///
+    /// Verifies the tile kind, the number of tiles and the ident against the constraints of the target system then lists
+    /// the files which would be produced for it. Exits with an error if any problem is found.
+    ///
+    /// The provenance recorded in the metadata sidecar file of a bin file is printed and its checksum verified.
+    ///
+    /// The severity of each rule can be changed with a lint config file, e.g.:{n}
+    ///     rules:{n}
+    ///       invalid-ident: warning{n}
+    ///     targets:{n}
+    ///       dji-wtfos:{n}
+    ///         not-enough-tiles: error{n}
+    /// Only the issues with the error severity make the command fail.
+    Check {
+
+        /// system the collection is meant to be used on
+        #[clap(short, long, value_enum)]
+        target: Target,
+
+        /// ident used to name the produced files
+        #[clap(short, long)]
+        ident: Option<String>,
+
+        /// lint config file setting the severity of the rules: `error`, `warning` or `off`, globally or per target
+        #[clap(long)]
+        lint_config: Option<PathBuf>,
+
+        /// collection to check in the form of a tile collection specification, see `convert` help
+        collection: String,
+    },
+
+    /// Renders a symbol to a PNG image addressing it by its name in the symbol specifications file
+    ///
+    /// Example: extracting the `GPS` symbol from a bin file:{n}
+    ///     `extract-symbol -s symbol_specs/inav.yaml --name GPS djibin:font.bin gps.png`
+    ExtractSymbol {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// name of the symbol to extract
+        #[clap(short, long, required_unless_present = "all")]
+        name: Option<String>,
+
+        /// extract every named symbol to the output directory
+        #[clap(short, long, conflicts_with = "name")]
+        all: bool,
+
+        /// collection to extract the symbol from in the form of a tile collection specification, see `convert` help
+        collection: String,
+
+        /// image file to write the symbol to, or directory with --all
+        output: PathBuf,
+    },
+
+    /// Lists the differences between two symbol specifications files
+    ///
+    /// Symbols are matched by name, a removed symbol whose tiles are used by an added symbol is reported as renamed.
+    /// Each line starts with `+` for added symbols, `-` for removed symbols and `~` for renamed or moved symbols.
+    DiffSpecs {
+
+        /// render the tiles of each changed symbol in the old and new specifications side by side to this image
+        #[clap(long, requires = "reference_font")]
+        visual: Option<PathBuf>,
+
+        /// font used to render the visual diff in the form of a tile collection specification, see `convert` help
+        #[clap(long)]
+        reference_font: Option<String>,
+
+        old_specs_file: PathBuf,
+
+        new_specs_file: PathBuf,
+    },
+
+    /// Generates a symbol specs file from the symbol defines of a firmware header like the `osd_symbols.h` of Betaflight or INAV,{n}
+    /// or from the symbols of a symbol directory
+    ///
+    /// Each `#define SYM_<NAME> <index>` line gives a single tile `<NAME>` symbol, a `<NAME>_START` index with a{n}
+    /// `<NAME>_COUNT` gives a `<NAME>` symbol spanning `COUNT` tiles. The multi-tile symbols of a symbol directory are{n}
+    /// named `SYMBOL_<start tile index>` since their files are only named after their tile indices. The specs are{n}
+    /// printed when no output file is given.
+    #[clap(alias = "generate-specs")]
+    GenSpecs {
+
+        /// header file to read the symbol defines from
+        #[clap(long, required_unless_present = "from_symdir", conflicts_with = "from_symdir")]
+        from_header: Option<PathBuf>,
+
+        /// symbol directory to read the symbols from
+        #[clap(long)]
+        from_symdir: Option<PathBuf>,
+
+        /// prefix of the names of the symbol defines, removed from the symbol names
+        #[clap(long, default_value = spec_header::DEFAULT_PREFIX)]
+        prefix: String,
+
+        /// symbol specs file to write
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Lists the colors used by the tiles of a collection, most used first
+    ///
+    /// The list can be saved to a palette file to be enforced during conversions with `convert --palette`. Palette files
+    /// contain one `#RRGGBB` or `#RRGGBBAA` color per line, anything following the color is ignored.
+    Palette {
+
+        /// save the colors to this palette file
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// collection to extract the colors from in the form of a tile collection specification, see `convert` help
+        collection: String,
+    },
+
+    /// Prints the content of a collection or the provenance recorded in the summary file of an output written with{n}
+    /// `--write-summary`
+    ///
+    /// Collections are given as tile collection specifications, see `convert` help: the tile kind, the number of tiles,{n}
+    /// of non-empty tiles and of empty tiles following the last non-empty one are printed, along with the symbol spans{n}
+    /// of symbol directories. The JSON output also holds the SHA-256 checksum of each tile.
+    ///
+    /// Files without a summary are read as raw RGBA tiles of unknown shape and the tile kinds are ranked by how{n}
+    /// plausibly the content decodes as tiles of each kind.
+    Inspect {
+
+        /// print the collection inventory or the summary as JSON
+        #[clap(long)]
+        json: bool,
+
+        /// tile collection specification, or output file or directory of the conversion
+        target: String,
+    },
+
+    /// Reports the tiles containing stray opaque pixels ("dust") left by slicing grid images taken from screenshots
+    ///
+    /// Dust is a small group of opaque pixels touching the border of a tile. Small groups away from the border are kept
+    /// since they can be legitimate parts of a glyph. Use `convert --remove-dust` to remove the reported dust.
+    Dust {
+
+        /// maximum number of pixels of a group for it to be considered dust
+        #[clap(short = 's', long, default_value_t = 2)]
+        max_size: usize,
+
+        /// collection to analyze in the form of a tile collection specification, see `convert` help
+        collection: String,
+    },
+
+    /// Saves the tiles of a collection which differ from a base collection to a patch file
+    ///
+    /// Patch files are PNG grid images of the changed tiles with their indices stored in the image metadata. They are
+    /// small enough to distribute customizations of a stock font and can be applied to newer versions of it.
+    MakePatch {
+
+        /// base collection in the form of a tile collection specification, see `convert` help
+        base: String,
+
+        /// modified collection in the form of a tile collection specification
+        modified: String,
+
+        /// patch file to write
+        patch_file: PathBuf,
+    },
+
+    /// Applies a patch file made with `make-patch` to a collection
+    ///
+    /// A warning is emitted when the collection is not the one the patch has been made from, the patch is applied anyway
+    /// to allow rebasing customizations onto new stock fonts.
+    ApplyPatch {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// collection to patch in the form of a tile collection specification, see `convert` help
+        base: String,
+
+        /// patch file to apply
+        patch_file: PathBuf,
+
+        /// destination collection in the form of a tile collection specification
+        to: String,
+    },
+
+    /// Replaces tiles of a collection with the tiles of PNG images
+    ///
+    /// Each replacement is given as `<tile index>[-<last tile index>]:<PNG file>`, the image being a row of as many{n}
+    /// tiles as replaced like the images of symbol directories: `--set 42:logo.png --set 100-102:big_symbol.png`. The{n}
+    /// replacements are applied in order, the collection growing with empty tiles when tiles are put past its end.
+    Patch {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// tiles to replace and the image to replace them with
+        #[clap(long = "set", required = true)]
+        replacements: Vec<String>,
+
+        /// destination collection in the form of a tile collection specification, see `convert` help
+        #[clap(short, long)]
+        output: String,
+
+        /// collection to edit in the form of a tile collection specification
+        collection: String,
+    },
+
+    /// Exports a rectangle of tiles of the grid layout of a collection
+    ///
+    /// The region is given as `x,y,width,height` in tiles from the top left tile of the 16 tiles wide grid. Given a
+    /// plain path the tiles are written as a region image laid out as in the region and recording it, so that
+    /// `import-region` can put them back at the same place once edited. Given a collection specification the tiles are
+    /// saved as a collection, row by row.
+    ExportRegion {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// region of the grid to export: x,y,width,height in tiles
+        #[clap(short, long)]
+        region: Region,
+
+        /// collection to export from in the form of a tile collection specification, see `convert` help
+        collection: String,
+
+        /// region image file or collection specification to write
+        to: String,
+    },
+
+    /// Puts the tiles of a region exported with `export-region` back into a collection
+    ///
+    /// The region recorded in region images is used unless given, it has to be given for regions saved as collections.
+    /// The collection is extended with empty tiles if the region goes past its end.
+    ImportRegion {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// region of the grid to replace: x,y,width,height in tiles
+        #[clap(short, long)]
+        region: Option<Region>,
+
+        /// collection to import into in the form of a tile collection specification, see `convert` help
+        base: String,
+
+        /// region image file or collection specification holding the tiles of the region
+        region_source: String,
+
+        /// destination collection in the form of a tile collection specification
+        to: String,
+    },
+
+    /// Exports each symbol of a collection as a frame of a numbered PNG sequence for DVR overlay tools
+    ///
+    /// The frames are named `symbol_0000.png`, `symbol_0001.png`... so that they can be given to ffmpeg with the
+    /// `symbol_%04d.png` pattern, the scaled variants are written in `<scale>x` sub-directories and `sequence.json`
+    /// records the first tile index and span of the symbol of each frame.
+    ExportSequence {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// scale factor of the variants written in addition to the original size frames, can be repeated
+        #[clap(long = "scale", default_values_t = [2])]
+        scales: Vec<u32>,
+
+        /// collection to export in the form of a tile collection specification, see `convert` help
+        from: String,
+
+        /// directory to write the sequence to
+        output_dir: PathBuf,
+    },
+
+    /// Reports the tiles populated in a reference collection which are empty in a collection derived from it and the
+    /// tiles which have been added, fails if some tiles are missing
+    Coverage {
+
+        /// collection the analyzed collection is derived from, e.g. the stock font, in the form of a tile collection
+        /// specification, see `convert` help
+        #[clap(short, long)]
+        reference: String,
+
+        /// collection to analyze in the form of a tile collection specification, see `convert` help
+        collection: String,
+    },
+
+    /// Detects a collection whose tiles are all shifted by the same number of indices relative to a reference and
+    /// repairs it
+    ///
+    /// Tiles are compared with the perceptual hash of `similarity`, the offset for which the most tiles look like the
+    /// reference ones is reported. Tile directories can be repaired in place by renaming their files.
+    FixOffset {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// collection with the tiles at their expected indices, e.g. the font the collection has been exported from, in
+        /// the form of a tile collection specification, see `convert` help
+        #[clap(short, long)]
+        reference: String,
+
+        /// largest offset searched in both directions
+        #[clap(long, default_value_t = 16)]
+        max_offset: usize,
+
+        /// maximum hash distance between two tiles for them to be considered the same
+        #[clap(short = 'd', long, default_value_t = 4)]
+        max_distance: u32,
+
+        /// rename the files of the tile directory to undo the detected offset
+        #[clap(long)]
+        rename: bool,
+
+        /// write the collection with the offset undone to this tile collection specification
+        #[clap(short, long)]
+        output: Option<String>,
+
+        /// collection to check in the form of a tile collection specification, see `convert` help
+        collection: String,
+    },
+
+    /// Reports groups of tiles which look alike to help spotting duplicated or misplaced tiles
+    ///
+    /// Tiles are compared with a perceptual hash, the distance being the number of differing hash bits out of 64.
+    /// Empty tiles are ignored.
+    Similarity {
+
+        /// maximum hash distance between two tiles for them to be considered similar
+        #[clap(short = 'd', long, default_value_t = 4)]
+        max_distance: u32,
+
+        /// also write an HTML report showing the tiles of each group
+        #[clap(long)]
+        html: Option<PathBuf>,
+
+        /// collection to analyze in the form of a tile collection specification, see `convert` help
+        collection: String,
+    },
+
+    /// Builds the fonts described by a font project file for each of their targets
+    ///
+    /// The project file lists the fonts with their source collection, the options of the conversion with the same keys
+    /// as the presets of `convert`, the targets and the ident used to name the produced files, e.g.:{n}
+    ///     sources: sources{n}
+    ///     output: build{n}
+    ///     symbol-specs-file: symbol_specs/ardu.yaml{n}
+    ///     fonts:{n}
+    ///       - name: ardu_hd{n}
+    ///         source: tilegrid:ardu_hd.png{n}
+    ///         ident: ardu{n}
+    ///         targets: [dji-wtfos, avatar]{n}
+    ///         options:{n}
+    ///           transform-map: arrows.map{n}
+    /// The fonts are checked against each target and written to `<output>/<target>`, the paths are relative to the{n}
+    /// directory of the project file.
+    Build {
+
+        /// number of times the file operations failing with a transient IO error, e.g. on network filesystems or SD
+        /// card readers, are attempted again, the delay between two attempts doubles from 100ms up to 5s
+        #[clap(long, default_value_t = 0)]
+        retries: u32,
+
+        /// font project file
+        #[clap(default_value = "fontproj.yaml")]
+        project_file: PathBuf,
+    },
+
+    /// Splits collections of up to 512 tiles into the normalized base and `_2` bin files of the firmware
+    ///
+    /// A single collection gives a pair of files, a SD and a HD collection give the four files of a set, e.g.{n}
+    /// `split tilegrid:sd.png tilegrid:hd.png --ident inav fonts` writes `font_inav.bin`, `font_inav_2.bin`,{n}
+    /// `font_inav_hd.bin` and `font_inav_hd_2.bin` in the `fonts` directory.
+    Split {
+
+        /// ident of the font files written
+        #[clap(short, long)]
+        ident: Option<String>,
+
+        /// `strict` rejects collections which are not exactly 512 tiles instead of padding them with empty tiles
+        #[clap(long, value_enum, default_value_t = PaddingPolicy::Pad)]
+        padding: PaddingPolicy,
+
+        /// collections to split in the form of tile collection specifications, see `convert` help
+        #[clap(required = true, num_args = 1..=2)]
+        collections: Vec<String>,
+
+        /// directory to write the bin files to
+        dir: PathBuf,
+    },
+
+    /// Concatenates pages of 256 tiles of the same kind into one collection, e.g. a base and a `_2` bin file
+    ///
+    /// Each page starts at a multiple of 256 tiles, pages of less than 256 tiles are padded with empty tiles.
+    Concat {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// `strict` rejects pages which are not exactly 256 tiles instead of padding them with empty tiles
+        #[clap(long, value_enum, default_value_t = PaddingPolicy::Pad)]
+        padding: PaddingPolicy,
+
+        /// pages in order in the form of tile collection specifications, see `convert` help
+        #[clap(required = true)]
+        pages: Vec<String>,
+
+        /// destination collection in the form of a tile collection specification
+        #[clap(short, long)]
+        to: String,
+    },
+
+    /// Replaces the digits of a collection, at the indices of the ASCII digits, with built-in digits of another style
+    RestyleDigits {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        #[clap(long, value_enum)]
+        style: DigitStyle,
+
+        /// also write an image of the digits of the style to this file
+        #[clap(long)]
+        preview: Option<PathBuf>,
+
+        /// collection to restyle in the form of a tile collection specification, see `convert` help
+        collection: String,
+
+        /// destination collection in the form of a tile collection specification
+        to: String,
+    },
+
+    /// Draws the icons of an icon font like Material Symbols at chosen tile indices
+    ///
+    /// The icon map file lists one `<codepoint> <tile index>` pair per line, e.g. `U+E88A 0x10`. Codepoints can also be{n}
+    /// given as `0xE88A` or as the character itself. The icons are scaled to fit the tiles, centered and drawn in white{n}
+    /// with a black stroke. Without base collection the icons are drawn in an empty font.
+    Icons {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// TrueType or OpenType font to take the icons from
+        #[clap(long)]
+        font: PathBuf,
+
+        /// file mapping the codepoints of the icons to tile indices
+        #[clap(long)]
+        map: PathBuf,
+
+        /// width in pixels of the black stroke around the icons, 0 for none
+        #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(0..=4))]
+        stroke: u32,
+
+        /// pixels left empty around the stroked icons
+        #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(0..=8))]
+        margin: u32,
+
+        /// kind of the tiles of the font when there is no base collection
+        #[clap(long, value_enum, default_value_t = TileKindArg::Hd)]
+        kind: TileKindArg,
+
+        /// collection to draw the icons over in the form of a tile collection specification, see `convert` help
+        #[clap(long)]
+        base: Option<String>,
+
+        /// destination collection in the form of a tile collection specification
+        to: String,
+    },
+
+    /// Displays a tile in the terminal, or the whole symbol it is part of when a symbol specs file is given
+    ///
+    /// The image is drawn with the kitty graphics protocol or sixel graphics when the terminal supports them, otherwise{n}
+    /// with unicode half block characters which also work over SSH.
+    Show {
+
+        /// collection to read the tile from in the form of a tile collection specification, see `convert` help
+        collection: String,
+
+        /// index of the tile: decimal, hexadecimal with the 0x prefix or `<page>/<offset>`
+        #[clap(short, long, value_parser = parse_index)]
+        index: usize,
+
+        /// show the symbol the tile is part of according to this symbol specs file
+        #[clap(short, long)]
+        symbol_specs_file: Option<PathBuf>,
+
+        #[clap(long, value_enum, default_value_t = Protocol::Auto)]
+        protocol: Protocol,
+
+        /// scale factor of the image drawn with the kitty or sixel protocol
+        #[clap(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..=16))]
+        scale: u32,
+    },
+
+    /// Reports the tiles which differ between two collections
+    ///
+    /// Tiles are compared pixel for pixel unless a tolerance is given, a tile missing from one of the collections is{n}
+    /// compared to an empty tile. The comparison images show the tile of the first collection, the one of the second{n}
+    /// collection and the differing pixels in magenta side by side.
+    Diff {
+
+        /// ignore the differences of at most this much on each channel of the pixels
+        #[clap(short, long)]
+        tolerance: Option<u8>,
+
+        /// write a comparison image named after the tile index for each differing tile to this directory
+        #[clap(long)]
+        images: Option<PathBuf>,
+
+        /// first collection in the form of a tile collection specification, see `convert` help
+        first: String,
+
+        /// second collection in the form of a tile collection specification, see `convert` help
+        second: String,
+    },
+
+    /// Prints which bin file and byte range store a tile
+    ///
+    /// Tiles of the first page are in the base bin file and the ones of the second page in the `_2` bin file, each tile{n}
+    /// is stored as raw RGBA pixels right after the previous one. Both SD and HD locations are printed unless --kind is{n}
+    /// given.
+    Locate {
+
+        /// index of the tile: decimal, hexadecimal with the 0x prefix or `<page>/<offset>`
+        #[clap(value_parser = parse_index)]
+        index: usize,
+
+        /// kind of the tiles of the font
+        #[clap(long, value_enum)]
+        kind: Option<TileKindArg>,
+
+        /// ident of the normalized bin file names
+        #[clap(short, long)]
+        ident: Option<String>,
+    },
+
+    /// Renders a mock-up of an OSD screen drawn with a font to a PNG image
+    ///
+    /// The layout file has one line per row of the screen. Characters are drawn with the tile at their ASCII code,{n}
+    /// `{<index>}` draws the tile at a decimal or 0x hexadecimal index and `{<name>}` draws the tiles of a symbol of the{n}
+    /// symbol specs file, `{{` draws a `{`. The screen is 30x16 characters for SD and 50x18 characters for HD.
+    ///
+    /// With `--compare-with` the layout is also drawn with a second font to compare the fonts: the screens are put{n}
+    /// side by side, the first font on the left, or alternated in an animated GIF with `--comparison toggle`.
+    GeneratePreview {
+
+        /// symbol specs file used to resolve the `{<name>}` symbol names of the layout
+        #[clap(short, long)]
+        symbol_specs_file: Option<PathBuf>,
+
+        /// character grid of the screen, defaults to the one matching the kind of the tiles
+        #[clap(short, long, value_enum)]
+        grid: Option<ScreenGrid>,
+
+        /// background drawn under the tiles: a color like `#202020` or `checkerboard[:square size]`, defaults to a
+        /// dark gray standing in for the video feed
+        #[clap(short, long)]
+        background: Option<Background>,
+
+        /// second collection to draw the layout with, in the form of a tile collection specification
+        #[clap(long)]
+        compare_with: Option<String>,
+
+        /// how the screens drawn with the two fonts are compared
+        #[clap(long, value_enum, default_value_t = ComparisonMode::SideBySide, requires = "compare_with")]
+        comparison: ComparisonMode,
+
+        /// time each font is shown for in toggle comparisons, in milliseconds
+        #[clap(long, default_value_t = 1000)]
+        toggle_interval: u64,
+
+        /// text layout file of the screen
+        layout_file: PathBuf,
+
+        /// collection to draw the screen with in the form of a tile collection specification, see `convert` help
+        collection: String,
+
+        /// PNG file to write, GIF file for toggle comparisons
+        output: PathBuf,
+    },
+
+    /// Renders a review sheet: the grid image of a collection with the notes of a CSV file marked on their tiles
+    ///
+    /// The CSV file has the tile index in its first column and the note in the second one, a header line and the{n}
+    /// following columns are ignored. Each note gets a numbered colored marker in the corner of its tile and a line of{n}
+    /// the legend written below the grid.
+    ReviewSheet {
+
+        /// CSV file of the `<tile index>,<note>` review notes
+        #[clap(short, long)]
+        notes: PathBuf,
+
+        /// background drawn under the tiles: a color like `#202020` or `checkerboard[:square size]`
+        #[clap(short, long)]
+        background: Option<Background>,
+
+        /// collection to review in the form of a tile collection specification, see `convert` help
+        collection: String,
+
+        /// PNG file to write
+        output: PathBuf,
+    },
+
+    /// Experimental: draws the tiles of a font page by page on the OSD of a HD FPV system
+    ///
+    /// The serial adapter has to be wired to the MSP link of the air unit in place of the flight controller, the tool{n}
+    /// then sends the MSP DisplayPort messages a flight controller draws its OSD with. The goggles draw the characters{n}
+    /// with the font installed on them, the collection only gives the number of pages to cycle through.
+    #[cfg(feature = "preview-on-fc")]
+    PreviewOnFc {
+
+        /// baud rate of the MSP link
+        #[clap(short, long, default_value_t = hd_fpv_osd_font_tool::fc_preview::DEFAULT_BAUD_RATE)]
+        baud_rate: u32,
+
+        /// character grid of the screen, defaults to the one matching the kind of the tiles
+        #[clap(short, long, value_enum)]
+        grid: Option<ScreenGrid>,
+
+        /// number of seconds each page is shown
+        #[clap(short, long, default_value_t = 3)]
+        interval: u64,
+
+        /// number of times the pages are cycled through
+        #[clap(short, long, default_value_t = 1)]
+        cycles: usize,
+
+        /// serial port wired to the air unit, e.g. `/dev/ttyUSB0` or `COM3`
+        port: String,
+
+        /// collection to preview in the form of a tile collection specification, see `convert` help
+        collection: String,
+    },
+
+    /// Writes a copy of a collection ready to be redistributed
+    ///
+    /// Only the pixels of the tiles are copied: sidecar metadata files, summaries, PNG text chunks and ICC profiles are{n}
+    /// left behind, images are encoded with the default options and the tiles past the tile count of the font profile{n}
+    /// are dropped. A license file (LICENSE, LICENCE or COPYING) has to be found in or next to the source, it is copied{n}
+    /// next to the destination.
+    Sanitize {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// font profile defining the number of tiles kept
+        #[clap(short = 'p', long, value_enum, default_value_t = FontProfile::DJI_2PAGE)]
+        font_profile: FontProfile,
+
+        /// write the copy even when the source has no license file
+        #[clap(long)]
+        no_license_check: bool,
+
+        /// source collection in the form of a tile collection specification, see `convert` help
+        from: String,
+
+        /// destination collection in the form of a tile collection specification
+        to: String,
+    },
+
+    /// Upgrades project and symbol specs files written for older versions of the tool to the current schema
+    ///
+    /// The files are upgraded in place keeping their comments, the original content is kept next to them in{n}
+    /// `<file>.v<version>.bak`. Project files are recognized by their `fontproj.yaml` name or their `fonts` key.
+    Migrate {
+
+        /// project or symbol specs files to upgrade
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Converts a collection twice with the same options and compares the outputs byte for byte
+    ///
+    /// The collection is converted to each format twice in a temporary directory, the second conversion starting a
+    /// second later. Each differing file is reported with the stage it depends on: `timestamps` for the metadata sidecar
+    /// and summary files recording their date, `output` for the others. The settings making the outputs depend on more
+    /// than the tiles are reported as well: the `encoder` settings and the `symbol-specs` listed in hash map iteration
+    /// order. Fails when the outputs differ so that the reproducibility of font pack builds can be checked in CI.
+    AuditDeterminism {
+
+        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
+        symbol_specs_file: PathBuf,
+
+        /// prefix of a format to convert to, e.g. `tilegrid`, can be repeated, defaults to every collection format,
+        /// symbol directories being skipped when the symbol specifications file does not exist
+        #[clap(short, long = "format", value_name = "PREFIX")]
+        formats: Vec<String>,
+
+        /// also write the bin file metadata sidecar files, see `convert --bin-metadata`
+        #[clap(long)]
+        bin_metadata: bool,
+
+        /// also write the summary files, see `convert --write-summary`
+        #[clap(long)]
+        write_summary: bool,
+
+        /// compression of the PNG files written
+        #[clap(long, value_enum, default_value_t = PngCompression::Default)]
+        png_compression: PngCompression,
+
+        /// filter applied to the rows of the PNG files written, see `convert --png-filter`
+        #[clap(long, value_enum, default_value_t = PngFilter::Adaptive)]
+        png_filter: PngFilter,
+
+        /// color space information written in the PNG files, see `convert --color-profile`
+        #[clap(long, value_enum, default_value_t = ColorProfile::Srgb)]
+        color_profile: ColorProfile,
+
+        /// collection to audit in the form of a tile collection specification, see `convert` help
+        source: String,
+    },
+
+    /// Prints the version of the tool, the commit it was built from, its enabled optional features and the collection
+    /// formats it supports
+    Version {
+
+        /// print the build information as JSON
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Writes the man pages of the tool and of its commands, or their Markdown version for wikis
     #[clap(hide(true))]
-    GenerateManPages,
+    GenerateManPages {
+
+        /// format of the pages
+        #[clap(short, long, value_enum, default_value_t = PageFormat::Man)]
+        format: PageFormat,
+
+        /// directory the pages are written to, created if needed
+        #[clap(short, long, default_value = hd_fpv_osd_font_tool::man_pages::DEFAULT_OUTPUT_DIR)]
+        output_dir: PathBuf,
+    },
 
 }
 
-#[derive(Getters)]
-pub struct ConvertOptions<'a> {
-    #[getset(get = "pub")]
-    pub symbol_specs_file: &'a PathBuf
+/// Kind of tiles given on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TileKindArg {
+    Sd,
+    Hd,
+}
+
+impl From<TileKindArg> for TileKind {
+    fn from(kind: TileKindArg) -> Self {
+        match kind {
+            TileKindArg::Sd => TileKind::SD,
+            TileKindArg::Hd => TileKind::HD,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::{parser::ValueSource, ArgMatches, ValueEnum};
+use serde::Deserialize;
+
+use crate::cli::{Cli, Commands};
+
+
+const CONFIG_DIR_NAME: &str = "hd_fpv_osd_font_tool";
+const CONFIG_FILE_NAME: &str = "config.yaml";
+
+/// Options of the `convert` command stored under a name in the config file
+///
+/// The values have the same syntax as the corresponding command line options.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Preset {
+    from: Option<String>,
+    to: Option<String>,
+    symbol_specs_file: Option<PathBuf>,
+    font_profile: Option<String>,
+    background: Option<String>,
+    repage: Option<usize>,
+    html_image_map: bool,
+    annotate_symbols: bool,
+    crop: bool,
+    pages: Option<u8>,
+    layout: Option<String>,
+    alpha_mode: Option<String>,
+    transform: Option<String>,
+    transform_map: Option<PathBuf>,
+    order: Option<PathBuf>,
+    shift: Option<Vec<String>>,
+    palette: Option<PathBuf>,
+    palette_mode: Option<String>,
+    remove_dust: Option<usize>,
+    bin_metadata: bool,
+    write_summary: bool,
+    split_kinds: bool,
+    copy_unmodified: bool,
+    png_compression: Option<String>,
+    png_filter: Option<String>,
+    color_profile: Option<String>,
+    scaled_variants: Option<Vec<u32>>,
+    scale_filter: Option<String>,
+    scale_to: Option<String>,
+    check: Option<String>,
+}
+
+fn parse_value_enum<T: ValueEnum>(key: &str, value: &str) -> anyhow::Result<T> {
+    T::from_str(value, false).map_err(|error| anyhow!("invalid `{key}` value `{value}`: {error}"))
+}
+
+impl Preset {
+
+    pub(crate) fn symbol_specs_file(&self) -> Option<&PathBuf> {
+        self.symbol_specs_file.as_ref()
+    }
+
+    /// Fills the options of the convert command which have not been given on the command line
+    pub(crate) fn apply(&self, command: &mut Commands, matches: &ArgMatches) -> anyhow::Result<()> {
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        if let Commands::Convert {
+            from, to, symbol_specs_file, font_profile, background, repage, html_image_map, annotate_symbols, crop, pages, layout, alpha_mode, transform,
+            transform_map, order, shifts, palette, palette_mode, remove_dust, bin_metadata, png_compression, png_filter, color_profile, scaled_variants, scale_filter,
+            scale_to, write_summary, report: _, split_kinds, copy_unmodified, retries: _, check, preset: _
+        } = command {
+            if from.is_none() { *from = self.from.clone() }
+            if to.is_none() { *to = self.to.clone() }
+            if let (Some(value), false) = (&self.symbol_specs_file, given("symbol_specs_file")) { *symbol_specs_file = value.clone() }
+            if let (Some(value), false) = (&self.font_profile, given("font_profile")) { *font_profile = parse_value_enum("font-profile", value)? }
+            if let (Some(value), true) = (&self.background, background.is_none()) { *background = Some(value.parse()?) }
+            if repage.is_none() { *repage = self.repage }
+            *html_image_map |= self.html_image_map;
+            *annotate_symbols |= self.annotate_symbols;
+            *crop |= self.crop;
+            if let (Some(value), false) = (self.pages, given("pages")) {
+                if ! (1..=2).contains(&value) { return Err(anyhow!("invalid `pages` value `{value}`: expected 1 or 2")) }
+                *pages = value
+            }
+            if let (Some(value), true) = (&self.layout, layout.is_none()) { *layout = Some(value.parse()?) }
+            if let (Some(value), false) = (&self.alpha_mode, given("alpha_mode")) { *alpha_mode = value.parse()? }
+            if let (Some(value), true) = (&self.transform, transform.is_none()) { *transform = Some(parse_value_enum("transform", value)?) }
+            if transform_map.is_none() { *transform_map = self.transform_map.clone() }
+            if order.is_none() { *order = self.order.clone() }
+            if let (Some(values), true) = (&self.shift, shifts.is_empty()) { *shifts = values.iter().map(|value| value.parse()).collect::<Result<_, _>>()? }
+            if palette.is_none() { *palette = self.palette.clone() }
+            if let (Some(value), false) = (&self.palette_mode, given("palette_mode")) { *palette_mode = parse_value_enum("palette-mode", value)? }
+            if remove_dust.is_none() { *remove_dust = self.remove_dust }
+            *bin_metadata |= self.bin_metadata;
+            *write_summary |= self.write_summary;
+            *split_kinds |= self.split_kinds;
+            *copy_unmodified |= self.copy_unmodified;
+            if let (Some(value), false) = (&self.png_compression, given("png_compression")) { *png_compression = parse_value_enum("png-compression", value)? }
+            if let (Some(value), false) = (&self.png_filter, given("png_filter")) { *png_filter = parse_value_enum("png-filter", value)? }
+            if let (Some(value), false) = (&self.color_profile, given("color_profile")) { *color_profile = parse_value_enum("color-profile", value)? }
+            if let (Some(value), true) = (&self.scaled_variants, scaled_variants.is_empty()) { *scaled_variants = value.clone() }
+            if let (Some(value), false) = (&self.scale_filter, given("scale_filter")) { *scale_filter = parse_value_enum("scale-filter", value)? }
+            if let (Some(value), true) = (&self.scale_to, scale_to.is_none()) { *scale_to = Some(parse_value_enum("scale-to", value)?) }
+            if let (Some(value), true) = (&self.check, check.is_none()) { *check = Some(parse_value_enum("check", value)?) }
+        }
+        Ok(())
+    }
+
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    presets: HashMap<String, Preset>,
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None if cfg!(windows) => PathBuf::from(std::env::var_os("APPDATA")?),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+impl Config {
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let config = serde_yaml::from_reader(fs_err::File::open(path.as_ref())?)
+            .map_err(|error| anyhow!("failed to parse config file {}: {error}", path.as_ref().to_string_lossy()))?;
+        Ok(config)
+    }
+
+}
+
+/// Applies the preset selected with `convert --preset`, the options given on the command line take precedence
+///
+/// The preset is looked up in the config file given with `--config` or in `hd_fpv_osd_font_tool/config.yaml` in the user
+/// config directory.
+pub fn apply_convert_preset(cli: &mut Cli, matches: &ArgMatches) -> anyhow::Result<()> {
+    let preset_name = match &cli.command {
+        Commands::Convert { preset: Some(preset_name), .. } => preset_name.clone(),
+        _ => return Ok(()),
+    };
+    let config_path = cli.config().clone().or_else(default_config_path)
+        .ok_or_else(|| anyhow!("cannot locate the config file, specify it with --config"))?;
+    let config = Config::load_file(&config_path)?;
+    let preset = config.presets.get(&preset_name)
+        .ok_or_else(|| anyhow!("no preset named `{preset_name}` in config file {}", config_path.to_string_lossy()))?;
+    let convert_matches = matches.subcommand_matches("convert").expect("convert command matched");
+    preset.apply(&mut cli.command, convert_matches)
+        .map_err(|error| anyhow!("preset `{preset_name}`: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use clap::{CommandFactory, FromArgMatches};
+    use hd_fpv_osd_font_tool::analysis::palette::PaletteMode;
+
+    use crate::cli::{Cli, Commands};
+
+    use super::Config;
+
+    #[test]
+    fn command_line_takes_precedence() {
+        let config: Config = serde_yaml::from_str("presets:\n  release:\n    to: djibin:font.bin\n    palette-mode: remap\n    repage: 256\n").unwrap();
+        let matches = Cli::command().get_matches_from(["hd_fpv_osd_font_tool", "convert", "--preset", "release", "--repage", "512", "tilegrid:grid.png"]);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        config.presets["release"].apply(&mut cli.command, matches.subcommand_matches("convert").unwrap()).unwrap();
+        match cli.command {
+            Commands::Convert { from, to, repage, palette_mode, .. } => {
+                assert_eq!(from.as_deref(), Some("tilegrid:grid.png"));
+                assert_eq!(to.as_deref(), Some("djibin:font.bin"));
+                assert_eq!(repage, Some(512));
+                assert_eq!(palette_mode, PaletteMode::Remap);
+            },
+            _ => unreachable!(),
+        }
+    }
+
+}
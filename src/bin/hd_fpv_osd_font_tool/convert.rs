@@ -1,74 +1,14 @@
-
-use std::{error::Error, fmt::Display, path::Path};
+use std::path::{Path, PathBuf};
 
 use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::osd::tile::container::uniq_tile_kind::UniqTileKind;
+use hd_fpv_osd_font_tool::{conversion::{self, CollectionFormat, CollectionSpec, ConversionError, ConversionOptions}, conversion_report::ConversionReport, diagnostics, preflight, retry::RetryPolicy};
 use thiserror::Error;
 
-use crate::ConvertOptions;
-
-
-#[derive(Debug)]
-pub enum InvalidConvertArgError {
-    InvalidPrefix(String),
-    InvalidImageFileExtension {
-        path: String,
-        extension: Option<String>
-    },
-    InvalidPath(String),
-    NoPrefix
-}
+use crate::archive;
 
-impl Error for InvalidConvertArgError {}
-
-impl Display for InvalidConvertArgError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use InvalidConvertArgError::*;
-        match self {
-            InvalidPrefix(prefix) => write!(f, "invalid prefix: {}", prefix),
-            NoPrefix => f.write_str("no prefix"),
-            InvalidImageFileExtension { path, extension: Some(extension) } => write!(f, "invalid image file extension `{}`: {}", extension, path),
-            InvalidImageFileExtension { path, extension: None } => write!(f, "image path has no file extension: {}", path),
-            InvalidPath(path) => write!(f, "invalid path: {}", path),
-        }
-    }
-}
 
-enum ConvertArg<'a> {
-    BinFile(&'a str),
-    AvatarFile(&'a str),
-    TileGrid(&'a str),
-    TileDir(&'a str),
-    SymbolDir(&'a str),
-}
-
-fn check_arg_image_file_extension(path: &str) -> Result<(), InvalidConvertArgError> {
-    match Path::extension(Path::new(path)) {
-        Some(os_str) => match os_str.to_str() {
-            Some("png") => Ok(()),
-            Some(extension) => Err(InvalidConvertArgError::InvalidImageFileExtension { path: path.to_owned(), extension: Some(extension.to_owned()) }),
-            None => Err(InvalidConvertArgError::InvalidPath(path.to_owned()))
-        },
-        None => Err(InvalidConvertArgError::InvalidImageFileExtension { path: path.to_owned(), extension: None })
-    }
-}
-
-fn identify_convert_arg(input: &str) -> Result<ConvertArg, InvalidConvertArgError> {
-    if let Some(path) = input.strip_prefix("djibin:") {
-        Ok(ConvertArg::BinFile(path))
-    } else if let Some(path) = input.strip_prefix("tilegrid:") {
-        Ok(ConvertArg::TileGrid(path))
-    } else if let Some(path) = input.strip_prefix("tiledir:") {
-        Ok(ConvertArg::TileDir(path))
-    } else if let Some(path) = input.strip_prefix("symdir:") {
-        Ok(ConvertArg::SymbolDir(path))
-    } else if let Some(path) = input.strip_prefix("avatar:") {
-        Ok(ConvertArg::AvatarFile(path))
-    } else if let Some((prefix, _)) = input.split_once(':') {
-        Err(InvalidConvertArgError::InvalidPrefix(prefix.to_owned()))
-    } else {
-        Err(InvalidConvertArgError::NoPrefix)
-    }
-}
+pub use hd_fpv_osd_font_tool::conversion::InvalidSpecError as InvalidConvertArgError;
 
 #[derive(Debug, Error)]
 pub enum ConvertError {
@@ -78,82 +18,66 @@ pub enum ConvertError {
     ToArg(InvalidConvertArgError),
 }
 
-fn convert_tiles(tiles: Vec<Tile>, to_arg: &ConvertArg, options: &ConvertOptions) -> anyhow::Result<()> {
-    use ConvertArg::*;
-    match to_arg {
-        TileGrid(to_path) => {
-            check_arg_image_file_extension(to_path).map_err(ConvertError::ToArg)?;
-            tiles.save_to_grid_image(to_path)?
-        },
-        TileDir(to_path) => tiles.save_tiles_to_dir(to_path)?,
-        SymbolDir(to_path) => {
-            let sym_specs = SymbolSpecs::load_file(options.symbol_specs_file())?;
-            tiles.to_symbols(&sym_specs)?.save_to_dir(to_path)?;
-        },
-        BinFile(to_path) => tiles.save_to_bin_file(to_path)?,
-        AvatarFile(to_path) => tiles.save_to_avatar_file(to_path)?,
+/// Path and tile file format of a tile directory collection specification
+pub fn tile_dir_path_and_format(collection: &str) -> Option<(PathBuf, TileFileFormat)> {
+    match collection.parse::<CollectionSpec>() {
+        Ok(CollectionSpec { format: CollectionFormat::TileDir { file_format }, path }) => Some((path, file_format)),
+        _ => None,
     }
-    Ok(())
 }
 
-fn convert_tile_grid(tile_grid: TileGrid, to_arg: &ConvertArg, options: &ConvertOptions) -> anyhow::Result<()> {
-    use ConvertArg::*;
-    match to_arg {
-        BinFile(to_path) => tile_grid.save_tiles_to_bin_file(to_path)?,
-        TileDir(to_path) => tile_grid.save_tiles_to_dir(to_path)?,
-        SymbolDir(to_path) => {
-            let sym_specs = SymbolSpecs::load_file(options.symbol_specs_file())?;
-            tile_grid.to_symbols(&sym_specs)?.save_to_dir(to_path)?;
-        },
-        TileGrid(to_path) => tile_grid.save_image(to_path)?,
-        AvatarFile(to_path) => tile_grid.save_tiles_to_avatar_file(to_path)?,
+// the spec errors found while loading or saving are reported with the argument they come from
+fn spec_error_context(error: ConversionError, arg_error: fn(InvalidConvertArgError) -> ConvertError) -> anyhow::Error {
+    match error {
+        ConversionError::InvalidSpec(error) => arg_error(error).into(),
+        error => error.into(),
     }
-    Ok(())
 }
 
-pub fn convert_command(from: &str, to: &str, options: ConvertOptions) -> anyhow::Result<()> {
-    let from_arg = identify_convert_arg(from).map_err(ConvertError::FromArg)?;
-    let to_arg = identify_convert_arg(to).map_err(ConvertError::ToArg)?;
-    log::info!("converting {} -> {}", from, to);
-
-    use ConvertArg::*;
-    match (&from_arg, &to_arg) {
-
-        (BinFile(from_path), to_arg) => {
-            let tiles = bin_file::load(from_path)?;
-            convert_tiles(tiles, to_arg, &options)?;
-        },
-
-        (TileGrid(from_path), to_arg) => {
-            check_arg_image_file_extension(from_path).map_err(ConvertError::FromArg)?;
-            let tile_grid = crate::TileGrid::load_from_image(from_path)?;
-            convert_tile_grid(tile_grid, to_arg, &options)?;
-        },
-
-        (TileDir(from_path), to_arg) => {
-            let tiles = load_tiles_from_dir(from_path, 512)?;
-            convert_tiles(tiles, to_arg, &options)?;
-        },
-
-        (SymbolDir(from_path), to_arg) => {
-            let tiles = load_symbols_from_dir(from_path, 512)?.into_tiles_vec();
-            convert_tiles(tiles, to_arg, &options)?;
-        },
-
-        (AvatarFile(from_path), to_arg) => {
-            let tiles = load_avatar_file(from_path)?;
-            convert_tiles(tiles, to_arg, &options)?;
-        }
+/// Loads the tiles of any collection specification accepted by `convert`
+pub fn load_tiles(from: &str, font_profile: FontProfile) -> anyhow::Result<Vec<Tile>> {
+    load_tiles_with_retry(from, font_profile, RetryPolicy::NONE)
+}
 
+/// Loads the tiles like [`load_tiles`] retrying the file operations failing with a transient IO error, for the batch commands
+pub fn load_tiles_with_retry(from: &str, font_profile: FontProfile, retry: RetryPolicy) -> anyhow::Result<Vec<Tile>> {
+    if archive::is_archive_arg(from) {
+        return archive::with_extracted_source(from, archive::Content::Collection, |from| load_tiles_with_retry(from, font_profile, retry));
     }
+    let from_spec: CollectionSpec = from.parse().map_err(ConvertError::FromArg)?;
+    let options = ConversionOptions { font_profile, retry, ..Default::default() };
+    conversion::load_tiles(&from_spec, &options, &diagnostics::log_event).map_err(|error| spec_error_context(error, ConvertError::FromArg))
+}
+
+/// Saves tiles to any collection specification accepted by `convert`
+pub fn save_tiles(tiles: &[Tile], to: &str, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    save_tiles_with_retry(tiles, to, symbol_specs_file, RetryPolicy::NONE)
+}
+
+/// Saves the tiles like [`save_tiles`] retrying the file operations failing with a transient IO error, for the batch commands
+pub fn save_tiles_with_retry(tiles: &[Tile], to: &str, symbol_specs_file: &Path, retry: RetryPolicy) -> anyhow::Result<()> {
+    let to_spec: CollectionSpec = to.parse().map_err(ConvertError::ToArg)?;
+    preflight::check_destination_writable(&to_spec.path)?;
+    if let Ok(tile_kind) = tiles.tile_kind() {
+        conversion::check_output_space(&to_spec, tiles.len(), tile_kind)?;
+    }
+    let options = ConversionOptions { symbol_specs_file: Some(symbol_specs_file.to_path_buf()), retry, ..Default::default() };
+    conversion::save_tiles(tiles, &to_spec, &options, &diagnostics::log_event).map_err(|error| spec_error_context(error, ConvertError::ToArg))
+}
 
-    Ok(())
+pub fn convert_command(from: &str, to: &str, options: &ConversionOptions) -> anyhow::Result<ConversionReport> {
+    if archive::is_archive_arg(from) || archive::is_archive_arg(to) {
+        return archive::convert_with_archives(from, to, archive::Content::Collection, |from, to| convert_command(from, to, options));
+    }
+    let from_spec: CollectionSpec = from.parse().map_err(ConvertError::FromArg)?;
+    let to_spec: CollectionSpec = to.parse().map_err(ConvertError::ToArg)?;
+    Ok(conversion::convert(from_spec, to_spec, options, &diagnostics::log_event)?)
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::path::{PathBuf, Path};
+    use std::path::PathBuf;
     use std::{io, fs};
 
     use hd_fpv_osd_font_tool::osd::tile;
@@ -163,6 +87,9 @@ mod tests {
     use sha2::{Sha256, Digest};
     use itertools::Itertools;
 
+    use hd_fpv_osd_font_tool::collection_format::{conversion_pairs, TILE_COLLECTION_FORMATS};
+    use hd_fpv_osd_font_tool::conversion::ConversionOptions;
+
     use super::convert_command;
 
     fn files_are_identical(files: &[PathBuf]) -> bool {
@@ -176,59 +103,25 @@ mod tests {
 
     #[test]
     fn convert_all() {
-        let formats = [
-            "djibin",
-            "avatar",
-            "tilegrid",
-            "tiledir",
-            "symdir"
-        ];
-
         let temp_dir = TempDir::new().unwrap();
+        let options = ConversionOptions { symbol_specs_file: Some(PathBuf::from("symbol_specs/ardu.yaml")), ..Default::default() };
 
         for tile_kind in tile::Kind::iter() {
             let from_djibin = bin_file::normalized_file_path("test_files/djibinsetnorm", tile_kind, &None, FontPart::Base);
             let from_arg = format!("djibin:{}", from_djibin.to_str().unwrap());
-            for to_format in formats {
-                println!("testing djibin ({tile_kind}) -> {to_format}");
-                let to_name = format!("{to_format}_{tile_kind}");
-                let to_rel_path = match to_format {
-                    "djibin" => format!("{to_name}.bin"),
-                    "tilegrid" | "avatar" => format!("{to_name}.png"),
-                    _ => to_name
-                };
-                let to_path = temp_dir.child(to_rel_path);
-                let to_arg = format!("{to_format}:{}", to_path.to_str().unwrap());
-                let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
-                convert_command(&from_arg, &to_arg, options).unwrap();
+            for to_format in TILE_COLLECTION_FORMATS {
+                println!("testing djibin ({tile_kind}) -> {}", to_format.prefix);
+                let to_arg = to_format.spec(temp_dir.child(format!("{}_{tile_kind}", to_format.prefix)));
+                convert_command(&from_arg, &to_arg, &options).unwrap();
             }
         }
 
         for tile_kind in tile::Kind::iter() {
-            for testing_formats in formats.iter().permutations(2) {
-                let (from_format, to_format) = (testing_formats[0], testing_formats[1]);
-                println!("testing {from_format} ({tile_kind}) -> {to_format}");
-
-                let from_name = format!("{from_format}_{tile_kind}");
-                let from_rel_path = match *from_format {
-                    "djibin" => format!("{from_name}.bin"),
-                    "tilegrid" | "avatar" => format!("{from_name}.png"),
-                    _ => from_name
-                };
-
-                let to_name = format!("{to_format}_{tile_kind}");
-                let to_rel_path = match *to_format {
-                    "djibin" => format!("{to_name}_from_{from_format}.bin"),
-                    "tilegrid" | "avatar" => format!("{to_name}_from_{from_format}.png"),
-                    _ => format!("{to_name}_from_{from_format}")
-                };
-
-                let from_path = temp_dir.child(from_rel_path);
-                let to_path = temp_dir.child(to_rel_path);
-                let from_arg = format!("{from_format}:{}", from_path.to_str().unwrap());
-                let to_arg = format!("{to_format}:{}", to_path.to_str().unwrap());
-                let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
-                convert_command(&from_arg, &to_arg, options).unwrap();
+            for (from_format, to_format) in conversion_pairs(TILE_COLLECTION_FORMATS) {
+                println!("testing {} ({tile_kind}) -> {}", from_format.prefix, to_format.prefix);
+                let from_arg = from_format.spec(temp_dir.child(format!("{}_{tile_kind}", from_format.prefix)));
+                let to_arg = to_format.spec(temp_dir.child(format!("{}_{tile_kind}_from_{}", to_format.prefix, from_format.prefix)));
+                convert_command(&from_arg, &to_arg, &options).unwrap();
             }
         }
 
@@ -237,8 +130,8 @@ mod tests {
             let original_djibin = bin_file::normalized_file_path("test_files/djibinsetnorm", tile_kind, &None, FontPart::Base);
 
 
-            let generated_files = [ "avatar", "tilegrid", "tiledir", "symdir" ].map(|format| temp_dir.child(format!("djibin_{tile_kind}_from_{format}.bin")));
-            let files = [original_djibin].into_iter().chain(generated_files.into_iter()).collect::<Vec<PathBuf>>();
+            let generated_files = [ "avatar", "tilegrid", "tiledir", "symdir", "atlas", "wtfos" ].map(|format| temp_dir.child(format!("djibin_{tile_kind}_from_{format}.bin")));
+            let files = [original_djibin].into_iter().chain(generated_files).collect::<Vec<PathBuf>>();
             assert!(files_are_identical(&files));
 
             // AVATAR
@@ -250,4 +143,25 @@ mod tests {
 
     }
 
+    #[test]
+    fn normalize_tile_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = ConversionOptions { symbol_specs_file: Some(PathBuf::from("symbol_specs/ardu.yaml")), ..Default::default() };
+
+        let original_djibin = bin_file::normalized_file_path("test_files/djibinsetnorm", tile::Kind::SD, &None, FontPart::Base);
+        let tile_dir = temp_dir.child("tiledir");
+        let tile_dir_arg = format!("tiledir:{}", tile_dir.to_str().unwrap());
+        convert_command(&format!("djibin:{}", original_djibin.to_str().unwrap()), &tile_dir_arg, &options).unwrap();
+
+        // simulate a third-party tile directory with unpadded file names
+        fs::rename(tile_dir.join("005.png"), tile_dir.join("5.png")).unwrap();
+        convert_command(&tile_dir_arg, &tile_dir_arg, &options).unwrap();
+        assert!(tile_dir.join("005.png").exists());
+        assert!(! tile_dir.join("5.png").exists());
+
+        let normalized_djibin = temp_dir.child("normalized.bin");
+        convert_command(&tile_dir_arg, &format!("djibin:{}", normalized_djibin.to_str().unwrap()), &options).unwrap();
+        assert!(files_are_identical(&[original_djibin, normalized_djibin]));
+    }
+
 }
\ No newline at end of file
@@ -1,13 +1,20 @@
 
 use std::cmp::Ordering;
+use std::path::Path;
 
 use derive_more::Display;
 use thiserror::Error;
 
-use crate::ConvertOptions;
+use crate::archive;
 
 use super::convert::InvalidConvertArgError;
 use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::osd::bin_file::FontPart;
+use hd_fpv_osd_font_tool::osd::tile::Kind as TileKind;
+use hd_fpv_osd_font_tool::osd::tile::container::set_dir_layout::SetDirLayout;
+use hd_fpv_osd_font_tool::osd::tile::grid::normalized_image_file_path;
+use hd_fpv_osd_font_tool::osd::{walksnail_osd_tool, wtfos_file};
+use hd_fpv_osd_font_tool::{conversion::{write_bin_metadata, write_summaries, ConversionOptions}, conversion_report::ConversionReport, diagnostics, io_context::IoContext, preflight, workspace::Workspaces};
 
 enum ConvertSetArg<'a> {
     BinFileSet {
@@ -30,6 +37,48 @@ enum ConvertSetArg<'a> {
     },
     TileSetDir(&'a str),
     SymbolSetDir(&'a str),
+    WalksnailOsdToolDir(&'a str),
+    WtfosSetNorm {
+        dir: &'a str,
+        ident: Option<&'a str>
+    },
+    AvatarSet {
+        sd_path: &'a str,
+        hd_path: &'a str,
+    },
+    AvatarSetNorm {
+        dir: &'a str,
+        ident: Option<&'a str>
+    },
+}
+
+impl<'a> ConvertSetArg<'a> {
+    fn paths(&self) -> Vec<&'a str> {
+        use ConvertSetArg::*;
+        match self {
+            BinFileSet { sd_path, sd_2_path, hd_path, hd_2_path } => vec![*sd_path, *sd_2_path, *hd_path, *hd_2_path],
+            BinFileSetNorm { dir, .. } | TileSetGridsNorm { dir, .. } | WtfosSetNorm { dir, .. } | AvatarSetNorm { dir, .. } => vec![*dir],
+            TileSetGrids { sd_path, hd_path } | AvatarSet { sd_path, hd_path } => vec![*sd_path, *hd_path],
+            TileSetDir(dir) | SymbolSetDir(dir) | WalksnailOsdToolDir(dir) => vec![*dir],
+        }
+    }
+
+    // the same argument with its paths replaced by `paths`, given in the order of `Self::paths`
+    fn with_paths<'b>(&self, paths: &[&'b str]) -> ConvertSetArg<'b> where 'a: 'b {
+        use ConvertSetArg::*;
+        match *self {
+            BinFileSet { .. } => BinFileSet { sd_path: paths[0], sd_2_path: paths[1], hd_path: paths[2], hd_2_path: paths[3] },
+            BinFileSetNorm { ident, .. } => BinFileSetNorm { dir: paths[0], ident },
+            TileSetGrids { .. } => TileSetGrids { sd_path: paths[0], hd_path: paths[1] },
+            TileSetGridsNorm { ident, .. } => TileSetGridsNorm { dir: paths[0], ident },
+            TileSetDir(_) => TileSetDir(paths[0]),
+            SymbolSetDir(_) => SymbolSetDir(paths[0]),
+            WalksnailOsdToolDir(_) => WalksnailOsdToolDir(paths[0]),
+            WtfosSetNorm { ident, .. } => WtfosSetNorm { dir: paths[0], ident },
+            AvatarSet { .. } => AvatarSet { sd_path: paths[0], hd_path: paths[1] },
+            AvatarSetNorm { ident, .. } => AvatarSetNorm { dir: paths[0], ident },
+        }
+    }
 }
 
 #[derive(Debug, Display)]
@@ -37,6 +86,8 @@ pub enum InvalidConvertSetArgError {
     InvalidConvertArgError(InvalidConvertArgError),
     BinSetInvalidArguments(&'static str),
     TileSetGridsInvalidArguments(&'static str),
+    AvatarSetInvalidArguments(&'static str),
+    InvalidNamedArguments(String),
 }
 
 fn argument_norm_args(arg: &str) -> Result<(&str, Option<&str>), InvalidConvertSetArgError> {
@@ -51,8 +102,73 @@ fn argument_norm_args(arg: &str) -> Result<(&str, Option<&str>), InvalidConvertS
     Ok((dir, ident))
 }
 
-fn identify_convert_set_arg(input: &str) -> Result<ConvertSetArg, InvalidConvertSetArgError> {
-    if let Some(file_paths) = input.strip_prefix("djibinset:") {
+// parameters of a specification given in the `prefix{key=value,...}` form, values cannot contain `,`
+struct NamedArgs<'a>(Vec<(&'a str, &'a str)>);
+
+impl<'a> NamedArgs<'a> {
+
+    fn parse(args: &'a str) -> Result<Self, InvalidConvertSetArgError> {
+        use InvalidConvertSetArgError::InvalidNamedArguments;
+        let args = args.strip_suffix('}').ok_or_else(|| InvalidNamedArguments("missing closing `}`".to_owned()))?;
+        let mut named_args: Vec<(&str, &str)> = vec![];
+        for arg in args.split(',').filter(|arg| ! arg.is_empty()) {
+            match arg.split_once('=') {
+                Some((key, _)) if named_args.iter().any(|(named_key, _)| *named_key == key) =>
+                    return Err(InvalidNamedArguments(format!("`{key}` given more than once"))),
+                Some((key, value)) => named_args.push((key, value)),
+                None => return Err(InvalidNamedArguments(format!("expected `key=value`, got `{arg}`"))),
+            }
+        }
+        Ok(Self(named_args))
+    }
+
+    fn take(&mut self, key: &str) -> Option<&'a str> {
+        let index = self.0.iter().position(|(named_key, _)| *named_key == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    fn take_required(&mut self, key: &str) -> Result<&'a str, InvalidConvertSetArgError> {
+        self.take(key).ok_or_else(|| InvalidConvertSetArgError::InvalidNamedArguments(format!("missing `{key}`")))
+    }
+
+    fn finish(self) -> Result<(), InvalidConvertSetArgError> {
+        match self.0.first() {
+            Some((key, _)) => Err(InvalidConvertSetArgError::InvalidNamedArguments(format!("unknown parameter `{key}`"))),
+            None => Ok(()),
+        }
+    }
+
+}
+
+fn identify_named_convert_set_arg<'a>(prefix: &str, args: &'a str) -> Result<ConvertSetArg<'a>, InvalidConvertSetArgError> {
+    let mut args = NamedArgs::parse(args)?;
+    let convert_set_arg = match prefix {
+        "djibinset" => ConvertSetArg::BinFileSet {
+            sd_path: args.take_required("sd")?,
+            sd_2_path: args.take_required("sd2")?,
+            hd_path: args.take_required("hd")?,
+            hd_2_path: args.take_required("hd2")?,
+        },
+        "djibinsetnorm" => ConvertSetArg::BinFileSetNorm { dir: args.take("dir").unwrap_or("."), ident: args.take("ident") },
+        "tilesetgrids" => ConvertSetArg::TileSetGrids { sd_path: args.take_required("sd")?, hd_path: args.take_required("hd")? },
+        "tilesetgridsnorm" => ConvertSetArg::TileSetGridsNorm { dir: args.take("dir").unwrap_or("."), ident: args.take("ident") },
+        "tilesetdir" => ConvertSetArg::TileSetDir(args.take_required("dir")?),
+        "symsetdir" => ConvertSetArg::SymbolSetDir(args.take_required("dir")?),
+        "wsltool" => ConvertSetArg::WalksnailOsdToolDir(args.take_required("dir")?),
+        "wtfos" => ConvertSetArg::WtfosSetNorm { dir: args.take("dir").unwrap_or("."), ident: args.take("ident") },
+        "avatarset" => ConvertSetArg::AvatarSet { sd_path: args.take_required("sd")?, hd_path: args.take_required("hd")? },
+        "avatarsetnorm" => ConvertSetArg::AvatarSetNorm { dir: args.take("dir").unwrap_or("."), ident: args.take("ident") },
+        _ => return Err(InvalidConvertSetArgError::InvalidConvertArgError(InvalidConvertArgError::InvalidPrefix(prefix.to_owned()))),
+    };
+    args.finish()?;
+    Ok(convert_set_arg)
+}
+
+fn identify_convert_set_arg(input: &str) -> Result<ConvertSetArg<'_>, InvalidConvertSetArgError> {
+    if let Some((prefix, args)) = input.split_once('{').filter(|(prefix, _)| ! prefix.contains(':')) {
+        identify_named_convert_set_arg(prefix, args)
+
+    } else if let Some(file_paths) = input.strip_prefix("djibinset:") {
         let files: Vec<&str> = file_paths.split(':').collect();
         match files.len().cmp(&4) {
             Ordering::Less => return Err(InvalidConvertSetArgError::BinSetInvalidArguments("too few arguments")),
@@ -84,6 +200,26 @@ fn identify_convert_set_arg(input: &str) -> Result<ConvertSetArg, InvalidConvert
     } else if let Some(path) = input.strip_prefix("symsetdir:") {
         Ok(ConvertSetArg::SymbolSetDir(path))
 
+    } else if let Some(path) = input.strip_prefix("wsltool:") {
+        Ok(ConvertSetArg::WalksnailOsdToolDir(path))
+
+    } else if let Some(path) = input.strip_prefix("wtfos:") {
+        let (dir, ident) = argument_norm_args(path)?;
+        Ok(ConvertSetArg::WtfosSetNorm { dir, ident })
+
+    } else if let Some(file_paths) = input.strip_prefix("avatarset:") {
+        let files: Vec<&str> = file_paths.split(':').collect();
+        match files.len().cmp(&2) {
+            Ordering::Less => return Err(InvalidConvertSetArgError::AvatarSetInvalidArguments("too few arguments")),
+            Ordering::Greater => return Err(InvalidConvertSetArgError::AvatarSetInvalidArguments("too many arguments")),
+            Ordering::Equal => {},
+        }
+        Ok(ConvertSetArg::AvatarSet { sd_path: files[0], hd_path: files[1] })
+
+    } else if let Some(path) = input.strip_prefix("avatarsetnorm:") {
+        let (dir, ident) = argument_norm_args(path)?;
+        Ok(ConvertSetArg::AvatarSetNorm { dir, ident })
+
     } else if let Some((prefix, _)) = input.split_once(':') {
         Err(InvalidConvertSetArgError::InvalidConvertArgError(InvalidConvertArgError::InvalidPrefix(prefix.to_owned())))
     } else {
@@ -99,107 +235,260 @@ pub enum ConvertSetError {
     ToArg(InvalidConvertSetArgError),
 }
 
-fn convert_tile_set(tile_set: TileSet, to_arg: &ConvertSetArg, options: &ConvertOptions) -> anyhow::Result<()> {
+fn convert_tile_set(tile_set: TileSet, to_arg: &ConvertSetArg, options: &ConversionOptions) -> anyhow::Result<()> {
     use ConvertSetArg::*;
+    if let TileSetDir(dir) | SymbolSetDir(dir) = to_arg {
+        let estimate = preflight::estimate_tile_files_size(tile_set.sd_tiles().len(), TileKind::SD)
+            + preflight::estimate_tile_files_size(tile_set.hd_tiles().len(), TileKind::HD);
+        preflight::check_available_space(dir, estimate)?;
+    }
+    let encode_options = &options.image_encode;
     match to_arg {
-        BinFileSet { sd_path, sd_2_path, hd_path, hd_2_path } => tile_set.save_to_bin_files(sd_path, sd_2_path, hd_path, hd_2_path)?,
-        BinFileSetNorm { dir, ident } => tile_set.save_to_bin_files_norm(dir, ident)?,
-        TileSetGrids { sd_path, hd_path } => tile_set.save_to_grids(sd_path, hd_path)?,
-        TileSetGridsNorm { dir, ident  } => tile_set.save_to_grids_norm(dir, ident)?,
-        TileSetDir(dir) => tile_set.save_tiles_to_dir(dir)?,
+        BinFileSet { sd_path, sd_2_path, hd_path, hd_2_path } => {
+            tile_set.save_to_bin_files(sd_path, sd_2_path, hd_path, hd_2_path)?;
+            for (tiles, paths) in [(tile_set.sd_tiles(), [sd_path, sd_2_path]), (tile_set.hd_tiles(), [hd_path, hd_2_path])] {
+                if ! tiles.is_empty() {
+                    for path in paths {
+                        write_bin_metadata(path, None, options)?;
+                    }
+                }
+            }
+        },
+        BinFileSetNorm { dir, ident } => {
+            tile_set.save_to_bin_files_norm(dir, ident)?;
+            let ident = ident.map(str::to_owned).or_else(|| tile_set.ident().clone());
+            for (tile_kind, tiles) in [(TileKind::SD, tile_set.sd_tiles()), (TileKind::HD, tile_set.hd_tiles())] {
+                if ! tiles.is_empty() {
+                    for part in [FontPart::Base, FontPart::Ext] {
+                        write_bin_metadata(bin_file::normalized_file_path(dir, tile_kind, &ident.as_deref(), part), ident.as_deref(), options)?;
+                    }
+                }
+            }
+        },
+        TileSetGrids { sd_path, hd_path } => {
+            let tile_grid_set = TileGridSet::from(tile_set);
+            match options.background {
+                Some(background) => tile_grid_set.save_images_with_background(sd_path, hd_path, &TileGridStyle::default(), &background, encode_options)?,
+                None => tile_grid_set.save_images_with_style(sd_path, hd_path, &TileGridStyle::default(), encode_options)?,
+            }
+        },
+        TileSetGridsNorm { dir, ident  } => {
+            let ident = ident.map(str::to_owned).or_else(|| tile_set.ident().clone());
+            let tile_grid_set = TileGridSet::from(tile_set);
+            match options.background {
+                Some(background) =>
+                    tile_grid_set.save_images_norm_with_background(dir, &ident.as_deref(), &TileGridStyle::default(), &background, encode_options)?,
+                None => tile_grid_set.save_images_norm_with_style(dir, &ident.as_deref(), &TileGridStyle::default(), encode_options)?,
+            }
+        },
+        TileSetDir(dir) => tile_set.save_tiles_to_dir_with_layout(dir, &save_layout(options), &TileSaveOptions { encode: encode_options.clone(), ..Default::default() })?,
         SymbolSetDir(dir) => {
-            let sym_specs = SymbolSpecs::load_file(options.symbol_specs_file())?;
-            tile_set.into_symbol_set(&sym_specs).unwrap().save_to_dir(dir)?;
+            let sym_specs = options.symbol_specs()?;
+            tile_set.into_symbol_set(&sym_specs).unwrap().save_to_dir_with_layout(dir, &save_layout(options), encode_options)?;
+        },
+        WalksnailOsdToolDir(dir) => walksnail_osd_tool::save_set(&tile_set, dir, encode_options)?,
+        WtfosSetNorm { dir, ident } => {
+            let ident = ident.map(str::to_owned).or_else(|| tile_set.ident().clone());
+            wtfos_file::save_set_norm(&tile_set, dir, &ident.as_deref(), encode_options)?;
         },
+        AvatarSet { sd_path, hd_path } => tile_set.save_to_avatar_files(sd_path, hd_path, encode_options)?,
+        AvatarSetNorm { dir, ident } => tile_set.save_to_avatar_files_norm(dir, ident, encode_options)?,
     }
     Ok(())
 }
 
-pub fn convert_set_command(from: &str, to: &str, options: ConvertOptions) -> anyhow::Result<()> {
+fn with_alpha_mode(mut tile_set: TileSet, options: &ConversionOptions) -> TileSet {
+    tile_set.apply_alpha_mode(options.alpha_mode);
+    tile_set
+}
+
+fn save_layout(options: &ConversionOptions) -> SetDirLayout {
+    options.set_dir_layout.clone().unwrap_or_default()
+}
+
+// the layout given on the command line takes precedence over the detected one
+fn load_layout(dir: &str, options: &ConversionOptions) -> SetDirLayout {
+    match &options.set_dir_layout {
+        Some(layout) => layout.clone(),
+        None => {
+            let layout = SetDirLayout::detect_or_default(dir);
+            log::debug!("{dir}: using set directory layout {layout}");
+            layout
+        },
+    }
+}
+
+// checks all the files of the bin file and grid sources at once so that inconsistent sets fail before anything is written
+fn check_inputs(from_arg: &ConvertSetArg, options: &ConversionOptions) -> Result<(), preflight::InputsError> {
+    use ConvertSetArg::*;
+    let swap_policy = options.kind_swap_policy;
+    match from_arg {
+        BinFileSet { sd_path, sd_2_path, hd_path, hd_2_path } =>
+            preflight::check_bin_file_set_inputs(Path::new(sd_path), Path::new(sd_2_path), Path::new(hd_path), Path::new(hd_2_path), swap_policy),
+        BinFileSetNorm { dir, ident } if ! options.allow_partial => {
+            let path = |tile_kind, part| bin_file::normalized_file_path(dir, tile_kind, ident, part);
+            preflight::check_bin_file_set_inputs(path(TileKind::SD, FontPart::Base), path(TileKind::SD, FontPart::Ext),
+                path(TileKind::HD, FontPart::Base), path(TileKind::HD, FontPart::Ext), swap_policy)
+        },
+        TileSetGrids { sd_path, hd_path } =>
+            preflight::check_grid_set_inputs(Path::new(sd_path), Path::new(hd_path), &TileGridStyle::default(), swap_policy),
+        TileSetGridsNorm { dir, ident } =>
+            preflight::check_grid_set_inputs(normalized_image_file_path(dir, TileKind::SD, ident), normalized_image_file_path(dir, TileKind::HD, ident),
+                &TileGridStyle::default(), swap_policy),
+        _ => Ok(()),
+    }
+}
+
+pub fn convert_set_command(from: &str, to: &str, options: &ConversionOptions) -> anyhow::Result<ConversionReport> {
+    if archive::is_archive_arg(from) || archive::is_archive_arg(to) {
+        return archive::convert_with_archives(from, to, archive::Content::Set, |from, to| convert_set_command(from, to, options));
+    }
+    let mut report = ConversionReport::start(from, to);
     let from_arg = identify_convert_set_arg(from).map_err(ConvertSetError::FromArg)?;
     let to_arg = identify_convert_set_arg(to).map_err(ConvertSetError::ToArg)?;
+    let options = &ConversionOptions { image_encode: options.image_encode.clone().with_source_icc_chunk(&from_arg.paths()), ..options.clone() };
+    for path in to_arg.paths() {
+        preflight::check_destination_writable(path)?;
+    }
+    check_inputs(&from_arg, options)?;
     log::info!("converting {} -> {}", from, to);
 
+    // the outputs are staged next to their destination so that they are moved in place without crossing filesystems
+    let mut workspaces = Workspaces::new();
+    let staged_paths = to_arg.paths().into_iter().map(|path| Ok(workspaces.stage(path)?.to_string_lossy().into_owned()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let to_arg = to_arg.with_paths(&staged_paths.iter().map(String::as_str).collect::<Vec<_>>());
+
+    // only the bin file and msp-osd font sources are read with the retry policy of the options
+    let context = IoContext::new(options.retry, &diagnostics::log_event);
     use ConvertSetArg::*;
     match (&from_arg, &to_arg) {
 
         (BinFileSet { sd_path, sd_2_path, hd_path, hd_2_path }, to_arg) => {
-            let tile_set = bin_file::load_set(sd_path, sd_2_path, hd_path, hd_2_path)?;
-            convert_tile_set(tile_set, to_arg, &options)
+            let tile_set = TileSet::load_bin_files_with_context(sd_path, sd_2_path, hd_path, hd_2_path, options.kind_swap_policy, &context)?;
+            convert_tile_set(tile_set, to_arg, options)
         },
 
         (BinFileSetNorm { dir, ident }, to_arg) => {
-            let tile_set = bin_file::load_set_norm(dir, ident)?;
-            convert_tile_set(tile_set, to_arg, &options)
+            let tile_set = match options.allow_partial {
+                true => TileSet::load_bin_files_norm_partial(dir, ident)?,
+                false => TileSet::load_bin_files_norm_with_context(dir, ident, options.kind_swap_policy, &context)?,
+            };
+            for tile_kind in tile_set.missing_tile_kinds() {
+                report.warn(format!("no {tile_kind} tiles, {tile_kind} outputs will not be produced"));
+            }
+            convert_tile_set(tile_set, to_arg, options)
         },
 
         (TileSetGrids { sd_path, hd_path }, to_arg) => {
-            let tile_grid_set = TileGridSet::load_from_images(sd_path, hd_path)?;
-            convert_tile_set(tile_grid_set.into_tile_set(), to_arg, &options)
+            let tile_grid_set = TileGridSet::load_from_images_with_swap_policy(sd_path, hd_path, &TileGridStyle::default(), options.kind_swap_policy)?;
+            convert_tile_set(with_alpha_mode(tile_grid_set.into_tile_set(), options), to_arg, options)
         },
 
         (TileSetGridsNorm { dir, ident }, to_arg) => {
-            let tile_grid_set = TileGridSet::load_from_images_norm(dir, ident)?;
-            convert_tile_set(tile_grid_set.into_tile_set(), to_arg, &options)
+            let tile_grid_set = TileGridSet::load_from_images_norm_with_swap_policy(dir, ident, &TileGridStyle::default(), options.kind_swap_policy)?;
+            convert_tile_set(with_alpha_mode(tile_grid_set.into_tile_set().with_ident(*ident), options), to_arg, options)
         },
 
         (TileSetDir(dir), to_arg) => {
-            let tile_set = TileSet::load_from_dir(dir, 512)?;
-            convert_tile_set(tile_set, to_arg, &options)
+            let tile_set = TileSet::load_from_dir_with_layout(dir, options.font_profile.tile_count(), &load_layout(dir, options))?;
+            convert_tile_set(with_alpha_mode(tile_set, options), to_arg, options)
         },
 
         (SymbolSetDir(dir), to_arg) => {
-            let symbol_set = SymbolSet::load_from_dir(dir, 512)?;
-            convert_tile_set(symbol_set.into(), to_arg, &options)
+            let symbol_set = SymbolSet::load_from_dir_with_layout(dir, options.font_profile.tile_count(), &load_layout(dir, options))?;
+            convert_tile_set(with_alpha_mode(symbol_set.into(), options), to_arg, options)
         },
 
-    }
+        (WalksnailOsdToolDir(dir), to_arg) => {
+            let tile_set = walksnail_osd_tool::load_set(dir)?;
+            convert_tile_set(with_alpha_mode(tile_set, options), to_arg, options)
+        },
+
+        (WtfosSetNorm { dir, ident }, to_arg) => {
+            let tile_set = wtfos_file::load_set_norm_with_context(dir, ident, &context)?.with_ident(*ident);
+            convert_tile_set(with_alpha_mode(tile_set, options), to_arg, options)
+        },
+
+        (AvatarSet { sd_path, hd_path }, to_arg) => {
+            let tile_set = TileSet::load_avatar_files_with_swap_policy(sd_path, hd_path, options.kind_swap_policy)?;
+            convert_tile_set(with_alpha_mode(tile_set, options), to_arg, options)
+        },
+
+        (AvatarSetNorm { dir, ident }, to_arg) => {
+            let tile_set = TileSet::load_avatar_files_norm_with_swap_policy(dir, ident, options.kind_swap_policy)?;
+            convert_tile_set(with_alpha_mode(tile_set, options), to_arg, options)
+        },
+
+    }?;
+
+    write_summaries(from, &from_arg.paths(), to, &to_arg.paths(), options)?;
+    report.add_files(&workspaces.commit()?)?;
+
+    Ok(report.finish())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::path::Path;
+    use std::path::PathBuf;
 
+    use hd_fpv_osd_font_tool::conversion::{CollectionSpec, ConversionOptions};
     use hd_fpv_osd_font_tool::osd::tile::container::tile_set::TileSet;
-    use itertools::Itertools;
+    use hd_fpv_osd_font_tool::collection_format::{conversion_pairs, SpecFormat, TILE_COLLECTION_FORMATS, TILE_SET_FORMATS};
     use temp_dir::TempDir;
 
     use crate::convert_set::convert_set_command;
 
-    use super::{identify_convert_set_arg, convert_tile_set};
+    use super::{identify_convert_set_arg, convert_tile_set, ConvertSetArg};
 
     #[test]
     fn convert_set_all() {
-        let formats = [
-            // "djibinset",
-            "djibinsetnorm",
-            // "tilesetgrids",
-            "tilesetgridsnorm",
-            "tilesetdir",
-            "symsetdir"
-        ];
+        // the sets of separate files are covered by the single path formats sharing their loaders
+        let formats = TILE_SET_FORMATS.iter().copied().filter(SpecFormat::is_single_path).collect::<Vec<_>>();
 
+        let options = ConversionOptions { symbol_specs_file: Some(PathBuf::from("symbol_specs/ardu.yaml")), ..Default::default() };
         let from_djibinsetnorm = TileSet::load_bin_files_norm("test_files/djibinsetnorm", &None).unwrap();
         let temp_dir = TempDir::new().unwrap();
 
-        for format in formats {
-            let to_arg_str = [format, temp_dir.child(format).to_str().unwrap()].join(":");
+        for format in &formats {
+            let to_arg_str = format.spec(temp_dir.child(format.prefix));
             let to_arg = identify_convert_set_arg(&to_arg_str).unwrap();
-            let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
             convert_tile_set(from_djibinsetnorm.clone(), &to_arg, &options).unwrap();
         }
 
-        for testing_formats in formats.iter().permutations(2) {
-            let (from_format, to_format) = (testing_formats[0], testing_formats[1]);
-            println!("testing {from_format} -> {to_format}");
-            let from_arg = [from_format, temp_dir.child(from_format).to_str().unwrap()].join(":");
-            let to_arg = [to_format, temp_dir.child(to_format).to_str().unwrap()].join(":");
-            let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
-            convert_set_command(&from_arg, &to_arg, options).unwrap();
+        for (from_format, to_format) in conversion_pairs(&formats) {
+            println!("testing {} -> {}", from_format.prefix, to_format.prefix);
+            let from_arg = from_format.spec(temp_dir.child(from_format.prefix));
+            let to_arg = to_format.spec(temp_dir.child(to_format.prefix));
+            convert_set_command(&from_arg, &to_arg, &options).unwrap();
         }
 
     }
 
+    #[test]
+    fn named_arguments() {
+        assert!(matches!(identify_convert_set_arg("djibinset{sd=a.bin,sd2=b.bin,hd=c.bin,hd2=d.bin}").unwrap(),
+            ConvertSetArg::BinFileSet { sd_path: "a.bin", sd_2_path: "b.bin", hd_path: "c.bin", hd_2_path: "d.bin" }));
+        assert!(matches!(identify_convert_set_arg("djibinsetnorm{ident=ardu}").unwrap(),
+            ConvertSetArg::BinFileSetNorm { dir: ".", ident: Some("ardu") }));
+        assert!(matches!(identify_convert_set_arg("avatarset{sd=a.png,hd=b.png}").unwrap(),
+            ConvertSetArg::AvatarSet { sd_path: "a.png", hd_path: "b.png" }));
+        assert!(identify_convert_set_arg("avatarset:a.png").is_err());
+        assert!(identify_convert_set_arg("djibinset{sd=a.bin,sd2=b.bin,hd=c.bin}").is_err());
+        assert!(identify_convert_set_arg("tilesetdir{dir=tiles,geometry=16x16}").is_err());
+        assert!(identify_convert_set_arg("tilesetdir{dir=tiles").is_err());
+    }
+
+    #[test]
+    fn listed_formats_are_identified() {
+        for format in TILE_SET_FORMATS {
+            assert!(identify_convert_set_arg(&format.spec("fonts/font")).is_ok(), "{} not identified", format.prefix);
+        }
+        for format in TILE_COLLECTION_FORMATS {
+            assert!(format.spec("fonts/font").parse::<CollectionSpec>().is_ok(), "{} not identified", format.prefix);
+        }
+    }
+
 
 }
\ No newline at end of file
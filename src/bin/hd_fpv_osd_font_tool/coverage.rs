@@ -0,0 +1,31 @@
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::analysis::coverage::compare_coverage;
+use hd_fpv_osd_font_tool::osd::tile::index::format_index;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+fn index_list(indices: &[usize]) -> String {
+    indices.iter().copied().map(format_index).collect::<Vec<_>>().join(", ")
+}
+
+pub fn coverage_command(reference: &str, collection: &str) -> anyhow::Result<()> {
+    let reference_tiles = load_tiles(reference, FontProfile::default())?;
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let coverage = compare_coverage(&reference_tiles, &tiles);
+
+    if ! coverage.missing.is_empty() {
+        println!("populated in the reference but empty: {}", index_list(&coverage.missing));
+    }
+    if ! coverage.added.is_empty() {
+        println!("added: {}", index_list(&coverage.added));
+    }
+
+    if coverage.has_regressions() {
+        return Err(anyhow!("{collection} is missing {} tile(s) populated in {reference}", coverage.missing.len()));
+    }
+    log::info!("all the tiles populated in the reference are populated, {} tile(s) added", coverage.added.len());
+    Ok(())
+}
@@ -0,0 +1,40 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::analysis::compare::{compare, side_by_side_image, CompareMode};
+use hd_fpv_osd_font_tool::osd::tile::{index::format_index, Kind as TileKind};
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+pub fn diff_command(first: &str, second: &str, tolerance: Option<u8>, images_dir: Option<&Path>) -> anyhow::Result<()> {
+    let first_tiles = load_tiles(first, FontProfile::default())?;
+    let second_tiles = load_tiles(second, FontProfile::default())?;
+    let mode = match tolerance {
+        Some(tolerance) => CompareMode::Fuzzy { tolerance },
+        None => CompareMode::Exact,
+    };
+    let differences = compare(&first_tiles, &second_tiles, mode)?;
+
+    if first_tiles.len() != second_tiles.len() {
+        println!("{first} has {} tiles, {second} has {} tiles", first_tiles.len(), second_tiles.len());
+    }
+    for difference in &differences {
+        println!("tile {}: {} pixel(s) differ, by up to {}", format_index(difference.index), difference.differing_pixels, difference.max_channel_delta);
+    }
+    log::info!("{} tile(s) differ", differences.len());
+
+    if let Some(images_dir) = images_dir {
+        fs_err::create_dir_all(images_dir)?;
+        let empty_tile = Tile::new(first_tiles.first().or_else(|| second_tiles.first()).map_or(TileKind::SD, Tile::kind));
+        for difference in &differences {
+            let tile = |tiles: &[Tile]| tiles.get(difference.index).unwrap_or(&empty_tile).clone();
+            let path = images_dir.join(format!("{:03}.png", difference.index));
+            side_by_side_image(&tile(&first_tiles), &tile(&second_tiles), mode).save(path)?;
+        }
+        log::info!("wrote the comparison images to {}", images_dir.to_string_lossy());
+    }
+
+    Ok(())
+}
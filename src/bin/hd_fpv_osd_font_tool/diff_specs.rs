@@ -0,0 +1,26 @@
+
+use std::path::{Path, PathBuf};
+
+use hd_fpv_osd_font_tool::osd::tile::container::symbol::spec_diff::{diff, render_visual_diff};
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+pub fn diff_specs_command(old_specs_file: &Path, new_specs_file: &Path, visual: &Option<PathBuf>, reference_font: &Option<String>) -> anyhow::Result<()> {
+    let changes = diff(&SymbolSpecs::load_file(old_specs_file)?, &SymbolSpecs::load_file(new_specs_file)?);
+
+    for change in &changes {
+        println!("{change}");
+    }
+    log::info!("{} changed symbols", changes.len());
+
+    if let (Some(visual), Some(reference_font)) = (visual, reference_font) {
+        let tiles = load_tiles(reference_font, FontProfile::default())?;
+        let image = render_visual_diff(&changes, &tiles)?;
+        image.save(visual)?;
+        log::info!("wrote visual diff to {}", visual.to_string_lossy());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::osd::tile::{container::uniq_tile_kind::UniqTileKind, digit_style::DigitStyle};
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::{load_tiles, save_tiles};
+
+
+pub fn restyle_digits_command(collection: &str, to: &str, style: DigitStyle, preview: Option<&Path>, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let restyled = style.apply(&tiles)?;
+    save_tiles(&restyled, to, symbol_specs_file)?;
+    log::info!("replaced the digits of {collection} with {style} digits");
+    if let Some(preview) = preview {
+        style.preview(tiles.tile_kind()?).save(preview)?;
+        log::info!("wrote preview of the digits to {}", preview.to_string_lossy());
+    }
+    Ok(())
+}
@@ -0,0 +1,19 @@
+
+use hd_fpv_osd_font_tool::analysis::dust::find_dust;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+pub fn dust_command(collection: &str, max_size: usize) -> anyhow::Result<()> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let dust = find_dust(&tiles, max_size);
+
+    for group in &dust {
+        let pixels = group.pixels.iter().map(|(x, y)| format!("{x},{y}")).collect::<Vec<_>>().join(" ");
+        println!("tile {}: {} stray pixels at {pixels}", group.tile_index, group.pixels.len());
+    }
+    log::info!("found {} groups of stray pixels", dust.len());
+
+    Ok(())
+}
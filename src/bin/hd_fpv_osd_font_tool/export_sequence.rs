@@ -0,0 +1,16 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::osd::tile::container::png_sequence::SaveSymbolsToPngSequence;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+pub fn export_sequence_command<P: AsRef<Path>>(from: &str, output_dir: P, scales: &[u32], symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let tiles = load_tiles(from, FontProfile::default())?;
+    let symbols = tiles.to_symbols(&SymbolSpecs::load_file(symbol_specs_file)?)?;
+    let metadata = symbols.save_to_png_sequence(&output_dir, scales)?;
+    log::info!("exported {} symbols to {}", metadata.frames.len(), output_dir.as_ref().to_string_lossy());
+    Ok(())
+}
@@ -0,0 +1,41 @@
+
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::osd::tile::container::symbol::Symbol;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+// symbol names can contain characters which are not valid in file names, e.g. `mAh/km`
+fn symbol_file_name(symbol_name: &str) -> PathBuf {
+    let file_stem: String = symbol_name.chars()
+        .map(|char| if char.is_ascii_alphanumeric() || char == '-' || char == '_' { char } else { '_' })
+        .collect();
+    PathBuf::from(format!("{file_stem}.png"))
+}
+
+pub fn extract_symbol_command(collection: &str, name: &Option<String>, output: &Path, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let specs = SymbolSpecs::load_file(symbol_specs_file)?;
+
+    match name {
+        Some(name) => {
+            let spec = specs.find_by_name(name)
+                .ok_or_else(|| anyhow!("no symbol named `{name}` in {}", symbol_specs_file.to_string_lossy()))?;
+            Symbol::extract(&tiles, spec)?.save_image_file(output)?;
+        },
+        None => {
+            fs_err::create_dir_all(output)?;
+            for spec in specs.named_specs() {
+                let name = spec.name().as_deref().unwrap();
+                let file_path = output.join(symbol_file_name(name));
+                Symbol::extract(&tiles, spec)?.save_image_file(&file_path)?;
+                log::info!("extracted symbol {name} to {}", file_path.to_string_lossy());
+            }
+        },
+    }
+
+    Ok(())
+}
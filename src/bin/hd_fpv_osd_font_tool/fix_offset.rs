@@ -0,0 +1,43 @@
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::analysis::index_offset::{detect_index_offset, undo_index_offset};
+use hd_fpv_osd_font_tool::osd::tile::container::reindex_tile_dir::reindex_tile_dir;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::{load_tiles, save_tiles, tile_dir_path_and_format};
+
+
+pub struct FixOffsetOptions<'a> {
+    pub max_offset: usize,
+    pub max_distance: u32,
+    pub rename: bool,
+    pub output: Option<&'a str>,
+    pub symbol_specs_file: &'a Path,
+}
+
+pub fn fix_offset_command(reference: &str, collection: &str, options: FixOffsetOptions) -> anyhow::Result<()> {
+    let reference_tiles = load_tiles(reference, FontProfile::default())?;
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let index_offset = detect_index_offset(&reference_tiles, &tiles, options.max_offset, options.max_distance);
+
+    if index_offset.offset == 0 {
+        println!("no offset detected, {} of {} tiles match the reference", index_offset.matching_tiles, index_offset.compared_tiles);
+        return Ok(());
+    }
+    println!("tiles are shifted by {}: {} of {} tiles match the reference once shifted back",
+        index_offset.offset, index_offset.matching_tiles, index_offset.compared_tiles);
+
+    if options.rename {
+        let (dir, format) = tile_dir_path_and_format(collection)
+            .ok_or_else(|| anyhow!("--rename can only be used with tile directories"))?;
+        let renamed_count = reindex_tile_dir(&dir, format, index_offset.offset)?;
+        log::info!("renamed {renamed_count} tile files of {}", dir.to_string_lossy());
+    }
+    if let Some(output) = options.output {
+        save_tiles(&undo_index_offset(&tiles, index_offset.offset), output, options.symbol_specs_file)?;
+        log::info!("wrote repaired collection to {output}");
+    }
+    Ok(())
+}
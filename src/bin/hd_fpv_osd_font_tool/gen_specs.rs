@@ -0,0 +1,30 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::osd::tile::container::symbol::spec_header::generate_specs_from_header;
+
+
+fn write_specs(content: &str, output: Option<&Path>) -> anyhow::Result<()> {
+    match output {
+        Some(output) => {
+            fs_err::write(output, content)?;
+            log::info!("wrote symbol specs to {}", output.to_string_lossy());
+        },
+        None => print!("{content}"),
+    }
+    Ok(())
+}
+
+pub fn gen_specs_command(header_path: &Path, prefix: &str, output: Option<&Path>) -> anyhow::Result<()> {
+    write_specs(&generate_specs_from_header(header_path, prefix)?, output)
+}
+
+pub fn gen_specs_from_symdir_command(dir: &Path, output: Option<&Path>) -> anyhow::Result<()> {
+    let symbols = load_symbols_from_dir(dir, FontProfile::default().tile_count())?;
+    let specs = SymbolSpecs::from_symbols(&symbols);
+    if specs.is_empty() {
+        log::warn!("{} has no multi-tile symbol, the specs are empty", dir.to_string_lossy());
+    }
+    write_specs(&specs.file_content(), output)
+}
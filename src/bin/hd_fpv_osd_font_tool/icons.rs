@@ -0,0 +1,36 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::osd::tile::{container::uniq_tile_kind::UniqTileKind, icons::{apply_icons, load_icon_map}, ttf::{Font, RasterStyle}, Kind as TileKind};
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::{load_tiles, save_tiles};
+
+
+pub struct IconsOptions<'a> {
+    pub font: &'a Path,
+    pub map: &'a Path,
+    pub stroke: u32,
+    pub margin: u32,
+    /// used when there is no base collection
+    pub kind: TileKind,
+    pub symbol_specs_file: &'a Path,
+}
+
+pub fn icons_command(base: Option<&str>, to: &str, options: IconsOptions) -> anyhow::Result<()> {
+    let font = Font::load_file(options.font)?;
+    let mappings = load_icon_map(options.map)?;
+    let (tiles, tile_kind) = match base {
+        Some(base) => {
+            let tiles = load_tiles(base, FontProfile::default())?;
+            let tile_kind = tiles.tile_kind()?;
+            (tiles, tile_kind)
+        },
+        None => (vec![], options.kind),
+    };
+    let style = RasterStyle { margin: options.margin, stroke: options.stroke, ..Default::default() };
+    let tiles = apply_icons(&tiles, tile_kind, &font, &mappings, &style)?;
+    save_tiles(&tiles, to, options.symbol_specs_file)?;
+    log::info!("drew {} icons from {} to {to}", mappings.len(), options.font.to_string_lossy());
+    Ok(())
+}
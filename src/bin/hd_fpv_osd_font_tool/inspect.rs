@@ -0,0 +1,97 @@
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::analysis::{inventory::Inventory, kind_guess::rank_kinds};
+use hd_fpv_osd_font_tool::conversion::{CollectionFormat, CollectionSpec, InvalidSpecError};
+use hd_fpv_osd_font_tool::summary::{paths_sha256, Summary};
+
+use crate::convert::load_tiles;
+
+
+// files without summary are treated as raw RGBA blobs of unknown shape
+fn inspect_blob(path: &Path) -> anyhow::Result<()> {
+    let bytes = fs_err::read(path)?;
+    let guesses = rank_kinds(&bytes);
+    if guesses.is_empty() {
+        return Err(anyhow!("{} has no summary file and is too small to hold a tile of any kind", path.to_string_lossy()));
+    }
+    println!("no summary file, guessing the tile kind of the content read as raw RGBA tiles, most likely first:");
+    for guess in guesses {
+        println!("{}: {} tiles + {} trailing bytes, discontinuity {:.2}", guess.tile_kind, guess.tile_count, guess.trailing_bytes, guess.discontinuity);
+    }
+    Ok(())
+}
+
+// the symbols of symbol directories are loaded as such to report their spans
+fn inspect_collection(collection: &str, spec: &CollectionSpec, json: bool) -> anyhow::Result<()> {
+    let inventory = match spec.format {
+        CollectionFormat::SymbolDir => Inventory::of_symbols(&load_symbols_from_dir(&spec.path, FontProfile::default().tile_count())?),
+        _ => Inventory::of_tiles(&load_tiles(collection, FontProfile::default())?),
+    };
+    if json {
+        println!("{}", inventory.to_json()?);
+        return Ok(());
+    }
+
+    match inventory.tile_kind {
+        Some(tile_kind) => println!("tile kind: {tile_kind}"),
+        None => println!("tile kind: unknown, the collection has no tiles"),
+    }
+    println!("tiles: {}", inventory.tile_count);
+    println!("non-empty tiles: {}", inventory.non_empty_tile_count);
+    println!("trailing empty tiles: {}", inventory.trailing_empty_tile_count);
+    if let Some(symbols) = &inventory.symbols {
+        let multi_tile_symbols: Vec<String> = symbols.iter()
+            .filter(|symbol| symbol.span > 1)
+            .map(|symbol| format!("{}-{}", symbol.start_tile_index, symbol.start_tile_index + symbol.span - 1))
+            .collect();
+        println!("symbols: {}", symbols.len());
+        if ! multi_tile_symbols.is_empty() {
+            println!("multi-tile symbols: {}", multi_tile_symbols.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Prints the content of a collection when `target` is a tile collection specification, the provenance recorded in its
+/// summary file otherwise
+pub fn inspect_command(target: &str, json: bool) -> anyhow::Result<()> {
+    let output = Path::new(target);
+    match target.parse::<CollectionSpec>() {
+        Ok(spec) => return inspect_collection(target, &spec, json),
+        // `C:\fonts` style paths are not specs with an invalid prefix
+        Err(error) if ! matches!(error, InvalidSpecError::NoPrefix) && ! output.exists() => return Err(anyhow!("invalid collection specification `{target}`: {error}")),
+        Err(_) => {},
+    }
+
+    let summary = match Summary::load(output)? {
+        Some(summary) => summary,
+        None if json => return Err(anyhow!("{} has no summary file, only the tile kind guesses of raw files can be printed, without `--json`", output.to_string_lossy())),
+        None if output.is_file() => return inspect_blob(output),
+        None => return Err(anyhow!("{} has no summary file", output.to_string_lossy())),
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    println!("created by version {} of the tool at {} (seconds since the UNIX epoch)", summary.tool_version, summary.created_at);
+    println!("source: {}", summary.source);
+    if let Some(source_sha256) = &summary.source_sha256 {
+        println!("source checksum: {source_sha256}");
+    }
+    println!("destination: {}", summary.destination);
+    println!("arguments: {}", summary.arguments.join(" "));
+    if let Some(symbol_specs_file) = &summary.symbol_specs_file {
+        println!("symbol specs file: {}", symbol_specs_file.to_string_lossy());
+        if let (Some(recorded), Ok(current)) = (&summary.symbol_specs_sha256, paths_sha256(&[symbol_specs_file])) {
+            if *recorded != current {
+                log::warn!("{} has been modified since the conversion", symbol_specs_file.to_string_lossy());
+            }
+        }
+    }
+
+    Ok(())
+}
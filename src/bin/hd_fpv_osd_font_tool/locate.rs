@@ -0,0 +1,17 @@
+
+use hd_fpv_osd_font_tool::osd::bin_file::location::locate_tile;
+use hd_fpv_osd_font_tool::osd::tile::{index::format_index, Kind as TileKind};
+
+
+pub fn locate_command(index: usize, kind: Option<TileKind>, ident: &Option<String>) -> anyhow::Result<()> {
+    let tile_kinds = match kind {
+        Some(tile_kind) => vec![tile_kind],
+        None => vec![TileKind::SD, TileKind::HD],
+    };
+    println!("tile {}", format_index(index));
+    for tile_kind in tile_kinds {
+        let location = locate_tile(index, tile_kind, &ident.as_deref())?;
+        println!("  {tile_kind}: {location}");
+    }
+    Ok(())
+}
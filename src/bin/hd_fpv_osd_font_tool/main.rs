@@ -4,39 +4,130 @@
 use std::env::current_exe;
 use std::{
     io::Write,
+    path::Path,
     process::exit
 };
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use anyhow::anyhow;
 use env_logger::fmt::Color;
 use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::build_info::BuildInfo;
+use hd_fpv_osd_font_tool::man_pages::{self, PageFormat};
+use hd_fpv_osd_font_tool::osd::tile::Kind as TileKind;
+use hd_fpv_osd_font_tool::{conversion::ConversionOptions, conversion_report::ConversionReport, diagnostics, image_encode::ImageEncodeOptions, log_level::LogLevel, retry::RetryPolicy};
 
+mod archive;
+mod audit_determinism;
+mod check;
+mod config;
 mod convert;
 mod convert_set;
-mod man_pages;
+mod coverage;
+mod diff;
+mod diff_specs;
+mod digits;
+mod dust;
+mod export_sequence;
+mod extract_symbol;
+mod fix_offset;
+mod gen_specs;
+mod icons;
+mod inspect;
+mod locate;
+mod migrate;
+mod pages;
+mod palette;
+mod patch;
+mod preview;
+mod project;
+mod region;
+mod sanitize;
+mod show;
+mod similarity;
 mod cli;
 
+use audit_determinism::audit_determinism_command;
+use check::check_command;
+use config::apply_convert_preset;
 use convert::convert_command;
 use convert_set::convert_set_command;
-use man_pages::*;
+use coverage::coverage_command;
+use diff::diff_command;
+use diff_specs::diff_specs_command;
+use digits::restyle_digits_command;
+use dust::dust_command;
+use export_sequence::export_sequence_command;
+use extract_symbol::extract_symbol_command;
+use fix_offset::{fix_offset_command, FixOffsetOptions};
+use gen_specs::{gen_specs_command, gen_specs_from_symdir_command};
+use icons::{icons_command, IconsOptions};
+use inspect::inspect_command;
+use locate::locate_command;
+use migrate::migrate_command;
+use pages::{concat_command, split_command};
+use palette::palette_command;
+use patch::{apply_patch_command, make_patch_command, patch_command};
+use preview::{generate_preview_command, review_sheet_command, Comparison};
+#[cfg(feature = "preview-on-fc")]
+use preview::preview_on_fc_command;
+use project::build_command;
+use region::{export_region_command, import_region_command};
+use sanitize::sanitize_command;
+use show::show_command;
+use similarity::similarity_command;
 use cli::*;
 
 fn current_exe_name() -> anyhow::Result<String> {
     let current_exe = current_exe().map_err(|error| anyhow!("failed to get exe name: {error}"))?;
     Ok(current_exe.file_name().unwrap().to_str().ok_or_else(|| anyhow!("exe file name contains invalid UTF-8 characters"))?.to_string())
 }
-fn generate_man_pages_command() -> anyhow::Result<()> {
-    let current_exe_name = current_exe_name()?;
-    generate_exe_man_page(&current_exe_name)?;
-    generate_man_page_for_subcommands(&current_exe_name)?;
+fn version_command(json: bool) -> anyhow::Result<()> {
+    let build_info = BuildInfo::current();
+    match json {
+        true => println!("{}", build_info.to_json()?),
+        false => println!("{build_info}"),
+    }
+    Ok(())
+}
+
+fn generate_man_pages_command(format: PageFormat, output_dir: &Path) -> anyhow::Result<()> {
+    let exclusions = ["generate-shell-autocompletion-files", "generate-man-pages"];
+    let paths = man_pages::generate(&Cli::command(), &current_exe_name()?, output_dir, format, &exclusions)?;
+    log::info!("{} pages written to {}", paths.len(), output_dir.to_string_lossy());
     Ok(())
 }
 
+#[cfg(feature = "http")]
+fn report_updates(cli: &Cli) {
+    use hd_fpv_osd_font_tool::update_check::check_for_updates;
+    if ! cli.check_updates() {
+        return;
+    }
+    match check_for_updates() {
+        Ok(releases) if releases.is_empty() => log::info!("version {} is the latest release", env!("CARGO_PKG_VERSION")),
+        Ok(releases) => for release in releases {
+            log::warn!("a newer release is available: {} {}", release.tag_name, release.html_url);
+            for change in release.format_changes() {
+                log::warn!("    {change}");
+            }
+        },
+        Err(error) => log::warn!("update check failed: {error}"),
+    }
+}
+
+#[cfg(not(feature = "http"))]
+fn report_updates(_cli: &Cli) {}
+
 fn main() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
 
+    let log_level = if cli.quiet() { LogLevel::Error } else { cli.log_level() };
+
+    // logs always go to stderr so that they never get mixed with output meant for stdout
     env_logger::builder()
+        .target(env_logger::Target::Stderr)
         .format(|buf, record| {
             let level_style = buf.default_level_style(record.level());
             write!(buf, "{:<5}", level_style.value(record.level()))?;
@@ -45,17 +136,161 @@ fn main() {
             write!(buf, "{}", style.value(" > "))?;
             writeln!(buf, "{}", record.args())
         })
-        .parse_filters(cli.log_level().to_string().as_str())
+        .parse_filters(log_level.to_string().as_str())
+        // the detections are logged with their explanation instead of a single line, see `diagnostics::log_event`
+        .filter_module(diagnostics::EXPLANATION_LOG_TARGET, match cli.explain_detection() {
+            true => log::LevelFilter::Info,
+            false => log::LevelFilter::Off,
+        })
         .init();
 
-    let command_result = match &cli.command {
-        Commands::Convert { from, to, symbol_specs_file } => convert_command(from, to, ConvertOptions { symbol_specs_file }),
-        Commands::ConvertSet { from, to, symbol_specs_file } => convert_set_command(from, to, ConvertOptions { symbol_specs_file }),
-        Commands::GenerateManPages => generate_man_pages_command(),
-    };
+    if let Err(error) = apply_convert_preset(&mut cli, &matches) {
+        log::error!("{}", error);
+        exit(1);
+    }
+
+    report_updates(&cli);
 
-    if let Err(error) = command_result {
+    if let Err(error) = run_command(&cli.command) {
         log::error!("{}", error);
         exit(1);
     }
 }
+
+// logs the files written by a conversion and writes its report as JSON if requested
+fn output_report(report: &ConversionReport, report_path: Option<&Path>) -> anyhow::Result<()> {
+    for line in report.to_string().lines() {
+        log::info!("{line}");
+    }
+    match report_path {
+        Some(path) if path == Path::new("-") => println!("{}", report.to_json()?),
+        Some(path) => fs_err::write(path, report.to_json()?)?,
+        None => {},
+    }
+    Ok(())
+}
+
+/// Runs a command once the presets are applied, also used by `build` to run the conversions of a project
+fn run_command(command: &Commands) -> anyhow::Result<()> {
+    match command {
+        Commands::Convert {
+            from: Some(from), to: Some(to), symbol_specs_file, font_profile, background, repage, html_image_map, annotate_symbols, crop, pages, layout, alpha_mode, transform,
+            transform_map, order, shifts, palette, palette_mode, remove_dust, bin_metadata, png_compression, png_filter, color_profile, scaled_variants, scale_filter,
+            scale_to, write_summary, report, split_kinds, copy_unmodified, retries, check, preset: _
+        } => {
+            let options = ConversionOptions {
+                font_profile: *font_profile,
+                symbol_specs_file: Some(symbol_specs_file.clone()),
+                image_encode: ImageEncodeOptions::new(*png_compression, *png_filter, *color_profile),
+                background: *background,
+                repage: *repage,
+                html_image_map: *html_image_map,
+                annotate_symbols: *annotate_symbols,
+                crop: *crop,
+                grid_pages: *pages as usize,
+                grid_layout: *layout,
+                palette: palette.clone(),
+                palette_mode: *palette_mode,
+                bin_metadata_source: bin_metadata.then(|| from.clone()),
+                remove_dust: *remove_dust,
+                transform: *transform,
+                transform_map: transform_map.clone(),
+                order: order.clone(),
+                shifts: shifts.clone(),
+                alpha_mode: *alpha_mode,
+                scaled_variants: scaled_variants.clone(),
+                scale_filter: *scale_filter,
+                scale_to: scale_to.map(TileKind::from),
+                write_summary: *write_summary,
+                split_kinds: *split_kinds,
+                copy_unmodified: *copy_unmodified,
+                retry: RetryPolicy::batch(*retries),
+                ..Default::default()
+            };
+            convert_command(from, to, &options).and_then(|conversion_report| output_report(&conversion_report, report.as_deref())).and_then(|()| match check {
+                Some(target) => check_command(to, *target, &None, &None),
+                None => Ok(()),
+            })
+        },
+        Commands::Convert { .. } => Err(anyhow!("the source and destination collections must be given on the command line or by the preset")),
+        Commands::ConvertSet { from, to, symbol_specs_file, font_profile, background, alpha_mode, auto_swap, allow_partial, retries, bin_metadata, png_compression, png_filter, color_profile, write_summary, report, set_dir_layout } => {
+            let kind_swap_policy = if *auto_swap { KindSwapPolicy::AutoSwap } else { KindSwapPolicy::Reject };
+            let options = ConversionOptions {
+                font_profile: *font_profile,
+                symbol_specs_file: Some(symbol_specs_file.clone()),
+                image_encode: ImageEncodeOptions::new(*png_compression, *png_filter, *color_profile),
+                background: *background,
+                bin_metadata_source: bin_metadata.then(|| from.clone()),
+                alpha_mode: *alpha_mode,
+                write_summary: *write_summary,
+                kind_swap_policy,
+                allow_partial: *allow_partial,
+                set_dir_layout: set_dir_layout.clone(),
+                retry: RetryPolicy::batch(*retries),
+                ..Default::default()
+            };
+            convert_set_command(from, to, &options).and_then(|conversion_report| output_report(&conversion_report, report.as_deref()))
+        },
+        Commands::Check { target, ident, lint_config, collection } => check_command(collection, *target, ident, lint_config),
+        Commands::ExtractSymbol { symbol_specs_file, name, all: _, collection, output } =>
+            extract_symbol_command(collection, name, output, symbol_specs_file),
+        Commands::DiffSpecs { visual, reference_font, old_specs_file, new_specs_file } =>
+            diff_specs_command(old_specs_file, new_specs_file, visual, reference_font),
+        Commands::GenSpecs { from_header, from_symdir, prefix, output } => match from_header {
+            Some(from_header) => gen_specs_command(from_header, prefix, output.as_deref()),
+            None => gen_specs_from_symdir_command(from_symdir.as_deref().unwrap(), output.as_deref()),
+        },
+        Commands::Inspect { json, target } => inspect_command(target, *json),
+        Commands::Dust { max_size, collection } => dust_command(collection, *max_size),
+        Commands::MakePatch { base, modified, patch_file } => make_patch_command(base, modified, patch_file),
+        Commands::ApplyPatch { symbol_specs_file, base, patch_file, to } => apply_patch_command(base, patch_file, to, symbol_specs_file),
+        Commands::Patch { symbol_specs_file, replacements, output, collection } => patch_command(collection, replacements, output, symbol_specs_file),
+        Commands::ExportRegion { symbol_specs_file, region, collection, to } => export_region_command(collection, *region, to, symbol_specs_file),
+        Commands::ImportRegion { symbol_specs_file, region, base, region_source, to } =>
+            import_region_command(base, region_source, *region, to, symbol_specs_file),
+        Commands::Palette { output, collection } => palette_command(collection, output),
+        Commands::ExportSequence { symbol_specs_file, scales, from, output_dir } => export_sequence_command(from, output_dir, scales, symbol_specs_file),
+        Commands::Coverage { reference, collection } => coverage_command(reference, collection),
+        Commands::FixOffset { symbol_specs_file, reference, max_offset, max_distance, rename, output, collection } =>
+            fix_offset_command(reference, collection, FixOffsetOptions {
+                max_offset: *max_offset,
+                max_distance: *max_distance,
+                rename: *rename,
+                output: output.as_deref(),
+                symbol_specs_file,
+            }),
+        Commands::Similarity { max_distance, html, collection } => similarity_command(collection, *max_distance, html),
+        Commands::Build { retries, project_file } => build_command(project_file, *retries),
+        Commands::Split { ident, padding, collections, dir } => split_command(collections, dir, ident.as_deref(), *padding),
+        Commands::Concat { symbol_specs_file, padding, pages, to } => concat_command(pages, to, *padding, symbol_specs_file),
+        Commands::RestyleDigits { symbol_specs_file, style, preview, collection, to } =>
+            restyle_digits_command(collection, to, *style, preview.as_deref(), symbol_specs_file),
+        Commands::Icons { symbol_specs_file, font, map, stroke, margin, kind, base, to } =>
+            icons_command(base.as_deref(), to, IconsOptions { font, map, stroke: *stroke, margin: *margin, kind: TileKind::from(*kind), symbol_specs_file }),
+        Commands::Show { collection, index, symbol_specs_file, protocol, scale } =>
+            show_command(collection, *index, symbol_specs_file.as_deref(), *protocol, *scale),
+        Commands::Diff { tolerance, images, first, second } => diff_command(first, second, *tolerance, images.as_deref()),
+        Commands::Locate { index, kind, ident } => locate_command(*index, kind.map(TileKind::from), ident),
+        Commands::GeneratePreview { symbol_specs_file, grid, background, compare_with, comparison, toggle_interval, layout_file, collection, output } => {
+            let comparison = compare_with.as_deref().map(|compare_with| Comparison {
+                collection: compare_with,
+                mode: *comparison,
+                toggle_interval: std::time::Duration::from_millis(*toggle_interval),
+            });
+            generate_preview_command(collection, layout_file, symbol_specs_file.as_deref(), *grid, *background, comparison, output)
+        },
+        Commands::ReviewSheet { notes, background, collection, output } => review_sheet_command(collection, notes, *background, output),
+        #[cfg(feature = "preview-on-fc")]
+        Commands::PreviewOnFc { baud_rate, grid, interval, cycles, port, collection } =>
+            preview_on_fc_command(collection, port, *baud_rate, *grid, std::time::Duration::from_secs(*interval), *cycles),
+        Commands::Sanitize { symbol_specs_file, font_profile, no_license_check, from, to } =>
+            sanitize_command(from, to, *font_profile, ! no_license_check, symbol_specs_file),
+        Commands::Migrate { files } => migrate_command(files),
+        Commands::AuditDeterminism { symbol_specs_file, formats, bin_metadata, write_summary, png_compression, png_filter, color_profile, source } => {
+            let image_encode = ImageEncodeOptions::new(*png_compression, *png_filter, *color_profile);
+            audit_determinism_command(source, formats, symbol_specs_file, &image_encode, *bin_metadata, *write_summary)
+        },
+        Commands::Version { json } => version_command(*json),
+        Commands::GenerateManPages { format, output_dir } => generate_man_pages_command(*format, output_dir),
+    }
+}
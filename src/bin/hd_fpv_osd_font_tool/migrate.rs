@@ -0,0 +1,41 @@
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::migrate::Migration;
+use hd_fpv_osd_font_tool::osd::tile::container::symbol::spec;
+
+use crate::project::{self, Project};
+
+
+// project files are recognized by their name or by their `fonts` key, the other files are symbol specs files
+fn is_project_file(path: &Path) -> anyhow::Result<bool> {
+    if path.file_name().is_some_and(|file_name| file_name == project::DEFAULT_FILE_NAME) {
+        return Ok(true);
+    }
+    let content: serde_yaml::Value = serde_yaml::from_reader(fs_err::File::open(path)?)
+        .map_err(|error| anyhow!("failed to parse {}: {error}", path.to_string_lossy()))?;
+    Ok(content.get("fonts").is_some())
+}
+
+fn print_migration(migration: &Migration) {
+    println!("{}: upgraded from version {} to version {}, the original file is kept in {}", migration.file_path.to_string_lossy(),
+        migration.from_version, migration.to_version, migration.backup_path.to_string_lossy());
+}
+
+pub fn migrate_command(files: &[impl AsRef<Path>]) -> anyhow::Result<()> {
+    for path in files {
+        let path = path.as_ref();
+        if is_project_file(path)? {
+            // there is no older project file version yet, the file is only verified to be readable
+            let project = Project::load_file(path)?;
+            println!("{}: project file version {} is current", path.to_string_lossy(), project.version());
+            continue;
+        }
+        match spec::migrate_file(path)? {
+            Some(migration) => print_migration(&migration),
+            None => println!("{}: symbol specs file version {} is current", path.to_string_lossy(), spec::FILE_VERSION),
+        }
+    }
+    Ok(())
+}
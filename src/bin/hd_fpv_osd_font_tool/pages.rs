@@ -0,0 +1,40 @@
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::osd::bin_file::{pages::{self, PaddingPolicy}, FontPart};
+use hd_fpv_osd_font_tool::osd::tile::container::uniq_tile_kind::UniqTileKind;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::{load_tiles, save_tiles};
+
+
+/// Writes each collection as a normalized pair of base and `_2` bin files, two collections of different kinds making a
+/// four files set
+pub fn split_command(collections: &[String], dir: &Path, ident: Option<&str>, padding: PaddingPolicy) -> anyhow::Result<()> {
+    let mut written_kinds = vec![];
+    for collection in collections {
+        let tiles = load_tiles(collection, FontProfile::default())?;
+        let tile_kind = tiles.tile_kind()?;
+        if written_kinds.contains(&tile_kind) {
+            return Err(anyhow!("{collection} is a second {tile_kind} collection, a set is made of one SD and one HD collection"));
+        }
+        let pages = pages::split(&tiles, 2, padding)?;
+        let ident = &ident;
+        pages[0].as_slice().save_to_bin_file_norm(dir, ident, FontPart::Base)?;
+        pages[1].as_slice().save_to_bin_file_norm(dir, ident, FontPart::Ext)?;
+        log::info!("{collection} -> {} + {}",
+            bin_file::normalized_file_path(dir, tile_kind, ident, FontPart::Base).to_string_lossy(),
+            bin_file::normalized_file_path(dir, tile_kind, ident, FontPart::Ext).to_string_lossy());
+        written_kinds.push(tile_kind);
+    }
+    Ok(())
+}
+
+pub fn concat_command(pages: &[String], to: &str, padding: PaddingPolicy, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let page_tiles = pages.iter().map(|page| load_tiles(page, FontProfile::default())).collect::<Result<Vec<_>, _>>()?;
+    let tiles = pages::concat(&page_tiles, padding)?;
+    save_tiles(&tiles, to, symbol_specs_file)?;
+    log::info!("concatenated {} pages into {} tiles", pages.len(), tiles.len());
+    Ok(())
+}
@@ -0,0 +1,28 @@
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use hd_fpv_osd_font_tool::analysis::palette::extract_colors;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+pub fn palette_command(collection: &str, output: &Option<PathBuf>) -> anyhow::Result<()> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let colors = extract_colors(&tiles);
+
+    let mut palette = String::new();
+    for (color, pixel_count) in &colors {
+        writeln!(palette, "{color} {pixel_count}").unwrap();
+    }
+    print!("{palette}");
+    log::info!("{} colors used", colors.len());
+
+    if let Some(output) = output {
+        fs_err::write(output, palette)?;
+        log::info!("wrote palette to {}", output.to_string_lossy());
+    }
+
+    Ok(())
+}
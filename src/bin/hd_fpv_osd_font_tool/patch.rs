@@ -0,0 +1,41 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::osd::tile::container::{edit::TileReplacement, patch::Patch};
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::{load_tiles, save_tiles};
+
+
+pub fn make_patch_command<P: AsRef<Path>>(base: &str, modified: &str, patch_file: P) -> anyhow::Result<()> {
+    let base_tiles = load_tiles(base, FontProfile::default())?;
+    let modified_tiles = load_tiles(modified, FontProfile::default())?;
+    let patch = Patch::diff(&base_tiles, &modified_tiles)?;
+    patch.save_file(&patch_file)?;
+    log::info!("wrote patch of {} tiles to {}", patch.tiles().len(), patch_file.as_ref().to_string_lossy());
+    Ok(())
+}
+
+pub fn apply_patch_command<P: AsRef<Path>>(base: &str, patch_file: P, to: &str, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let base_tiles = load_tiles(base, FontProfile::default())?;
+    let patch = Patch::load_file(&patch_file)?;
+    let patched = patch.apply(&base_tiles)?;
+    if ! patched.base_matches {
+        log::warn!("{base} is not the collection the patch has been made from, the patched tiles may not fit");
+    }
+    save_tiles(&patched.tiles, to, symbol_specs_file)?;
+    log::info!("applied patch of {} tiles", patch.tiles().len());
+    Ok(())
+}
+
+pub fn patch_command(collection: &str, replacements: &[String], to: &str, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    // all the replacements are checked before loading anything
+    let replacements = replacements.iter().map(|replacement| replacement.parse()).collect::<Result<Vec<TileReplacement>, _>>()?;
+    let mut tiles = load_tiles(collection, FontProfile::default())?;
+    for replacement in &replacements {
+        replacement.apply(&mut tiles)?;
+    }
+    save_tiles(&tiles, to, symbol_specs_file)?;
+    log::info!("replaced {} tiles", replacements.iter().map(|replacement| replacement.tile_index_range.len()).sum::<usize>());
+    Ok(())
+}
@@ -0,0 +1,80 @@
+
+use std::path::Path;
+use std::time::Duration;
+
+use hd_fpv_osd_font_tool::background::Background;
+use hd_fpv_osd_font_tool::osd::preview::{self, ComparisonMode, Layout, ScreenGrid, DEFAULT_BACKGROUND};
+use hd_fpv_osd_font_tool::osd::tile::container::uniq_tile_kind::UniqTileKind;
+use hd_fpv_osd_font_tool::osd::tile::grid::review::load_review_notes;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+/// Second font a preview is compared with
+pub struct Comparison<'a> {
+    pub collection: &'a str,
+    pub mode: ComparisonMode,
+    pub toggle_interval: Duration,
+}
+
+// the screen grid defaults to the one matching the kind of the tiles of each font
+fn render_preview(collection: &str, layout: &Layout, grid: Option<ScreenGrid>, background: &Background) -> anyhow::Result<(preview::Image, ScreenGrid)> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let grid = match grid {
+        Some(grid) => grid,
+        None => ScreenGrid::for_tile_kind(tiles.tile_kind()?),
+    };
+    Ok((preview::render(&tiles, layout, grid, background)?, grid))
+}
+
+pub fn generate_preview_command(collection: &str, layout_file: &Path, symbol_specs_file: Option<&Path>, grid: Option<ScreenGrid>,
+        background: Option<Background>, comparison: Option<Comparison>, output: &Path) -> anyhow::Result<()> {
+    let specs = symbol_specs_file.map(SymbolSpecs::load_file).transpose()?;
+    let layout = Layout::load_file(layout_file, specs.as_ref())?;
+    let background = background.unwrap_or(DEFAULT_BACKGROUND);
+    let (image, screen_grid) = render_preview(collection, &layout, grid, &background)?;
+    let comparison = match comparison {
+        Some(comparison) => comparison,
+        None => {
+            image.save(output)?;
+            log::info!("preview of {collection} on a {}x{} screen written to {}", screen_grid.columns(), screen_grid.rows(), output.to_string_lossy());
+            return Ok(());
+        },
+    };
+
+    let (other_image, _) = render_preview(comparison.collection, &layout, grid, &background)?;
+    match comparison.mode {
+        ComparisonMode::SideBySide => preview::side_by_side(&image, &other_image).save(output)?,
+        ComparisonMode::Toggle => preview::write_toggle_gif(output, &[image, other_image], comparison.toggle_interval)?,
+    }
+    log::info!("comparison of {collection} and {} written to {}", comparison.collection, output.to_string_lossy());
+    Ok(())
+}
+
+pub fn review_sheet_command(collection: &str, notes_file: &Path, background: Option<Background>, output: &Path) -> anyhow::Result<()> {
+    let notes = load_review_notes(notes_file)?;
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let outside_notes = notes.iter().filter(|note| note.tile_index >= tiles.len()).count();
+    if outside_notes > 0 {
+        log::warn!("{outside_notes} notes are about tiles past the {} tiles of {collection}", tiles.len());
+    }
+    tiles.as_slice().into_tile_grid().save_review_sheet(output, &TileGridStyle::default(), background.as_ref(), &notes)?;
+    log::info!("review sheet of {} notes written to {}", notes.len(), output.to_string_lossy());
+    Ok(())
+}
+
+#[cfg(feature = "preview-on-fc")]
+pub fn preview_on_fc_command(collection: &str, port: &str, baud_rate: u32, grid: Option<ScreenGrid>, interval: Duration, cycles: usize) -> anyhow::Result<()> {
+    use hd_fpv_osd_font_tool::fc_preview::{open_serial_port, FcPreview};
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let grid = match grid {
+        Some(grid) => grid,
+        None => ScreenGrid::for_tile_kind(tiles.tile_kind()?),
+    };
+    let mut preview = FcPreview::new(open_serial_port(port, baud_rate)?, grid);
+    preview.cycle_pages(tiles.len(), interval, cycles)?;
+    preview.release()?;
+    log::info!("{} tiles of {collection} shown on {port}", tiles.len());
+    Ok(())
+}
@@ -0,0 +1,197 @@
+
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::{CommandFactory, FromArgMatches};
+use hd_fpv_osd_font_tool::check::{self, Target};
+use hd_fpv_osd_font_tool::osd::tile::container::{repage::split_pages, uniq_tile_kind::UniqTileKind};
+use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::retry::RetryPolicy;
+use serde::Deserialize;
+
+use crate::cli::Cli;
+use crate::config::Preset;
+use crate::convert::{load_tiles_with_retry, save_tiles_with_retry};
+
+
+pub const DEFAULT_FILE_NAME: &str = "fontproj.yaml";
+
+/// Schema version of the project files, the files without the `version` key are version 1
+pub const FILE_VERSION: u32 = 1;
+
+// directory of the tiles converted from the source of each font with its options, inside the output directory
+const WORK_DIR_NAME: &str = ".work";
+
+fn default_version() -> u32 {
+    1
+}
+
+fn default_sources() -> PathBuf {
+    PathBuf::from(".")
+}
+
+fn default_output() -> PathBuf {
+    PathBuf::from("build")
+}
+
+/// Font project file describing how to build a font pack, e.g.:
+///
+/// ```yaml
+/// sources: sources
+/// output: build
+/// symbol-specs-file: symbol_specs/ardu.yaml
+/// fonts:
+///   - name: ardu_hd
+///     source: tilegrid:ardu_hd.png
+///     ident: ardu
+///     targets: [dji-wtfos, avatar]
+///     options:
+///       transform-map: arrows.map
+/// ```
+///
+/// The paths are relative to the directory of the project file, the paths of the sources are relative to the `sources`
+/// directory. The options have the same keys as the presets of the `convert` command.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Project {
+    #[serde(default = "default_version")]
+    version: u32,
+    #[serde(default = "default_sources")]
+    sources: PathBuf,
+    #[serde(default = "default_output")]
+    output: PathBuf,
+    symbol_specs_file: Option<PathBuf>,
+    #[serde(default)]
+    fonts: Vec<ProjectFont>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ProjectFont {
+    name: String,
+    /// tile collection specification, see `convert` help
+    source: String,
+    ident: Option<String>,
+    #[serde(default)]
+    targets: Vec<Target>,
+    #[serde(default)]
+    options: Preset,
+}
+
+impl Project {
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let project: Self = serde_yaml::from_reader(fs_err::File::open(path.as_ref())?)
+            .map_err(|error| anyhow!("failed to parse project file {}: {error}", path.as_ref().to_string_lossy()))?;
+        if project.version > FILE_VERSION {
+            return Err(anyhow!("project file {} has version {} which is newer than the supported version {FILE_VERSION}",
+                path.as_ref().to_string_lossy(), project.version));
+        }
+        Ok(project)
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn source_spec(&self, font: &ProjectFont) -> anyhow::Result<String> {
+        let (prefix, path) = font.source.split_once(':')
+            .ok_or_else(|| anyhow!("font `{}`: source `{}` has no prefix", font.name, font.source))?;
+        Ok(format!("{prefix}:{}", self.sources.join(path).to_string_lossy()))
+    }
+
+}
+
+// converts the source of the font with its options to a tile directory through the `convert` command
+fn convert_font(project: &Project, font: &ProjectFont, work_dir: &Path, retries: u32) -> anyhow::Result<String> {
+    let work_spec = format!("tiledir:{}", work_dir.join(&font.name).to_string_lossy());
+    let mut args = vec!["hd_fpv_osd_font_tool".to_owned(), "convert".to_owned(), "--retries".to_owned(), retries.to_string()];
+    if let (Some(symbol_specs_file), None) = (&project.symbol_specs_file, font.options.symbol_specs_file()) {
+        args.extend(["--symbol-specs-file".to_owned(), symbol_specs_file.to_string_lossy().into_owned()]);
+    }
+    args.extend([project.source_spec(font)?, work_spec.clone()]);
+
+    let matches = Cli::command().try_get_matches_from(args)?;
+    let mut cli = Cli::from_arg_matches(&matches)?;
+    let convert_matches = matches.subcommand_matches("convert").expect("convert command matched");
+    font.options.apply(&mut cli.command, convert_matches)
+        .map_err(|error| anyhow!("font `{}`: {error}", font.name))?;
+    crate::run_command(&cli.command)?;
+    Ok(work_spec)
+}
+
+fn write_target_files(tiles: &[Tile], target: Target, ident: &Option<&str>, dir: &Path, symbol_specs_file: &Path, retry: RetryPolicy)
+        -> anyhow::Result<Vec<PathBuf>> {
+    let file_paths = target.output_file_names(tiles.tile_kind()?, ident).into_iter().map(|file_name| dir.join(file_name)).collect::<Vec<_>>();
+    let path_spec = |prefix: &str, path: &Path| format!("{prefix}:{}", path.to_string_lossy());
+    match target {
+        Target::DjiWtfos => {
+            let pages = split_pages(tiles, bin_file::TILE_COUNT)?;
+            for (page, file_path) in pages.iter().zip(&file_paths) {
+                save_tiles_with_retry(page, &path_spec("djibin", file_path), symbol_specs_file, retry)?;
+            }
+            Ok(file_paths.into_iter().take(pages.len()).collect())
+        },
+        Target::Avatar => {
+            save_tiles_with_retry(tiles, &path_spec("avatar", &file_paths[0]), symbol_specs_file, retry)?;
+            Ok(file_paths)
+        },
+        Target::Hdzero => Err(anyhow!("writing {target} fonts is not supported")),
+    }
+}
+
+/// Builds every font of the project for each of its targets, the fonts are checked against the targets first
+///
+/// The file operations failing with a transient IO error are attempted `retries` more times.
+pub fn build_command(project_file: &Path, retries: u32) -> anyhow::Result<()> {
+    let retry = RetryPolicy::batch(retries);
+    let project = Project::load_file(project_file)?;
+    let project_dir = project_file.parent().filter(|dir| ! dir.as_os_str().is_empty()).unwrap_or_else(|| Path::new("."));
+    std::env::set_current_dir(project_dir)
+        .map_err(|error| anyhow!("failed to enter project directory {}: {error}", project_dir.to_string_lossy()))?;
+
+    let work_dir = project.output.join(WORK_DIR_NAME);
+    let symbol_specs_file = project.symbol_specs_file.clone().unwrap_or_else(|| PathBuf::from("sym_specs.yaml"));
+    for font in &project.fonts {
+        log::info!("building font `{}`", font.name);
+        let tiles = load_tiles_with_retry(&convert_font(&project, font, &work_dir, retries)?, FontProfile::default(), retry)?;
+        let ident = font.ident.as_deref();
+        for target in &font.targets {
+            let report = check::check(&tiles, *target, &ident);
+            for issue in &report.issues {
+                println!("{}: {issue}", font.name);
+            }
+            if report.has_errors() {
+                return Err(anyhow!("font `{}` cannot be used on {target}: {} error(s)", font.name, report.error_count()));
+            }
+            let target_dir = project.output.join(target.to_string());
+            for file_path in write_target_files(&tiles, *target, &ident, &target_dir, &symbol_specs_file, retry)? {
+                log::info!("wrote {}", file_path.to_string_lossy());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Project;
+
+    #[test]
+    fn parse_project() {
+        let project: Project = serde_yaml::from_str(concat!(
+            "sources: sources\n",
+            "fonts:\n",
+            "  - name: ardu_hd\n",
+            "    source: tilegrid:ardu_hd.png\n",
+            "    targets: [dji-wtfos, avatar]\n",
+            "    options:\n",
+            "      transform-map: arrows.map\n",
+        )).unwrap();
+        assert_eq!(project.version, 1);
+        assert_eq!(project.output.to_str(), Some("build"));
+        assert_eq!(project.fonts[0].targets.len(), 2);
+        assert_eq!(project.source_spec(&project.fonts[0]).unwrap(), format!("tilegrid:{}", std::path::Path::new("sources").join("ardu_hd.png").to_string_lossy()));
+        assert!(serde_yaml::from_str::<Project>("fonts:\n  - name: a\n    source: djibin:a.bin\n    unknown: 1\n").is_err());
+    }
+}
@@ -0,0 +1,35 @@
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::conversion::CollectionSpec;
+use hd_fpv_osd_font_tool::osd::tile::container::region::Region;
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::{load_tiles, save_tiles};
+
+
+pub fn export_region_command(collection: &str, region: Region, to: &str, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let region_tiles = region.extract(&tiles)?;
+    // a plain path is a region image which records the region, otherwise the tiles are saved as a collection
+    match to.parse::<CollectionSpec>() {
+        Ok(_) => save_tiles(&region_tiles, to, symbol_specs_file)?,
+        Err(_) => region.save_image(&region_tiles, to)?,
+    }
+    log::info!("exported the {} tiles of region {region} to {to}", region_tiles.len());
+    Ok(())
+}
+
+pub fn import_region_command(base: &str, region_source: &str, region: Option<Region>, to: &str, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let base_tiles = load_tiles(base, FontProfile::default())?;
+    let (region, region_tiles) = match (region_source.parse::<CollectionSpec>(), region) {
+        (Ok(_), Some(region)) => (region, load_tiles(region_source, FontProfile::default())?),
+        (Ok(_), None) => return Err(anyhow!("the region has to be given to import the tiles of the {region_source} collection")),
+        (Err(_), region) => Region::load_image(region_source, region)?,
+    };
+    let tiles = region.insert(&base_tiles, &region_tiles)?;
+    save_tiles(&tiles, to, symbol_specs_file)?;
+    log::info!("imported the {} tiles of region {region} from {region_source}", region_tiles.len());
+    Ok(())
+}
@@ -0,0 +1,70 @@
+
+use std::path::{Path, PathBuf};
+
+use hd_fpv_osd_font_tool::conversion::CollectionSpec;
+use hd_fpv_osd_font_tool::prelude::*;
+use thiserror::Error;
+
+use crate::convert::{load_tiles, save_tiles, ConvertError};
+
+
+// names of the license files found in font collections, looked for in this order
+const LICENSE_FILE_NAMES: &[&str] = &["LICENSE", "LICENSE.txt", "LICENSE.md", "LICENCE", "LICENCE.txt", "COPYING", "COPYING.txt"];
+
+#[derive(Debug, Error)]
+pub enum SanitizeError {
+    #[error("no license file found in or next to {}, looked for {}: add one or skip the check with --no-license-check",
+        .from_path.to_string_lossy(), LICENSE_FILE_NAMES.join(", "))]
+    MissingLicense { from_path: PathBuf },
+}
+
+// directory holding a file output or the directory output itself, where the license file goes
+fn collection_dir(path: &Path) -> &Path {
+    match path.is_dir() {
+        true => path,
+        false => path.parent().filter(|parent| ! parent.as_os_str().is_empty()).unwrap_or_else(|| Path::new(".")),
+    }
+}
+
+fn find_license_file(from_path: &Path) -> Option<PathBuf> {
+    let mut dirs = vec![collection_dir(from_path)];
+    if from_path.is_dir() {
+        dirs.extend(from_path.parent().filter(|parent| ! parent.as_os_str().is_empty()));
+    }
+    dirs.iter().flat_map(|dir| LICENSE_FILE_NAMES.iter().map(move |name| dir.join(name))).find(|path| path.is_file())
+}
+
+/// Writes a copy of a collection ready to be redistributed
+///
+/// Only the pixels of the tiles are kept: the sidecar files, summaries, text chunks and color profiles of the source are
+/// left behind, the images are encoded with the default options and the tiles past the font profile tile count are
+/// dropped. The license file of the source is copied along.
+pub fn sanitize_command(from: &str, to: &str, font_profile: FontProfile, license_check: bool, symbol_specs_file: &Path) -> anyhow::Result<()> {
+    let from_path = from.parse::<CollectionSpec>().map_err(ConvertError::FromArg)?.path;
+    let to_path = to.parse::<CollectionSpec>().map_err(ConvertError::ToArg)?.path;
+    let license_file = find_license_file(&from_path);
+    if license_file.is_none() && license_check {
+        return Err(SanitizeError::MissingLicense { from_path }.into());
+    }
+
+    let mut tiles = load_tiles(from, font_profile)?;
+    if tiles.len() > font_profile.tile_count() {
+        log::info!("dropping the {} tiles past the {} tiles of the {font_profile} font profile", tiles.len() - font_profile.tile_count(), font_profile.tile_count());
+        tiles.truncate(font_profile.tile_count());
+    }
+    save_tiles(&tiles, to, symbol_specs_file)?;
+
+    match license_file {
+        Some(license_file) => {
+            let license_copy = collection_dir(&to_path).join(license_file.file_name().unwrap());
+            // the destination may be next to the source, copying a file over itself truncates it
+            if license_copy.canonicalize().ok() != license_file.canonicalize().ok() {
+                fs_err::copy(&license_file, &license_copy)?;
+                log::info!("copied license file {} to {}", license_file.to_string_lossy(), license_copy.to_string_lossy());
+            }
+        },
+        None => log::warn!("no license file found for {from}, the copy can not be redistributed as is"),
+    }
+    log::info!("sanitized {from} -> {to}");
+    Ok(())
+}
@@ -0,0 +1,37 @@
+
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::osd::tile::{container::symbol::Symbol, index::format_index};
+use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::terminal_graphics::{self, Protocol};
+
+use crate::convert::load_tiles;
+
+
+pub fn show_command(collection: &str, index: usize, symbol_specs_file: Option<&Path>, protocol: Protocol, scale: u32) -> anyhow::Result<()> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let tile = tiles.get(index)
+        .ok_or_else(|| anyhow!("{collection} has no tile at index {}, it has {} tiles", format_index(index), tiles.len()))?;
+
+    let specs = symbol_specs_file.map(SymbolSpecs::load_file).transpose()?;
+    let spec = specs.as_ref().and_then(|specs| specs.find_containing_index(index));
+    let image = match spec {
+        Some(spec) => {
+            let name = spec.name().as_deref().unwrap_or("unnamed");
+            println!("tile {} is part of symbol {name} starting at tile {} spanning {} tile(s)",
+                format_index(index), format_index(spec.start_tile_index()), spec.span());
+            Symbol::extract(&tiles, spec)?.generate_image()
+        },
+        None => {
+            println!("tile {}", format_index(index));
+            tile.image().clone()
+        },
+    };
+
+    let mut stdout = std::io::stdout();
+    stdout.write_all(terminal_graphics::render(&image, protocol, scale).as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,30 @@
+
+use std::path::PathBuf;
+
+use hd_fpv_osd_font_tool::analysis::similarity::{find_clusters, html_report};
+use hd_fpv_osd_font_tool::prelude::*;
+
+use crate::convert::load_tiles;
+
+
+pub fn similarity_command(collection: &str, max_distance: u32, html: &Option<PathBuf>) -> anyhow::Result<()> {
+    let tiles = load_tiles(collection, FontProfile::default())?;
+    let clusters = find_clusters(&tiles, max_distance);
+
+    for cluster in &clusters {
+        let indices = cluster.tile_indices.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        if cluster.identical {
+            println!("identical tiles: {indices}");
+        } else {
+            println!("near-duplicate tiles (distance up to {}): {indices}", cluster.max_distance);
+        }
+    }
+    log::info!("found {} groups of similar tiles", clusters.len());
+
+    if let Some(html_path) = html {
+        fs_err::write(html_path, html_report(&tiles, &clusters)?)?;
+        log::info!("wrote similarity report to {}", html_path.to_string_lossy());
+    }
+
+    Ok(())
+}
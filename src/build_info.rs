@@ -0,0 +1,83 @@
+
+//! Capabilities of the build: version, commit, enabled optional features and supported collection formats
+//!
+//! Meant for bug reports and automation which need to know what the installed binary can do. The commit hash is
+//! recorded by the build script when building from a git checkout.
+
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::collection_format::{SpecFormat, TILE_COLLECTION_FORMATS, TILE_SET_FORMATS};
+
+
+/// Optional features of the crate and whether they are enabled in this build
+const FEATURES: &[(&str, bool)] = &[
+    ("rayon", cfg!(feature = "rayon")),
+    ("samples", cfg!(feature = "samples")),
+    ("http", cfg!(feature = "http")),
+    ("preview-on-fc", cfg!(feature = "preview-on-fc")),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub git_hash: Option<&'static str>,
+    pub features: Vec<&'static str>,
+    /// prefixes of the collection specifications of the `convert` command
+    pub collection_formats: Vec<&'static str>,
+    /// prefixes of the tile set specifications of the `convert-set` command
+    pub tile_set_formats: Vec<&'static str>,
+}
+
+fn prefixes(formats: &[SpecFormat]) -> Vec<&'static str> {
+    formats.iter().map(|format| format.prefix).collect()
+}
+
+impl BuildInfo {
+
+    pub fn current() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: option_env!("HD_FPV_OSD_FONT_TOOL_GIT_HASH"),
+            features: FEATURES.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect(),
+            collection_formats: prefixes(TILE_COLLECTION_FORMATS),
+            tile_set_formats: prefixes(TILE_SET_FORMATS),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+}
+
+impl Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.git_hash {
+            Some(git_hash) => writeln!(f, "{} {} ({git_hash})", self.name, self.version)?,
+            None => writeln!(f, "{} {}", self.name, self.version)?,
+        }
+        let list = |items: &[&str]| if items.is_empty() { "none".to_owned() } else { items.join(", ") };
+        writeln!(f, "features: {}", list(&self.features))?;
+        writeln!(f, "collection formats: {}", list(&self.collection_formats))?;
+        write!(f, "tile set formats: {}", list(&self.tile_set_formats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildInfo;
+
+    #[test]
+    fn current_build() {
+        let build_info = BuildInfo::current();
+        assert_eq!(build_info.version, env!("CARGO_PKG_VERSION"));
+        assert!(build_info.collection_formats.contains(&"tiledir"));
+        assert_eq!(build_info.features.contains(&"rayon"), cfg!(feature = "rayon"));
+        assert!(build_info.to_string().lines().nth(2).unwrap().starts_with("collection formats: djibin, "));
+        assert!(build_info.to_json().unwrap().contains("\"tile_set_formats\""));
+    }
+}
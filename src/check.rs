@@ -0,0 +1,374 @@
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use derive_more::From;
+use fs_err::File;
+use image::Rgba;
+use serde::Deserialize;
+use strum::Display as StrumDisplay;
+use thiserror::Error;
+
+use crate::osd::{
+    avatar_file,
+    bin_file::{self, FontPart},
+    font_profile::FontProfile,
+    tile::{
+        Tile,
+        Kind as TileKind,
+        container::uniq_tile_kind::{TileKindError, UniqTileKind},
+        index::format_index,
+    },
+};
+
+
+/// System the font is meant to be used on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, StrumDisplay, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Target {
+    #[value(name = "dji-wtfos")]
+    #[strum(serialize = "dji-wtfos")]
+    DjiWtfos,
+    #[strum(serialize = "avatar")]
+    Avatar,
+    #[strum(serialize = "hdzero")]
+    Hdzero,
+}
+
+impl Target {
+
+    /// Luminance levels displayed cleanly, the video range of 16 to 235 is used for the systems which encode the OSD
+    /// with the video where the levels outside of it shimmer or bleed into the neighbouring pixels
+    pub const fn luminance_range(&self) -> LuminanceRange {
+        match self {
+            Target::DjiWtfos => LuminanceRange { min: 0, max: 255 },
+            Target::Avatar | Target::Hdzero => LuminanceRange { min: 16, max: 235 },
+        }
+    }
+
+    pub const fn font_profile(&self) -> FontProfile {
+        match self {
+            Target::DjiWtfos => FontProfile::DJI_2PAGE,
+            Target::Avatar => FontProfile::AVATAR_1PAGE,
+            Target::Hdzero => FontProfile::HDZERO,
+        }
+    }
+
+    /// Names of the files making up a font of the given tile kind for this target
+    pub fn output_file_names(&self, tile_kind: TileKind, ident: &Option<&str>) -> Vec<PathBuf> {
+        match self {
+            Target::DjiWtfos => vec![
+                bin_file::normalized_file_name(tile_kind, ident, FontPart::Base),
+                bin_file::normalized_file_name(tile_kind, ident, FontPart::Ext),
+            ],
+            Target::Avatar => vec![avatar_file::normalized_file_name(tile_kind, ident)],
+            // HDZero fonts are a single 16x32 tiles BMP image without separators
+            Target::Hdzero => {
+                let ident = match ident {
+                    Some(ident) => format!("_{ident}"),
+                    None => "".to_owned(),
+                };
+                vec![PathBuf::from(format!("font{ident}.bmp"))]
+            },
+        }
+    }
+
+}
+
+/// Inclusive range of luminance levels of the visible pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LuminanceRange {
+    pub min: u8,
+    pub max: u8,
+}
+
+impl LuminanceRange {
+    pub fn contains(&self, luminance: u8) -> bool {
+        (self.min..=self.max).contains(&luminance)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, StrumDisplay, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    #[strum(serialize = "error")]
+    Error,
+    #[strum(serialize = "warning")]
+    Warning,
+    /// the rule is disabled
+    #[strum(serialize = "off")]
+    Off,
+}
+
+/// Individual verification performed by `check`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, StrumDisplay, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    #[strum(serialize = "mixed-tile-kinds")]
+    MixedTileKinds,
+    #[strum(serialize = "unsupported-tile-kind")]
+    UnsupportedTileKind,
+    #[strum(serialize = "too-many-tiles")]
+    TooManyTiles,
+    #[strum(serialize = "not-enough-tiles")]
+    NotEnoughTiles,
+    #[strum(serialize = "invalid-ident")]
+    InvalidIdent,
+    #[strum(serialize = "semi-transparent-pixels")]
+    SemiTransparentPixels,
+    #[strum(serialize = "unsafe-luminance")]
+    UnsafeLuminance,
+}
+
+impl Rule {
+
+    /// Severity of the rule when it is not configured
+    pub fn default_severity(&self, target: Target) -> Severity {
+        match (self, target) {
+            // only Avatar fonts must be complete, DJI and HDZero fonts can be shorter than the maximum
+            (Rule::NotEnoughTiles, Target::Avatar) => Severity::Error,
+            (Rule::NotEnoughTiles, _) => Severity::Off,
+            // the font still works, the glyphs only look worse
+            (Rule::SemiTransparentPixels | Rule::UnsafeLuminance, _) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadLintConfigError {
+    #[error("failed to open lint config file: {0}")]
+    OpenError(IOError),
+    #[error("failed to parse lint config file {file_path}: {error}")]
+    FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
+}
+
+/// Severity of the rules of `check`, e.g.:
+///
+/// ```yaml
+/// rules:
+///   invalid-ident: warning
+/// targets:
+///   dji-wtfos:
+///     not-enough-tiles: warning
+/// luminance:
+///   hdzero: { min: 20, max: 230 }
+/// ```
+///
+/// Rules configured for the checked target take precedence over the ones configured for all targets, the rules which
+/// are not configured have their default severity. The luminance ranges replace the default range of the targets.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    rules: HashMap<Rule, Severity>,
+    targets: HashMap<Target, HashMap<Rule, Severity>>,
+    luminance: HashMap<Target, LuminanceRange>,
+}
+
+impl LintConfig {
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadLintConfigError> {
+        serde_yaml::from_reader(File::open(path.as_ref())?)
+            .map_err(|error| LoadLintConfigError::FileStructureError { file_path: path.as_ref().to_path_buf(), error })
+    }
+
+    pub fn severity(&self, rule: Rule, target: Target) -> Severity {
+        self.targets.get(&target).and_then(|rules| rules.get(&rule))
+            .or_else(|| self.rules.get(&rule))
+            .copied()
+            .unwrap_or_else(|| rule.default_severity(target))
+    }
+
+    pub fn luminance_range(&self, target: Target) -> LuminanceRange {
+        self.luminance.get(&target).copied().unwrap_or_else(|| target.luminance_range())
+    }
+
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]: {}", self.severity, self.rule, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " ({suggestion})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub target: Target,
+    pub issues: Vec<Issue>,
+    pub output_files: Vec<PathBuf>,
+}
+
+impl Report {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error).count()
+    }
+}
+
+// issue found by a rule before its severity is known
+struct Finding {
+    rule: Rule,
+    message: String,
+    suggestion: Option<String>,
+}
+
+impl Finding {
+    fn new(rule: Rule, message: String, suggestion: Option<String>) -> Self {
+        Self { rule, message, suggestion }
+    }
+}
+
+fn check_ident(ident: &str) -> Option<Finding> {
+    if ident.is_empty() || ! ident.chars().all(|char| char.is_ascii_alphanumeric() || char == '-') {
+        return Some(Finding::new(Rule::InvalidIdent,
+            format!("ident `{ident}` would produce file names the goggles do not recognize"),
+            Some("use only ASCII letters, digits and `-` in the ident".to_owned())));
+    }
+    None
+}
+
+// pixels matching the predicate, reported with the first few tiles containing them
+fn check_pixels<F: Fn(&Rgba<u8>) -> bool>(tiles: &[Tile], rule: Rule, description: &str, suggestion: &str, predicate: F) -> Option<Finding> {
+    const LISTED_TILES: usize = 10;
+    let counts = tiles.iter().enumerate()
+        .map(|(index, tile)| (index, tile.pixels().filter(|pixel| predicate(pixel)).count()))
+        .filter(|(_, count)| *count > 0)
+        .collect::<Vec<_>>();
+    if counts.is_empty() {
+        return None;
+    }
+    let pixel_count: usize = counts.iter().map(|(_, count)| count).sum();
+    let mut tile_list = counts.iter().take(LISTED_TILES).map(|(index, _)| format_index(*index)).collect::<Vec<_>>().join(", ");
+    if counts.len() > LISTED_TILES {
+        tile_list.push_str(&format!(" and {} more", counts.len() - LISTED_TILES));
+    }
+    Some(Finding::new(rule, format!("{pixel_count} {description} pixels in tiles {tile_list}"), Some(suggestion.to_owned())))
+}
+
+fn luminance(pixel: &Rgba<u8>) -> u8 {
+    let [red, green, blue, _] = pixel.0;
+    ((299 * red as u32 + 587 * green as u32 + 114 * blue as u32) / 1000) as u8
+}
+
+/// Verifies that the tiles satisfy the constraints of the target system with the default rule severities
+pub fn check(tiles: &[Tile], target: Target, ident: &Option<&str>) -> Report {
+    check_with_config(tiles, target, ident, &LintConfig::default())
+}
+
+/// Verifies that the tiles satisfy the constraints of the target system, the rules disabled by the config are skipped
+pub fn check_with_config(tiles: &[Tile], target: Target, ident: &Option<&str>, config: &LintConfig) -> Report {
+    let profile = target.font_profile();
+    let mut findings = vec![];
+    let mut output_files = vec![];
+
+    if let Some(finding) = ident.and_then(check_ident) {
+        findings.push(finding);
+    }
+
+    match tiles.tile_kind() {
+        Ok(tile_kind) => {
+            if ! profile.supports_tile_kind(tile_kind) {
+                findings.push(Finding::new(Rule::UnsupportedTileKind,
+                    format!("{target} does not support {tile_kind} tiles"),
+                    Some(format!("provide a font with {} tiles", profile.tile_kinds().iter().map(ToString::to_string).collect::<Vec<_>>().join("/")))));
+            }
+            output_files = target.output_file_names(tile_kind, ident);
+        },
+        Err(TileKindError::MultipleTileKinds) => findings.push(Finding::new(Rule::MixedTileKinds,
+            "collection contains both SD and HD tiles".to_owned(),
+            Some("make sure all the tiles come from the same font kind".to_owned()))),
+        Err(error) => findings.push(Finding::new(Rule::MixedTileKinds, error.to_string(), None)),
+    }
+
+    if tiles.len() > profile.tile_count() {
+        findings.push(Finding::new(Rule::TooManyTiles,
+            format!("collection contains {} tiles but {target} fonts can only contain {}", tiles.len(), profile.tile_count()),
+            Some(format!("remove the tiles after index {}", profile.tile_count() - 1))));
+    }
+
+    if tiles.len() < profile.tile_count() {
+        findings.push(Finding::new(Rule::NotEnoughTiles,
+            format!("collection contains {} tiles but {target} fonts must contain {}", tiles.len(), profile.tile_count()),
+            Some("add empty tiles to complete the collection".to_owned())));
+    }
+
+    findings.extend(check_pixels(tiles, Rule::SemiTransparentPixels, "semi-transparent",
+        "snap the alpha to fully transparent or opaque with `convert --transform snap-alpha`",
+        |pixel| pixel.0[3] != 0 && pixel.0[3] != 255));
+
+    let luminance_range = config.luminance_range(target);
+    findings.extend(check_pixels(tiles, Rule::UnsafeLuminance,
+        &format!("visible with a luminance outside of {}-{}", luminance_range.min, luminance_range.max),
+        "use colors in the luminance range of the target",
+        |pixel| pixel.0[3] != 0 && ! luminance_range.contains(luminance(pixel))));
+
+    let issues = findings.into_iter()
+        .map(|Finding { rule, message, suggestion }| Issue { rule, severity: config.severity(rule, target), message, suggestion })
+        .filter(|issue| issue.severity != Severity::Off)
+        .collect();
+
+    Report { target, issues, output_files }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{transform::Transform, Kind as TileKind, Tile};
+
+    use super::{check_with_config, LintConfig, Report, Rule, Severity, Target};
+
+    #[test]
+    fn configured_severities() {
+        let tiles = vec![Tile::new(TileKind::SD); 10];
+        assert!(check_with_config(&tiles, Target::DjiWtfos, &None, &LintConfig::default()).issues.is_empty());
+        assert!(check_with_config(&tiles, Target::Avatar, &None, &LintConfig::default()).has_errors());
+
+        let config: LintConfig = serde_yaml::from_str("rules:\n  not-enough-tiles: warning\ntargets:\n  avatar:\n    not-enough-tiles: 'off'\n").unwrap();
+        assert_eq!(config.severity(Rule::NotEnoughTiles, Target::DjiWtfos), Severity::Warning);
+        assert!(check_with_config(&tiles, Target::Avatar, &None, &config).issues.is_empty());
+        let report = check_with_config(&tiles, Target::DjiWtfos, &None, &config);
+        assert!(! report.has_errors());
+        assert_eq!(report.issues.len(), 1);
+    }
+
+    #[test]
+    fn pixel_rules() {
+        let mut tiles = vec![Tile::new(TileKind::HD); 256];
+        tiles[3].put_pixel(0, 0, Rgba([255, 255, 255, 128]));
+        tiles[4].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let report = check_with_config(&tiles, Target::DjiWtfos, &None, &LintConfig::default());
+        assert_eq!(report.issues.iter().map(|issue| issue.rule).collect::<Vec<_>>(), vec![Rule::SemiTransparentPixels]);
+        assert!(! report.has_errors());
+
+        let rules = |report: Report| report.issues.iter().map(|issue| issue.rule).collect::<Vec<_>>();
+        let report = check_with_config(&tiles, Target::Hdzero, &None, &LintConfig::default());
+        assert!(rules(report).contains(&Rule::UnsafeLuminance));
+        let config: LintConfig = serde_yaml::from_str("luminance:\n  hdzero: { min: 0, max: 255 }\n").unwrap();
+        assert!(! rules(check_with_config(&tiles, Target::Hdzero, &None, &config)).contains(&Rule::UnsafeLuminance));
+
+        let snapped = tiles[3].transformed(Transform::SnapAlpha);
+        assert_eq!(*snapped.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+    }
+}
@@ -0,0 +1,97 @@
+
+//! Collection specification formats of the command line tool, `<prefix>:<path>`
+//!
+//! The lists are the reference of the supported formats: the tests convert between every pair of them so that a new
+//! format is covered as soon as it is listed.
+
+use std::path::Path;
+
+
+/// What the path of a collection specification points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathKind {
+    /// a file with the given extension
+    File(&'static str),
+    Dir,
+    /// several files given as `:` separated paths, named from a common stem with the given suffixes
+    FileSet(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecFormat {
+    pub prefix: &'static str,
+    pub path_kind: PathKind,
+}
+
+impl SpecFormat {
+
+    const fn new(prefix: &'static str, path_kind: PathKind) -> Self {
+        Self { prefix, path_kind }
+    }
+
+    pub fn is_single_path(&self) -> bool {
+        ! matches!(self.path_kind, PathKind::FileSet(_))
+    }
+
+    /// Specification of a collection of this format stored at `stem`, the file extension or suffixes are appended for
+    /// file formats
+    pub fn spec<P: AsRef<Path>>(&self, stem: P) -> String {
+        let stem = stem.as_ref().to_string_lossy();
+        match self.path_kind {
+            PathKind::File(extension) => format!("{}:{stem}.{extension}", self.prefix),
+            PathKind::Dir => format!("{}:{stem}", self.prefix),
+            PathKind::FileSet(suffixes) => {
+                let paths = suffixes.iter().map(|suffix| format!("{stem}{suffix}")).collect::<Vec<_>>().join(":");
+                format!("{}:{paths}", self.prefix)
+            },
+        }
+    }
+
+}
+
+/// Formats of the `convert` command
+pub const TILE_COLLECTION_FORMATS: &[SpecFormat] = &[
+    SpecFormat::new("djibin", PathKind::File("bin")),
+    SpecFormat::new("avatar", PathKind::File("png")),
+    SpecFormat::new("tilegrid", PathKind::File("png")),
+    SpecFormat::new("tiledir", PathKind::Dir),
+    SpecFormat::new("symdir", PathKind::Dir),
+    SpecFormat::new("atlas", PathKind::File("png")),
+    SpecFormat::new("wtfos", PathKind::File("png")),
+    SpecFormat::new("mcm", PathKind::File("mcm")),
+];
+
+/// Formats of the `convert-set` command
+pub const TILE_SET_FORMATS: &[SpecFormat] = &[
+    SpecFormat::new("djibinset", PathKind::FileSet(&["_sd.bin", "_sd_2.bin", "_hd.bin", "_hd_2.bin"])),
+    SpecFormat::new("djibinsetnorm", PathKind::Dir),
+    SpecFormat::new("tilesetgrids", PathKind::FileSet(&["_sd.png", "_hd.png"])),
+    SpecFormat::new("tilesetgridsnorm", PathKind::Dir),
+    SpecFormat::new("tilesetdir", PathKind::Dir),
+    SpecFormat::new("symsetdir", PathKind::Dir),
+    SpecFormat::new("wsltool", PathKind::Dir),
+    SpecFormat::new("wtfos", PathKind::Dir),
+    SpecFormat::new("avatarset", PathKind::FileSet(&["_sd.png", "_hd.png"])),
+    SpecFormat::new("avatarsetnorm", PathKind::Dir),
+];
+
+/// Every ordered pair of distinct formats
+pub fn conversion_pairs(formats: &[SpecFormat]) -> Vec<(SpecFormat, SpecFormat)> {
+    formats.iter()
+        .flat_map(|from| formats.iter().filter(move |to| *to != from).map(move |to| (*from, *to)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{conversion_pairs, TILE_COLLECTION_FORMATS, TILE_SET_FORMATS};
+
+    #[test]
+    fn pairs_and_specs() {
+        let pairs = conversion_pairs(TILE_COLLECTION_FORMATS);
+        assert_eq!(pairs.len(), TILE_COLLECTION_FORMATS.len() * (TILE_COLLECTION_FORMATS.len() - 1));
+        assert!(pairs.iter().all(|(from, to)| from != to));
+        assert_eq!(TILE_COLLECTION_FORMATS[0].spec("fonts/font"), "djibin:fonts/font.bin");
+        assert_eq!(TILE_SET_FORMATS[2].spec("grid"), "tilesetgrids:grid_sd.png:grid_hd.png");
+    }
+}
@@ -0,0 +1,910 @@
+
+//! Loading and saving tile collections of any of the supported formats, for applications which do not want to go
+//! through the command line tool
+//!
+//! A [`CollectionSpec`] is a format and a path. It can be built directly or parsed from the `<prefix>:<path>`
+//! specifications accepted by the `convert` command, e.g. `djibin:font.bin` or `tiledir:tiles:ppm`.
+//!
+//! [`convert`] is what the `convert` command runs: the [`ConversionOptions`] hold the options of the command and the
+//! tiles go through the same reordering, repaging, transforms, dust removal and palette enforcement before being saved.
+//! The loads and conversions send their diagnostics events, e.g. the detected tile kinds, to the handler they are given.
+//!
+//! ```no_run
+//! use hd_fpv_osd_font_tool::conversion::{convert, CollectionFormat, CollectionSpec, ConversionOptions};
+//! use hd_fpv_osd_font_tool::diagnostics;
+//!
+//! let from: CollectionSpec = "djibin:font_hd.bin".parse().unwrap();
+//! let to = CollectionSpec::new(CollectionFormat::TileGrid, "grid.png");
+//! let report = convert(from, to, &ConversionOptions::default(), &diagnostics::log_event).unwrap();
+//! println!("{report}");
+//! ```
+
+use std::fmt::Display;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use derive_more::From;
+use thiserror::Error;
+
+use crate::analysis::{dust::remove_dust, palette::{LoadPaletteError, Palette, PaletteMode, PaletteViolationError}};
+use crate::background::Background;
+use crate::conversion_report::ConversionReport;
+use crate::diagnostics::Event;
+use crate::image_encode::ImageEncodeOptions;
+use crate::io_context::IoContext;
+use crate::preflight::{self, DestinationError};
+use crate::retry::RetryPolicy;
+use crate::scale::{self, ScaleError, ScaleFilter};
+use crate::storage::StorageError;
+use crate::summary::{Summary, SummaryError};
+use crate::workspace::Workspace;
+use crate::osd::{
+    archive::ArchiveError,
+    atlas,
+    avatar_file,
+    bin_file::{self, metadata::{Metadata as BinFileMetadata, MetadataError}},
+    font_profile::{FontProfile, UnsupportedTileKindError},
+    mcm_file,
+    screenshot::{self, Geometry as ScreenshotGeometry},
+    tile::{
+        Tile,
+        Kind as TileKind,
+        KindSwapPolicy,
+        alpha_mode::AlphaMode,
+        file_format::{FileFormat as TileFileFormat, SaveOptions as TileSaveOptions},
+        grid::{
+            Grid as TileGrid,
+            GridStyle as TileGridStyle,
+            LoadError as GridLoadError,
+            SaveImageError as GridSaveImageError,
+            pages::{self, GridLayout, LoadPagesError, SavePagesError},
+        },
+        transform::{Shift, ShiftOutOfBoundsError, Transform},
+        container::{
+            IntoTilesVec,
+            ToSymbols,
+            copy_png_tiles::{CopyPngTilesError, PngTileFiles},
+            into_tile_grid::IntoTileGrid,
+            load_symbols_from_dir::{load_symbols_from_dir_with_context, LoadSymbolsFromDirError},
+            load_tiles_from_dir::{load_tiles_from_dir_split_kinds_with_context, load_tiles_from_dir_with_context, LoadTilesFromDirError},
+            repage::repage,
+            save_symbols_to_dir::{SaveSymbolsToDir, SaveSymbolsToDirError},
+            save_tiles_to_dir::{save_tiles_to_dir_with_context, SaveTilesToDirError},
+            save_to_bin_file::{save_to_bin_file_with_context, SaveTilesToBinFileError},
+            set_dir_layout::SetDirLayout,
+            symbol::spec::{LoadSpecsFileError, Specs as SymbolSpecs},
+            tile_order::{LoadTileOrderError, TileOrder},
+            transform_map::{LoadTransformMapError, TileIndexOutOfBoundsError, TransformMap},
+            uniq_tile_kind::{TileKindError, UniqTileKind},
+        },
+    },
+    wtfos_file,
+};
+
+
+#[derive(Debug)]
+pub enum InvalidSpecError {
+    InvalidPrefix(String),
+    InvalidImageFileExtension {
+        path: PathBuf,
+        extension: Option<String>
+    },
+    InvalidPath(PathBuf),
+    InvalidScreenshotGeometry(String),
+    SourceOnly(&'static str),
+    NoPrefix
+}
+
+impl std::error::Error for InvalidSpecError {}
+
+impl Display for InvalidSpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use InvalidSpecError::*;
+        match self {
+            InvalidPrefix(prefix) => write!(f, "invalid prefix: {}", prefix),
+            NoPrefix => f.write_str("no prefix"),
+            InvalidImageFileExtension { path, extension: Some(extension) } => write!(f, "invalid image file extension `{}`: {}", extension, path.to_string_lossy()),
+            InvalidImageFileExtension { path, extension: None } => write!(f, "image path has no file extension: {}", path.to_string_lossy()),
+            InvalidPath(path) => write!(f, "invalid path: {}", path.to_string_lossy()),
+            InvalidScreenshotGeometry(error) => f.write_str(error),
+            SourceOnly(prefix) => write!(f, "`{}` collections can only be converted from", prefix),
+        }
+    }
+}
+
+#[derive(Debug, From, Error)]
+pub enum ConversionError {
+    #[error(transparent)]
+    InvalidSpec(InvalidSpecError),
+    #[from(ignore)]
+    #[error("invalid source collection: {0}")]
+    InvalidSource(InvalidSpecError),
+    #[from(ignore)]
+    #[error("invalid destination collection: {0}")]
+    InvalidDestination(InvalidSpecError),
+    #[error(transparent)]
+    BinFileLoadError(bin_file::LoadError),
+    #[error(transparent)]
+    AvatarFileLoadError(avatar_file::LoadError),
+    #[error(transparent)]
+    WtfosFileLoadError(wtfos_file::LoadError),
+    #[error(transparent)]
+    McmFileLoadError(mcm_file::LoadError),
+    #[error(transparent)]
+    GridLoadError(GridLoadError),
+    #[error(transparent)]
+    LoadTilesFromDirError(LoadTilesFromDirError),
+    #[error(transparent)]
+    LoadSymbolsFromDirError(LoadSymbolsFromDirError),
+    #[error(transparent)]
+    AtlasLoadError(atlas::LoadError),
+    #[error(transparent)]
+    ScreenshotLoadError(screenshot::LoadError),
+    #[error(transparent)]
+    SaveTilesToBinFileError(SaveTilesToBinFileError),
+    #[error(transparent)]
+    AvatarFileSaveError(avatar_file::SaveError),
+    #[error(transparent)]
+    WtfosFileSaveError(wtfos_file::SaveError),
+    #[error(transparent)]
+    McmFileSaveError(mcm_file::SaveError),
+    #[error(transparent)]
+    GridSaveImageError(GridSaveImageError),
+    #[error(transparent)]
+    SaveTilesToDirError(SaveTilesToDirError),
+    #[error(transparent)]
+    SaveSymbolsToDirError(SaveSymbolsToDirError),
+    #[error(transparent)]
+    AtlasSaveError(atlas::SaveError),
+    #[error(transparent)]
+    LoadSpecsFileError(LoadSpecsFileError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error(transparent)]
+    ArchiveError(ArchiveError),
+    #[error(transparent)]
+    StorageError(StorageError),
+    #[error(transparent)]
+    UnsupportedTileKindError(UnsupportedTileKindError),
+    #[error(transparent)]
+    DestinationError(DestinationError),
+    #[error(transparent)]
+    LoadTileOrderError(LoadTileOrderError),
+    #[error(transparent)]
+    LoadTransformMapError(LoadTransformMapError),
+    #[error(transparent)]
+    TileIndexOutOfBoundsError(TileIndexOutOfBoundsError),
+    #[error(transparent)]
+    ShiftOutOfBoundsError(ShiftOutOfBoundsError),
+    #[error(transparent)]
+    LoadPaletteError(LoadPaletteError),
+    #[error(transparent)]
+    PaletteViolationError(PaletteViolationError),
+    #[error(transparent)]
+    LoadPagesError(LoadPagesError),
+    #[error(transparent)]
+    SavePagesError(SavePagesError),
+    #[error(transparent)]
+    CopyPngTilesError(CopyPngTilesError),
+    #[error(transparent)]
+    MetadataError(MetadataError),
+    #[error(transparent)]
+    SummaryError(SummaryError),
+    #[error(transparent)]
+    ScaleError(ScaleError),
+    #[error(transparent)]
+    IOError(IOError),
+    #[from(ignore)]
+    #[error("a symbol specs file is needed to save symbol directories")]
+    MissingSymbolSpecs,
+    #[from(ignore)]
+    #[error("paged grids cannot be combined with a background, cropping, an HTML image map or symbol annotations")]
+    PagedGridOptions,
+}
+
+impl ConversionError {
+
+    // the spec errors found while loading or saving are reported with the collection they come from
+    fn in_source(self) -> Self {
+        match self {
+            Self::InvalidSpec(error) => Self::InvalidSource(error),
+            error => error,
+        }
+    }
+
+    fn in_destination(self) -> Self {
+        match self {
+            Self::InvalidSpec(error) => Self::InvalidDestination(error),
+            error => error,
+        }
+    }
+
+}
+
+/// Format of a tile collection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollectionFormat {
+    DjiBin,
+    Avatar,
+    Wtfos,
+    /// the tile kind the characters are scaled to when loading
+    Mcm { tile_kind: TileKind },
+    TileGrid,
+    TileDir { file_format: TileFileFormat },
+    SymbolDir,
+    Atlas,
+    /// source only
+    Screenshot { geometry: ScreenshotGeometry },
+}
+
+impl CollectionFormat {
+
+    /// Prefix of the format in collection specifications
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Self::DjiBin => "djibin",
+            Self::Avatar => "avatar",
+            Self::Wtfos => "wtfos",
+            Self::Mcm { .. } => "mcm",
+            Self::TileGrid => "tilegrid",
+            Self::TileDir { .. } => "tiledir",
+            Self::SymbolDir => "symdir",
+            Self::Atlas => "atlas",
+            Self::Screenshot { .. } => "screenshot",
+        }
+    }
+
+    pub fn is_source_only(&self) -> bool {
+        matches!(self, Self::Screenshot { .. })
+    }
+
+    /// Whether both formats write the same files, the tile kind of MCM files and the geometry of screenshots only
+    /// matter when loading
+    pub fn is_same_format(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::TileDir { file_format }, Self::TileDir { file_format: other_file_format }) => file_format == other_file_format,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    // the alpha channel of the images of these formats can be given by a color key
+    fn has_alpha_mode(&self) -> bool {
+        matches!(self, Self::TileGrid | Self::TileDir { .. } | Self::SymbolDir | Self::Wtfos)
+    }
+
+}
+
+impl FromStr for CollectionFormat {
+    type Err = InvalidSpecError;
+
+    /// Parses a prefix, the formats having parameters get the default ones: PNG tile files and SD MCM characters
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "djibin" => Ok(Self::DjiBin),
+            "avatar" => Ok(Self::Avatar),
+            "wtfos" => Ok(Self::Wtfos),
+            "mcm" => Ok(Self::Mcm { tile_kind: TileKind::SD }),
+            "tilegrid" => Ok(Self::TileGrid),
+            "tiledir" => Ok(Self::TileDir { file_format: TileFileFormat::Png }),
+            "symdir" => Ok(Self::SymbolDir),
+            "atlas" => Ok(Self::Atlas),
+            // the geometry of the grid in the screenshot cannot be defaulted
+            "screenshot" => Err(InvalidSpecError::InvalidScreenshotGeometry("screenshot collections need a geometry: screenshot:<path>:<geometry>".to_owned())),
+            prefix => Err(InvalidSpecError::InvalidPrefix(prefix.to_owned())),
+        }
+    }
+}
+
+/// Format and path of a tile collection
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionSpec {
+    pub format: CollectionFormat,
+    pub path: PathBuf,
+}
+
+impl CollectionSpec {
+
+    pub fn new<P: AsRef<Path>>(format: CollectionFormat, path: P) -> Self {
+        Self { format, path: path.as_ref().to_path_buf() }
+    }
+
+}
+
+impl FromStr for CollectionSpec {
+    type Err = InvalidSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, path) = s.split_once(':').ok_or(InvalidSpecError::NoPrefix)?;
+        let (format, path) = match prefix {
+            // an optional tile file extension can follow the path, `C:\tiles` must not be mistaken for one
+            "tiledir" => match path.rsplit_once(':').and_then(|(path, extension)| Some((path, extension.parse::<TileFileFormat>().ok()?))) {
+                Some((path, file_format)) => (CollectionFormat::TileDir { file_format }, path),
+                None => (CollectionFormat::TileDir { file_format: TileFileFormat::Png }, path),
+            },
+            "mcm" => match path.rsplit_once(':') {
+                Some((path, "sd")) => (CollectionFormat::Mcm { tile_kind: TileKind::SD }, path),
+                Some((path, "hd")) => (CollectionFormat::Mcm { tile_kind: TileKind::HD }, path),
+                _ => (CollectionFormat::Mcm { tile_kind: TileKind::SD }, path),
+            },
+            "screenshot" => {
+                let (path, geometry) = path.rsplit_once(':').ok_or_else(|| InvalidSpecError::InvalidPath(path.into()))?;
+                let geometry = geometry.parse().map_err(|error: screenshot::InvalidGeometryError| InvalidSpecError::InvalidScreenshotGeometry(error.to_string()))?;
+                (CollectionFormat::Screenshot { geometry }, path)
+            },
+            prefix => (prefix.parse()?, path),
+        };
+        Ok(Self::new(format, path))
+    }
+}
+
+// parses back to the same format and path
+impl Display for CollectionSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.format.prefix(), self.path.to_string_lossy())?;
+        match self.format {
+            CollectionFormat::TileDir { file_format } if file_format != TileFileFormat::Png => write!(f, ":{}", file_format.extension()),
+            CollectionFormat::Mcm { tile_kind: TileKind::HD } => f.write_str(":hd"),
+            CollectionFormat::Screenshot { geometry } => write!(f, ":{geometry}"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Options of the conversions, the defaults are the ones of the `convert` and `convert-set` commands
+#[derive(Debug, Clone)]
+pub struct ConversionOptions {
+    /// limits the number of tiles loaded from directories and the tile kinds which can be converted
+    pub font_profile: FontProfile,
+    /// symbol specs used to group the tiles into symbols when saving symbol directories and to name the symbols of grids
+    pub symbol_specs_file: Option<PathBuf>,
+    /// encode options of the PNG files written, [`convert`] loads the ICC profile of its source for the `source` color
+    /// profile
+    pub image_encode: ImageEncodeOptions,
+    /// the grid images are composited over the background, they cannot be converted back losslessly
+    pub background: Option<Background>,
+    /// number of tiles the collection is truncated or padded to
+    pub repage: Option<usize>,
+    /// writes an HTML image map of the grid images next to them
+    pub html_image_map: bool,
+    /// draws the name of the symbols on the grid images
+    pub annotate_symbols: bool,
+    /// crops the grid images to the rows holding non-empty tiles
+    pub crop: bool,
+    /// number of images the grids are split across
+    pub grid_pages: usize,
+    /// shape of the pages of the grids, the default one for the number of pages if `None`
+    pub grid_layout: Option<GridLayout>,
+    pub palette: Option<PathBuf>,
+    pub palette_mode: PaletteMode,
+    /// source recorded in the bin file metadata sidecar files, no sidecar file is written when `None`
+    pub bin_metadata_source: Option<String>,
+    /// maximum size of the groups of stray pixels removed
+    pub remove_dust: Option<usize>,
+    pub transform: Option<Transform>,
+    pub transform_map: Option<PathBuf>,
+    pub order: Option<PathBuf>,
+    pub shifts: Vec<Shift>,
+    pub alpha_mode: AlphaMode,
+    /// factors of the scaled copies written next to the image outputs
+    pub scaled_variants: Vec<u32>,
+    pub scale_filter: ScaleFilter,
+    pub scale_to: Option<TileKind>,
+    pub write_summary: bool,
+    /// the SD and HD tiles of tile directories holding both kinds are converted to their own destination
+    pub split_kinds: bool,
+    /// the PNG tile files are copied instead of being decoded and encoded again when the tiles are not modified
+    pub copy_unmodified: bool,
+    /// what to do when the SD and HD sources of a set have been given in the wrong order
+    pub kind_swap_policy: KindSwapPolicy,
+    /// sets missing the tiles of a kind can be loaded
+    pub allow_partial: bool,
+    /// layout of the set directories, detected when loading and the default one when saving if `None`
+    pub set_dir_layout: Option<SetDirLayout>,
+    /// how the file operations failing with a transient IO error are retried, they are attempted once by default
+    pub retry: RetryPolicy,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        Self {
+            font_profile: FontProfile::default(),
+            symbol_specs_file: None,
+            image_encode: ImageEncodeOptions::default(),
+            background: None,
+            repage: None,
+            html_image_map: false,
+            annotate_symbols: false,
+            crop: false,
+            grid_pages: 1,
+            grid_layout: None,
+            palette: None,
+            palette_mode: PaletteMode::Error,
+            bin_metadata_source: None,
+            remove_dust: None,
+            transform: None,
+            transform_map: None,
+            order: None,
+            shifts: vec![],
+            alpha_mode: AlphaMode::default(),
+            scaled_variants: vec![],
+            scale_filter: ScaleFilter::default(),
+            scale_to: None,
+            write_summary: false,
+            split_kinds: false,
+            copy_unmodified: false,
+            kind_swap_policy: KindSwapPolicy::Reject,
+            allow_partial: false,
+            set_dir_layout: None,
+            retry: RetryPolicy::NONE,
+        }
+    }
+}
+
+impl ConversionOptions {
+
+    /// Loads the symbol specs, fails when no specs file is set
+    pub fn symbol_specs(&self) -> Result<SymbolSpecs, ConversionError> {
+        let symbol_specs_file = self.symbol_specs_file.as_ref().ok_or(ConversionError::MissingSymbolSpecs)?;
+        Ok(SymbolSpecs::load_file(symbol_specs_file)?)
+    }
+
+    // the symbols are named when a specs file exists
+    fn optional_symbol_specs(&self) -> Result<Option<SymbolSpecs>, ConversionError> {
+        match &self.symbol_specs_file {
+            Some(symbol_specs_file) if symbol_specs_file.exists() => Ok(Some(SymbolSpecs::load_file(symbol_specs_file)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn tile_save_options(&self, format: TileFileFormat) -> TileSaveOptions {
+        TileSaveOptions { format, encode: self.image_encode.clone() }
+    }
+
+    // layout of the pages of the grids when they are split across images or have a non-default shape
+    fn paged_grid_layout(&self) -> Option<GridLayout> {
+        match (self.grid_pages, self.grid_layout) {
+            (1, None) => None,
+            (pages, layout) => Some(layout.unwrap_or_else(|| GridLayout::default_for_pages(pages))),
+        }
+    }
+
+    /// Whether the options change the content or the order of the tiles
+    pub fn modifies_tiles(&self) -> bool {
+        self.repage.is_some() || self.palette.is_some() || self.remove_dust.is_some() || self.transform.is_some()
+            || self.transform_map.is_some() || ! self.shifts.is_empty() || self.order.is_some() || self.scale_to.is_some()
+    }
+
+}
+
+/// Checks that the path of an image collection has the `png` extension
+pub fn check_image_file_extension<P: AsRef<Path>>(path: P) -> Result<(), InvalidSpecError> {
+    let path = path.as_ref();
+    match path.extension() {
+        Some(os_str) => match os_str.to_str() {
+            Some("png") => Ok(()),
+            Some(extension) => Err(InvalidSpecError::InvalidImageFileExtension { path: path.to_path_buf(), extension: Some(extension.to_owned()) }),
+            None => Err(InvalidSpecError::InvalidPath(path.to_path_buf())),
+        },
+        None => Err(InvalidSpecError::InvalidImageFileExtension { path: path.to_path_buf(), extension: None }),
+    }
+}
+
+/// Checks that the files written to a directory output, one per tile or per symbol, fit before writing the first one
+pub fn check_output_space(to: &CollectionSpec, tile_count: usize, tile_kind: TileKind) -> Result<(), DestinationError> {
+    match to.format {
+        CollectionFormat::TileDir { .. } | CollectionFormat::SymbolDir =>
+            preflight::check_available_space(&to.path, preflight::estimate_tile_files_size(tile_count, tile_kind)),
+        _ => Ok(()),
+    }
+}
+
+/// Loads the tiles of the collection, the file operations are retried with the policy of the options and the
+/// diagnostics events of the load are sent to `diagnostics`
+pub fn load_tiles(from: &CollectionSpec, options: &ConversionOptions, diagnostics: &(dyn Fn(&Event) + Sync)) -> Result<Vec<Tile>, ConversionError> {
+    load_tiles_with_context(from, options, &IoContext::new(options.retry, diagnostics))
+}
+
+fn load_tiles_with_context(from: &CollectionSpec, options: &ConversionOptions, context: &IoContext) -> Result<Vec<Tile>, ConversionError> {
+    use CollectionFormat::*;
+    let path = &from.path;
+    let tiles = match from.format {
+        DjiBin => bin_file::load_with_context(path, context)?,
+        Avatar => avatar_file::load_with_context(path, context)?,
+        Wtfos => {
+            check_image_file_extension(path)?;
+            wtfos_file::load_with_context(path, context)?
+        },
+        Mcm { tile_kind } => mcm_file::load_with_context(path, tile_kind, context)?,
+        TileGrid => {
+            check_image_file_extension(path)?;
+            crate::osd::tile::grid::Grid::load_from_image_with_context(path, &TileGridStyle::default(), context)?.into_iter().collect()
+        },
+        TileDir { file_format } => load_tiles_from_dir_with_context(path, options.font_profile.tile_count(), file_format, context)?,
+        SymbolDir => load_symbols_from_dir_with_context(path, options.font_profile.tile_count(), context)?.into_tiles_vec(),
+        Atlas => {
+            check_image_file_extension(path)?;
+            atlas::load_with_context(path, context)?
+        },
+        Screenshot { geometry } => screenshot::load_with_context(path, &geometry, context)?,
+    };
+    Ok(tiles)
+}
+
+fn save_html_image_map(tile_grid: &TileGrid, grid_path: &Path, options: &ConversionOptions) -> Result<(), ConversionError> {
+    let image_src = grid_path.file_name().unwrap_or_default().to_string_lossy();
+    let html = tile_grid.html_image_map(&image_src, &TileGridStyle::default(), options.optional_symbol_specs()?.as_ref())?;
+    let html_path = grid_path.with_extension("html");
+    fs_err::write(html_path, html)?;
+    Ok(())
+}
+
+fn save_tile_grid(tile_grid: &TileGrid, path: &Path, options: &ConversionOptions) -> Result<(), ConversionError> {
+    if let Some(layout) = options.paged_grid_layout() {
+        if options.background.is_some() || options.crop || options.html_image_map || options.annotate_symbols {
+            return Err(ConversionError::PagedGridOptions);
+        }
+        pages::save_paged_images(tile_grid, path, layout, options.grid_pages, &options.image_encode)?;
+        return Ok(());
+    }
+    let style = TileGridStyle::default();
+    let encode_options = &options.image_encode;
+    if options.annotate_symbols {
+        let symbol_specs = options.symbol_specs()?;
+        let background = options.background.as_ref();
+        match options.crop {
+            true => tile_grid.cropped_to_populated_rows(&style).save_annotated_image(path, &style, background, &symbol_specs, encode_options)?,
+            false => tile_grid.save_annotated_image(path, &style, background, &symbol_specs, encode_options)?,
+        }
+    } else {
+        match (options.background, options.crop) {
+            (Some(background), true) => tile_grid.cropped_to_populated_rows(&style).save_image_with_background(path, &style, &background, encode_options)?,
+            (Some(background), false) => tile_grid.save_image_with_background(path, &style, &background, encode_options)?,
+            (None, true) => tile_grid.save_image_cropped_with_style(path, &style, encode_options)?,
+            (None, false) => tile_grid.save_image_with_style(path, &style, encode_options)?,
+        }
+    }
+    if options.html_image_map {
+        match options.crop {
+            true => save_html_image_map(&tile_grid.cropped_to_populated_rows(&style), path, options)?,
+            false => save_html_image_map(tile_grid, path, options)?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes the metadata sidecar file of a bin file which has just been saved if the options give its source
+pub fn write_bin_metadata<P: AsRef<Path>>(bin_file_path: P, ident: Option<&str>, options: &ConversionOptions) -> Result<(), ConversionError> {
+    if let Some(source) = &options.bin_metadata_source {
+        BinFileMetadata::write_sidecar(&bin_file_path, Some(source), ident)?;
+    }
+    Ok(())
+}
+
+/// Writes the summary file of each output of a conversion which has just been done if requested
+pub fn write_summaries<P: AsRef<Path>, Q: AsRef<Path>>(from: &str, source_paths: &[P], to: &str, output_paths: &[Q], options: &ConversionOptions)
+        -> Result<(), ConversionError> {
+    if options.write_summary {
+        let summary = Summary::new(from, source_paths, to, options.symbol_specs_file.as_deref());
+        for output_path in output_paths {
+            summary.write(output_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves the tiles, grid images are drawn with the grid options and bin files get their metadata sidecar file
+///
+/// The bin files and tile files are written with the retry policy of the options, the diagnostics events of the
+/// retries are sent to `diagnostics`.
+pub fn save_tiles(tiles: &[Tile], to: &CollectionSpec, options: &ConversionOptions, diagnostics: &(dyn Fn(&Event) + Sync)) -> Result<(), ConversionError> {
+    save_tiles_with_context(tiles, to, options, &IoContext::new(options.retry, diagnostics))
+}
+
+fn save_tiles_with_context(tiles: &[Tile], to: &CollectionSpec, options: &ConversionOptions, context: &IoContext) -> Result<(), ConversionError> {
+    use CollectionFormat::*;
+    let path = &to.path;
+    match to.format {
+        DjiBin => {
+            save_to_bin_file_with_context(tiles, path, context)?;
+            write_bin_metadata(path, None, options)?;
+        },
+        Avatar => avatar_file::save_with_options(tiles, path, &options.image_encode)?,
+        Wtfos => {
+            check_image_file_extension(path)?;
+            wtfos_file::save_with_options(tiles, path, &options.image_encode)?;
+        },
+        Mcm { .. } => mcm_file::save(tiles, path)?,
+        TileGrid => {
+            check_image_file_extension(path)?;
+            save_tile_grid(&tiles.into_tile_grid(), path, options)?;
+        },
+        TileDir { file_format } => save_tiles_to_dir_with_context(tiles, path, "", &options.tile_save_options(file_format), context)?,
+        SymbolDir => tiles.to_symbols(&options.symbol_specs()?)?.save_to_dir_with_options(path, &options.image_encode)?,
+        Atlas => {
+            check_image_file_extension(path)?;
+            atlas::save_with_options(tiles, path, &options.image_encode)?;
+        },
+        Screenshot { .. } => return Err(InvalidSpecError::SourceOnly(to.format.prefix()).into()),
+    }
+    Ok(())
+}
+
+fn repage_tiles(tiles: Vec<Tile>, options: &ConversionOptions, report: &mut ConversionReport) -> Result<Vec<Tile>, ConversionError> {
+    match options.repage {
+        Some(tile_count) => {
+            let repaged = repage(&tiles, tile_count)?;
+            if ! repaged.dropped_non_empty_tiles.is_empty() {
+                let dropped_indices = repaged.dropped_non_empty_tiles.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+                report.warn(format!("dropped non-empty tiles while repaging to {tile_count} tiles: {dropped_indices}"));
+            }
+            Ok(repaged.tiles)
+        },
+        None => Ok(tiles),
+    }
+}
+
+fn enforce_palette(mut tiles: Vec<Tile>, options: &ConversionOptions, report: &mut ConversionReport) -> Result<Vec<Tile>, ConversionError> {
+    if let Some(palette_file) = &options.palette {
+        let remapped_count = Palette::load_file(palette_file)?.enforce(&mut tiles, options.palette_mode)?;
+        if remapped_count > 0 {
+            report.warn(format!("remapped {remapped_count} pixels to the nearest palette color"));
+        }
+    }
+    Ok(tiles)
+}
+
+fn resize_tiles(tiles: Vec<Tile>, options: &ConversionOptions) -> Vec<Tile> {
+    match options.scale_to {
+        Some(tile_kind) => {
+            log::info!("scaling the tiles to {tile_kind} tiles");
+            tiles.iter().map(|tile| tile.resize_to(tile_kind, options.scale_filter)).collect()
+        },
+        None => tiles,
+    }
+}
+
+fn apply_alpha_mode(mut tiles: Vec<Tile>, options: &ConversionOptions) -> Vec<Tile> {
+    for tile in &mut tiles {
+        tile.apply_alpha_mode(options.alpha_mode);
+    }
+    tiles
+}
+
+fn reorder_tiles(tiles: Vec<Tile>, options: &ConversionOptions) -> Result<Vec<Tile>, ConversionError> {
+    match &options.order {
+        Some(order_file) => Ok(TileOrder::load_file(order_file)?.apply(&tiles)?),
+        None => Ok(tiles),
+    }
+}
+
+fn transform_tiles(tiles: Vec<Tile>, options: &ConversionOptions, report: &mut ConversionReport) -> Result<Vec<Tile>, ConversionError> {
+    let mut tiles = match options.transform {
+        Some(transform) => tiles.iter().map(|tile| tile.transformed(transform)).collect(),
+        None => tiles,
+    };
+    if let Some(transform_map_file) = &options.transform_map {
+        TransformMap::load_file(transform_map_file)?.apply(&mut tiles)?;
+    }
+    for shift in &options.shifts {
+        let clipped = shift.apply(&mut tiles)?;
+        if ! clipped.is_empty() {
+            report.warn(format!("shift {shift} moved content past the edges of tiles {}", clipped.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")));
+        }
+    }
+    Ok(tiles)
+}
+
+fn clean_dust(mut tiles: Vec<Tile>, options: &ConversionOptions) -> Vec<Tile> {
+    if let Some(max_size) = options.remove_dust {
+        let dust = remove_dust(&mut tiles, max_size);
+        if ! dust.is_empty() {
+            log::info!("removed {} groups of stray pixels", dust.len());
+        }
+    }
+    tiles
+}
+
+// loads the tiles of the source with the alpha mode applied
+fn load_source_tiles(from: &CollectionSpec, options: &ConversionOptions, context: &IoContext) -> Result<Vec<Tile>, ConversionError> {
+    let tiles = match (from.format, options.paged_grid_layout()) {
+        (CollectionFormat::TileGrid, Some(layout)) => {
+            check_image_file_extension(&from.path)?;
+            pages::load_paged_images_with_context(&from.path, layout, options.grid_pages, context)?
+        },
+        _ => load_tiles_with_context(from, options, context)?,
+    };
+    match from.format.has_alpha_mode() {
+        true => Ok(apply_alpha_mode(tiles, options)),
+        false => Ok(tiles),
+    }
+}
+
+// returns the number of tiles saved
+fn convert_tiles(tiles: Vec<Tile>, to: &CollectionSpec, options: &ConversionOptions, context: &IoContext, report: &mut ConversionReport)
+        -> Result<usize, ConversionError> {
+    let tiles = resize_tiles(tiles, options);
+    options.font_profile.check_tile_kind(tiles.tile_kind()?)?;
+    let tiles = transform_tiles(repage_tiles(reorder_tiles(tiles, options)?, options, report)?, options, report)?;
+    let tiles = enforce_palette(clean_dust(tiles, options), options, report)?;
+    check_output_space(to, tiles.len(), tiles.tile_kind()?)?;
+    save_tiles_with_context(&tiles, to, options, context).map_err(ConversionError::in_destination)?;
+    Ok(tiles.len())
+}
+
+// copies the PNG files of the tiles instead of decoding and encoding them again, returns the number of tiles saved
+fn copy_unmodified_tiles(from: &CollectionSpec, to: &CollectionSpec, options: &ConversionOptions) -> Result<usize, ConversionError> {
+    let tile_files = PngTileFiles::scan(&from.path, options.font_profile.tile_count())?;
+    options.font_profile.check_tile_kind(tile_files.tile_kind())?;
+    check_output_space(to, tile_files.len(), tile_files.tile_kind())?;
+    match to.format {
+        CollectionFormat::SymbolDir => tile_files.copy_to_symbol_dir(&to.path, &options.symbol_specs()?, &options.image_encode)?,
+        _ => tile_files.copy_to_tile_dir(&to.path, &options.tile_save_options(TileFileFormat::Png))?,
+    }
+    Ok(tile_files.len())
+}
+
+fn copies_png_tile_files(from: &CollectionSpec, to: &CollectionSpec, options: &ConversionOptions) -> bool {
+    let png_tile_dir = CollectionFormat::TileDir { file_format: TileFileFormat::Png };
+    options.copy_unmodified && ! options.modifies_tiles() && options.alpha_mode == AlphaMode::Alpha
+        && from.format == png_tile_dir && (to.format == png_tile_dir || to.format == CollectionFormat::SymbolDir)
+}
+
+// removes the tile files left over from the collection before normalization: unpadded file names and tiles past the end
+fn remove_stale_tile_files(dir: &Path, format: TileFileFormat, tile_count: usize) -> Result<(), IOError> {
+    for entry in fs_err::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        if let Some(index) = file_name.to_str().and_then(|file_name| format.tile_file_index(file_name)) {
+            if index >= tile_count || Path::new(&file_name) != format.tile_file_name(index) {
+                fs_err::remove_file(entry.path())?;
+                log::info!("removed stale tile file {}", entry.path().to_string_lossy());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn save_scaled_variants(to: &CollectionSpec, options: &ConversionOptions, report: &mut ConversionReport) -> Result<(), ConversionError> {
+    use CollectionFormat::*;
+    for factor in options.scaled_variants.iter().copied().filter(|factor| *factor > 1) {
+        match to.format {
+            TileGrid | Avatar | Wtfos => scale::save_scaled_image_copy(&to.path, factor, options.scale_filter, &options.image_encode)?,
+            TileDir { file_format: TileFileFormat::Png } | SymbolDir =>
+                scale::save_scaled_dir_copy(&to.path, factor, options.scale_filter, &options.image_encode)?,
+            _ => {
+                report.warn("scaled variants can only be generated for grid, avatar, msp-osd, PNG tile directory and symbol directory outputs".to_owned());
+                return Ok(());
+            },
+        };
+    }
+    Ok(())
+}
+
+// the file name gets the tile kind as suffix: `font.bin` -> `font_sd.bin`, `tiles` -> `tiles_sd`
+fn kind_destination(to: &CollectionSpec, tile_kind: TileKind) -> CollectionSpec {
+    let suffix = tile_kind.to_string().to_lowercase();
+    let stem = to.path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match to.path.extension() {
+        Some(extension) => format!("{stem}_{suffix}.{}", extension.to_string_lossy()),
+        None => format!("{stem}_{suffix}"),
+    };
+    CollectionSpec::new(to.format, to.path.with_file_name(file_name))
+}
+
+// converts each kind of tiles of a tile directory containing both SD and HD tiles to its own destination staged in
+// the workspace
+fn convert_split_kinds(from: &CollectionSpec, file_format: TileFileFormat, to: &CollectionSpec, options: &ConversionOptions, context: &IoContext,
+        workspace: &mut Workspace, report: &mut ConversionReport) -> Result<(), ConversionError> {
+    let partitions = load_tiles_from_dir_split_kinds_with_context(&from.path, options.font_profile.tile_count(), file_format, context)?;
+    let mixed = partitions.len() > 1;
+    for partition in partitions {
+        let destination = match mixed {
+            true => kind_destination(to, partition.tile_kind),
+            false => to.clone(),
+        };
+        if mixed {
+            log::info!("{} {} tile(s) of {} -> {destination}", partition.indices.len(), partition.tile_kind, from.path.to_string_lossy());
+        }
+        let staged = CollectionSpec::new(destination.format, workspace.stage(&destination.path)?);
+        convert_tiles(apply_alpha_mode(partition.tiles, options), &staged, options, context, report)?;
+        save_scaled_variants(&staged, options, report)?;
+        write_summaries(&from.to_string(), &[&from.path], &destination.to_string(), &[&staged.path], options)?;
+    }
+    Ok(())
+}
+
+/// Converts a collection to another format with the options of the `convert` command
+///
+/// Converting to the same format is a normalization pass: tiles are renumbered, images encoded again and bin files
+/// padded. The outputs are staged in a workspace next to the destination and only moved in place once all of them are
+/// written, see [`crate::workspace`]. The file operations are retried with the policy of the options and the
+/// diagnostics events of the conversion are sent to `diagnostics`.
+pub fn convert(from: CollectionSpec, to: CollectionSpec, options: &ConversionOptions, diagnostics: &(dyn Fn(&Event) + Sync))
+        -> Result<ConversionReport, ConversionError> {
+    if to.format.is_source_only() {
+        return Err(ConversionError::InvalidDestination(InvalidSpecError::SourceOnly(to.format.prefix())));
+    }
+    let context = IoContext::new(options.retry, diagnostics);
+    let mut report = ConversionReport::start(&from.to_string(), &to.to_string());
+    preflight::check_destination_writable(&to.path)?;
+    let options = &ConversionOptions { image_encode: options.image_encode.clone().with_source_icc_chunk(&[&from.path]), ..options.clone() };
+
+    let normalizing = from.format.is_same_format(&to.format);
+    match normalizing {
+        true => log::info!("normalizing {from} -> {to}"),
+        false => log::info!("converting {from} -> {to}"),
+    }
+
+    let mut workspace = Workspace::for_destination(&to.path)?;
+    if let (true, CollectionFormat::TileDir { file_format }) = (options.split_kinds, from.format) {
+        convert_split_kinds(&from, file_format, &to, options, &context, &mut workspace, &mut report)?;
+        report.add_files(&workspace.commit()?)?;
+        return Ok(report.finish());
+    }
+
+    let staged = CollectionSpec::new(to.format, workspace.stage(&to.path)?);
+    let tile_count = match copies_png_tile_files(&from, &to, options) {
+        true => copy_unmodified_tiles(&from, &staged, options)?,
+        false => convert_tiles(load_source_tiles(&from, options, &context).map_err(ConversionError::in_source)?, &staged, options, &context, &mut report)?,
+    };
+    save_scaled_variants(&staged, options, &mut report)?;
+    write_summaries(&from.to_string(), &[&from.path], &to.to_string(), &[&staged.path], options)?;
+    report.add_files(&workspace.commit()?)?;
+
+    // the files of the previous version of the collection are only removed once the new version is in place
+    if let (true, CollectionFormat::TileDir { file_format }) = (normalizing, to.format) {
+        remove_stale_tile_files(&to.path, file_format, tile_count)?;
+    }
+
+    Ok(report.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use temp_dir::TempDir;
+
+    use crate::diagnostics::{self, Event};
+    use crate::osd::tile::{Kind as TileKind, Tile, file_format::FileFormat as TileFileFormat};
+
+    use super::{convert, kind_destination, load_tiles, save_tiles, CollectionFormat, CollectionSpec, ConversionOptions};
+
+    #[test]
+    fn convert_specs() {
+        let spec: CollectionSpec = "tiledir:C:\\tiles:ppm".parse().unwrap();
+        assert_eq!(spec, CollectionSpec::new(CollectionFormat::TileDir { file_format: TileFileFormat::Ppm }, "C:\\tiles"));
+        assert_eq!(spec.to_string(), "tiledir:C:\\tiles:ppm");
+        assert_eq!("mcm:font.mcm:hd".parse::<CollectionSpec>().unwrap().format, CollectionFormat::Mcm { tile_kind: TileKind::HD });
+        assert!("font.bin".parse::<CollectionSpec>().is_err());
+        assert!("djbin:font.bin".parse::<CollectionSpec>().is_err());
+        let screenshot_spec: CollectionSpec = "screenshot:dvr.png:hd,30x16,10,20,1270,700".parse().unwrap();
+        assert_eq!(screenshot_spec.to_string().parse::<CollectionSpec>().unwrap(), screenshot_spec);
+
+        let bin_spec: CollectionSpec = "djibin:fonts/font.bin".parse().unwrap();
+        assert_eq!(kind_destination(&bin_spec, TileKind::SD).to_string(), "djibin:fonts/font_sd.bin");
+        let tile_dir_spec: CollectionSpec = "tiledir:tiles:ppm".parse().unwrap();
+        assert_eq!(kind_destination(&tile_dir_spec, TileKind::HD).to_string(), "tiledir:tiles_hd:ppm");
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut tiles = vec![Tile::new(TileKind::HD); 256];
+        tiles[3].put_pixel(1, 2, image::Rgba([255, 255, 255, 255]));
+        let bin_spec = CollectionSpec::new(CollectionFormat::DjiBin, temp_dir.child("font.bin"));
+        save_tiles(&tiles, &bin_spec, &ConversionOptions::default(), &diagnostics::log_event).unwrap();
+        let grid_spec: CollectionSpec = format!("tilegrid:{}", temp_dir.child("grid.png").to_string_lossy()).parse().unwrap();
+        let report = convert(bin_spec, grid_spec.clone(), &ConversionOptions::default(), &diagnostics::log_event).unwrap();
+        assert_eq!(report.artifacts.len(), 1);
+        let detected_kinds = Mutex::new(vec![]);
+        let record_detection = |event: &Event| if let Event::Detection(detection) = event {
+            detected_kinds.lock().unwrap().push(detection.tile_kind);
+        };
+        let loaded = load_tiles(&grid_spec, &ConversionOptions::default(), &record_detection).unwrap();
+        assert_eq!(loaded[3].as_raw(), tiles[3].as_raw());
+        assert_eq!(*detected_kinds.lock().unwrap(), [TileKind::HD]);
+        assert!(grid_spec.path.is_file());
+
+        let symbol_dir_spec = CollectionSpec::new(CollectionFormat::SymbolDir, temp_dir.child("symbols"));
+        assert!(save_tiles(&tiles, &symbol_dir_spec, &ConversionOptions::default(), &diagnostics::log_event).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+
+//! Outcome of a conversion: the files written, the warnings raised and how long it took
+//!
+//! The files listed are the ones moved in place by the workspace the outputs of the conversion are staged in, along
+//! with their summary, metadata sidecar, UV map and scaled variant files. The warnings are the ones raised by the
+//! steps of the conversion, which are logged as well.
+
+use std::fmt::Display;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::summary::paths_sha256;
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Artifact {
+    pub path: PathBuf,
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl Artifact {
+    pub fn of_file<P: AsRef<Path>>(path: P) -> Result<Self, IOError> {
+        Ok(Self { path: path.as_ref().to_path_buf(), size: fs_err::metadata(&path)?.len(), sha256: paths_sha256(&[&path])? })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionReport {
+    pub source: String,
+    pub destination: String,
+    pub artifacts: Vec<Artifact>,
+    pub warnings: Vec<String>,
+    /// seconds since the UNIX epoch
+    pub started_at: u64,
+    pub duration_ms: u64,
+    #[serde(skip)]
+    start_instant: Instant,
+}
+
+impl ConversionReport {
+
+    pub fn start(source: &str, destination: &str) -> Self {
+        Self {
+            source: source.to_owned(),
+            destination: destination.to_owned(),
+            artifacts: vec![],
+            warnings: vec![],
+            started_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0),
+            duration_ms: 0,
+            start_instant: Instant::now(),
+        }
+    }
+
+    /// Logs a warning and records it in the report
+    pub fn warn(&mut self, message: String) {
+        log::warn!("{message}");
+        self.warnings.push(message);
+    }
+
+    /// Lists the files written by the conversion, as returned by [`crate::workspace::Workspace::commit`]
+    pub fn add_files<P: AsRef<Path>>(&mut self, paths: &[P]) -> Result<(), IOError> {
+        for path in paths {
+            self.artifacts.push(Artifact::of_file(path)?);
+        }
+        Ok(())
+    }
+
+    /// Ends the report of the conversion
+    pub fn finish(mut self) -> Self {
+        self.duration_ms = self.start_instant.elapsed().as_millis() as u64;
+        self
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.artifacts.iter().map(|artifact| artifact.size).sum()
+    }
+
+}
+
+impl Display for ConversionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} -> {}: {} file(s), {}B in {}ms", self.source, self.destination, self.artifacts.len(), self.total_size(), self.duration_ms)?;
+        for artifact in &self.artifacts {
+            writeln!(f, "    {} {}B sha256:{}", artifact.path.to_string_lossy(), artifact.size, &artifact.sha256[..16])?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "    warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::workspace::Workspace;
+
+    use super::ConversionReport;
+
+    #[test]
+    fn lists_written_files() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.child("other.png"), [0u8; 4]).unwrap();
+
+        let mut report = ConversionReport::start("djibin:font.bin", "tiledir:tiles");
+        let mut workspace = Workspace::for_destination(temp_dir.child("tiles")).unwrap();
+        let staged_dir = workspace.stage(temp_dir.child("tiles")).unwrap();
+        std::fs::create_dir(&staged_dir).unwrap();
+        std::fs::write(staged_dir.join("000.png"), [0u8; 8]).unwrap();
+        std::fs::write(staged_dir.with_file_name("tiles@2x"), [0u8; 4]).unwrap();
+        report.warn("some warning".to_owned());
+        report.add_files(&workspace.commit().unwrap()).unwrap();
+        let report = report.finish();
+        let paths = report.artifacts.iter().map(|artifact| artifact.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths, vec![temp_dir.child("tiles").join("000.png"), temp_dir.child("tiles@2x")]);
+        assert_eq!(report.total_size(), 12);
+        assert_eq!(report.warnings, vec!["some warning".to_owned()]);
+        assert!(report.to_json().unwrap().contains("\"sha256\""));
+    }
+}
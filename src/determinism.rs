@@ -0,0 +1,213 @@
+
+//! Reproducibility audit of the conversions
+//!
+//! Font packs are built reproducibly when converting the same source twice with the same options produces the same
+//! bytes. The outputs of two conversions are compared file by file and each difference is attributed to the stage
+//! producing it. The settings known to make the outputs depend on more than the tiles are reported as well: the
+//! adaptive PNG filter, the ICC profile copied from the source and the order of the symbol specs, which follows the
+//! iteration order of a hash map.
+
+use std::collections::BTreeSet;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use strum::Display;
+
+use crate::image_encode::{ColorProfile, ImageEncodeOptions, PngFilter};
+use crate::osd::bin_file::metadata;
+use crate::osd::tile::container::symbol::spec::{LoadSpecsFileError, Specs};
+use crate::summary;
+
+
+// the iteration order of two hash maps with the same keys differs most of the time, a few loads make a coincidence
+// unlikely
+const SPECS_LOAD_COUNT: usize = 4;
+
+/// Stage of a conversion an output depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Stage {
+    /// settings of the PNG encoder
+    Encoder,
+    /// order of the symbol specs
+    SymbolSpecs,
+    /// dates recorded in the metadata sidecar and summary files
+    Timestamps,
+    /// any other output file
+    Output,
+}
+
+/// Setting making the outputs depend on more than the tiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub stage: Stage,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(stage: Stage, message: &str) -> Self {
+        Self { stage, message: message.to_owned() }
+    }
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.stage, self.message)
+    }
+}
+
+/// How a file differs between the outputs of two conversions, the paths are relative to the output directories
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// only written by the first conversion
+    Missing(PathBuf),
+    /// only written by the second conversion
+    Extra(PathBuf),
+    /// written by both with different content from `offset`
+    Content { path: PathBuf, offset: usize },
+}
+
+impl Difference {
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Missing(path) | Self::Extra(path) | Self::Content { path, .. } => path,
+        }
+    }
+
+    pub fn stage(&self) -> Stage {
+        file_stage(self.path())
+    }
+
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(path) => write!(f, "{} was only written by the first conversion", path.to_string_lossy()),
+            Self::Extra(path) => write!(f, "{} was only written by the second conversion", path.to_string_lossy()),
+            Self::Content { path, offset } => write!(f, "{} differs from byte {offset}", path.to_string_lossy()),
+        }
+    }
+}
+
+/// Stage the content of an output file depends on besides the tiles, the metadata sidecar and summary files record
+/// the date they are written at
+pub fn file_stage<P: AsRef<Path>>(path: P) -> Stage {
+    let file_name = path.as_ref().file_name().map(|file_name| file_name.to_string_lossy()).unwrap_or_default();
+    let timestamped_extensions = [metadata::SIDECAR_EXTENSION, summary::FILE_EXTENSION];
+    match file_name == summary::DIR_FILE_NAME || timestamped_extensions.iter().any(|extension| file_name.ends_with(&format!(".{extension}"))) {
+        true => Stage::Timestamps,
+        false => Stage::Output,
+    }
+}
+
+/// Offset of the first byte differing between two contents, the length of the shortest when one is a prefix of the other
+pub fn first_difference_offset(first: &[u8], second: &[u8]) -> Option<usize> {
+    match first.iter().zip(second).position(|(first_byte, second_byte)| first_byte != second_byte) {
+        Some(offset) => Some(offset),
+        None if first.len() != second.len() => Some(first.len().min(second.len())),
+        None => None,
+    }
+}
+
+fn relative_file_paths(root: &Path, dir: &Path, paths: &mut BTreeSet<PathBuf>) -> Result<(), IOError> {
+    for entry in fs_err::read_dir(dir)? {
+        let path = entry?.path();
+        match path.is_dir() {
+            true => relative_file_paths(root, &path, paths)?,
+            false => { paths.insert(path.strip_prefix(root).expect("the files are listed from the root").to_path_buf()); },
+        }
+    }
+    Ok(())
+}
+
+/// Compares the files of two directories and of their subdirectories byte for byte, the differences are ordered by path
+pub fn compare_dirs<P: AsRef<Path>, Q: AsRef<Path>>(first: P, second: Q) -> Result<Vec<Difference>, IOError> {
+    let (first, second) = (first.as_ref(), second.as_ref());
+    let mut first_paths = BTreeSet::new();
+    let mut second_paths = BTreeSet::new();
+    relative_file_paths(first, first, &mut first_paths)?;
+    relative_file_paths(second, second, &mut second_paths)?;
+
+    let mut differences = vec![];
+    for path in first_paths.union(&second_paths) {
+        match (first_paths.contains(path), second_paths.contains(path)) {
+            (true, false) => differences.push(Difference::Missing(path.clone())),
+            (false, true) => differences.push(Difference::Extra(path.clone())),
+            _ => if let Some(offset) = first_difference_offset(&fs_err::read(first.join(path))?, &fs_err::read(second.join(path))?) {
+                differences.push(Difference::Content { path: path.clone(), offset });
+            },
+        }
+    }
+    Ok(differences)
+}
+
+/// Settings of the PNG encoder making the images depend on more than their pixels
+pub fn encoder_findings(options: &ImageEncodeOptions) -> Vec<Finding> {
+    let mut findings = vec![];
+    if options.filter == PngFilter::Adaptive {
+        findings.push(Finding::new(Stage::Encoder, "the adaptive PNG filter picks the filter of each row with the heuristics of the encoder \
+            which can change between versions of the tool, pin a filter with `--png-filter`"));
+    }
+    if options.color_profile == ColorProfile::Source {
+        findings.push(Finding::new(Stage::Encoder, "the ICC profile of the source images is copied, the images depend on the profile \
+            embedded by the editor the source was saved with"));
+    }
+    findings
+}
+
+fn spec_order(specs: &Specs) -> Vec<usize> {
+    specs.iter().map(|sym_spec| sym_spec.start_tile_index()).collect()
+}
+
+/// Whether the order of the specs loaded from a symbol specs file changes between loads
+pub fn symbol_specs_findings<P: AsRef<Path>>(path: P) -> Result<Vec<Finding>, LoadSpecsFileError> {
+    let first_order = spec_order(&Specs::load_file(&path)?);
+    for _ in 1..SPECS_LOAD_COUNT {
+        if spec_order(&Specs::load_file(&path)?) != first_order {
+            let message = format!("the symbols of {} are listed in the iteration order of a hash map which changes between runs, \
+                the outputs iterating over the symbol specs can differ", path.as_ref().to_string_lossy());
+            return Ok(vec![Finding::new(Stage::SymbolSpecs, &message)]);
+        }
+    }
+    Ok(vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use temp_dir::TempDir;
+
+    use super::{compare_dirs, file_stage, first_difference_offset, symbol_specs_findings, Difference, Stage};
+
+    #[test]
+    fn differences() {
+        assert_eq!(first_difference_offset(b"font", b"font"), None);
+        assert_eq!(first_difference_offset(b"font", b"fond"), Some(3));
+        assert_eq!(first_difference_offset(b"font", b"fonts"), Some(4));
+
+        assert_eq!(file_stage("fonts/font_hd.bin.meta.yaml"), Stage::Timestamps);
+        assert_eq!(file_stage("tiles/summary.yaml"), Stage::Timestamps);
+        assert_eq!(file_stage("fonts/grid.png.summary.yaml"), Stage::Timestamps);
+        assert_eq!(file_stage("fonts/font_hd.bin"), Stage::Output);
+
+        let (first, second) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        for dir in [&first, &second] {
+            std::fs::create_dir(dir.child("tiles")).unwrap();
+            std::fs::write(dir.child("tiles/000.png"), b"tile").unwrap();
+        }
+        std::fs::write(first.child("font.bin"), b"font").unwrap();
+        std::fs::write(second.child("font.bin"), b"fond").unwrap();
+        std::fs::write(second.child("font.bin.meta.yaml"), b"created_at").unwrap();
+        assert_eq!(compare_dirs(first.path(), second.path()).unwrap(), [
+            Difference::Content { path: PathBuf::from("font.bin"), offset: 3 },
+            Difference::Extra(PathBuf::from("font.bin.meta.yaml")),
+        ]);
+
+        let specs_file = first.child("sym_specs.yaml");
+        std::fs::write(&specs_file, "version: 2\nsymbols:\n  ARROW: '0x10:2'\n").unwrap();
+        assert!(symbol_specs_findings(&specs_file).unwrap().is_empty());
+    }
+}
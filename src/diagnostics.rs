@@ -0,0 +1,141 @@
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    dimensions,
+    osd::{avatar_file, bin_file, tile::Kind as TileKind, wtfos_file},
+};
+
+
+pub type ImageDimensions = dimensions::Dimensions<u32>;
+
+/// log target of the detection explanations, the command line tool only enables it with `--explain-detection`
+pub const EXPLANATION_LOG_TARGET: &str = "hd_fpv_osd_font_tool::detection_explanation";
+
+/// What a tile kind detection was based on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectionEvidence {
+    /// size of a raw RGBA bin file
+    FileSize { bytes: u64 },
+    /// dimensions of the first tile/symbol image file found in a directory
+    ImageDimensions { file_path: PathBuf, dimensions: ImageDimensions },
+    /// dimensions of a grid image and the grid dimensions deduced from it
+    GridImageDimensions { dimensions: ImageDimensions, grid_width: usize, grid_height: usize, separator_thickness: u32 },
+    /// dimensions of an Avatar font image
+    AvatarImageDimensions { dimensions: ImageDimensions },
+    /// dimensions of a msp-osd font image
+    WtfosImageDimensions { dimensions: ImageDimensions },
+}
+
+impl DetectionEvidence {
+
+    // what the evidence would be for tiles of `tile_kind`
+    fn expectation(&self, tile_kind: TileKind) -> String {
+        let tile = tile_kind.dimensions();
+        match self {
+            Self::FileSize { .. } =>
+                format!("{} tiles of {tile} pixels of 4 bytes = {}B", bin_file::TILE_COUNT, tile_kind.bin_file_size_bytes()),
+            Self::ImageDimensions { .. } => format!("tile images of {tile} pixels"),
+            Self::GridImageDimensions { grid_width, separator_thickness, .. } => {
+                let width = *grid_width as u32 * tile.width + (*grid_width as u32 - 1) * separator_thickness;
+                format!("{grid_width} tiles of {} pixels and {} separators of {separator_thickness} pixels = {width} pixels wide, \
+                    rows of {} pixels separated by {separator_thickness} pixels", tile.width, grid_width - 1, tile.height)
+            },
+            Self::AvatarImageDimensions { .. } =>
+                format!("a column of {} tiles of {tile} pixels = {}", avatar_file::TILE_COUNT, tile_kind.avatar_image_dimensions()),
+            Self::WtfosImageDimensions { .. } =>
+                format!("{} columns of {} tiles of {tile} pixels = {}", wtfos_file::PAGE_COUNT, wtfos_file::PAGE_TILE_COUNT, tile_kind.wtfos_image_dimensions()),
+        }
+    }
+
+}
+
+impl Display for DetectionEvidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FileSize { bytes } => write!(f, "file size {bytes}B"),
+            Self::ImageDimensions { file_path, dimensions } => write!(f, "image {} has dimensions {dimensions}", file_path.to_string_lossy()),
+            Self::GridImageDimensions { dimensions, grid_width, grid_height, .. } => write!(f, "grid image dimensions {dimensions}, {grid_width}x{grid_height} grid"),
+            Self::AvatarImageDimensions { dimensions } => write!(f, "Avatar image dimensions {dimensions}"),
+            Self::WtfosImageDimensions { dimensions } => write!(f, "msp-osd image dimensions {dimensions}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DetectionEvent {
+    pub path: PathBuf,
+    pub tile_kind: TileKind,
+    pub evidence: DetectionEvidence,
+}
+
+impl DetectionEvent {
+    pub fn new<P: AsRef<Path>>(path: P, tile_kind: TileKind, evidence: DetectionEvidence) -> Self {
+        Self { path: path.as_ref().to_path_buf(), tile_kind, evidence }
+    }
+
+    /// The detection followed by what each tile kind expects, one line per tile kind
+    pub fn explanation(&self) -> String {
+        let mut explanation = self.to_string();
+        for tile_kind in TileKind::iter() {
+            let verdict = if tile_kind == self.tile_kind { "matches" } else { "does not match" };
+            explanation.push_str(&format!("\n  {tile_kind}: {}, {verdict}", self.evidence.expectation(tile_kind)));
+        }
+        if let DetectionEvidence::GridImageDimensions { dimensions, grid_height, separator_thickness, .. } = &self.evidence {
+            let tile_height = self.tile_kind.dimensions().height;
+            explanation.push_str(&format!("\n  {grid_height} rows = ({} - {tile_height}) / ({tile_height} + {separator_thickness}) + 1", dimensions.height));
+        }
+        explanation
+    }
+}
+
+impl Display for DetectionEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "detected {} kind of tiles in {} ({})", self.tile_kind, self.path.to_string_lossy(), self.evidence)
+    }
+}
+
+/// A file operation failed with a transient IO error and is going to be attempted again, see [`crate::retry`]
+#[derive(Debug, Clone)]
+pub struct RetryEvent {
+    pub path: PathBuf,
+    pub failed_attempt: u32,
+    pub delay: Duration,
+    pub error: String,
+}
+
+impl Display for RetryEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "attempt {} on {} failed: {}, retrying in {}ms", self.failed_attempt, self.path.to_string_lossy(), self.error, self.delay.as_millis())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Detection(DetectionEvent),
+    Retry(RetryEvent),
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Detection(event) => event.fmt(f),
+            Event::Retry(event) => event.fmt(f),
+        }
+    }
+}
+
+/// Logs the events at info level, the handler of the loaders which are not given one
+///
+/// The detections are logged with their explanation to [`EXPLANATION_LOG_TARGET`] when it is enabled.
+pub fn log_event(event: &Event) {
+    match event {
+        Event::Detection(detection) if log::log_enabled!(target: EXPLANATION_LOG_TARGET, log::Level::Info) =>
+            log::info!(target: EXPLANATION_LOG_TARGET, "{}", detection.explanation()),
+        event => log::info!("{event}"),
+    }
+}
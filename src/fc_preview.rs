@@ -0,0 +1,172 @@
+
+//! Experimental preview of the tiles of a font on the OSD of a HD FPV system, the tool playing the part of the flight
+//! controller
+//!
+//! A serial adapter is wired to the MSP link of the air unit in place of the flight controller and MSP DisplayPort
+//! messages, the ones the firmwares use to draw their OSD, are sent to show the tiles of the font page by page as blocks
+//! of 16 rows of 16 tiles. The goggles draw the characters with the font they have installed: this shows how the
+//! tiles render on the real system, not the tiles of the collection being edited which is only used for its tile count.
+
+use std::io::{Error as IOError, Write};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::osd::preview::ScreenGrid;
+
+
+pub const DEFAULT_BAUD_RATE: u32 = 115_200;
+
+const MSP_DISPLAYPORT: u8 = 182;
+const TILES_PER_PAGE: usize = 256;
+/// columns of the block of tiles a page is drawn as
+const PAGE_COLUMNS: usize = 16;
+/// the air units stop drawing the OSD when they do not receive heartbeats for a while
+const HEARTBEAT_PERIOD: Duration = Duration::from_millis(500);
+const SERIAL_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy)]
+enum DisplayPortCommand {
+    Heartbeat = 0,
+    Release = 1,
+    ClearScreen = 2,
+    WriteString = 3,
+    DrawScreen = 4,
+}
+
+#[derive(Debug, Error)]
+pub enum FcPreviewError {
+    #[error("failed to open serial port {port}: {error}")]
+    OpenError { port: String, error: serialport::Error },
+    #[error("failed to write to the serial port: {0}")]
+    WriteError(#[from] IOError),
+}
+
+/// MSP v1 frame as sent by a flight controller: `$M>`, payload size, command, payload and XOR checksum of the size,
+/// command and payload
+pub fn msp_frame(command: u8, payload: &[u8]) -> Vec<u8> {
+    let payload_size = u8::try_from(payload.len()).expect("MSP v1 payloads are at most 255 bytes long");
+    let mut frame = Vec::with_capacity(payload.len() + 6);
+    frame.extend_from_slice(b"$M>");
+    frame.push(payload_size);
+    frame.push(command);
+    frame.extend_from_slice(payload);
+    let checksum = frame[3..].iter().fold(0, |checksum, byte| checksum ^ byte);
+    frame.push(checksum);
+    frame
+}
+
+fn displayport_frame(command: DisplayPortCommand, arguments: &[u8]) -> Vec<u8> {
+    msp_frame(MSP_DISPLAYPORT, &[&[command as u8], arguments].concat())
+}
+
+/// DisplayPort message writing tiles of a page of 256 tiles from a screen position, the page is selected with the
+/// attribute byte
+pub fn write_tiles_frame(row: u8, column: u8, page: u8, tiles: &[u8]) -> Vec<u8> {
+    displayport_frame(DisplayPortCommand::WriteString, &[&[row, column, page], tiles].concat())
+}
+
+/// DisplayPort messages writing a page of the tiles of a font of `tile_count` tiles centered on the screen, one message
+/// per row of the block
+pub fn page_frames(page: usize, tile_count: usize, grid: ScreenGrid) -> Vec<Vec<u8>> {
+    let page_start = page * TILES_PER_PAGE;
+    let page_end = tile_count.min(page_start + TILES_PER_PAGE);
+    let top = grid.rows().saturating_sub(TILES_PER_PAGE / PAGE_COLUMNS) / 2;
+    let left = grid.columns().saturating_sub(PAGE_COLUMNS) / 2;
+    (page_start..page_end).step_by(PAGE_COLUMNS).enumerate()
+        .take(grid.rows())
+        .map(|(row, row_start)| {
+            let tiles: Vec<u8> = (row_start..page_end.min(row_start + PAGE_COLUMNS)).map(|index| (index % TILES_PER_PAGE) as u8).collect();
+            write_tiles_frame((top + row) as u8, left as u8, page as u8, &tiles)
+        })
+        .collect()
+}
+
+pub fn open_serial_port(port: &str, baud_rate: u32) -> Result<Box<dyn serialport::SerialPort>, FcPreviewError> {
+    serialport::new(port, baud_rate).timeout(SERIAL_TIMEOUT).open()
+        .map_err(|error| FcPreviewError::OpenError { port: port.to_owned(), error })
+}
+
+/// DisplayPort session drawing the pages of a font on the OSD
+pub struct FcPreview<W: Write> {
+    port: W,
+    grid: ScreenGrid,
+}
+
+impl<W: Write> FcPreview<W> {
+
+    pub fn new(port: W, grid: ScreenGrid) -> Self {
+        Self { port, grid }
+    }
+
+    fn send(&mut self, command: DisplayPortCommand) -> Result<(), IOError> {
+        self.port.write_all(&displayport_frame(command, &[]))
+    }
+
+    pub fn show_page(&mut self, page: usize, tile_count: usize) -> Result<(), IOError> {
+        self.send(DisplayPortCommand::Heartbeat)?;
+        self.send(DisplayPortCommand::ClearScreen)?;
+        for frame in page_frames(page, tile_count, self.grid) {
+            self.port.write_all(&frame)?;
+        }
+        self.send(DisplayPortCommand::DrawScreen)?;
+        self.port.flush()
+    }
+
+    /// Keeps the screen drawn for `duration`, sending heartbeats
+    pub fn hold(&mut self, duration: Duration) -> Result<(), IOError> {
+        let start = Instant::now();
+        while let Some(remaining) = duration.checked_sub(start.elapsed()) {
+            sleep(remaining.min(HEARTBEAT_PERIOD));
+            self.send(DisplayPortCommand::Heartbeat)?;
+            self.port.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Shows the pages of a font of `tile_count` tiles one after the other for `interval` each, `cycles` times
+    pub fn cycle_pages(&mut self, tile_count: usize, interval: Duration, cycles: usize) -> Result<(), IOError> {
+        let page_count = (tile_count + TILES_PER_PAGE - 1) / TILES_PER_PAGE;
+        for _ in 0..cycles {
+            for page in 0..page_count {
+                log::info!("showing tiles {} to {}", page * TILES_PER_PAGE, tile_count.min((page + 1) * TILES_PER_PAGE) - 1);
+                self.show_page(page, tile_count)?;
+                self.hold(interval)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears the screen and hands the OSD back to the air unit
+    pub fn release(&mut self) -> Result<(), IOError> {
+        self.send(DisplayPortCommand::ClearScreen)?;
+        self.send(DisplayPortCommand::DrawScreen)?;
+        self.send(DisplayPortCommand::Release)?;
+        self.port.flush()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::preview::ScreenGrid;
+
+    use super::{msp_frame, page_frames, FcPreview};
+
+    #[test]
+    fn displayport_frames() {
+        assert_eq!(msp_frame(182, &[0]), b"$M>\x01\xb6\x00\xb7");
+
+        let frames = page_frames(1, 260, ScreenGrid::HD);
+        assert_eq!(frames.len(), 1);
+        // row 1 of the 18 rows, column 17 of the 50 columns, page 1 then tiles 256 to 259 of the page
+        assert_eq!(frames[0][5..frames[0].len() - 1], [3, 1, 17, 1, 0, 1, 2, 3]);
+        assert_eq!(page_frames(0, 260, ScreenGrid::SD).len(), 16);
+
+        let mut preview = FcPreview::new(vec![], ScreenGrid::SD);
+        preview.show_page(0, 16).unwrap();
+        // heartbeat, clear screen, a row of tiles, draw screen
+        assert_eq!(preview.port.len(), 7 + 7 + 26 + 7);
+    }
+}
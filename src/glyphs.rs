@@ -0,0 +1,75 @@
+
+//! Tile indices of well-known glyphs of the fonts of each firmware
+//!
+//! The constants of the `ardu` and `inav` modules are generated from the `glyphs/<firmware>.txt` data files, e.g.
+//! `glyphs::inav::BATTERY`, the ranges of glyphs like the ArduPilot home arrows are listed apart from the glyphs in
+//! `RANGES`. `Firmware` selects the glyphs of a firmware at runtime.
+
+use std::ops::Range;
+
+use clap::ValueEnum;
+use strum::EnumIter;
+
+
+include!(concat!(env!("OUT_DIR"), "/glyphs.rs"));
+
+/// Firmware named by the ident of its font files, e.g. `font_inav.bin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, ValueEnum, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Firmware {
+    Ardu,
+    Inav,
+}
+
+impl Firmware {
+
+    pub fn from_ident(ident: &str) -> Option<Self> {
+        Self::from_str(ident, true).ok()
+    }
+
+    /// Glyph names and indices in the order of the data file of the firmware
+    pub fn glyphs(&self) -> &'static [(&'static str, usize)] {
+        match self {
+            Self::Ardu => ardu::ALL,
+            Self::Inav => inav::ALL,
+        }
+    }
+
+    /// Index of the glyph named `name`, the name is case insensitive
+    pub fn glyph_index(&self, name: &str) -> Option<usize> {
+        self.glyphs().iter().find(|(glyph_name, _)| glyph_name.eq_ignore_ascii_case(name)).map(|(_, index)| *index)
+    }
+
+    /// Name of the glyph at `index`, `None` if it is not a well-known glyph of the firmware
+    pub fn glyph_name(&self, index: usize) -> Option<&'static str> {
+        self.glyphs().iter().find(|(_, glyph_index)| *glyph_index == index).map(|(glyph_name, _)| *glyph_name)
+    }
+
+    /// Ranges of glyphs with their name, e.g. the home arrows of ArduPilot
+    pub fn glyph_ranges(&self) -> &'static [(&'static str, Range<usize>)] {
+        match self {
+            Self::Ardu => ardu::RANGES,
+            Self::Inav => inav::RANGES,
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ardu, inav, Firmware};
+
+    #[test]
+    fn glyphs() {
+        assert_eq!(inav::BATTERY, 0x90);
+        assert_eq!(Firmware::from_ident("inav"), Some(Firmware::Inav));
+        assert_eq!(Firmware::Inav.glyph_index("lat"), Some(inav::LAT));
+        assert_eq!(Firmware::Ardu.glyph_name(ardu::HOME_ARROW_START), Some("HOME_ARROW_START"));
+        assert_eq!(Firmware::Ardu.glyph_name(0x10), None);
+        assert_eq!(Firmware::Inav.glyph_index("HOME_ARROW_START"), None);
+        assert_eq!(Firmware::Ardu.glyph_index("HOME_ARROW_COUNT"), None);
+        assert_eq!(ardu::HOME_ARROW_COUNT, 16);
+        assert_eq!(Firmware::Ardu.glyph_ranges()[0], ("HOME_ARROW", 0x60..0x70));
+        assert!(Firmware::Inav.glyph_ranges().is_empty());
+    }
+}
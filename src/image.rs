@@ -1,12 +1,18 @@
 
 use std::path::{Path, PathBuf};
-use std::io::Error as IOError;
+use std::io::{Error as IOError, Read};
 use std::ops::Deref;
 
 use derive_more::From;
 use thiserror::Error;
-use image::{DynamicImage, ImageError, EncodableLayout, ImageBuffer, PixelWithColorType};
+use image::{ColorType, DynamicImage, ImageEncoder, ImageError, EncodableLayout, ImageBuffer, PixelWithColorType};
+use image::codecs::png::PngEncoder;
 use image::io::Reader as ImageReader;
+use fs_err::File;
+
+use crate::image_encode::{self, ImageEncodeOptions};
+use crate::input_file::{self, DegenerateFileError};
+use crate::io_context::IoContext;
 
 
 #[derive(Debug, Error, From)]
@@ -20,7 +26,9 @@ pub enum ReadError {
     DecodeError {
         file_path: PathBuf,
         error: ImageError
-    }
+    },
+    #[error(transparent)]
+    DegenerateFile(DegenerateFileError),
 }
 
 impl ReadError {
@@ -36,8 +44,22 @@ impl ReadError {
     }
 }
 
+// reads the first bytes of the file to report empty and truncated files before trying to decode them
+fn check_image_file<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<(), ReadError> {
+    let mut file = context.retry_io(path.as_ref(), || File::open(path.as_ref())).map_err(|error| ReadError::open_error(&path, error))?;
+    let size = file.metadata().map_err(|error| ReadError::open_error(&path, error))?.len();
+    let mut header = Vec::with_capacity(input_file::HEADER_SIZE);
+    (&mut file).take(input_file::HEADER_SIZE as u64).read_to_end(&mut header).map_err(|error| ReadError::open_error(&path, error))?;
+    Ok(input_file::check_image_file_header(&path, size, &header)?)
+}
+
 pub fn read_image_file<P: AsRef<Path>>(path: P) -> Result<DynamicImage, ReadError> {
-    let reader = ImageReader::open(&path).map_err(|error| ReadError::open_error(&path, error))?;
+    read_image_file_with_context(path, &IoContext::default())
+}
+
+pub fn read_image_file_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<DynamicImage, ReadError> {
+    check_image_file(&path, context)?;
+    let reader = context.retry_io(path.as_ref(), || ImageReader::open(&path)).map_err(|error| ReadError::open_error(&path, error))?;
     reader.decode().map_err(|error| ReadError::decode_error(&path, error) )
 }
 
@@ -55,7 +77,13 @@ impl WriteError {
 }
 
 pub trait WriteImageFile {
-    fn write_image_file<Q: AsRef<Path>>(&self, path: Q) -> Result<(), WriteError>;
+    /// Writes the image with the default encode options, the format is selected from the file extension
+    fn write_image_file<Q: AsRef<Path>>(&self, path: Q) -> Result<(), WriteError> {
+        self.write_image_file_with_options(path, &ImageEncodeOptions::default())
+    }
+
+    /// The options are only used when writing PNG files
+    fn write_image_file_with_options<Q: AsRef<Path>>(&self, path: Q, options: &ImageEncodeOptions) -> Result<(), WriteError>;
 }
 
 impl<P, Container> WriteImageFile for ImageBuffer<P, Container>
@@ -64,7 +92,23 @@ where
     [P::Subpixel]: EncodableLayout,
     Container: Deref<Target = [P::Subpixel]>,
 {
-    fn write_image_file<Q: AsRef<Path>>(&self, path: Q) -> Result<(), WriteError> {
-        self.save(&path).map_err(|error| WriteError::new(&path, error) )
+    fn write_image_file_with_options<Q: AsRef<Path>>(&self, path: Q, options: &ImageEncodeOptions) -> Result<(), WriteError> {
+        if path.as_ref().extension().map_or(true, |extension| ! extension.eq_ignore_ascii_case("png")) {
+            return self.save(&path).map_err(|error| WriteError::new(&path, error));
+        }
+        let file = fs_err::File::create(path.as_ref()).map_err(|error| WriteError::new(&path, ImageError::IoError(error)))?;
+        let writer = std::io::BufWriter::new(file);
+        let color_type = match P::COLOR_TYPE {
+            ColorType::L8 => png::ColorType::Grayscale,
+            ColorType::La8 => png::ColorType::GrayscaleAlpha,
+            ColorType::Rgb8 => png::ColorType::Rgb,
+            ColorType::Rgba8 => png::ColorType::Rgba,
+            // no color profile for the 16 bits and floating point images which the library does not write itself
+            color_type => return PngEncoder::new_with_quality(writer, options.compression_type(), options.filter_type())
+                .write_image((**self).as_bytes(), self.width(), self.height(), color_type)
+                .map_err(|error| WriteError::new(&path, error)),
+        };
+        image_encode::write_png(writer, options, self.dimensions(), color_type, (**self).as_bytes(), &[])
+            .map_err(|error| WriteError::new(&path, image_encode::image_error(error)))
     }
 }
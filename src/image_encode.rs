@@ -0,0 +1,224 @@
+
+//! Parameters of the PNG encoder used for every PNG file written by the library
+//!
+//! Images are never interlaced. The parameters default to the `image` crate defaults and are given to the save
+//! functions taking [`ImageEncodeOptions`] so that batch exports trade speed against size or produce identical bytes.
+//! Images are tagged as sRGB unless the color profile is stripped or copied from the source.
+
+use std::io::Write;
+use std::path::Path;
+
+use clap::ValueEnum;
+use image::{codecs::png::{CompressionType, FilterType}, error::EncodingError, ImageError, ImageFormat};
+
+use crate::osd::tile::file_format::PngCompression;
+
+
+/// Filter applied to the rows of the PNG images before compressing them, `adaptive` picks the best one for each row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PngFilter {
+    None,
+    Sub,
+    Up,
+    Avg,
+    Paeth,
+    #[default]
+    Adaptive,
+}
+
+impl From<PngFilter> for FilterType {
+    fn from(filter: PngFilter) -> Self {
+        match filter {
+            PngFilter::None => FilterType::NoFilter,
+            PngFilter::Sub => FilterType::Sub,
+            PngFilter::Up => FilterType::Up,
+            PngFilter::Avg => FilterType::Avg,
+            PngFilter::Paeth => FilterType::Paeth,
+            PngFilter::Adaptive => FilterType::Adaptive,
+        }
+    }
+}
+
+/// Color space information written in the PNG files, without it color managed editors may shift the glyph colors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorProfile {
+    /// tag the images as sRGB
+    #[default]
+    Srgb,
+    /// copy the ICC profile of the source images, the images are tagged as sRGB when the source has none
+    Source,
+    /// write no color space information
+    Strip,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageEncodeOptions {
+    pub compression: PngCompression,
+    pub filter: PngFilter,
+    pub color_profile: ColorProfile,
+    /// content of the iCCP chunk written with the `source` color profile, see [`Self::with_source_icc_chunk`]
+    pub source_icc_chunk: Option<Vec<u8>>,
+}
+
+impl ImageEncodeOptions {
+
+    pub const fn new(compression: PngCompression, filter: PngFilter, color_profile: ColorProfile) -> Self {
+        Self { compression, filter, color_profile, source_icc_chunk: None }
+    }
+
+    /// Keeps the ICC profile of the first PNG file of the source paths, directories included, when the color profile is
+    /// `source`
+    pub fn with_source_icc_chunk<P: AsRef<Path>>(self, source_paths: &[P]) -> Self {
+        match self.color_profile {
+            ColorProfile::Source => Self { source_icc_chunk: load_source_icc_chunk(source_paths), ..self },
+            _ => self,
+        }
+    }
+
+    pub fn compression_type(&self) -> CompressionType {
+        self.compression.into()
+    }
+
+    pub fn filter_type(&self) -> FilterType {
+        self.filter.into()
+    }
+
+    /// Applies the options to an encoder of the `png` crate
+    pub(crate) fn configure_png_encoder<W: std::io::Write>(&self, encoder: &mut png::Encoder<W>) {
+        encoder.set_compression(match self.compression {
+            PngCompression::Fast => png::Compression::Fast,
+            PngCompression::Default => png::Compression::Default,
+            PngCompression::Best => png::Compression::Best,
+        });
+        match self.filter {
+            PngFilter::Adaptive => encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive),
+            filter => {
+                encoder.set_adaptive_filter(png::AdaptiveFilterType::NonAdaptive);
+                encoder.set_filter(match filter {
+                    PngFilter::Sub => png::FilterType::Sub,
+                    PngFilter::Up => png::FilterType::Up,
+                    PngFilter::Avg => png::FilterType::Avg,
+                    PngFilter::Paeth => png::FilterType::Paeth,
+                    _ => png::FilterType::NoFilter,
+                });
+            },
+        }
+    }
+
+    /// Writes the sRGB or iCCP chunk, must be called before the image data is written
+    pub(crate) fn write_color_profile<W: Write>(&self, writer: &mut png::Writer<W>) -> Result<(), png::EncodingError> {
+        let icc_chunk = match self.color_profile {
+            ColorProfile::Strip => return Ok(()),
+            ColorProfile::Srgb => None,
+            ColorProfile::Source => self.source_icc_chunk.as_deref(),
+        };
+        match icc_chunk {
+            Some(icc_chunk) => writer.write_chunk(png::chunk::iCCP, icc_chunk),
+            // perceptual rendering intent
+            None => writer.write_chunk(png::chunk::sRGB, &[0]),
+        }
+    }
+
+}
+
+/// Encodes 8 bits per channel image data as PNG with the given options and uncompressed text chunks
+pub(crate) fn write_png<W: Write>(writer: W, options: &ImageEncodeOptions, dimensions: (u32, u32), color_type: png::ColorType,
+        data: &[u8], text_chunks: &[(&str, String)]) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, dimensions.0, dimensions.1);
+    encoder.set_color(color_type);
+    encoder.set_depth(png::BitDepth::Eight);
+    options.configure_png_encoder(&mut encoder);
+    for (keyword, text) in text_chunks {
+        encoder.add_text_chunk((*keyword).to_owned(), text.clone())?;
+    }
+    let mut writer = encoder.write_header()?;
+    options.write_color_profile(&mut writer)?;
+    writer.write_image_data(data)?;
+    writer.finish()
+}
+
+pub(crate) fn image_error(error: png::EncodingError) -> ImageError {
+    match error {
+        png::EncodingError::IoError(error) => ImageError::IoError(error),
+        error => ImageError::Encoding(EncodingError::new(ImageFormat::Png.into(), error)),
+    }
+}
+
+/// Raw content of the iCCP chunk of a PNG file: profile name, compression method and compressed profile
+pub fn read_png_icc_chunk<P: AsRef<Path>>(path: P) -> Option<Vec<u8>> {
+    const SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    let bytes = fs_err::read(path).ok()?;
+    let mut chunks = bytes.strip_prefix(SIGNATURE)?;
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+        match &chunks[4..8] {
+            b"iCCP" => return chunks.get(8..8 + length).map(<[u8]>::to_vec),
+            // the profile must come before the image data
+            b"IDAT" | b"IEND" => return None,
+            _ => chunks = chunks.get(12 + length..)?,
+        }
+    }
+    None
+}
+
+/// Raw content of the iCCP chunk of the first PNG file of the source paths with one, the PNG files of the directories are
+/// looked at in the order of their names
+pub fn load_source_icc_chunk<P: AsRef<Path>>(source_paths: &[P]) -> Option<Vec<u8>> {
+    let mut png_paths = vec![];
+    for path in source_paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            let mut dir_png_paths = fs_err::read_dir(path).into_iter().flatten().flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("png")))
+                .collect::<Vec<_>>();
+            dir_png_paths.sort();
+            png_paths.extend(dir_png_paths);
+        } else {
+            png_paths.push(path.to_path_buf());
+        }
+    }
+    png_paths.iter().find_map(read_png_icc_chunk)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::image::WriteImageFile;
+    use crate::osd::tile::file_format::PngCompression;
+
+    use super::{read_png_icc_chunk, ColorProfile, ImageEncodeOptions, PngFilter};
+
+    #[test]
+    fn pinned_options() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut image = image::RgbaImage::new(8, 8);
+        image.put_pixel(2, 5, image::Rgba([10, 20, 30, 255]));
+        let options = ImageEncodeOptions::new(PngCompression::Best, PngFilter::None, ColorProfile::Strip);
+        for file_name in ["a.png", "b.png"] {
+            image.write_image_file_with_options(temp_dir.child(file_name), &options).unwrap();
+        }
+        assert_eq!(std::fs::read(temp_dir.child("a.png")).unwrap(), std::fs::read(temp_dir.child("b.png")).unwrap());
+        assert_eq!(image::open(temp_dir.child("a.png")).unwrap().into_rgba8(), image);
+    }
+
+    #[test]
+    fn color_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let image = image::RgbaImage::new(8, 8);
+        let options = ImageEncodeOptions { color_profile: ColorProfile::Srgb, ..Default::default() };
+        image.write_image_file_with_options(temp_dir.child("srgb.png"), &options).unwrap();
+        let file = std::fs::File::open(temp_dir.child("srgb.png")).unwrap();
+        let reader = png::Decoder::new(file).read_info().unwrap();
+        assert!(reader.info().srgb.is_some());
+        assert!(read_png_icc_chunk(temp_dir.child("srgb.png")).is_none());
+
+        let icc_chunk = b"profile\0\0icc".to_vec();
+        let options = ImageEncodeOptions { color_profile: ColorProfile::Source, source_icc_chunk: Some(icc_chunk.clone()), ..Default::default() };
+        image.write_image_file_with_options(temp_dir.child("source.png"), &options).unwrap();
+        let options = ImageEncodeOptions { color_profile: ColorProfile::Source, ..Default::default() };
+        assert_eq!(options.with_source_icc_chunk(&[temp_dir.child("source.png")]).source_icc_chunk, Some(icc_chunk));
+        assert!(ImageEncodeOptions::default().with_source_icc_chunk(&[temp_dir.child("source.png")]).source_icc_chunk.is_none());
+    }
+}
@@ -0,0 +1,87 @@
+
+//! Checks of the input files run before decoding them so that empty and truncated files are reported as such instead
+//! of as files of the wrong size or format
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::osd::tile::Kind as TileKind;
+
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// signature, header chunk and end chunk, a PNG file cannot be shorter
+const MIN_PNG_FILE_SIZE: u64 = 8 + 25 + 12;
+/// number of bytes of the header read to identify the format of an image file
+pub const HEADER_SIZE: usize = PNG_SIGNATURE.len();
+
+#[derive(Debug, Error)]
+pub enum DegenerateFileError {
+    #[error("file {0} is empty")]
+    Empty(PathBuf),
+    #[error("file {file_path} is truncated: it is {size}B long, {expected}")]
+    Truncated { file_path: PathBuf, size: u64, expected: String },
+}
+
+impl DegenerateFileError {
+    pub fn truncated<P: AsRef<Path>>(file_path: P, size: u64, expected: String) -> Self {
+        Self::Truncated { file_path: file_path.as_ref().to_path_buf(), size, expected }
+    }
+}
+
+fn check_not_empty<P: AsRef<Path>>(path: P, size: u64) -> Result<(), DegenerateFileError> {
+    if size == 0 {
+        return Err(DegenerateFileError::Empty(path.as_ref().to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Checks that a file is long enough to be a bin file of the smallest tile kind
+pub fn check_bin_file_size<P: AsRef<Path>>(path: P, size: u64) -> Result<(), DegenerateFileError> {
+    check_not_empty(&path, size)?;
+    let min_size = TileKind::HD.bin_file_size_bytes().min(TileKind::SD.bin_file_size_bytes()) as u64;
+    if size < min_size {
+        return Err(DegenerateFileError::truncated(path, size, format!("bin files are at least {min_size}B long")));
+    }
+    Ok(())
+}
+
+/// Checks that an image file is long enough to be decoded given its size and its first bytes
+pub fn check_image_file_header<P: AsRef<Path>>(path: P, size: u64, header: &[u8]) -> Result<(), DegenerateFileError> {
+    check_not_empty(&path, size)?;
+    let header = &header[..header.len().min(PNG_SIGNATURE.len())];
+    if PNG_SIGNATURE.starts_with(header) && size < MIN_PNG_FILE_SIZE {
+        return Err(DegenerateFileError::truncated(path, size, format!("PNG files are at least {MIN_PNG_FILE_SIZE}B long")));
+    }
+    if size < HEADER_SIZE as u64 {
+        return Err(DegenerateFileError::truncated(path, size, "it is too short to be an image file".to_owned()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::image::{read_image_file, ReadError};
+    use crate::osd::avatar_file::{self, LoadError as AvatarLoadError};
+    use crate::osd::bin_file::{BinFileReader, OpenError};
+
+    use super::DegenerateFileError;
+
+    #[test]
+    fn degenerate_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let empty_path = temp_dir.child("empty.png");
+        std::fs::write(&empty_path, []).unwrap();
+        let tiny_path = temp_dir.child("tiny.png");
+        std::fs::write(&tiny_path, b"\x89PN").unwrap();
+
+        assert!(matches!(BinFileReader::open(&empty_path), Err(OpenError::DegenerateFileError(DegenerateFileError::Empty(_)))));
+        assert!(matches!(BinFileReader::open(&tiny_path), Err(OpenError::DegenerateFileError(DegenerateFileError::Truncated { size: 3, .. }))));
+        assert!(matches!(read_image_file(&empty_path), Err(ReadError::DegenerateFile(DegenerateFileError::Empty(_)))));
+        assert!(matches!(read_image_file(&tiny_path), Err(ReadError::DegenerateFile(DegenerateFileError::Truncated { .. }))));
+        assert!(matches!(avatar_file::load(&tiny_path),
+            Err(AvatarLoadError::ImageReadError(ReadError::DegenerateFile(DegenerateFileError::Truncated { .. })))));
+    }
+}
@@ -0,0 +1,51 @@
+//! Context the loaders and savers run their file operations in
+//!
+//! The context is given by the caller of the conversion entry points and passed down to the loaders and savers
+//! instead of being set for the whole library, so that several conversions can report to their own handler and retry
+//! with their own policy. The loaders and savers taking no context run in the default one which logs the events and
+//! does not retry.
+
+use std::io::Error as IOError;
+use std::path::Path;
+
+use crate::diagnostics::{self, DetectionEvent, DetectionEvidence, Event};
+use crate::osd::tile::Kind as TileKind;
+use crate::retry::RetryPolicy;
+
+
+#[derive(Clone, Copy)]
+pub struct IoContext<'a> {
+    /// how the file operations failing with a transient IO error are retried
+    pub retry: RetryPolicy,
+    /// called for each diagnostics event, from the threads loading and saving the tiles when the `rayon` feature is
+    /// enabled
+    pub diagnostics: &'a (dyn Fn(&Event) + Sync),
+}
+
+impl<'a> IoContext<'a> {
+
+    pub fn new(retry: RetryPolicy, diagnostics: &'a (dyn Fn(&Event) + Sync)) -> Self {
+        Self { retry, diagnostics }
+    }
+
+    pub fn emit(&self, event: Event) {
+        (self.diagnostics)(&event)
+    }
+
+    pub fn emit_detection<P: AsRef<Path>>(&self, path: P, tile_kind: TileKind, evidence: DetectionEvidence) {
+        self.emit(Event::Detection(DetectionEvent::new(path, tile_kind, evidence)))
+    }
+
+    /// Runs the file operation with the retry policy of the context, see [`RetryPolicy::retry_io`]
+    pub fn retry_io<T, F: FnMut() -> Result<T, IOError>>(&self, path: &Path, operation: F) -> Result<T, IOError> {
+        self.retry.retry_io(path, self.diagnostics, operation)
+    }
+
+}
+
+// the events are logged, see `diagnostics::log_event`
+impl Default for IoContext<'_> {
+    fn default() -> Self {
+        Self::new(RetryPolicy::NONE, &diagnostics::log_event)
+    }
+}
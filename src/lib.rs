@@ -1,9 +1,42 @@
 
 #![forbid(unsafe_code)]
 
+extern crate alloc;
+
+pub mod analysis;
+pub mod background;
+pub mod build_info;
+pub mod check;
+pub mod collection_format;
+pub mod conversion;
+pub mod conversion_report;
+pub mod determinism;
 pub mod dimensions;
+pub mod diagnostics;
+#[cfg(feature = "preview-on-fc")]
+pub mod fc_preview;
+pub mod glyphs;
+pub mod image_encode;
+pub mod input_file;
+pub mod io_context;
 pub mod osd;
 pub mod prelude;
 pub mod log_level;
+pub mod man_pages;
+pub mod migrate;
+pub mod preflight;
+pub mod retry;
+pub mod scale;
+pub mod summary;
+#[cfg(feature = "samples")]
+pub mod samples;
+pub mod stable;
+pub mod storage;
+pub mod terminal_graphics;
+pub mod tile_core;
+#[cfg(feature = "http")]
+pub mod update_check;
+pub mod workspace;
 mod image;
-mod create_path;
\ No newline at end of file
+mod create_path;
+mod thread_safety;
\ No newline at end of file
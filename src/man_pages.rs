@@ -0,0 +1,175 @@
+
+//! Reference pages of a command line interface and its subcommands
+//!
+//! The pages are written as roff man pages for installation or as Markdown for project wikis, one page for the command
+//! and one page per subcommand: `<exe name>.1` and `<exe name>-<subcommand>.1` or the same names with the `md`
+//! extension. The Markdown page of the command links to the pages of the subcommands.
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, Command, ValueEnum};
+
+use crate::workspace;
+
+
+pub const DEFAULT_OUTPUT_DIR: &str = "man_pages";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PageFormat {
+    /// roff man pages
+    Man,
+    /// Markdown CLI reference
+    Markdown,
+}
+
+impl PageFormat {
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Man => "1",
+            Self::Markdown => "md",
+        }
+    }
+
+}
+
+pub fn page_file_name(exe_name: &str, subcommand: Option<&Command>, format: PageFormat) -> PathBuf {
+    let file_name = match subcommand {
+        Some(command) => PathBuf::from(format!("{exe_name}-{}", command.get_name())),
+        None => PathBuf::from(exe_name),
+    };
+    file_name.with_extension(format.extension())
+}
+
+pub fn render_man_page(command: &Command) -> Result<Vec<u8>, IOError> {
+    let mut buffer = vec![];
+    clap_mangen::Man::new(command.to_owned()).render(&mut buffer)?;
+    Ok(buffer)
+}
+
+// continuation lines are indented to stay in the list item
+fn markdown_list_item(synopsis: &str, description: &str) -> String {
+    let description = description.trim().replace('\n', "\n  ");
+    match description.is_empty() {
+        true => format!("* `{synopsis}`\n"),
+        false => format!("* `{synopsis}`: {description}\n"),
+    }
+}
+
+fn markdown_arg(arg: &Arg) -> String {
+    let value = match arg.get_value_names() {
+        Some(names) => names.iter().map(|name| format!("<{name}>")).collect::<Vec<_>>().join(" "),
+        None => format!("<{}>", arg.get_id().as_str().to_uppercase()),
+    };
+    let names: Vec<String> = arg.get_short().map(|short| format!("-{short}")).into_iter()
+        .chain(arg.get_long().map(|long| format!("--{long}")))
+        .collect();
+    let synopsis = match (arg.is_positional(), arg.get_action().takes_values()) {
+        (true, _) => value,
+        (false, true) => format!("{} {value}", names.join(", ")),
+        (false, false) => names.join(", "),
+    };
+    let mut description = arg.get_long_help().or_else(|| arg.get_help()).map(ToString::to_string).unwrap_or_default();
+    let possible_values: Vec<String> = arg.get_possible_values().iter()
+        .filter(|value| ! value.is_hide_set())
+        .map(|value| format!("`{}`", value.get_name()))
+        .collect();
+    if ! possible_values.is_empty() && arg.get_action().takes_values() {
+        description.push_str(&format!("\n[possible values: {}]", possible_values.join(", ")));
+    }
+    let default_values: Vec<String> = arg.get_default_values().iter().map(|value| format!("`{}`", value.to_string_lossy())).collect();
+    if ! default_values.is_empty() && arg.get_action().takes_values() {
+        description.push_str(&format!("\n[default: {}]", default_values.join(", ")));
+    }
+    markdown_list_item(&synopsis, &description)
+}
+
+/// Markdown page of a command, `exe_name` is used to link the pages of the visible subcommands not in `exclusions`
+pub fn render_markdown_page(command: &Command, exe_name: &str, exclusions: &[&str]) -> String {
+    let mut command = command.to_owned();
+    // listed before rendering the usage, which adds the `help` subcommand
+    let subcommands: String = command.get_subcommands()
+        .filter(|subcommand| ! subcommand.is_hide_set() && ! exclusions.contains(&subcommand.get_name()))
+        .map(|subcommand| {
+            let link = format!("[{}]({})", subcommand.get_name(), page_file_name(exe_name, Some(subcommand), PageFormat::Markdown).to_string_lossy());
+            let about = subcommand.get_about().map(ToString::to_string).unwrap_or_default();
+            match about.is_empty() {
+                true => format!("* {link}\n"),
+                false => format!("* {link}: {}\n", about.trim()),
+            }
+        })
+        .collect();
+    let title = command.get_bin_name().unwrap_or_else(|| command.get_name()).to_owned();
+    let mut page = format!("# {title}\n\n");
+    if let Some(about) = command.get_long_about().or_else(|| command.get_about()) {
+        page.push_str(&format!("{}\n\n", about.to_string().trim()));
+    }
+    page.push_str(&format!("## Usage\n\n```text\n{}\n```\n", command.render_usage().to_string().trim()));
+    for (heading, positional) in [("Arguments", true), ("Options", false)] {
+        let items: String = command.get_arguments()
+            .filter(|arg| ! arg.is_hide_set() && arg.is_positional() == positional)
+            .map(markdown_arg)
+            .collect();
+        if ! items.is_empty() {
+            page.push_str(&format!("\n## {heading}\n\n{items}"));
+        }
+    }
+    if ! subcommands.is_empty() {
+        page.push_str(&format!("\n## Commands\n\n{subcommands}"));
+    }
+    page
+}
+
+/// Writes the pages of `command` and of its subcommands which are not in `exclusions` to `output_dir`, which is
+/// created if needed, returns the paths of the written pages
+pub fn generate<P: AsRef<Path>>(command: &Command, exe_name: &str, output_dir: P, format: PageFormat, exclusions: &[&str]) -> Result<Vec<PathBuf>, IOError> {
+    fs_err::create_dir_all(output_dir.as_ref())?;
+    let command = command.to_owned().bin_name(exe_name);
+    let subcommands = command.get_subcommands()
+        .filter(|subcommand| ! exclusions.contains(&subcommand.get_name()))
+        .map(|subcommand| (Some(subcommand), subcommand.to_owned().bin_name(format!("{exe_name} {}", subcommand.get_name()))));
+    let mut paths = vec![];
+    for (subcommand, page_command) in [(None, command.clone())].into_iter().chain(subcommands) {
+        let path = output_dir.as_ref().join(page_file_name(exe_name, subcommand, format));
+        let content = match format {
+            PageFormat::Man => render_man_page(&page_command)?,
+            PageFormat::Markdown => render_markdown_page(&page_command, exe_name, exclusions).into_bytes(),
+        };
+        workspace::write_file(&path, content)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Arg, ArgAction, Command};
+    use temp_dir::TempDir;
+
+    use super::{generate, render_markdown_page, PageFormat};
+
+    fn command() -> Command {
+        Command::new("tool").about("Converts fonts")
+            .subcommand(Command::new("convert").about("Converts a collection")
+                .arg(Arg::new("crop").long("crop").action(ArgAction::SetTrue).help("crops the tiles"))
+                .arg(Arg::new("from").required(true).help("source collection")))
+            .subcommand(Command::new("generate-man-pages").hide(true))
+    }
+
+    #[test]
+    fn markdown_pages() {
+        let page = render_markdown_page(&command().bin_name("tool"), "tool", &[]);
+        assert!(page.starts_with("# tool\n\nConverts fonts\n"));
+        assert!(page.contains("* [convert](tool-convert.md): Converts a collection\n"));
+        assert!(! page.contains("generate-man-pages"));
+
+        let temp_dir = TempDir::new().unwrap();
+        let paths = generate(&command(), "tool", temp_dir.child("docs"), PageFormat::Markdown, &["generate-man-pages"]).unwrap();
+        assert_eq!(paths, vec![temp_dir.child("docs").join("tool.md"), temp_dir.child("docs").join("tool-convert.md")]);
+        let convert_page = std::fs::read_to_string(&paths[1]).unwrap();
+        assert!(convert_page.starts_with("# tool convert\n"));
+        assert!(convert_page.contains("* `--crop`: crops the tiles\n"));
+        assert!(convert_page.contains("* `<FROM>`: source collection\n"));
+    }
+}
@@ -0,0 +1,33 @@
+
+//! Upgrades of files written with older schema versions to the current one
+//!
+//! The files are upgraded in place, the original content is kept next to them in `<file>.v<version>.bak`.
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use crate::workspace;
+
+
+/// Upgrade of a file from a schema version to another one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub file_path: PathBuf,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub backup_path: PathBuf,
+}
+
+pub fn backup_path<P: AsRef<Path>>(path: P, version: u32) -> PathBuf {
+    let mut backup_path = path.as_ref().as_os_str().to_owned();
+    backup_path.push(format!(".v{version}.bak"));
+    backup_path.into()
+}
+
+/// Copies the file to its backup then replaces it with the upgraded content
+pub fn write_migrated<P: AsRef<Path>>(path: P, from_version: u32, to_version: u32, content: &str) -> Result<Migration, IOError> {
+    let backup_path = backup_path(&path, from_version);
+    fs_err::copy(&path, &backup_path)?;
+    workspace::write_file(&path, content)?;
+    Ok(Migration { file_path: path.as_ref().to_path_buf(), from_version, to_version, backup_path })
+}
@@ -1,4 +1,12 @@
 
 pub mod tile;
 pub mod bin_file;
-pub mod avatar_file;
\ No newline at end of file
+pub mod avatar_file;
+pub mod mcm_file;
+pub mod atlas;
+pub mod screenshot;
+pub mod preview;
+pub mod font_profile;
+pub mod walksnail_osd_tool;
+pub mod wtfos_file;
+pub mod archive;
\ No newline at end of file
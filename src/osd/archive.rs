@@ -0,0 +1,209 @@
+
+//! Zip archives of fonts
+//!
+//! Fonts are commonly distributed as zip files holding the bin files or the grid images of a set. An archive is
+//! extracted to a temporary workspace which the loaders of any format then read from, and the outputs are written to
+//! a temporary workspace which is packed into the archive once complete. The workspaces are deleted when dropped.
+
+use std::io::{Error as IOError, Write};
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use fs_err::File;
+use thiserror::Error;
+use zip::{result::ZipError, write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{
+    bin_file::{self, FontPart},
+    tile::{
+        Kind as TileKind,
+        Tile,
+        grid::{LoadError as GridLoadError, Set as TileGridSet},
+        container::{save_to_bin_file::SaveTilesToBinFileError, tile_set::TileSet},
+    },
+};
+
+use crate::conversion::{self, CollectionSpec, ConversionError, ConversionOptions};
+use crate::diagnostics::Event;
+use crate::workspace::{self, Workspace};
+
+
+pub const EXTENSION: &str = "zip";
+
+// the content is kept apart from the lock file of the workspace
+const CONTENT_DIR_NAME: &str = "content";
+
+#[derive(Debug, From, Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    FileError(IOError),
+    #[from(ignore)]
+    #[error("invalid zip archive {archive_path}: {error}")]
+    InvalidArchive { archive_path: PathBuf, error: ZipError },
+}
+
+impl ArchiveError {
+    fn invalid_archive<P: AsRef<Path>>(archive_path: P, error: ZipError) -> Self {
+        Self::InvalidArchive { archive_path: archive_path.as_ref().to_path_buf(), error }
+    }
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadSetError {
+    #[error(transparent)]
+    ArchiveError(ArchiveError),
+    #[error(transparent)]
+    BinFileLoadError(bin_file::LoadError),
+    #[error(transparent)]
+    GridLoadError(GridLoadError),
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveSetError {
+    #[error(transparent)]
+    ArchiveError(ArchiveError),
+    #[error(transparent)]
+    SaveTilesToBinFileError(SaveTilesToBinFileError),
+}
+
+/// Content of an archive extracted to a temporary workspace
+#[derive(Debug)]
+pub struct ExtractedArchive {
+    workspace: Workspace,
+}
+
+impl ExtractedArchive {
+
+    /// Extracts an archive, the entries with a path leading out of the extraction directory are rejected
+    pub fn extract<P: AsRef<Path>>(archive_path: P) -> Result<Self, ArchiveError> {
+        let file = File::open(archive_path.as_ref())?;
+        let mut archive = ZipArchive::new(file).map_err(|error| ArchiveError::invalid_archive(&archive_path, error))?;
+        let extracted = Self { workspace: Workspace::create_in(std::env::temp_dir())? };
+        fs_err::create_dir(extracted.path())?;
+        archive.extract(extracted.path()).map_err(|error| ArchiveError::invalid_archive(&archive_path, error))?;
+        Ok(extracted)
+    }
+
+    /// Directory the archive is extracted to
+    pub fn path(&self) -> PathBuf {
+        self.workspace.path().join(CONTENT_DIR_NAME)
+    }
+
+}
+
+/// Archive being written: the files are written to a temporary directory then packed into the archive by `finish`
+#[derive(Debug)]
+pub struct ArchiveWriter {
+    workspace: Workspace,
+    archive_path: PathBuf,
+}
+
+impl ArchiveWriter {
+
+    pub fn create<P: AsRef<Path>>(archive_path: P) -> Result<Self, ArchiveError> {
+        let writer = Self { workspace: Workspace::create_in(std::env::temp_dir())?, archive_path: archive_path.as_ref().to_path_buf() };
+        fs_err::create_dir(writer.path())?;
+        Ok(writer)
+    }
+
+    /// Directory the files to pack are written to
+    pub fn path(&self) -> PathBuf {
+        self.workspace.path().join(CONTENT_DIR_NAME)
+    }
+
+    pub fn finish(self) -> Result<(), ArchiveError> {
+        write_dir(self.path(), &self.archive_path)
+    }
+
+}
+
+fn file_paths(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), IOError> {
+    let mut entry_paths = fs_err::read_dir(dir)?.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>()?;
+    entry_paths.sort();
+    for path in entry_paths {
+        match path.is_dir() {
+            true => file_paths(&path, paths)?,
+            false => paths.push(path),
+        }
+    }
+    Ok(())
+}
+
+// archive entries are named with `/` separators whatever the platform
+fn entry_name(dir: &Path, path: &Path) -> String {
+    let relative_path = path.strip_prefix(dir).expect("the files are listed from the directory");
+    relative_path.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+/// Packs the files of `dir` and of its subdirectories into a zip archive, each named from its path relative to `dir`
+pub fn write_dir<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, archive_path: Q) -> Result<(), ArchiveError> {
+    let dir = dir.as_ref();
+    let mut paths = vec![];
+    file_paths(dir, &mut paths)?;
+    workspace::write_atomically(&archive_path, |staging_path| {
+        let mut writer = ZipWriter::new(File::create(staging_path)?);
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        for path in &paths {
+            writer.start_file(entry_name(dir, path), options).map_err(|error| ArchiveError::invalid_archive(&archive_path, error))?;
+            writer.write_all(&fs_err::read(path)?)?;
+        }
+        writer.finish().map_err(|error| ArchiveError::invalid_archive(&archive_path, error))?;
+        Ok(())
+    }, ArchiveError::FileError)
+}
+
+/// Whether a directory contains normalized bin files of at least one of the tile kinds
+pub fn contains_bin_files_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> bool {
+    [TileKind::SD, TileKind::HD].into_iter().any(|tile_kind| bin_file::normalized_file_path(&dir, tile_kind, ident, FontPart::Base).exists())
+}
+
+/// Loads a collection from an archive, the path of the collection is relative to the root of the archive
+pub fn load_tiles<P: AsRef<Path>>(archive_path: P, from: &CollectionSpec, options: &ConversionOptions, diagnostics: &(dyn Fn(&Event) + Sync))
+        -> Result<Vec<Tile>, ConversionError> {
+    let extracted = ExtractedArchive::extract(archive_path)?;
+    conversion::load_tiles(&CollectionSpec::new(from.format, extracted.path().join(&from.path)), options, diagnostics)
+}
+
+/// Loads a set from the normalized bin files at the root of an archive or from its normalized grid images if it has no
+/// bin files
+pub fn load_set_norm<P: AsRef<Path>>(archive_path: P, ident: &Option<&str>) -> Result<TileSet, LoadSetError> {
+    let extracted = ExtractedArchive::extract(archive_path)?;
+    let tile_set = match contains_bin_files_norm(extracted.path(), ident) {
+        true => TileSet::load_bin_files_norm_partial(extracted.path(), ident)?,
+        false => TileGridSet::load_from_images_norm(extracted.path(), ident)?.into_tile_set(),
+    };
+    Ok(tile_set.with_ident(*ident))
+}
+
+/// Writes the normalized bin files of a set to an archive
+pub fn save_set_norm<P: AsRef<Path>>(tile_set: &TileSet, archive_path: P, ident: &Option<&str>) -> Result<(), SaveSetError> {
+    let writer = ArchiveWriter::create(archive_path)?;
+    tile_set.save_to_bin_files_norm(writer.path(), ident)?;
+    Ok(writer.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::container::tile_set::TileSet;
+
+    use super::{load_set_norm, save_set_norm, ExtractedArchive};
+
+    #[test]
+    fn set_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.child("font.zip");
+        let tile_set = TileSet::load_bin_files_norm("test_files/djibinsetnorm", &None).unwrap();
+        save_set_norm(&tile_set, &archive_path, &Some("ardu")).unwrap();
+
+        let extracted = ExtractedArchive::extract(&archive_path).unwrap();
+        assert!(extracted.path().join("font_ardu_hd_2.bin").is_file());
+        assert!(! extracted.path().join(".lock").exists());
+
+        let loaded = load_set_norm(&archive_path, &Some("ardu")).unwrap();
+        assert_eq!(loaded.sd_tiles().len(), tile_set.sd_tiles().len());
+        assert_eq!(loaded.hd_tiles().len(), tile_set.hd_tiles().len());
+        assert_eq!(loaded.hd_tiles()[10].as_raw(), tile_set.hd_tiles()[10].as_raw());
+    }
+}
@@ -0,0 +1,200 @@
+
+use std::{
+    io::Error as IOError,
+    path::{Path, PathBuf},
+};
+
+use derive_more::From;
+use fs_err::File;
+use image::{GenericImage, GenericImageView, ImageBuffer, Rgba};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::tile::{
+    Tile,
+    Kind as TileKind,
+    container::uniq_tile_kind::{TileKindError, UniqTileKind},
+};
+
+use crate::{
+    dimensions,
+    image_encode::ImageEncodeOptions,
+    image::{
+        read_image_file_with_context,
+        ReadError as ImageReadError,
+        WriteImageFile,
+        WriteError as ImageWriteError,
+    },
+    io_context::IoContext,
+    osd::tile::InvalidDimensionsError,
+};
+
+
+pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+pub type ImageDimensions = dimensions::Dimensions<u32>;
+
+/// Transparent pixels left on the right of and below each tile so that sampling with filtering does not bleed into
+/// the neighbouring tiles
+pub const TILE_PADDING: u32 = 1;
+
+/// Location of a tile in the atlas, in pixels and in texture coordinates with the origin at the top left corner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UvMapEntry {
+    pub index: usize,
+    pub x: u32,
+    pub y: u32,
+    pub u0: f32,
+    pub v0: f32,
+    pub u1: f32,
+    pub v1: f32,
+}
+
+/// Content of the JSON file describing where the tiles are in an atlas image
+///
+/// Only the tiles with non-zero bytes are packed in the atlas, `tile_count` is the size of the whole collection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UvMap {
+    pub image: String,
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tile_count: usize,
+    pub tiles: Vec<UvMapEntry>,
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    ImageReadError(ImageReadError),
+    #[error("failed to open UV map file: {0}")]
+    UvMapOpenError(IOError),
+    #[error("failed to parse UV map file {file_path}: {error}")]
+    UvMapStructureError { file_path: PathBuf, error: serde_json::Error },
+    #[error("UV map has invalid tile dimensions: {0}")]
+    InvalidTileDimensions(InvalidDimensionsError),
+    #[error("UV map entry of tile {0} is out of the collection or of the atlas image")]
+    InvalidEntry(usize),
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+    #[error("failed to write UV map file: {0}")]
+    UvMapWriteError(IOError),
+    #[error("failed to serialize UV map: {0}")]
+    UvMapSerializeError(serde_json::Error),
+}
+
+/// Path of the UV map file of an atlas image: the image path with the `json` extension
+pub fn uv_map_path<P: AsRef<Path>>(image_path: P) -> PathBuf {
+    image_path.as_ref().with_extension("json")
+}
+
+// side of the smallest power of two square image fitting `count` cells
+fn atlas_side(cell_dimensions: ImageDimensions, count: usize) -> u32 {
+    let mut side = 1;
+    while (((side / cell_dimensions.width) * (side / cell_dimensions.height)) as usize) < count.max(1) {
+        side *= 2;
+    }
+    side
+}
+
+/// Packs the tiles in a power of two square image and writes it along with its UV map, the tiles whose bytes are all
+/// zero are left out since they are loaded back as such
+pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveError> {
+    save_with_options(tiles, path, &ImageEncodeOptions::default())
+}
+
+pub fn save_with_options<P: AsRef<Path>>(tiles: &[Tile], path: P, encode_options: &ImageEncodeOptions) -> Result<(), SaveError> {
+    let tile_kind = tiles.tile_kind()?;
+    let tile_dimensions = tile_kind.dimensions();
+    let cell_dimensions = ImageDimensions::new(tile_dimensions.width + TILE_PADDING, tile_dimensions.height + TILE_PADDING);
+    let packed_tiles: Vec<(usize, &Tile)> = tiles.iter().enumerate().filter(|(_, tile)| tile.as_raw().iter().any(|byte| *byte != 0)).collect();
+    let side = atlas_side(cell_dimensions, packed_tiles.len());
+    let columns = (side / cell_dimensions.width) as usize;
+
+    let mut image = Image::new(side, side);
+    let mut entries = Vec::with_capacity(packed_tiles.len());
+    for (cell_index, (index, tile)) in packed_tiles.into_iter().enumerate() {
+        let x = (cell_index % columns) as u32 * cell_dimensions.width;
+        let y = (cell_index / columns) as u32 * cell_dimensions.height;
+        image.copy_from(tile.image(), x, y).unwrap();
+        entries.push(UvMapEntry {
+            index, x, y,
+            u0: x as f32 / side as f32,
+            v0: y as f32 / side as f32,
+            u1: (x + tile_dimensions.width) as f32 / side as f32,
+            v1: (y + tile_dimensions.height) as f32 / side as f32,
+        });
+    }
+    image.write_image_file_with_options(&path, encode_options)?;
+
+    let uv_map = UvMap {
+        image: path.as_ref().file_name().map(|file_name| file_name.to_string_lossy().into_owned()).unwrap_or_default(),
+        width: side,
+        height: side,
+        tile_width: tile_dimensions.width,
+        tile_height: tile_dimensions.height,
+        tile_count: tiles.len(),
+        tiles: entries,
+    };
+    serde_json::to_writer_pretty(File::create(uv_map_path(&path))?, &uv_map)?;
+    Ok(())
+}
+
+/// Loads the tiles of an atlas image using its UV map, the tiles missing from the map are empty
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
+    load_with_context(path, &IoContext::default())
+}
+
+pub fn load_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    let uv_map_path = uv_map_path(&path);
+    let uv_map: UvMap = serde_json::from_reader(File::open(&uv_map_path)?)
+        .map_err(|error| LoadError::UvMapStructureError { file_path: uv_map_path.clone(), error })?;
+    let tile_kind = TileKind::try_from(ImageDimensions::new(uv_map.tile_width, uv_map.tile_height))?;
+    let image = read_image_file_with_context(&path, context)?.into_rgba8();
+
+    let mut tiles = vec![Tile::new(tile_kind); uv_map.tile_count];
+    for entry in &uv_map.tiles {
+        if entry.index >= uv_map.tile_count
+            || entry.x + uv_map.tile_width > image.width() || entry.y + uv_map.tile_height > image.height() {
+            return Err(LoadError::InvalidEntry(entry.index));
+        }
+        let tile_image = image.view(entry.x, entry.y, uv_map.tile_width, uv_map.tile_height).to_image();
+        tiles[entry.index] = Tile::try_from(tile_image).unwrap();
+    }
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{load, save, uv_map_path, UvMap};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut tiles = vec![Tile::new(TileKind::HD); 20];
+        tiles[3].put_pixel(1, 2, image::Rgba([255, 0, 0, 255]));
+        tiles[17].put_pixel(5, 6, image::Rgba([0, 255, 0, 128]));
+        // fully transparent but its color has to be kept for the conversions to be lossless
+        tiles[9].put_pixel(0, 0, image::Rgba([255, 255, 255, 0]));
+        let path = temp_dir.child("atlas.png");
+        save(&tiles, &path).unwrap();
+
+        let uv_map: UvMap = serde_json::from_reader(std::fs::File::open(uv_map_path(&path)).unwrap()).unwrap();
+        assert_eq!((uv_map.width, uv_map.height, uv_map.tile_count), (128, 128, 20));
+        assert_eq!(uv_map.tiles.iter().map(|entry| entry.index).collect::<Vec<_>>(), vec![3, 9, 17]);
+
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.len(), tiles.len());
+        assert!(loaded.iter().zip(&tiles).all(|(loaded, tile)| loaded.as_raw() == tile.as_raw()));
+    }
+}
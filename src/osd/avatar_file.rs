@@ -6,19 +6,27 @@ use std::{
 
 use derive_more::From;
 use image::{GenericImageView, GenericImage, ImageBuffer, Rgba};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use thiserror::Error;
 use strum::IntoEnumIterator;
 
 use super::tile::{
     Tile,
     Kind as TileKind,
-    container::uniq_tile_kind::{TileKindError, UniqTileKind},
+    KindSwapPolicy,
+    SwappedKindsError,
+    container::{tile_set::TileSet, uniq_tile_kind::{TileKindError, UniqTileKind}},
 };
 
 use crate::{
+    create_path::{create_path, CreatePathError},
+    diagnostics::DetectionEvidence,
     dimensions,
+    image_encode::ImageEncodeOptions,
+    io_context::IoContext,
     image::{
-        read_image_file,
+        read_image_file_with_context,
         ReadError as ImageReadError,
         WriteImageFile,
         WriteError as ImageWriteError,
@@ -80,12 +88,17 @@ impl LoadError {
 }
 
 pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
-    let image = read_image_file(&path)?;
+    load_with_context(path, &IoContext::default())
+}
+
+pub fn load_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    let image = read_image_file_with_context(&path, context)?;
     let tile_kind = TileKind::for_avatar_image_dimensions(image.dimensions().into())
             .map_err(|error| {
                 let InvalidDimensionsError { dimensions } = error;
                 LoadError::invalid_dimensions(&path, dimensions)
             })?;
+    context.emit_detection(&path, tile_kind, DetectionEvidence::AvatarImageDimensions { dimensions: image.dimensions().into() });
     let tile_dimensions = tile_kind.dimensions();
     let mut tiles = vec![Tile::new(tile_kind); TILE_COUNT];
     for (tile_index, tile) in tiles.iter_mut().enumerate() {
@@ -96,6 +109,18 @@ pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
     Ok(tiles)
 }
 
+pub fn normalized_file_name(tile_kind: TileKind, ident: &Option<&str>) -> PathBuf {
+    let ident = match ident {
+        Some(ident) => format!("_{ident}"),
+        None => "".to_owned(),
+    };
+    PathBuf::from(format!("font{ident}_{}.png", tile_kind.dimensions().width))
+}
+
+pub fn normalized_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> PathBuf {
+    [dir.as_ref().to_path_buf(), normalized_file_name(tile_kind, ident)].into_iter().collect()
+}
+
 #[derive(Debug, From, Error)]
 pub enum SaveError {
     #[error(transparent)]
@@ -104,9 +129,15 @@ pub enum SaveError {
     ImageWriteError(ImageWriteError),
     #[error("not enough tiles, Avatar tile collection must contain 256 tiles")]
     WrongCollectionSize(usize),
+    #[error(transparent)]
+    CreatePathError(CreatePathError),
 }
 
 pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveError> {
+    save_with_options(tiles, path, &ImageEncodeOptions::default())
+}
+
+pub fn save_with_options<P: AsRef<Path>>(tiles: &[Tile], path: P, encode_options: &ImageEncodeOptions) -> Result<(), SaveError> {
     if tiles.len() < TILE_COUNT {
         return Err(SaveError::WrongCollectionSize(tiles.len()));
     }
@@ -116,10 +147,76 @@ pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveError> {
     let tile_kind = tiles.tile_kind()?;
     let img_dim = tile_kind.avatar_image_dimensions();
     let mut image = Image::new(img_dim.width(), img_dim.height());
-    for (tile_index, tile) in tiles[0..TILE_COUNT].iter().enumerate() {
-        let tile_y = tile_index as u32 * tile_kind.dimensions().height;
-        image.copy_from(tile.image(), 0, tile_y).unwrap();
-    }
-    image.write_image_file(path)?;
+    // the image being a single column of tiles, the pixels of each tile are a contiguous band of its bytes
+    let tile_bytes = tiles[0].as_raw().len();
+    let copy_tile = |(band, tile): (&mut [u8], &Tile)| band.copy_from_slice(tile.as_raw());
+    #[cfg(feature = "rayon")]
+    image.par_chunks_mut(tile_bytes).zip(tiles[0..TILE_COUNT].par_iter()).for_each(copy_tile);
+    #[cfg(not(feature = "rayon"))]
+    image.chunks_mut(tile_bytes).zip(tiles[0..TILE_COUNT].iter()).for_each(copy_tile);
+    image.write_image_file_with_options(path, encode_options)?;
     Ok(())
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadSetError {
+    #[error(transparent)]
+    LoadError(LoadError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error(transparent)]
+    SwappedKinds(SwappedKindsError),
+}
+
+impl TileSet {
+
+    pub fn load_avatar_files<P: AsRef<Path>>(sd_path: P, hd_path: P) -> Result<Self, LoadSetError> {
+        Self::load_avatar_files_with_swap_policy(sd_path, hd_path, KindSwapPolicy::Reject)
+    }
+
+    pub fn load_avatar_files_with_swap_policy<P: AsRef<Path>>(sd_path: P, hd_path: P, swap_policy: KindSwapPolicy) -> Result<Self, LoadSetError> {
+        let sd_tiles = load(&sd_path)?;
+        let hd_tiles = load(&hd_path)?;
+        if sd_tiles.tile_kind()? == TileKind::HD && hd_tiles.tile_kind()? == TileKind::SD {
+            return match swap_policy {
+                KindSwapPolicy::Reject => Err(SwappedKindsError::new(&sd_path, &hd_path).into()),
+                KindSwapPolicy::AutoSwap => {
+                    log::warn!("SD and HD Avatar files are swapped, using {} as the SD font and {} as the HD font",
+                        hd_path.as_ref().to_string_lossy(), sd_path.as_ref().to_string_lossy());
+                    Ok(Self::try_from_tiles(hd_tiles, sd_tiles)?)
+                },
+            };
+        }
+        Ok(Self::try_from_tiles(sd_tiles, hd_tiles)?)
+    }
+
+    pub fn load_avatar_files_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<Self, LoadSetError> {
+        Self::load_avatar_files_norm_with_swap_policy(dir, ident, KindSwapPolicy::Reject)
+    }
+
+    pub fn load_avatar_files_norm_with_swap_policy<P: AsRef<Path>>(dir: P, ident: &Option<&str>, swap_policy: KindSwapPolicy) -> Result<Self, LoadSetError> {
+        Self::load_avatar_files_with_swap_policy(
+            normalized_file_path(&dir, TileKind::SD, ident),
+            normalized_file_path(&dir, TileKind::HD, ident),
+            swap_policy
+        ).map(|tile_set| tile_set.with_ident(*ident))
+    }
+
+    pub fn save_to_avatar_files<P: AsRef<Path>>(&self, sd_path: P, hd_path: P, encode_options: &ImageEncodeOptions) -> Result<(), SaveError> {
+        if ! self.sd_tiles.is_empty() {
+            save_with_options(&self.sd_tiles, sd_path, encode_options)?;
+        }
+        if ! self.hd_tiles.is_empty() {
+            save_with_options(&self.hd_tiles, hd_path, encode_options)?;
+        }
+        Ok(())
+    }
+
+    /// Saves the set to the normalized Avatar files of `dir`, the ident of the set is used when `ident` is `None`
+    pub fn save_to_avatar_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, encode_options: &ImageEncodeOptions) -> Result<(), SaveError> {
+        create_path(&dir)?;
+        let ident = ident.or(self.ident.as_deref());
+        self.save_to_avatar_files(normalized_file_path(&dir, TileKind::SD, &ident), normalized_file_path(&dir, TileKind::HD, &ident), encode_options)
+    }
+
 }
\ No newline at end of file
@@ -1,7 +1,12 @@
 
+pub mod metadata;
+pub mod pages;
+pub mod location;
+
 use std::path::{Path, PathBuf};
 use std::io::{Error as IOError, Read, Seek, Write};
 
+use close_err::Closable;
 use derive_more::From;
 use thiserror::Error;
 use getset::Getters;
@@ -12,6 +17,8 @@ use super::tile::{
     self,
     Tile,
     Kind as TileKind,
+    KindSwapPolicy,
+    SwappedKindsError,
     grid::Grid as TileGrid,
     container::{
         into_tile_grid::IntoTileGrid,
@@ -20,6 +27,9 @@ use super::tile::{
     },
 };
 
+use crate::diagnostics::DetectionEvidence;
+use crate::input_file::{self, DegenerateFileError};
+use crate::io_context::IoContext;
 use crate::osd::tile::InvalidSizeError;
 
 
@@ -46,6 +56,8 @@ impl TileKind {
 pub enum OpenError {
     #[error(transparent)]
     FileError(IOError),
+    #[error(transparent)]
+    DegenerateFileError(DegenerateFileError),
     #[from(ignore)]
     #[error("file {file_path} has a size ({size}B) which does not match a valid bin file size")]
     InvalidSizeError {
@@ -93,6 +105,8 @@ pub enum LoadError {
     LoadedTileKindDoesNotMatchRequested { file_path: PathBuf, loaded: TileKind, requested: TileKind },
     #[error("File size does not match a valid bin file size: file {file_path}, size {size}B")]
     WrongSizeError { file_path: PathBuf, size: u64 },
+    #[error(transparent)]
+    SwappedKinds(SwappedKindsError),
 }
 
 impl LoadError {
@@ -114,10 +128,15 @@ pub enum SeekFrom {
     Current(isize)
 }
 
+/// name the streams opened with `BinFileReader::from_reader` are given in the errors and diagnostics
+pub const READER_SOURCE_NAME: &str = "<reader>";
+
 #[derive(Getters)]
-pub struct BinFileReader {
+pub struct BinFileReader<R: Read + Seek = File> {
     file_path: PathBuf,
-    file: File,
+    file: R,
+    // offset of the first tile in the stream
+    start: u64,
 
     #[getset(get = "pub")]
     tile_kind: tile::Kind,
@@ -126,17 +145,49 @@ pub struct BinFileReader {
     pos: usize
 }
 
-impl BinFileReader {
+impl BinFileReader<File> {
 
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OpenError> {
-        let file = File::open(&path)?;
-        let tile_kind = tile::Kind::for_bin_file_size_bytes(file.metadata().unwrap().len())
+        Self::open_with_context(path, &IoContext::default())
+    }
+
+    pub fn open_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Self, OpenError> {
+        let file = context.retry_io(path.as_ref(), || File::open(path.as_ref()))?;
+        Self::from_source(path.as_ref(), file, context)
+    }
+
+}
+
+impl<R: Read + Seek> BinFileReader<R> {
+
+    /// Reads a bin file from a stream, e.g. a `Cursor` over bytes embedded in a program or an archive entry
+    ///
+    /// The bin file starts at the current position of the stream and extends to its end.
+    pub fn from_reader(reader: R) -> Result<Self, OpenError> {
+        Self::from_reader_with_context(reader, &IoContext::default())
+    }
+
+    pub fn from_reader_with_context(reader: R, context: &IoContext) -> Result<Self, OpenError> {
+        Self::from_source(Path::new(READER_SOURCE_NAME), reader, context)
+    }
+
+    fn from_source(source: &Path, mut reader: R, context: &IoContext) -> Result<Self, OpenError> {
+        let start = reader.stream_position()?;
+        let size = reader.seek(std::io::SeekFrom::End(0))? - start;
+        reader.seek(std::io::SeekFrom::Start(start))?;
+        input_file::check_bin_file_size(source, size)?;
+        let tile_kind = tile::Kind::for_bin_file_size_bytes(size)
             .map_err(|error| {
                 let InvalidSizeError(size) = error;
-                OpenError::invalid_size(&path, size)
+                OpenError::invalid_size(source, size)
             })?;
-        log::info!("detected {} kind of tiles in {}", tile_kind, path.as_ref().to_string_lossy());
-        Ok(Self { file, file_path: path.as_ref().to_path_buf(), tile_kind, pos: 0 })
+        context.emit_detection(source, tile_kind, DetectionEvidence::FileSize { bytes: size });
+        Ok(Self { file: reader, file_path: source.to_path_buf(), start, tile_kind, pos: 0 })
+    }
+
+    /// Gives back the stream, positioned after the last tile read
+    pub fn into_inner(self) -> R {
+        self.file
     }
 
     pub(crate) fn read_tile_bytes(&mut self) -> Result<tile::Bytes, IOError> {
@@ -166,8 +217,8 @@ impl BinFileReader {
         if new_pos < 0 || new_pos >= TILE_COUNT as isize {
             return Err(SeekError::out_of_bounds(&self.file_path, new_pos));
         }
-        let new_pos= new_pos * self.tile_kind.raw_rgba_size_bytes() as isize;
-        self.file.seek(std::io::SeekFrom::Start(new_pos as u64))?;
+        let byte_offset = new_pos as u64 * self.tile_kind.raw_rgba_size_bytes() as u64;
+        self.file.seek(std::io::SeekFrom::Start(self.start + byte_offset))?;
         self.pos = new_pos as usize;
         Ok(self.pos)
     }
@@ -195,9 +246,9 @@ impl BinFileReader {
 
 }
 
-pub struct BinFileReaderIterator(BinFileReader);
+pub struct BinFileReaderIterator<R: Read + Seek = File>(BinFileReader<R>);
 
-impl Iterator for BinFileReaderIterator {
+impl<R: Read + Seek> Iterator for BinFileReaderIterator<R> {
     type Item = Result<Tile, IOError>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -208,10 +259,10 @@ impl Iterator for BinFileReaderIterator {
     }
 }
 
-impl IntoIterator for BinFileReader {
+impl<R: Read + Seek> IntoIterator for BinFileReader<R> {
     type Item = Result<Tile, IOError>;
 
-    type IntoIter = BinFileReaderIterator;
+    type IntoIter = BinFileReaderIterator<R>;
 
     fn into_iter(self) -> Self::IntoIter {
         BinFileReaderIterator(self)
@@ -219,7 +270,20 @@ impl IntoIterator for BinFileReader {
 }
 
 pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
-    Ok(BinFileReader::open(path)?.read_tiles()?)
+    load_with_context(path, &IoContext::default())
+}
+
+pub fn load_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    Ok(BinFileReader::open_with_context(path, context)?.read_tiles()?)
+}
+
+/// Loads the tiles of a bin file read from a stream, see `BinFileReader::from_reader`
+pub fn load_from_reader<R: Read + Seek>(reader: R) -> Result<Vec<Tile>, LoadError> {
+    load_from_reader_with_context(reader, &IoContext::default())
+}
+
+pub fn load_from_reader_with_context<R: Read + Seek>(reader: R, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    Ok(BinFileReader::from_reader_with_context(reader, context)?.read_tiles()?)
 }
 
 pub fn load_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, part: FontPart) -> Result<Vec<Tile>, LoadError> {
@@ -233,9 +297,13 @@ pub fn load_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&st
 }
 
 pub fn load_extended<P: AsRef<Path>>(base_path: P, ext_path: P) -> Result<Vec<Tile>, LoadError> {
-    let base_tiles = load(&base_path)?;
+    load_extended_with_context(base_path, ext_path, &IoContext::default())
+}
+
+pub fn load_extended_with_context<P: AsRef<Path>>(base_path: P, ext_path: P, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    let base_tiles = load_with_context(&base_path, context)?;
     let base_tile_kind = base_tiles.tile_kind().expect("should not fail for collections from bin files");
-    let ext_tiles = load(&ext_path)?;
+    let ext_tiles = load_with_context(&ext_path, context)?;
     let ext_tile_kind = ext_tiles.tile_kind().expect("should not fail for collections from bin files");
     if ext_tile_kind != base_tile_kind {
         return Err(LoadError::tile_kind_mismatch(&ext_path, ext_tile_kind, base_tile_kind))
@@ -301,15 +369,73 @@ pub fn load_extended_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &O
 impl TileSet {
 
     pub fn load_bin_files<P: AsRef<Path>>(sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P) -> Result<Self, LoadError> {
-        let sd_tiles = load_extended_check_kind(&sd_path, &sd_2_path, TileKind::SD)?;
-        let hd_tiles = load_extended_check_kind(&hd_path, &hd_2_path, TileKind::HD)?;
-        Ok(Self { sd_tiles, hd_tiles })
+        Self::load_bin_files_with_swap_policy(sd_path, sd_2_path, hd_path, hd_2_path, KindSwapPolicy::Reject)
+    }
+
+    pub fn load_bin_files_with_swap_policy<P: AsRef<Path>>(sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P, swap_policy: KindSwapPolicy) -> Result<Self, LoadError> {
+        Self::load_bin_files_with_context(sd_path, sd_2_path, hd_path, hd_2_path, swap_policy, &IoContext::default())
+    }
+
+    pub fn load_bin_files_with_context<P: AsRef<Path>>(sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P, swap_policy: KindSwapPolicy,
+            context: &IoContext) -> Result<Self, LoadError> {
+        let sd_tiles = load_extended_with_context(&sd_path, &sd_2_path, context)?;
+        let sd_tile_kind = sd_tiles.tile_kind().expect("should not fail for collections from bin files");
+        let hd_tiles = load_extended_with_context(&hd_path, &hd_2_path, context)?;
+        let hd_tile_kind = hd_tiles.tile_kind().expect("should not fail for collections from bin files");
+        if sd_tile_kind == TileKind::HD && hd_tile_kind == TileKind::SD {
+            return match swap_policy {
+                KindSwapPolicy::Reject => Err(SwappedKindsError::new(&sd_path, &hd_path).into()),
+                KindSwapPolicy::AutoSwap => {
+                    log::warn!("SD and HD bin files are swapped, using {} as the SD font and {} as the HD font",
+                        hd_path.as_ref().to_string_lossy(), sd_path.as_ref().to_string_lossy());
+                    Ok(Self { sd_tiles: hd_tiles, hd_tiles: sd_tiles, ident: None })
+                },
+            };
+        }
+        if sd_tile_kind != TileKind::SD {
+            return Err(LoadError::tile_kind_mismatch(&sd_path, sd_tile_kind, TileKind::SD));
+        }
+        if hd_tile_kind != TileKind::HD {
+            return Err(LoadError::tile_kind_mismatch(&hd_path, hd_tile_kind, TileKind::HD));
+        }
+        Ok(Self { sd_tiles, hd_tiles, ident: None })
     }
 
     pub fn load_bin_files_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<Self, LoadError> {
-        let sd_tiles = load_extended_norm(&dir, TileKind::SD, ident)?;
-        let hd_tiles = load_extended_norm(&dir, TileKind::HD, ident)?;
-        Ok(Self { sd_tiles, hd_tiles })
+        Self::load_bin_files_norm_with_swap_policy(dir, ident, KindSwapPolicy::Reject)
+    }
+
+    pub fn load_bin_files_norm_with_swap_policy<P: AsRef<Path>>(dir: P, ident: &Option<&str>, swap_policy: KindSwapPolicy) -> Result<Self, LoadError> {
+        Self::load_bin_files_norm_with_context(dir, ident, swap_policy, &IoContext::default())
+    }
+
+    pub fn load_bin_files_norm_with_context<P: AsRef<Path>>(dir: P, ident: &Option<&str>, swap_policy: KindSwapPolicy, context: &IoContext) -> Result<Self, LoadError> {
+        Self::load_bin_files_with_context(
+            normalized_file_path(&dir, TileKind::SD, ident, FontPart::Base),
+            normalized_file_path(&dir, TileKind::SD, ident, FontPart::Ext),
+            normalized_file_path(&dir, TileKind::HD, ident, FontPart::Base),
+            normalized_file_path(&dir, TileKind::HD, ident, FontPart::Ext),
+            swap_policy,
+            context
+        ).map(|tile_set| tile_set.with_ident(*ident))
+    }
+
+    /// Loads a set of normalized bin files where the files of one of the tile kinds can be missing
+    ///
+    /// The collection of the missing tile kind is left empty and the set savers only save the available one.
+    pub fn load_bin_files_norm_partial<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<Self, LoadError> {
+        let load_tiles = |tile_kind| match load_extended_norm(&dir, tile_kind, ident) {
+            Err(error) if error.because_file_is_missing() => {
+                log::warn!("{tile_kind} font files are missing, only loading the other tile kind: {error}");
+                Ok(Err(error))
+            },
+            result => result.map(Ok),
+        };
+        let (sd_tiles, hd_tiles) = match (load_tiles(TileKind::SD)?, load_tiles(TileKind::HD)?) {
+            (Err(error), Err(_)) => return Err(error),
+            (sd_tiles, hd_tiles) => (sd_tiles.unwrap_or_default(), hd_tiles.unwrap_or_default()),
+        };
+        Ok(Self { sd_tiles, hd_tiles, ident: ident.map(str::to_owned) })
     }
 
 }
@@ -339,6 +465,24 @@ pub enum TileWriteError {
     NotEnoughTiles(BinFileWriter)
 }
 
+impl TileWriteError {
+    /// Gives the writer back when the error is recoverable so that more tiles can be written or the file padded
+    pub fn into_writer(self) -> Option<BinFileWriter> {
+        match self {
+            TileWriteError::NotEnoughTiles(writer) => Some(writer),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ResumeError {
+    #[error(transparent)]
+    FileError(IOError),
+    #[error("cannot resume writing {tile_kind} tiles to {file_path}: size {size}B is not a whole number of tiles or exceeds 256 tiles")]
+    InvalidSize { file_path: PathBuf, size: u64, tile_kind: TileKind },
+}
+
 #[derive(Debug, Error, From)]
 pub enum FillRemainingSpaceError {
     #[error(transparent)]
@@ -348,23 +492,86 @@ pub enum FillRemainingSpaceError {
     Empty
 }
 
+// the errors of the writes buffered by the system can only be reported when closing the file
+fn close_file(file: File) -> Result<(), IOError> {
+    let (file, path) = file.into_parts();
+    file.close().map_err(|error| IOError::new(error.kind(), format!("failed to close file `{}`: {error}", path.to_string_lossy())))
+}
+
 #[derive(Debug)]
-pub struct BinFileWriter {
-    file: File,
+pub struct BinFileWriter<W: Write = File> {
+    file: W,
     tile_count: usize,
     tile_kind: Option<TileKind>,
 }
 
-impl BinFileWriter {
+impl BinFileWriter<File> {
 
     pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, IOError> {
+        Self::create_with_context(path, &IoContext::default())
+    }
+
+    pub fn create_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Self, IOError> {
         Ok(Self {
-            file: File::create(path)?,
+            file: context.retry_io(path.as_ref(), || File::create(path.as_ref()))?,
             tile_count: 0,
-            tile_kind: None
+            tile_kind: None,
         })
     }
 
+    /// Reopens a partially written bin file to append more tiles to it
+    ///
+    /// The tile kind has to be provided since some file sizes are valid for both SD and HD tiles.
+    pub fn resume<P: AsRef<Path>>(path: P, tile_kind: TileKind) -> Result<Self, ResumeError> {
+        let file = fs_err::OpenOptions::new().append(true).open(path.as_ref()).map_err(ResumeError::FileError)?;
+        let size = file.metadata().map_err(ResumeError::FileError)?.len();
+        let tile_size = tile_kind.raw_rgba_size_bytes() as u64;
+        if size % tile_size != 0 || size / tile_size > TILE_COUNT as u64 {
+            return Err(ResumeError::InvalidSize { file_path: path.as_ref().to_path_buf(), size, tile_kind });
+        }
+        let tile_count = (size / tile_size) as usize;
+        Ok(Self {
+            file,
+            tile_count,
+            tile_kind: if tile_count > 0 { Some(tile_kind) } else { None },
+        })
+    }
+
+    pub fn finish(self) -> Result<(), TileWriteError> {
+        if self.tile_count < TILE_COUNT {
+            return Err(TileWriteError::NotEnoughTiles(self));
+        }
+        close_file(self.file)?;
+        Ok(())
+    }
+
+    /// Fills the remaining space with transparent tiles then closes the file
+    pub fn finish_padded(mut self) -> Result<(), FillRemainingSpaceError> {
+        self.fill_remaining_space()?;
+        self.finish()?;
+        Ok(())
+    }
+
+    /// Closes and deletes the partially written file
+    pub fn abort(self) -> Result<(), IOError> {
+        let path = self.file.path().to_path_buf();
+        close_file(self.file)?;
+        fs_err::remove_file(path)
+    }
+
+}
+
+impl<W: Write> BinFileWriter<W> {
+
+    /// Writes a bin file to a stream, nothing is written besides the tiles
+    pub fn from_writer(writer: W) -> Self {
+        Self { file: writer, tile_count: 0, tile_kind: None }
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tile_count
+    }
+
     pub fn write_tile(&mut self, tile: &Tile) -> Result<(), TileWriteError> {
         if self.tile_count >= TILE_COUNT {
             return Err(TileWriteError::MaximumTilesReached);
@@ -393,12 +600,47 @@ impl BinFileWriter {
         Ok(())
     }
 
-    pub fn finish(self) -> Result<(), TileWriteError> {
-        if self.tile_count < TILE_COUNT {
-            return Err(TileWriteError::NotEnoughTiles(self));
-        }
-        self.file.close()?;
-        Ok(())
+    /// Flushes then gives back the stream, which only holds a valid bin file once 256 tiles have been written, see
+    /// `fill_remaining_space`
+    pub fn into_inner(mut self) -> Result<W, IOError> {
+        self.file.flush()?;
+        Ok(self.file)
     }
 
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Seek, SeekFrom as IOSeekFrom};
+
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{BinFileReader, BinFileWriter, SeekFrom};
+
+    #[test]
+    fn streams() {
+        let mut stream = Cursor::new(b"header".to_vec());
+        stream.seek(IOSeekFrom::End(0)).unwrap();
+        let mut writer = BinFileWriter::from_writer(stream);
+        let mut tile = Tile::new(TileKind::SD);
+        tile.put_pixel(1, 2, Rgba([255, 0, 0, 255]));
+        for _ in 0..4 {
+            writer.write_tile(&Tile::new(TileKind::SD)).unwrap();
+        }
+        writer.write_tile(&tile).unwrap();
+        writer.fill_remaining_space().unwrap();
+        let mut stream = writer.into_inner().unwrap();
+
+        // the bin file follows a header in the stream
+        stream.set_position(0);
+        stream.read_exact(&mut [0; 6]).unwrap();
+        let mut reader = BinFileReader::from_reader(stream).unwrap();
+        assert_eq!(*reader.tile_kind(), TileKind::SD);
+        assert_eq!(reader.seek(SeekFrom::Start(4)).unwrap(), 4);
+        assert_eq!(reader.read_tile().unwrap().get_pixel(1, 2), &Rgba([255, 0, 0, 255]));
+        reader.rewind().unwrap();
+        assert_eq!(reader.read_tiles().unwrap().len(), 256);
+    }
 }
\ No newline at end of file
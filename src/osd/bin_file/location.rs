@@ -0,0 +1,73 @@
+
+//! Position of the tiles in the bin files, for patching fonts with an hex editor or debugging how devices load them
+//!
+//! The tiles of the first page are stored in the base bin file and the ones of the second page in the extended `_2`
+//! bin file. Each tile is stored as raw RGBA pixels, row by row, right after the previous one without any header.
+
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::osd::tile::{Kind as TileKind, index::{PageOffset, PageOffsetError}};
+
+use super::{normalized_file_name, FontPart};
+
+
+/// Bin file and byte range storing a tile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileLocation {
+    /// normalized name of the bin file
+    pub file_name: PathBuf,
+    pub page_offset: PageOffset,
+    /// position of the first byte of the tile in the file
+    pub byte_offset: usize,
+    pub byte_length: usize,
+}
+
+impl TileLocation {
+
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_offset..self.byte_offset + self.byte_length
+    }
+
+}
+
+impl Display for TileLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: page {} offset 0x{:02X}, bytes 0x{:X}..0x{:X} ({} bytes)", self.file_name.to_string_lossy(), self.page_offset.page(),
+            self.page_offset.offset(), self.byte_offset, self.byte_offset + self.byte_length, self.byte_length)
+    }
+}
+
+/// Locates the tile at a linear index in the normalized bin files of a font
+pub fn locate_tile(index: usize, tile_kind: TileKind, ident: &Option<&str>) -> Result<TileLocation, PageOffsetError> {
+    let page_offset = PageOffset::from_index(index)?;
+    let part = if page_offset.page() == 1 { FontPart::Base } else { FontPart::Ext };
+    let byte_length = tile_kind.raw_rgba_size_bytes();
+    Ok(TileLocation {
+        file_name: normalized_file_name(tile_kind, ident, part),
+        page_offset,
+        byte_offset: page_offset.offset() * byte_length,
+        byte_length,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::osd::tile::Kind as TileKind;
+
+    use super::locate_tile;
+
+    #[test]
+    fn locate() {
+        let location = locate_tile(0x101, TileKind::HD, &Some("inav")).unwrap();
+        assert_eq!(location.file_name, Path::new("font_inav_hd_2.bin"));
+        assert_eq!(location.page_offset.offset(), 1);
+        assert_eq!(location.byte_range(), 24 * 36 * 4..2 * 24 * 36 * 4);
+
+        assert_eq!(locate_tile(3, TileKind::SD, &None).unwrap().file_name, Path::new("font.bin"));
+        assert!(locate_tile(512, TileKind::SD, &None).is_err());
+    }
+}
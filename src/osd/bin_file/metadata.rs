@@ -0,0 +1,117 @@
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use derive_more::From;
+use fs_err::File;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+
+pub const SIDECAR_EXTENSION: &str = "meta.yaml";
+
+#[derive(Debug, From, Error)]
+pub enum MetadataError {
+    #[error(transparent)]
+    IOError(IOError),
+    #[error("failed to parse metadata file {file_path}: {error}")]
+    FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
+}
+
+impl MetadataError {
+    pub fn file_structure<P: AsRef<Path>>(file_path: P, error: serde_yaml::Error) -> Self {
+        Self::FileStructureError { file_path: file_path.as_ref().to_path_buf(), error }
+    }
+}
+
+/// Provenance of a bin file, stored in a `<bin file>.meta.yaml` sidecar file since the bin format cannot carry any
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub tool_version: String,
+    /// seconds since the UNIX epoch
+    pub created_at: u64,
+    pub source: Option<String>,
+    pub ident: Option<String>,
+    pub sha256: String,
+}
+
+/// Path of the sidecar file holding the metadata of the bin file at `bin_file_path`
+pub fn sidecar_path<P: AsRef<Path>>(bin_file_path: P) -> PathBuf {
+    let mut path = bin_file_path.as_ref().as_os_str().to_owned();
+    path.push(".");
+    path.push(SIDECAR_EXTENSION);
+    path.into()
+}
+
+fn file_sha256<P: AsRef<Path>>(path: P) -> Result<String, IOError> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path.as_ref())?, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+impl Metadata {
+
+    /// Collects the metadata of an already written bin file
+    pub fn of_file<P: AsRef<Path>>(bin_file_path: P, source: Option<&str>, ident: Option<&str>) -> Result<Self, IOError> {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+        Ok(Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_owned(),
+            created_at,
+            source: source.map(str::to_owned),
+            ident: ident.map(str::to_owned),
+            sha256: file_sha256(bin_file_path)?,
+        })
+    }
+
+    /// Collects the metadata of an already written bin file and saves it to its sidecar file
+    pub fn write_sidecar<P: AsRef<Path>>(bin_file_path: P, source: Option<&str>, ident: Option<&str>) -> Result<Self, MetadataError> {
+        let metadata = Self::of_file(&bin_file_path, source, ident)?;
+        let path = sidecar_path(&bin_file_path);
+        let content = serde_yaml::to_string(&metadata).map_err(|error| MetadataError::file_structure(&path, error))?;
+        fs_err::write(&path, content)?;
+        Ok(metadata)
+    }
+
+    /// Loads the metadata from the sidecar file of a bin file, returns `None` if there is no sidecar file
+    pub fn load_sidecar<P: AsRef<Path>>(bin_file_path: P) -> Result<Option<Self>, MetadataError> {
+        let path = sidecar_path(bin_file_path);
+        if ! path.is_file() {
+            return Ok(None);
+        }
+        let metadata = serde_yaml::from_reader(File::open(&path)?)
+            .map_err(|error| MetadataError::file_structure(&path, error))?;
+        Ok(Some(metadata))
+    }
+
+    /// Whether the bin file content still matches the recorded checksum
+    pub fn matches_file<P: AsRef<Path>>(&self, bin_file_path: P) -> Result<bool, IOError> {
+        Ok(file_sha256(bin_file_path)? == self.sha256)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::{Metadata, sidecar_path};
+
+    #[test]
+    fn sidecar_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let bin_file_path = temp_dir.child("font.bin");
+        assert_eq!(sidecar_path(&bin_file_path), temp_dir.child("font.bin.meta.yaml"));
+        std::fs::write(&bin_file_path, [0u8; 16]).unwrap();
+
+        assert!(Metadata::load_sidecar(&bin_file_path).unwrap().is_none());
+        let metadata = Metadata::write_sidecar(&bin_file_path, Some("tilegrid:font.png"), None).unwrap();
+        let loaded = Metadata::load_sidecar(&bin_file_path).unwrap().unwrap();
+        assert_eq!(loaded, metadata);
+        assert!(loaded.matches_file(&bin_file_path).unwrap());
+
+        std::fs::write(&bin_file_path, [1u8; 16]).unwrap();
+        assert!(! loaded.matches_file(&bin_file_path).unwrap());
+    }
+}
@@ -0,0 +1,100 @@
+
+//! Splitting collections into the 256 tiles pages of bin files and concatenating pages back into one collection
+//!
+//! A 512 tiles collection is stored as a base file and an extended `_2` file, a set of SD and HD collections as four
+//! files. These functions replace concatenating or cutting the files by hand which silently corrupts fonts when the
+//! pages are not of the same kind or not whole.
+
+use clap::ValueEnum;
+use thiserror::Error;
+
+use crate::osd::tile::{container::uniq_tile_kind::{TileKindError, UniqTileKind}, Kind as TileKind, Tile};
+use super::TILE_COUNT as PAGE_TILE_COUNT;
+
+
+/// How collections which are not made of whole pages are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PaddingPolicy {
+    /// the missing tiles of the last page are empty tiles
+    #[default]
+    Pad,
+    /// collections which are not made of whole pages are rejected
+    Strict,
+}
+
+#[derive(Debug, Error)]
+pub enum PagesError {
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error("page {page} has {found} tiles while page 1 has {expected} tiles")]
+    KindMismatch { page: usize, expected: TileKind, found: TileKind },
+    #[error("the collection has {tile_count} tiles which do not fit in {page_count} pages of {PAGE_TILE_COUNT} tiles")]
+    TooManyTiles { tile_count: usize, page_count: usize },
+    #[error("{what} has {tile_count} tiles which is not a whole number of pages of {PAGE_TILE_COUNT} tiles")]
+    PartialPage { what: String, tile_count: usize },
+}
+
+/// Splits the collection in `page_count` pages of 256 tiles
+pub fn split(tiles: &[Tile], page_count: usize, padding: PaddingPolicy) -> Result<Vec<Vec<Tile>>, PagesError> {
+    let tile_kind = tiles.tile_kind().map_err(PagesError::TileKindError)?;
+    let capacity = page_count * PAGE_TILE_COUNT;
+    if tiles.len() > capacity {
+        return Err(PagesError::TooManyTiles { tile_count: tiles.len(), page_count });
+    }
+    if padding == PaddingPolicy::Strict && tiles.len() != capacity {
+        return Err(PagesError::PartialPage { what: "the collection".to_owned(), tile_count: tiles.len() });
+    }
+    let mut tiles = tiles.to_vec();
+    tiles.resize(capacity, Tile::new(tile_kind));
+    Ok(tiles.chunks(PAGE_TILE_COUNT).map(<[Tile]>::to_vec).collect())
+}
+
+/// Concatenates pages of the same kind in order into one collection
+pub fn concat(pages: &[Vec<Tile>], padding: PaddingPolicy) -> Result<Vec<Tile>, PagesError> {
+    let mut tiles = vec![];
+    let mut first_tile_kind = None;
+    for (page_index, page_tiles) in pages.iter().enumerate() {
+        let page = page_index + 1;
+        let tile_kind = page_tiles.tile_kind().map_err(PagesError::TileKindError)?;
+        match first_tile_kind {
+            None => first_tile_kind = Some(tile_kind),
+            Some(expected) if expected != tile_kind => return Err(PagesError::KindMismatch { page, expected, found: tile_kind }),
+            Some(_) => (),
+        }
+        if page_tiles.len() > PAGE_TILE_COUNT || (padding == PaddingPolicy::Strict && page_tiles.len() != PAGE_TILE_COUNT) {
+            return Err(PagesError::PartialPage { what: format!("page {page}"), tile_count: page_tiles.len() });
+        }
+        tiles.extend(page_tiles.iter().cloned());
+        // the following pages must start at their page boundary
+        if page < pages.len() {
+            tiles.resize(page * PAGE_TILE_COUNT, Tile::new(tile_kind));
+        }
+    }
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{concat, split, PaddingPolicy, PagesError};
+
+    #[test]
+    fn split_and_concat() {
+        let mut tiles = vec![Tile::new(TileKind::SD); 300];
+        tiles[299].put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+        let pages = split(&tiles, 2, PaddingPolicy::Pad).unwrap();
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), vec![256, 256]);
+        assert!(! pages[1][299 - 256].is_empty());
+        assert!(matches!(split(&tiles, 2, PaddingPolicy::Strict), Err(PagesError::PartialPage { .. })));
+        assert!(matches!(split(&tiles, 1, PaddingPolicy::Pad), Err(PagesError::TooManyTiles { .. })));
+
+        let concatenated = concat(&[tiles[0..100].to_vec(), pages[1].clone()], PaddingPolicy::Pad).unwrap();
+        assert_eq!(concatenated.len(), 512);
+        assert!(! concatenated[299].is_empty());
+        assert!(matches!(concat(&[tiles[0..100].to_vec()], PaddingPolicy::Strict), Err(PagesError::PartialPage { .. })));
+        let hd_page = vec![Tile::new(TileKind::HD); 256];
+        assert!(matches!(concat(&[pages[0].clone(), hd_page], PaddingPolicy::Pad),
+            Err(PagesError::KindMismatch { page: 2, expected: TileKind::SD, found: TileKind::HD })));
+    }
+}
@@ -0,0 +1,73 @@
+
+use std::fmt::Display;
+
+use clap::{builder::PossibleValue, ValueEnum};
+use getset::CopyGetters;
+use thiserror::Error;
+
+use super::{avatar_file, bin_file, tile::Kind as TileKind};
+
+
+#[derive(Debug, Error)]
+#[error("{tile_kind} tiles are not supported by the {profile_name} font profile")]
+pub struct UnsupportedTileKindError {
+    pub tile_kind: TileKind,
+    pub profile_name: &'static str,
+}
+
+/// Limits of the fonts used by a system: how many tiles a font can contain and which tile kinds are supported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct FontProfile {
+    name: &'static str,
+    tile_count: usize,
+    tile_kinds: &'static [TileKind],
+}
+
+impl FontProfile {
+
+    /// DJI fonts made of a base and an extended bin file of 256 tiles each
+    pub const DJI_2PAGE: Self = Self { name: "dji-2page", tile_count: 2 * bin_file::TILE_COUNT, tile_kinds: &[TileKind::SD, TileKind::HD] };
+
+    /// Walksnail Avatar fonts made of a single image of 256 tiles
+    pub const AVATAR_1PAGE: Self = Self { name: "avatar-1page", tile_count: avatar_file::TILE_COUNT, tile_kinds: &[TileKind::SD, TileKind::HD] };
+
+    /// HDZero fonts made of 512 HD tiles
+    pub const HDZERO: Self = Self { name: "hdzero", tile_count: 512, tile_kinds: &[TileKind::HD] };
+
+    pub const PRESETS: &'static [Self] = &[Self::DJI_2PAGE, Self::AVATAR_1PAGE, Self::HDZERO];
+
+    pub fn supports_tile_kind(&self, tile_kind: TileKind) -> bool {
+        self.tile_kinds.contains(&tile_kind)
+    }
+
+    pub fn check_tile_kind(&self, tile_kind: TileKind) -> Result<(), UnsupportedTileKindError> {
+        if ! self.supports_tile_kind(tile_kind) {
+            return Err(UnsupportedTileKindError { tile_kind, profile_name: self.name });
+        }
+        Ok(())
+    }
+
+}
+
+impl Default for FontProfile {
+    fn default() -> Self {
+        Self::DJI_2PAGE
+    }
+}
+
+impl Display for FontProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl ValueEnum for FontProfile {
+    fn value_variants<'a>() -> &'a [Self] {
+        Self::PRESETS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name))
+    }
+}
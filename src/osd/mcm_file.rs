@@ -0,0 +1,192 @@
+
+//! MAX7456 character memory files (.mcm) of the analog OSD of Betaflight and INAV
+//!
+//! The file starts with a `MAX7456` line followed by 256 characters of 64 lines each, every line being a byte written
+//! as 8 binary digits. A character is 12x18 pixels with 2 bits per pixel, 4 pixels per byte starting from the most
+//! significant bits, the 54 bytes of the pixels are padded with 10 unused bytes. Pixels are black (`00`), white (`10`)
+//! or transparent (`01` and `11`).
+//!
+//! The characters are scaled by whole factors to tiles: 3 for SD tiles and 2 for HD tiles. When saving each character
+//! pixel is made from the block of tile pixels it covers: transparent if most of them are transparent, otherwise white
+//! or black depending on the mean luminance of the opaque ones.
+
+use std::fmt::Write as _;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use image::Rgba;
+use thiserror::Error;
+
+use super::tile::{
+    Tile,
+    Kind as TileKind,
+    container::uniq_tile_kind::{TileKindError, UniqTileKind},
+};
+use crate::io_context::IoContext;
+
+
+pub const HEADER: &str = "MAX7456";
+pub const TILE_COUNT: usize = 256;
+pub const CHARACTER_WIDTH: u32 = 12;
+pub const CHARACTER_HEIGHT: u32 = 18;
+/// bytes of a character in the file, padding included
+pub const CHARACTER_BYTES: usize = 64;
+const PIXEL_BYTES: usize = (CHARACTER_WIDTH * CHARACTER_HEIGHT / 4) as usize;
+
+const BLACK: u8 = 0b00;
+const WHITE: u8 = 0b10;
+const TRANSPARENT: u8 = 0b01;
+
+impl TileKind {
+
+    /// Tile pixels per MCM character pixel in each direction
+    pub const fn mcm_scale_factor(&self) -> u32 {
+        self.dimensions().width / CHARACTER_WIDTH
+    }
+
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadError {
+    #[error("failed to read MCM file {file_path}: {error}")]
+    FileError { file_path: PathBuf, error: IOError },
+    #[from(ignore)]
+    #[error("file {0} is not a MCM file, the first line should be `MAX7456`")]
+    InvalidHeader(PathBuf),
+    #[from(ignore)]
+    #[error("{file_path}:{line_number}: expected 8 binary digits, got `{line}`")]
+    InvalidLine { file_path: PathBuf, line_number: usize, line: String },
+    #[from(ignore)]
+    #[error("MCM file {file_path} contains {line_count} character memory lines, expected {}", TILE_COUNT * CHARACTER_BYTES)]
+    WrongLineCount { file_path: PathBuf, line_count: usize },
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error("failed to write MCM file {file_path}: {error}")]
+    FileError { file_path: PathBuf, error: IOError },
+}
+
+fn pixel_color(value: u8) -> Rgba<u8> {
+    match value {
+        BLACK => Rgba([0, 0, 0, 255]),
+        WHITE => Rgba([255, 255, 255, 255]),
+        _ => Rgba([0, 0, 0, 0]),
+    }
+}
+
+fn character_tile(bytes: &[u8], tile_kind: TileKind) -> Tile {
+    let factor = tile_kind.mcm_scale_factor();
+    let mut tile = Tile::new(tile_kind);
+    for pixel_index in 0..(CHARACTER_WIDTH * CHARACTER_HEIGHT) {
+        let value = (bytes[pixel_index as usize / 4] >> (6 - 2 * (pixel_index % 4))) & 0b11;
+        let (x, y) = (pixel_index % CHARACTER_WIDTH, pixel_index / CHARACTER_WIDTH);
+        for (dx, dy) in (0..factor).flat_map(|dx| (0..factor).map(move |dy| (dx, dy))) {
+            tile.put_pixel(x * factor + dx, y * factor + dy, pixel_color(value));
+        }
+    }
+    tile
+}
+
+// value of the character pixel covering the block of tile pixels at x, y
+fn block_value(tile: &Tile, x: u32, y: u32, factor: u32) -> u8 {
+    let block: Vec<&Rgba<u8>> = (0..factor).flat_map(|dx| (0..factor).map(move |dy| (dx, dy)))
+        .map(|(dx, dy)| tile.get_pixel(x * factor + dx, y * factor + dy))
+        .collect();
+    let opaque: Vec<&&Rgba<u8>> = block.iter().filter(|pixel| pixel[3] >= 128).collect();
+    if opaque.len() * 2 < block.len() {
+        return TRANSPARENT;
+    }
+    let luminance_sum: u32 = opaque.iter().map(|pixel| (pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000).sum();
+    if luminance_sum >= 128 * opaque.len() as u32 { WHITE } else { BLACK }
+}
+
+fn character_bytes(tile: &Tile) -> [u8; CHARACTER_BYTES] {
+    let factor = tile.kind().mcm_scale_factor();
+    // the unused padding bytes are conventionally written as transparent pixels
+    let mut bytes = [0b01010101; CHARACTER_BYTES];
+    bytes[..PIXEL_BYTES].fill(0);
+    for pixel_index in 0..(CHARACTER_WIDTH * CHARACTER_HEIGHT) {
+        let value = block_value(tile, pixel_index % CHARACTER_WIDTH, pixel_index / CHARACTER_WIDTH, factor);
+        bytes[pixel_index as usize / 4] |= value << (6 - 2 * (pixel_index % 4));
+    }
+    bytes
+}
+
+/// Loads the characters of a MCM file scaled to tiles of the given kind
+pub fn load<P: AsRef<Path>>(path: P, tile_kind: TileKind) -> Result<Vec<Tile>, LoadError> {
+    load_with_context(path, tile_kind, &IoContext::default())
+}
+
+pub fn load_with_context<P: AsRef<Path>>(path: P, tile_kind: TileKind, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    let file_path = path.as_ref().to_path_buf();
+    let content = context.retry_io(&file_path, || fs_err::read_to_string(&file_path)).map_err(|error| LoadError::FileError { file_path: file_path.clone(), error })?;
+    let mut lines = content.lines().enumerate().map(|(index, line)| (index + 1, line.trim())).filter(|(_, line)| ! line.is_empty());
+    match lines.next() {
+        Some((_, HEADER)) => {},
+        _ => return Err(LoadError::InvalidHeader(file_path)),
+    }
+    let mut bytes = Vec::with_capacity(TILE_COUNT * CHARACTER_BYTES);
+    for (line_number, line) in lines {
+        match u8::from_str_radix(line, 2) {
+            Ok(byte) if line.len() == 8 => bytes.push(byte),
+            _ => return Err(LoadError::InvalidLine { file_path, line_number, line: line.to_owned() }),
+        }
+    }
+    if bytes.len() != TILE_COUNT * CHARACTER_BYTES {
+        return Err(LoadError::WrongLineCount { file_path, line_count: bytes.len() });
+    }
+    Ok(bytes.chunks(CHARACTER_BYTES).map(|character| character_tile(character, tile_kind)).collect())
+}
+
+/// Saves the tiles to a MCM file, a collection of less than 256 tiles is padded with transparent characters
+pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveError> {
+    let tile_kind = tiles.tile_kind()?;
+    if tiles.len() > TILE_COUNT {
+        log::warn!("MCM files can only contain {TILE_COUNT} characters but the source collection contains {}", tiles.len());
+    }
+    let empty_tile = Tile::new(tile_kind);
+    let mut content = format!("{HEADER}\n");
+    for tile_index in 0..TILE_COUNT {
+        for byte in character_bytes(tiles.get(tile_index).unwrap_or(&empty_tile)) {
+            writeln!(content, "{byte:08b}").unwrap();
+        }
+    }
+    fs_err::write(&path, content).map_err(|error| SaveError::FileError { file_path: path.as_ref().to_path_buf(), error })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{load, save, TILE_COUNT};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font.mcm");
+        let mut tiles = vec![Tile::new(TileKind::SD); 10];
+        for x in 0..3 {
+            for y in 0..3 {
+                tiles[1].put_pixel(3 + x, y, Rgba([255, 255, 255, 255]));
+                tiles[1].put_pixel(6 + x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        save(&tiles, &path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().nth(64 + 1), Some("01100001"));
+
+        let loaded = load(&path, TileKind::SD).unwrap();
+        assert_eq!(loaded.len(), TILE_COUNT);
+        assert_eq!(loaded[1].as_raw(), tiles[1].as_raw());
+        let hd_tiles = load(&path, TileKind::HD).unwrap();
+        assert_eq!(*hd_tiles[1].get_pixel(3, 1), Rgba([255, 255, 255, 255]));
+    }
+}
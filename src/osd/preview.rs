@@ -0,0 +1,248 @@
+
+//! Mock-ups of an OSD screen drawn with a font, to see how the symbols look in context before flashing
+//!
+//! The layout is a text file with one line per row of the screen. Characters are drawn with the tile at their ASCII
+//! code like the firmwares do, `{<index>}` draws the tile at a decimal or `0x` hexadecimal index and `{<name>}` draws all
+//! the tiles of a symbol of the symbol specs. `{{` draws a `{`. Cells past the end of a line are left empty.
+//!
+//! The same layout drawn with two fonts can be put side by side or alternated in an animated GIF, to show proposed font
+//! changes in a single image.
+
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Error as IOError};
+use std::path::Path;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use derive_more::From;
+use image::{Delay, Frame, GenericImage, ImageBuffer, ImageError, Rgba};
+use image::codecs::gif::{GifEncoder, Repeat};
+use thiserror::Error;
+
+use super::tile::{Kind as TileKind, Tile, container::{symbol::spec::Specs as SymbolSpecs, uniq_tile_kind::{TileKindError, UniqTileKind}}};
+use super::tile::index::parse_index;
+use crate::background::Background;
+use crate::workspace;
+
+
+pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+/// Video feed stand-in drawn under the tiles
+pub const DEFAULT_BACKGROUND: Background = Background::Color(Rgba([0x40, 0x48, 0x50, 255]));
+
+/// Color of the margins around the screens of a comparison
+const COMPARISON_MARGIN_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+/// Width in pixels of the margin between the screens of a side by side comparison
+const COMPARISON_GAP: u32 = 8;
+pub const DEFAULT_TOGGLE_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// How the screens drawn with two fonts are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ComparisonMode {
+    /// first font on the left, second font on the right
+    SideBySide,
+    /// animated GIF alternating between the fonts
+    Toggle,
+}
+
+#[derive(Debug, Error)]
+pub enum LayoutParseError {
+    #[error("line {line}: missing `}}` after `{{`")]
+    UnterminatedBrace { line: usize },
+    #[error("line {line}: `{name}` is neither a tile index nor the name of a symbol of the symbol specs")]
+    UnknownSymbol { line: usize, name: String },
+    #[error("line {line}: `{character}` is not an ASCII character, use `{{<tile index>}}` or `{{<symbol name>}}` instead")]
+    NonAsciiCharacter { line: usize, character: char },
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadLayoutError {
+    #[error("failed to read layout file: {0}")]
+    ReadError(IOError),
+    #[error(transparent)]
+    ParseError(LayoutParseError),
+}
+
+/// Character grid of the OSD screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ScreenGrid {
+    /// 30 columns by 16 rows
+    SD,
+    /// 50 columns by 18 rows
+    HD,
+}
+
+impl ScreenGrid {
+
+    pub const fn columns(&self) -> usize {
+        match self {
+            Self::SD => 30,
+            Self::HD => 50,
+        }
+    }
+
+    pub const fn rows(&self) -> usize {
+        match self {
+            Self::SD => 16,
+            Self::HD => 18,
+        }
+    }
+
+    pub const fn for_tile_kind(tile_kind: TileKind) -> Self {
+        match tile_kind {
+            TileKind::SD => Self::SD,
+            TileKind::HD => Self::HD,
+        }
+    }
+
+}
+
+/// Tile indices of the cells of the screen, row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    rows: Vec<Vec<usize>>,
+}
+
+impl Layout {
+
+    fn parse_line(line: &str, line_number: usize, specs: Option<&SymbolSpecs>) -> Result<Vec<usize>, LayoutParseError> {
+        let mut cells = vec![];
+        let mut chars = line.chars();
+        while let Some(character) = chars.next() {
+            match character {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    cells.push('{' as usize);
+                },
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or(LayoutParseError::UnterminatedBrace { line: line_number })?;
+                    let name = rest[..end].trim();
+                    match parse_index(name) {
+                        Ok(index) => cells.push(index),
+                        Err(_) => {
+                            let spec = specs.and_then(|specs| specs.find_by_name(name))
+                                .ok_or_else(|| LayoutParseError::UnknownSymbol { line: line_number, name: name.to_owned() })?;
+                            cells.extend(spec.tile_index_range());
+                        },
+                    }
+                    chars = rest[end + 1..].chars();
+                },
+                character if character.is_ascii() => cells.push(character as usize),
+                character => return Err(LayoutParseError::NonAsciiCharacter { line: line_number, character }),
+            }
+        }
+        Ok(cells)
+    }
+
+    /// Parses a layout, symbol names can only be used when symbol specs are given
+    pub fn parse(content: &str, specs: Option<&SymbolSpecs>) -> Result<Self, LayoutParseError> {
+        let rows = content.lines().enumerate()
+            .map(|(line_index, line)| Self::parse_line(line, line_index + 1, specs))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rows })
+    }
+
+    pub fn load_file<P: AsRef<Path>>(path: P, specs: Option<&SymbolSpecs>) -> Result<Self, LoadLayoutError> {
+        Ok(Self::parse(&fs_err::read_to_string(path)?, specs)?)
+    }
+
+    pub fn rows(&self) -> &[Vec<usize>] {
+        &self.rows
+    }
+
+}
+
+/// Draws the layout with the tiles of a font on a screen of the given grid
+///
+/// The rows and columns which do not fit the screen are dropped and the cells whose tile is not in the font are left
+/// empty, both with a warning.
+pub fn render(tiles: &[Tile], layout: &Layout, grid: ScreenGrid, background: &Background) -> Result<Image, TileKindError> {
+    let tile_dimensions = tiles.tile_kind()?.dimensions();
+    let mut image = Image::new(grid.columns() as u32 * tile_dimensions.width, grid.rows() as u32 * tile_dimensions.height);
+    if layout.rows.len() > grid.rows() {
+        log::warn!("the layout has {} rows, only the first {} fit on the screen", layout.rows.len(), grid.rows());
+    }
+    let mut missing_indices = BTreeSet::new();
+    for (row_index, row) in layout.rows.iter().take(grid.rows()).enumerate() {
+        if row.len() > grid.columns() {
+            log::warn!("row {} has {} cells, only the first {} fit on the screen", row_index + 1, row.len(), grid.columns());
+        }
+        for (column_index, tile_index) in row.iter().take(grid.columns()).enumerate() {
+            match tiles.get(*tile_index) {
+                Some(tile) => image.copy_from(tile.image(), column_index as u32 * tile_dimensions.width, row_index as u32 * tile_dimensions.height).unwrap(),
+                None => { missing_indices.insert(*tile_index); },
+            }
+        }
+    }
+    if ! missing_indices.is_empty() {
+        let indices: Vec<String> = missing_indices.iter().map(usize::to_string).collect();
+        log::warn!("the font has {} tiles, cells with tile(s) {} are left empty", tiles.len(), indices.join(", "));
+    }
+    Ok(background.composite(&image))
+}
+
+// the screens of fonts of different kinds have different dimensions, they are centered on a canvas fitting both
+fn centered(image: &Image, width: u32, height: u32) -> Image {
+    let mut canvas = Image::from_pixel(width, height, COMPARISON_MARGIN_COLOR);
+    canvas.copy_from(image, (width - image.width()) / 2, (height - image.height()) / 2).unwrap();
+    canvas
+}
+
+/// Screens drawn with two fonts next to each other
+pub fn side_by_side(first: &Image, second: &Image) -> Image {
+    let height = first.height().max(second.height());
+    let mut image = Image::from_pixel(first.width() + COMPARISON_GAP + second.width(), height, COMPARISON_MARGIN_COLOR);
+    image.copy_from(&centered(first, first.width(), height), 0, 0).unwrap();
+    image.copy_from(&centered(second, second.width(), height), first.width() + COMPARISON_GAP, 0).unwrap();
+    image
+}
+
+/// Writes an animated GIF looping over the screens, each one being shown for `interval`
+///
+/// GIF images have at most 256 colors per frame: the gradients of anti-aliased tiles are approximated.
+pub fn write_toggle_gif<P: AsRef<Path>>(path: P, screens: &[Image], interval: Duration) -> Result<(), ImageError> {
+    let width = screens.iter().map(Image::width).max().unwrap_or(0);
+    let height = screens.iter().map(Image::height).max().unwrap_or(0);
+    workspace::write_atomically(&path, |staging_path| {
+        let mut encoder = GifEncoder::new(BufWriter::new(fs_err::File::create(staging_path)?));
+        encoder.set_repeat(Repeat::Infinite)?;
+        let frames = screens.iter().map(|screen| Frame::from_parts(centered(screen, width, height), 0, 0, Delay::from_saturating_duration(interval)));
+        encoder.encode_frames(frames)
+    }, ImageError::IoError)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{render, side_by_side, write_toggle_gif, Layout, ScreenGrid, DEFAULT_BACKGROUND, DEFAULT_TOGGLE_INTERVAL};
+
+    #[test]
+    fn render_layout() {
+        let layout = Layout::parse("A{0x01}{{\n\n {2}", None).unwrap();
+        assert_eq!(layout.rows(), &[vec![65, 1, 123], vec![], vec![32, 2]]);
+        assert!(Layout::parse("{RSSI}", None).is_err());
+        assert!(Layout::parse("{0x01", None).is_err());
+
+        let mut tiles = vec![Tile::new(TileKind::SD); 66];
+        tiles[65].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let image = render(&tiles, &layout, ScreenGrid::SD, &DEFAULT_BACKGROUND).unwrap();
+        assert_eq!(image.dimensions(), (30 * 36, 16 * 54));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([0x40, 0x48, 0x50, 255]));
+
+        let hd_image = render(&[Tile::new(TileKind::HD)], &Layout::parse("", None).unwrap(), ScreenGrid::HD, &DEFAULT_BACKGROUND).unwrap();
+        let comparison = side_by_side(&image, &hd_image);
+        assert_eq!(comparison.dimensions(), (30 * 36 + 8 + 50 * 24, 16 * 54));
+        assert_eq!(*comparison.get_pixel(30 * 36 + 8, 0), Rgba([0, 0, 0, 255]));
+
+        let temp_dir = TempDir::new().unwrap();
+        let gif_path = temp_dir.child("comparison.gif");
+        write_toggle_gif(&gif_path, &[image, hd_image], DEFAULT_TOGGLE_INTERVAL).unwrap();
+        assert_eq!(image::image_dimensions(&gif_path).unwrap(), (50 * 24, 16 * 54));
+    }
+}
@@ -0,0 +1,171 @@
+
+//! Approximate fonts sampled from photos or DVR screenshots of the font test screen of some goggles
+//!
+//! The visible grid of tiles is given by its number of columns and rows and by its bounding box in the screenshot.
+//! Giving the four corners of the grid instead corrects the perspective of photos taken at an angle, the positions are
+//! interpolated bilinearly between the corners which is a best-effort approximation of the perspective. Each tile pixel
+//! takes the color of the screenshot pixel nearest to its center, the result is meant for analysis rather than flashing.
+
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use derive_more::From;
+use image::{Rgba, RgbaImage};
+use thiserror::Error;
+
+use super::tile::{Kind as TileKind, Tile};
+
+use crate::image::{read_image_file_with_context, ReadError as ImageReadError};
+use crate::io_context::IoContext;
+
+
+#[derive(Debug, Error)]
+#[error("invalid screenshot geometry `{0}`: expected `<sd|hd>,<columns>x<rows>,<left>,<top>,<right>,<bottom>` or the four corners `<sd|hd>,<columns>x<rows>,<x>,<y>,<x>,<y>,<x>,<y>,<x>,<y>` clockwise from the top left one")]
+pub struct InvalidGeometryError(String);
+
+#[derive(Debug, From, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    ImageReadError(ImageReadError),
+    #[error("screenshot grid corner ({0}, {1}) is outside of the {2}x{3} screenshot")]
+    CornerOutOfBounds(f32, f32, u32, u32),
+}
+
+/// Position of the visible grid of tiles in a screenshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry {
+    pub tile_kind: TileKind,
+    pub columns: usize,
+    pub rows: usize,
+    /// top left, top right, bottom right and bottom left corners of the grid in screenshot pixels
+    pub corners: [(f32, f32); 4],
+}
+
+impl Geometry {
+
+    /// Grid filling an axis aligned rectangle, the right and bottom edges are excluded
+    pub fn rectangle(tile_kind: TileKind, columns: usize, rows: usize, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self { tile_kind, columns, rows, corners: [(left, top), (right, top), (right, bottom), (left, bottom)] }
+    }
+
+    /// Position in the screenshot of a point given in fractions of the grid width and height
+    fn position(&self, u: f32, v: f32) -> (f32, f32) {
+        let [top_left, top_right, bottom_right, bottom_left] = self.corners;
+        let interpolate = |coordinate: fn((f32, f32)) -> f32|
+            (1.0 - v) * ((1.0 - u) * coordinate(top_left) + u * coordinate(top_right))
+                + v * ((1.0 - u) * coordinate(bottom_left) + u * coordinate(bottom_right));
+        (interpolate(|point| point.0), interpolate(|point| point.1))
+    }
+
+}
+
+// the four corners form, parses back to the same geometry
+impl Display for Geometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}x{}", self.tile_kind.to_string().to_lowercase(), self.columns, self.rows)?;
+        for (x, y) in self.corners {
+            write!(f, ",{x},{y}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Geometry {
+    type Err = InvalidGeometryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidGeometryError(s.to_owned());
+        let fields: Vec<&str> = s.split(',').map(str::trim).collect();
+        if fields.len() < 2 {
+            return Err(invalid());
+        }
+        let tile_kind = match fields[0].to_ascii_lowercase().as_str() {
+            "sd" => TileKind::SD,
+            "hd" => TileKind::HD,
+            _ => return Err(invalid()),
+        };
+        let (columns, rows) = fields[1].split_once('x').ok_or_else(invalid)?;
+        let (columns, rows): (usize, usize) = (columns.parse().map_err(|_| invalid())?, rows.parse().map_err(|_| invalid())?);
+        if columns == 0 || rows == 0 {
+            return Err(invalid());
+        }
+        let coordinates = fields[2..].iter().map(|field| field.parse::<f32>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+        match *coordinates.as_slice() {
+            [left, top, right, bottom] if left < right && top < bottom =>
+                Ok(Self::rectangle(tile_kind, columns, rows, left, top, right, bottom)),
+            [x0, y0, x1, y1, x2, y2, x3, y3] =>
+                Ok(Self { tile_kind, columns, rows, corners: [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Samples the tiles of the grid row by row
+pub fn sample_tiles(screenshot: &RgbaImage, geometry: &Geometry) -> Result<Vec<Tile>, LoadError> {
+    let (width, height) = screenshot.dimensions();
+    for &(x, y) in &geometry.corners {
+        if ! (0.0..=width as f32).contains(&x) || ! (0.0..=height as f32).contains(&y) {
+            return Err(LoadError::CornerOutOfBounds(x, y, width, height));
+        }
+    }
+    let tile_dimensions = geometry.tile_kind.dimensions();
+    let mut tiles = Vec::with_capacity(geometry.columns * geometry.rows);
+    for row in 0..geometry.rows {
+        for column in 0..geometry.columns {
+            let mut tile = Tile::new(geometry.tile_kind);
+            for y in 0..tile_dimensions.height {
+                for x in 0..tile_dimensions.width {
+                    let u = (column as f32 + (x as f32 + 0.5) / tile_dimensions.width as f32) / geometry.columns as f32;
+                    let v = (row as f32 + (y as f32 + 0.5) / tile_dimensions.height as f32) / geometry.rows as f32;
+                    let (sample_x, sample_y) = geometry.position(u, v);
+                    let sample_x = (sample_x.max(0.0) as u32).min(width - 1);
+                    let sample_y = (sample_y.max(0.0) as u32).min(height - 1);
+                    let Rgba([red, green, blue, _]) = *screenshot.get_pixel(sample_x, sample_y);
+                    tile.put_pixel(x, y, Rgba([red, green, blue, 255]));
+                }
+            }
+            tiles.push(tile);
+        }
+    }
+    Ok(tiles)
+}
+
+pub fn load<P: AsRef<Path>>(path: P, geometry: &Geometry) -> Result<Vec<Tile>, LoadError> {
+    load_with_context(path, geometry, &IoContext::default())
+}
+
+pub fn load_with_context<P: AsRef<Path>>(path: P, geometry: &Geometry, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    let screenshot = read_image_file_with_context(path, context)?.into_rgba8();
+    sample_tiles(&screenshot, geometry)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use crate::osd::tile::Kind as TileKind;
+
+    use super::{sample_tiles, Geometry};
+
+    #[test]
+    fn sample_scaled_grid() {
+        // 2x1 grid of HD tiles shown at twice their size, the second tile is red
+        let mut screenshot = RgbaImage::from_pixel(100, 40, Rgba([0, 0, 0, 255]));
+        for y in 0..36 {
+            for x in 48..96 {
+                screenshot.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+            }
+        }
+        let geometry: Geometry = "hd,2x1,0,0,96,36".parse().unwrap();
+        assert_eq!(geometry, Geometry::rectangle(TileKind::HD, 2, 1, 0.0, 0.0, 96.0, 36.0));
+        let tiles = sample_tiles(&screenshot, &geometry).unwrap();
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(*tiles[0].get_pixel(23, 17), Rgba([0, 0, 0, 255]));
+        assert_eq!(*tiles[1].get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+
+        assert!("hd,2x1,0,0,200,36".parse::<Geometry>().map(|geometry| sample_tiles(&screenshot, &geometry).is_err()).unwrap());
+        assert!("xd,2x1,0,0,96,36".parse::<Geometry>().is_err());
+        assert!("hd,2x1,0,0,96".parse::<Geometry>().is_err());
+    }
+}
@@ -1,24 +1,33 @@
 
+pub mod alpha_mode;
 pub mod grid;
 pub mod container;
+pub mod digit_style;
+pub mod file_format;
+pub mod icons;
+pub mod index;
+pub mod transform;
+pub mod ttf;
+pub mod typed;
 
 use std::{
-    io::Error as IOError,
+    io::{Error as IOError, Read, Seek},
     path::{Path, PathBuf},
 };
 
 use derive_more::{Deref,DerefMut, From};
 use getset::{Getters, CopyGetters};
-use strum::{EnumIter,IntoEnumIterator, Display};
+use strum::IntoEnumIterator;
 use image::{ImageBuffer, Rgba, GenericImageView, GenericImage};
 use thiserror::Error;
 
 use crate::{
     dimensions,
     image::{
-        read_image_file,
+        read_image_file_with_context,
         ReadError as ImageReadError,
-    }
+    },
+    io_context::IoContext,
 };
 
 use super::bin_file::BinFileReader;
@@ -26,8 +35,7 @@ use super::bin_file::BinFileReader;
 
 pub type Dimensions = dimensions::Dimensions<u32>;
 
-pub const SD_DIMENSIONS: Dimensions = Dimensions::new(36, 54);
-pub const HD_DIMENSIONS: Dimensions = Dimensions::new(24, 36);
+pub use crate::tile_core::{Kind, SD_DIMENSIONS, HD_DIMENSIONS};
 
 #[derive(Debug, Error, Getters)]
 #[getset(get = "pub")]
@@ -42,20 +50,29 @@ pub struct InvalidSizeError(pub u64);
 #[error("height does not match any tile kind: {0}")]
 pub struct InvalidHeightError(pub u32);
 
-#[derive(Debug, Copy, Clone, EnumIter, PartialEq, Eq, Display)]
-pub enum Kind {
-    SD,
-    HD
+#[derive(Debug, Error)]
+#[error("SD and HD sources are swapped: {sd_path} contains HD tiles and {hd_path} contains SD tiles, swap them or use the auto-swap option")]
+pub struct SwappedKindsError {
+    pub sd_path: PathBuf,
+    pub hd_path: PathBuf,
 }
 
-impl Kind {
-
-    pub const fn dimensions(&self) -> Dimensions {
-        match self {
-            Kind::SD => SD_DIMENSIONS,
-            Kind::HD => HD_DIMENSIONS,
-        }
+impl SwappedKindsError {
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(sd_path: P, hd_path: Q) -> Self {
+        Self { sd_path: sd_path.as_ref().to_path_buf(), hd_path: hd_path.as_ref().to_path_buf() }
     }
+}
+
+/// What to do when loading a SD/HD set from sources which have been given in the wrong order
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KindSwapPolicy {
+    /// fail with a `SwappedKindsError`
+    Reject,
+    /// swap the sources back
+    AutoSwap,
+}
+
+impl Kind {
 
     pub const fn set_dir_name(&self) -> &'static str {
         match self {
@@ -68,18 +85,8 @@ impl Kind {
         [base_dir.as_ref(), Path::new(self.set_dir_name())].iter().collect()
     }
 
-    pub const fn raw_rgba_size_bytes(&self) -> usize {
-        let Dimensions { width, height } = self.dimensions();
-        width as usize * height as usize * 4
-    }
-
     pub fn for_size_bytes(bytes: u64) -> Result<Self, InvalidSizeError> {
-        for kind in Self::iter() {
-            if bytes == kind.raw_rgba_size_bytes() as u64 {
-                return Ok(kind);
-            }
-        }
-        Err(InvalidSizeError(bytes))
+        Self::for_raw_rgba_size_bytes(bytes).ok_or(InvalidSizeError(bytes))
     }
 
     pub fn for_height(height: u32) -> Result<Self, InvalidHeightError> {
@@ -98,11 +105,7 @@ impl TryFrom<Dimensions> for Kind {
     type Error = InvalidDimensionsError;
 
     fn try_from(dimensions: Dimensions) -> Result<Self, Self::Error> {
-        match dimensions {
-            SD_DIMENSIONS => Ok(Self::SD),
-            HD_DIMENSIONS => Ok(Self::HD),
-            _ => Err(InvalidDimensionsError { dimensions })
-        }
+        Self::for_dimensions(dimensions).ok_or(InvalidDimensionsError { dimensions })
     }
 }
 
@@ -119,6 +122,8 @@ pub enum LoadError {
     ImageReadError(ImageReadError),
     #[error("invalid tile image size in file {file_path}: {dimensions}")]
     InvalidDimensionsError { file_path: PathBuf, dimensions: Dimensions },
+    #[error("size of raw tile file {file_path} does not match any tile kind: {size}B")]
+    InvalidRawSize { file_path: PathBuf, size: u64 },
 }
 
 impl LoadError {
@@ -149,7 +154,11 @@ impl Tile {
     }
 
     pub fn load_image_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
-        let image = read_image_file(&path)?;
+        Self::load_image_file_with_context(path, &IoContext::default())
+    }
+
+    pub fn load_image_file_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Self, LoadError> {
+        let image = read_image_file_with_context(&path, context)?;
         let kind = Kind::try_from(Dimensions::from(image.dimensions()))
             .map_err(|error| {
                 let InvalidDimensionsError { dimensions } = error;
@@ -158,7 +167,12 @@ impl Tile {
         Ok(Self { kind, image: image.into_rgba8() })
     }
 
-    pub fn read_from_bin_file(file: &mut BinFileReader) -> Result<Self, LoadError> {
+    /// A tile is empty when all its pixels are fully transparent
+    pub fn is_empty(&self) -> bool {
+        self.image.pixels().all(|pixel| pixel.0[3] == 0)
+    }
+
+    pub fn read_from_bin_file<R: Read + Seek>(file: &mut BinFileReader<R>) -> Result<Self, LoadError> {
         Ok(Self::try_from(file.read_tile_bytes()?).expect("did not read the right number of bytes"))
     }
 
@@ -185,10 +199,10 @@ impl TryFrom<Image> for Tile {
     }
 }
 
-impl TryFrom<&mut BinFileReader> for Tile {
+impl<R: Read + Seek> TryFrom<&mut BinFileReader<R>> for Tile {
     type Error = LoadError;
 
-    fn try_from(file: &mut BinFileReader) -> Result<Self, Self::Error> {
+    fn try_from(file: &mut BinFileReader<R>) -> Result<Self, Self::Error> {
         Self::read_from_bin_file(file)
     }
 }
@@ -211,7 +225,7 @@ mod tests {
     }
 
     fn test_tile_file_path(tile_kind: Kind) -> PathBuf {
-        test_file_path(&format!("{}_tile.png", tile_kind.to_string().to_lowercase()))
+        test_file_path(format!("{}_tile.png", tile_kind.to_string().to_lowercase()))
     }
 
     #[test]
@@ -247,7 +261,7 @@ mod tests {
         for kind in Kind::iter() {
             let bytes = vec![0; kind.raw_rgba_size_bytes()];
             let result = Tile::try_from(bytes);
-            assert!(matches!(result, Ok(_)))
+            assert!(result.is_ok())
         }
 
         let bytes = vec![1, 2, 3];
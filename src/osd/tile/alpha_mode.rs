@@ -0,0 +1,91 @@
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use image::Rgba;
+use thiserror::Error;
+
+use crate::background::parse_opaque_color;
+
+use super::Tile;
+
+
+/// Magenta, the color key used by most legacy analog OSD art
+pub const DEFAULT_COLOR_KEY: [u8; 3] = [0xFF, 0x00, 0xFF];
+
+#[derive(Debug, Error)]
+#[error("invalid alpha mode `{0}`: expected `alpha`, `color-key` or `color-key:<color>` with a color like `#FF00FF`")]
+pub struct InvalidAlphaModeError(String);
+
+/// How transparency is expressed in the imported images
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// the alpha channel of the images is used as is
+    #[default]
+    Alpha,
+    /// pixels of this color are transparent, for images without an alpha channel like legacy MAX7456 art
+    ColorKey([u8; 3]),
+}
+
+impl Display for AlphaMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlphaMode::Alpha => f.write_str("alpha"),
+            AlphaMode::ColorKey([red, green, blue]) => write!(f, "color-key:#{red:02X}{green:02X}{blue:02X}"),
+        }
+    }
+}
+
+impl FromStr for AlphaMode {
+    type Err = InvalidAlphaModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alpha" => Ok(Self::Alpha),
+            "color-key" => Ok(Self::ColorKey(DEFAULT_COLOR_KEY)),
+            _ => {
+                let color = s.strip_prefix("color-key:").and_then(parse_opaque_color).ok_or_else(|| InvalidAlphaModeError(s.to_owned()))?;
+                let [red, green, blue, _] = color.0;
+                Ok(Self::ColorKey([red, green, blue]))
+            },
+        }
+    }
+}
+
+impl Tile {
+
+    /// Makes the pixels matching the color key fully transparent, does nothing in `Alpha` mode
+    pub fn apply_alpha_mode(&mut self, alpha_mode: AlphaMode) {
+        if let AlphaMode::ColorKey(color_key) = alpha_mode {
+            for pixel in self.pixels_mut() {
+                if pixel.0[0..3] == color_key {
+                    *pixel = Rgba([0, 0, 0, 0]);
+                }
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::AlphaMode;
+
+    #[test]
+    fn color_key() {
+        assert_eq!("color-key".parse::<AlphaMode>().unwrap(), AlphaMode::ColorKey([0xFF, 0x00, 0xFF]));
+        assert_eq!("color-key:#00FF00".parse::<AlphaMode>().unwrap(), AlphaMode::ColorKey([0x00, 0xFF, 0x00]));
+        assert!("color-key:green".parse::<AlphaMode>().is_err());
+
+        let mut tile = Tile::new(TileKind::SD);
+        tile.put_pixel(0, 0, Rgba([0xFF, 0x00, 0xFF, 255]));
+        tile.put_pixel(1, 0, Rgba([0xFF, 0xFF, 0xFF, 255]));
+        tile.apply_alpha_mode(AlphaMode::ColorKey([0xFF, 0x00, 0xFF]));
+        assert_eq!(tile.get_pixel(0, 0).0[3], 0);
+        assert_eq!(tile.get_pixel(1, 0).0[3], 255);
+    }
+}
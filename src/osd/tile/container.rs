@@ -11,6 +11,18 @@ pub mod symbol_tiles_iter;
 pub mod load_symbols_from_dir;
 pub mod save_to_grid;
 pub mod save_to_avatar_file;
+pub mod repage;
+pub mod reindex_tile_dir;
+pub mod load_observer;
+pub mod patch;
+pub mod edit;
+pub mod region;
+pub mod png_sequence;
+pub mod transform_map;
+pub mod tile_order;
+pub mod to_grid_image;
+pub mod copy_png_tiles;
+pub mod set_dir_layout;
 
 use tap::Tap;
 
@@ -49,12 +61,14 @@ pub trait ToSymbols {
     fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, TileKindError>;
 }
 
+// tiles not covered by a spec, including the ones of a symbol truncated by the end of the collection, become single tile
+// symbols so that converting back to tiles gives the same tiles at the same indices
 impl ToSymbols for &[Tile] {
     fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, TileKindError> {
         let mut tile_index = 0;
         let mut symbols = vec![];
         while tile_index < self.len() {
-            let symbol = match specs.find_start_index(tile_index) {
+            let symbol = match specs.find_start_index(tile_index).filter(|sym_spec| sym_spec.end_tile_index() <= self.len()) {
                 Some(sym_spec) =>
                     Symbol::try_from(Vec::from(&self[sym_spec.tile_index_range()]))?
                         .tap(|_| tile_index += sym_spec.span()),
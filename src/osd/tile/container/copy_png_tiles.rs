@@ -0,0 +1,197 @@
+
+//! Conversions of PNG tile directories copying the bytes of the tile files
+//!
+//! Re-encoding the PNG files dominates the conversion time of large directories. When the tiles are not modified the
+//! files of the tiles, and of the symbols spanning a single tile, are copied as they are and only the headers of the
+//! source files are read to check the kind of the tiles. Symbols spanning several tiles are still decoded and composed.
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use image::ImageError;
+use thiserror::Error;
+
+use super::load_tiles_from_dir::LoadTilesFromDirError;
+use super::symbol::{spec::Specs as SymbolSpecs, Symbol};
+use super::uniq_tile_kind::TileKindError;
+
+use crate::create_path::{create_path, CreatePathError};
+use crate::dimensions::Dimensions;
+use crate::image::{WriteImageFile, WriteError as ImageWriteError};
+use crate::image_encode::ImageEncodeOptions;
+use crate::osd::tile::{
+    Kind as TileKind,
+    LoadError as TileLoadError,
+    Tile,
+    file_format::{FileFormat as TileFileFormat, SaveError as TileSaveError, SaveOptions as TileSaveOptions},
+};
+
+
+#[derive(Debug, From, Error)]
+pub enum CopyPngTilesError {
+    #[error("failed to read the header of tile file {file_path}: {error}")]
+    HeaderError { file_path: PathBuf, error: ImageError },
+    #[error("invalid tile image dimensions in file {file_path}: {dimensions}")]
+    InvalidDimensions { file_path: PathBuf, dimensions: Dimensions<u32> },
+    #[error(transparent)]
+    LoadTilesFromDirError(LoadTilesFromDirError),
+    #[error("failed to copy tile file {file_path}: {error}")]
+    CopyError { file_path: PathBuf, error: IOError },
+    #[error(transparent)]
+    CreatePathError(CreatePathError),
+    #[error(transparent)]
+    TileLoadError(TileLoadError),
+    #[error(transparent)]
+    TileSaveError(TileSaveError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+}
+
+/// The PNG files of a tile directory, by tile index
+#[derive(Debug)]
+pub struct PngTileFiles {
+    tile_kind: TileKind,
+    /// `None` for the missing tiles
+    files: Vec<Option<PathBuf>>,
+}
+
+fn same_file(path: &Path, other_path: &Path) -> bool {
+    matches!((path.canonicalize(), other_path.canonicalize()), (Ok(path), Ok(other_path)) if path == other_path)
+}
+
+impl PngTileFiles {
+
+    /// Finds the tile files of the directory up to `max_tiles`, with the same fallback to unpadded names as the loader
+    pub fn scan<P: AsRef<Path>>(dir: P, max_tiles: usize) -> Result<Self, CopyPngTilesError> {
+        let format = TileFileFormat::Png;
+        let mut tile_kind = None;
+        let mut files = vec![];
+        for index in 0..max_tiles {
+            let candidates = [dir.as_ref().join(format.tile_file_name(index)), dir.as_ref().join(format.unpadded_tile_file_name(index))];
+            let file_path = match candidates.into_iter().find(|path| path.is_file()) {
+                Some(file_path) => file_path,
+                None => {
+                    files.push(None);
+                    continue;
+                },
+            };
+            let dimensions = image::image_dimensions(&file_path)
+                .map_err(|error| CopyPngTilesError::HeaderError { file_path: file_path.clone(), error })?;
+            let file_tile_kind = TileKind::for_dimensions(Dimensions::from(dimensions))
+                .ok_or_else(|| CopyPngTilesError::InvalidDimensions { file_path: file_path.clone(), dimensions: Dimensions::from(dimensions) })?;
+            if *tile_kind.get_or_insert(file_tile_kind) != file_tile_kind {
+                return Err(LoadTilesFromDirError::kind_mismatch(&dir).into());
+            }
+            files.push(Some(file_path));
+        }
+        let tile_kind = tile_kind.ok_or_else(|| LoadTilesFromDirError::no_tile_found(&dir))?;
+        let tile_count = files.iter().rposition(Option::is_some).unwrap() + 1;
+        files.truncate(tile_count);
+        Ok(Self { tile_kind, files })
+    }
+
+    pub fn tile_kind(&self) -> TileKind {
+        self.tile_kind
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn copy_file(source: &Path, destination: &Path) -> Result<(), CopyPngTilesError> {
+        // normalizing a directory in place, copying a file over itself truncates it
+        if ! same_file(source, destination) {
+            fs_err::copy(source, destination).map_err(|error| CopyPngTilesError::CopyError { file_path: source.to_path_buf(), error })?;
+        }
+        Ok(())
+    }
+
+    fn load_tile(&self, index: usize) -> Result<Tile, CopyPngTilesError> {
+        match &self.files[index] {
+            Some(file_path) => Ok(Tile::load_image_file(file_path)?),
+            None => Ok(Tile::new(self.tile_kind)),
+        }
+    }
+
+    /// Writes a tile directory with padded file names, the missing tiles are written as empty tiles
+    pub fn copy_to_tile_dir<P: AsRef<Path>>(&self, dir: P, options: &TileSaveOptions) -> Result<(), CopyPngTilesError> {
+        create_path(&dir)?;
+        for (index, file_path) in self.files.iter().enumerate() {
+            let destination = dir.as_ref().join(TileFileFormat::Png.tile_file_name(index));
+            match file_path {
+                Some(file_path) => Self::copy_file(file_path, &destination)?,
+                None => Tile::new(self.tile_kind).save_file_with_options(destination, options)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a symbol directory with the symbols of the specs, the tiles not covered by the specs are single tile symbols
+    pub fn copy_to_symbol_dir<P: AsRef<Path>>(&self, dir: P, specs: &SymbolSpecs, encode_options: &ImageEncodeOptions) -> Result<(), CopyPngTilesError> {
+        create_path(&dir)?;
+        let mut tile_index = 0;
+        while tile_index < self.len() {
+            match specs.find_start_index(tile_index).filter(|sym_spec| sym_spec.span() > 1 && sym_spec.end_tile_index() <= self.len()) {
+                Some(sym_spec) => {
+                    let tiles = sym_spec.tile_index_range().map(|index| self.load_tile(index)).collect::<Result<Vec<_>, _>>()?;
+                    let file_name = format!("{tile_index:03}-{:03}.png", sym_spec.end_tile_index() - 1);
+                    Symbol::try_from(tiles)?.generate_image().write_image_file_with_options(dir.as_ref().join(file_name), encode_options)?;
+                    tile_index += sym_spec.span();
+                },
+                None => {
+                    let destination = dir.as_ref().join(format!("{tile_index:03}.png"));
+                    match &self.files[tile_index] {
+                        Some(file_path) => Self::copy_file(file_path, &destination)?,
+                        None => Symbol::from(Tile::new(self.tile_kind)).generate_image().write_image_file_with_options(destination, encode_options)?,
+                    }
+                    tile_index += 1;
+                },
+            }
+        }
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::image_encode::ImageEncodeOptions;
+    use crate::osd::tile::{Kind as TileKind, Tile, container::{load_symbols_from_dir::load_symbols_from_dir, save_tiles_to_dir::SaveTilesToDir}};
+    use crate::osd::tile::container::symbol::spec::Specs as SymbolSpecs;
+
+    use super::PngTileFiles;
+
+    #[test]
+    fn copy_to_symbol_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut tiles = vec![Tile::new(TileKind::HD); 6];
+        for (index, tile) in tiles.iter_mut().enumerate() {
+            tile.put_pixel(index as u32, 0, Rgba([255, 0, 0, 255]));
+        }
+        tiles.save_tiles_to_dir(temp_dir.child("tiles")).unwrap();
+        std::fs::remove_file(temp_dir.child("tiles").join("004.png")).unwrap();
+        let specs_path = temp_dir.child("specs.yaml");
+        std::fs::write(&specs_path, "version: 2\nsymbols:\n  arrow: '1:2'\n").unwrap();
+
+        let tile_files = PngTileFiles::scan(temp_dir.child("tiles"), 512).unwrap();
+        assert_eq!((tile_files.tile_kind(), tile_files.len()), (TileKind::HD, 6));
+        tile_files.copy_to_symbol_dir(temp_dir.child("symbols"), &SymbolSpecs::load_file(&specs_path).unwrap(), &ImageEncodeOptions::default()).unwrap();
+        assert_eq!(std::fs::read(temp_dir.child("symbols").join("005.png")).unwrap(), std::fs::read(temp_dir.child("tiles").join("005.png")).unwrap());
+        assert!(temp_dir.child("symbols").join("001-002.png").is_file());
+
+        let symbol_tiles: Vec<Tile> = load_symbols_from_dir(temp_dir.child("symbols"), 512).unwrap().into_iter().flat_map(|symbol| symbol.into_tiles()).collect();
+        assert_eq!(symbol_tiles.len(), 6);
+        assert!(symbol_tiles[4].is_empty());
+        assert_eq!(symbol_tiles[2].as_raw(), tiles[2].as_raw());
+    }
+}
@@ -0,0 +1,132 @@
+
+//! In place edits of the tiles of a collection
+//!
+//! Tiles are replaced by index from the tiles of PNG images in the format of the symbol images: a row of tiles of a single
+//! kind, a single tile image replacing a single tile. The collection grows with empty tiles when tiles are put past its
+//! end, up to the last tile index of the font pages.
+
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::osd::tile::{Kind as TileKind, Tile, index::TILE_COUNT};
+use super::symbol::{LoadError as SymbolLoadError, Symbol};
+use super::uniq_tile_kind::UniqTileKind;
+
+
+#[derive(Debug, Error)]
+pub enum EditError {
+    #[error("cannot put {replacement} tiles in a collection of {collection} tiles")]
+    TileKindMismatch { replacement: TileKind, collection: TileKind },
+    #[error("replacing tiles {start_index}-{end_index} goes past the last tile index {}", TILE_COUNT - 1)]
+    OutOfRange { start_index: usize, end_index: usize },
+    #[error("{file_path} holds {span} tiles, {replaced} tiles are replaced")]
+    SpanMismatch { file_path: PathBuf, span: usize, replaced: usize },
+    #[error("failed to load replacement tiles from {file_path}: {error}")]
+    LoadError { file_path: PathBuf, error: SymbolLoadError },
+}
+
+pub trait EditTiles {
+    /// Replaces the tiles from `start_index` with `tiles`, the tiles have to be of the kind of the collection
+    fn replace_tiles(&mut self, start_index: usize, tiles: &[Tile]) -> Result<(), EditError>;
+}
+
+impl EditTiles for Vec<Tile> {
+    fn replace_tiles(&mut self, start_index: usize, tiles: &[Tile]) -> Result<(), EditError> {
+        let end_index = start_index + tiles.len();
+        if end_index > TILE_COUNT {
+            return Err(EditError::OutOfRange { start_index, end_index: end_index - 1 });
+        }
+        let replacement_kind = match tiles.tile_kind() {
+            Ok(tile_kind) => tile_kind,
+            Err(_) => return Ok(()),
+        };
+        if let Ok(collection_kind) = self.tile_kind() {
+            if collection_kind != replacement_kind {
+                return Err(EditError::TileKindMismatch { replacement: replacement_kind, collection: collection_kind });
+            }
+        }
+        if self.len() < end_index {
+            self.resize(end_index, Tile::new(replacement_kind));
+        }
+        self[start_index..end_index].clone_from_slice(tiles);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidReplacementError {
+    #[error("invalid tile replacement `{0}`, expected `<tile index>[-<last tile index>]:<PNG file>`")]
+    Syntax(String),
+    #[error("invalid tile replacement `{0}`, the last tile index is before the first one")]
+    ReversedRange(String),
+}
+
+/// Tiles of a collection to replace with the tiles of an image, written `<tile index>[-<last tile index>]:<PNG file>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileReplacement {
+    pub tile_index_range: Range<usize>,
+    pub file_path: PathBuf,
+}
+
+impl TileReplacement {
+
+    pub fn apply(&self, tiles: &mut Vec<Tile>) -> Result<(), EditError> {
+        let symbol = Symbol::load_image_file(&self.file_path)
+            .map_err(|error| EditError::LoadError { file_path: self.file_path.clone(), error })?;
+        if symbol.span() != self.tile_index_range.len() {
+            return Err(EditError::SpanMismatch { file_path: self.file_path.clone(), span: symbol.span(), replaced: self.tile_index_range.len() });
+        }
+        tiles.replace_tiles(self.tile_index_range.start, symbol.tiles())
+    }
+
+}
+
+impl FromStr for TileReplacement {
+    type Err = InvalidReplacementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let syntax_error = || InvalidReplacementError::Syntax(s.to_owned());
+        let (indices, file_path) = s.split_once(':').filter(|(_, file_path)| ! file_path.is_empty()).ok_or_else(syntax_error)?;
+        let (first, last) = indices.split_once('-').unwrap_or((indices, indices));
+        let first: usize = first.parse().map_err(|_| syntax_error())?;
+        let last: usize = last.parse().map_err(|_| syntax_error())?;
+        if last < first {
+            return Err(InvalidReplacementError::ReversedRange(s.to_owned()));
+        }
+        Ok(Self { tile_index_range: first..last + 1, file_path: PathBuf::from(file_path) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile, container::symbol::Symbol};
+
+    use super::{EditError, EditTiles, InvalidReplacementError, TileReplacement};
+
+    #[test]
+    fn replace_tiles() {
+        assert_eq!("100-102:big_symbol.png".parse::<TileReplacement>().unwrap().tile_index_range, 100..103);
+        assert_eq!("42:C:\\logo.png".parse::<TileReplacement>().unwrap().file_path.to_string_lossy(), "C:\\logo.png");
+        assert!(matches!("5-3:x.png".parse::<TileReplacement>(), Err(InvalidReplacementError::ReversedRange(_))));
+        assert!(matches!("42:".parse::<TileReplacement>(), Err(InvalidReplacementError::Syntax(_))));
+
+        let mut replacement = Tile::new(TileKind::SD);
+        replacement.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("symbol.png");
+        Symbol::try_from(vec![replacement.clone(); 2]).unwrap().save_image_file(&path).unwrap();
+
+        let mut tiles = vec![Tile::new(TileKind::SD); 4];
+        TileReplacement { tile_index_range: 3..5, file_path: path.clone() }.apply(&mut tiles).unwrap();
+        assert_eq!(tiles.len(), 5);
+        assert!(tiles[2].is_empty() && ! tiles[3].is_empty() && ! tiles[4].is_empty());
+        assert!(matches!(TileReplacement { tile_index_range: 0..1, file_path: path }.apply(&mut tiles), Err(EditError::SpanMismatch { span: 2, .. })));
+        assert!(matches!(vec![Tile::new(TileKind::HD)].replace_tiles(0, &tiles[3..4]), Err(EditError::TileKindMismatch { .. })));
+    }
+}
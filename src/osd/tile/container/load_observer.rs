@@ -0,0 +1,21 @@
+
+/// Hooks called by the directory loaders so that frontends can show the progress of long scans and cancel them
+///
+/// All the methods do nothing by default.
+pub trait LoadObserver {
+    /// Called after each index has been looked at, `index` goes from 0 to `max_items` - 1
+    fn on_item(&mut self, _index: usize, _max_items: usize) {}
+
+    /// Called once the scan is complete with the number of items found
+    fn on_done(&mut self, _loaded_count: usize) {}
+
+    /// Checked before each index, the loader stops with a `Cancelled` error as soon as it returns true
+    fn should_cancel(&self) -> bool {
+        false
+    }
+}
+
+/// Observer ignoring every event, used by the loaders taking no observer
+pub struct NoopObserver;
+
+impl LoadObserver for NoopObserver {}
\ No newline at end of file
@@ -8,7 +8,10 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use thiserror::Error;
 
+use crate::diagnostics::DetectionEvidence;
+use crate::io_context::IoContext;
 use crate::osd::tile::container::symbol::{LoadError as SymbolLoadError, Symbol};
+use super::load_observer::{LoadObserver, NoopObserver};
 
 
 struct DirFilesIterator(ReadDir);
@@ -51,7 +54,9 @@ pub enum LoadSymbolsFromDirError {
     #[error("no symbol found in directory: {0}")]
     NoSymbolFound(PathBuf),
     #[error("directory should contain a single kind of tile: {0}")]
-    KindMismatch(PathBuf)
+    KindMismatch(PathBuf),
+    #[error("loading symbols from {0} has been cancelled")]
+    Cancelled(PathBuf),
 }
 
 impl LoadSymbolsFromDirError {
@@ -94,12 +99,13 @@ impl SymbolDirFileType {
     }
 }
 
-fn identify_file_name<P: AsRef<Path>>(path: P) -> Option<SymbolDirFileType> {
+fn identify_file_name<P: AsRef<Path>>(path: P, prefix: &str) -> Option<SymbolDirFileType> {
     lazy_static! {
         static ref FILE_NAME_RE: Regex = Regex::new(r"\A(?P<start_index>\d{3})(?:-(?P<end_index>\d{3}))?\.").unwrap();
     }
 
-    if let Some(captures) = FILE_NAME_RE.captures(path.as_ref().file_name().unwrap().to_string_lossy().to_string().as_str()) {
+    let file_name = path.as_ref().file_name().unwrap().to_string_lossy().to_string();
+    if let Some(captures) = file_name.strip_prefix(prefix).and_then(|file_name| FILE_NAME_RE.captures(file_name)) {
         let start_index = captures.name("start_index").unwrap().as_str().parse().expect("failed to parse start index");
         match captures.name("end_index") {
             Some(end_index) => {
@@ -113,14 +119,34 @@ fn identify_file_name<P: AsRef<Path>>(path: P) -> Option<SymbolDirFileType> {
     }
 }
 
-pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+/// Loads the symbols of a symbol directory starting before tile index `max_tiles`, the missing tiles are loaded as empty
+/// single tile symbols so that each symbol keeps the tile index of its file name
+pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_tiles: usize) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+    load_symbols_from_dir_with_observer(dir_path, max_tiles, &mut NoopObserver)
+}
+
+pub fn load_symbols_from_dir_with_context<P: AsRef<Path>>(dir_path: P, max_tiles: usize, context: &IoContext) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+    load_prefixed_symbols_from_dir(dir_path, "", max_tiles, &mut NoopObserver, context)
+}
+
+/// Loads the symbols of the files of a directory whose names start with `prefix` followed by the tile indices
+pub fn load_symbols_from_dir_with_prefix<P: AsRef<Path>>(dir_path: P, prefix: &str, max_tiles: usize) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+    load_prefixed_symbols_from_dir(dir_path, prefix, max_tiles, &mut NoopObserver, &IoContext::default())
+}
+
+pub fn load_symbols_from_dir_with_observer<P: AsRef<Path>>(dir_path: P, max_tiles: usize, observer: &mut dyn LoadObserver) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+    load_prefixed_symbols_from_dir(dir_path, "", max_tiles, observer, &IoContext::default())
+}
+
+fn load_prefixed_symbols_from_dir<P: AsRef<Path>>(dir_path: P, prefix: &str, max_tiles: usize, observer: &mut dyn LoadObserver, context: &IoContext)
+        -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
 
     let mut symbol_files = BTreeMap::new();
     let dir_files_iter = dir_files_iter(&dir_path).map_err(|error| LoadSymbolsFromDirError::dir_list_files(&dir_path, error))?;
     for file_path in dir_files_iter {
         let file_path = file_path.map_err(|error| LoadSymbolsFromDirError::dir_list_files(&dir_path, error))?;
 
-        if let Some(file_type) = identify_file_name(&file_path) {
+        if let Some(file_type) = identify_file_name(&file_path, prefix) {
             use std::collections::btree_map;
             match symbol_files.entry(file_type.start_index()) {
                 btree_map::Entry::Vacant(entry) => { entry.insert((file_path, file_type)); },
@@ -136,7 +162,10 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
     let mut tile_kind = None;
     let mut tile_index = 0;
     let mut previous_symbol_file_path: Option<&PathBuf> = None;
-    for _symbol_index in 0..max_symbols {
+    while tile_index < max_tiles {
+        if observer.should_cancel() {
+            return Err(LoadSymbolsFromDirError::Cancelled(dir_path.as_ref().to_path_buf()));
+        }
 
         let symbol = match symbol_files.get(&tile_index) {
             Some((file_path, file_type)) => {
@@ -147,7 +176,7 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
 
                 previous_symbol_file_path = Some(file_path);
 
-                match Symbol::load_image_file(file_path) {
+                match Symbol::load_image_file_with_context(file_path, context) {
                     Ok(loaded_symbol) => {
 
                         if loaded_symbol.span() != file_type.span() {
@@ -165,6 +194,8 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
                                     _ => return Err(error.into()),
                                 },
                                 DecodeError {..} => return Err(error.into()),
+                                // reported as the empty or truncated file it is rather than as a decoding failure
+                                DegenerateFile(_) => return Err(error.into()),
                             }
                         },
                         _ => return Err(error.into())
@@ -179,14 +210,15 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
 
             // first loaded tile: record the kind of tile
             (Some(symbol), None) => {
-                log::info!("detected {} kind of tiles in {}", symbol.tile_kind(), dir_path.as_ref().to_string_lossy());
+                context.emit_detection(&dir_path, symbol.tile_kind(), DetectionEvidence::ImageDimensions {
+                    file_path: previous_symbol_file_path.unwrap().clone(),
+                    dimensions: symbol.image_dimensions()
+                });
                 tile_kind = Some(symbol.tile_kind());
             },
 
             // we have already loaded a tile before, check that the new tile kind is matching what had recorded
-            (Some(symbol), Some(tile_kind)) => if symbol.tile_kind() != *tile_kind {
-                return Err(LoadSymbolsFromDirError::kind_mismatch(&dir_path))
-            },
+            (Some(symbol), Some(tile_kind)) if symbol.tile_kind() != *tile_kind => return Err(LoadSymbolsFromDirError::kind_mismatch(&dir_path)),
 
             _ => {}
 
@@ -199,9 +231,10 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
         }
 
         symbols.push(symbol);
+        observer.on_item(tile_index.min(max_tiles) - 1, max_tiles);
     }
 
-    let symbols = match tile_kind {
+    let symbols: Vec<Symbol> = match tile_kind {
         Some(tile_kind) => {
             let last_some_index = symbols.iter().rposition(Option::is_some).unwrap();
             symbols[0..=last_some_index].iter().map(|symbol| symbol.clone().unwrap_or_else(|| Symbol::new(tile_kind))).collect()
@@ -209,5 +242,6 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
         None => return Err(LoadSymbolsFromDirError::no_symbol_found(&dir_path)),
     };
 
+    observer.on_done(symbols.len());
     Ok(symbols)
 }
\ No newline at end of file
@@ -1,10 +1,15 @@
 
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use thiserror::Error;
 
-use crate::osd::tile::{LoadError as TileLoadError, Tile};
-use crate::image::ReadError as ImageReadError;
+use crate::osd::tile::{Kind as TileKind, LoadError as TileLoadError, Tile, file_format::FileFormat as TileFileFormat};
+use super::load_observer::{LoadObserver, NoopObserver};
+use crate::diagnostics::DetectionEvidence;
+use crate::io_context::IoContext;
 
 
 #[derive(Debug, Error)]
@@ -14,7 +19,9 @@ pub enum LoadTilesFromDirError {
     #[error("no tile found in directory: {0}")]
     NoTileFound(PathBuf),
     #[error("directory should contain a single kind of tile: {0}")]
-    KindMismatch(PathBuf)
+    KindMismatch(PathBuf),
+    #[error("loading tiles from {0} has been cancelled")]
+    Cancelled(PathBuf),
 }
 
 impl LoadTilesFromDirError {
@@ -33,45 +40,120 @@ impl From<TileLoadError> for LoadTilesFromDirError {
     }
 }
 
+/// Tiles of one kind found in a directory containing both kinds of tiles
+#[derive(Debug)]
+pub struct KindPartition {
+    pub tile_kind: TileKind,
+    /// tiles at the indices of their files, the indices of the tiles of the other kind are blank tiles
+    pub tiles: Vec<Tile>,
+    /// indices of the tile files of this kind
+    pub indices: Vec<usize>,
+}
+
+// the file names are prefixed in set directories holding both kinds of tiles, e.g. sd_011.png
+fn tile_file_path<P: AsRef<Path>>(dir: P, prefix: &str, index: usize, format: TileFileFormat) -> PathBuf {
+    let prefixed = |file_name: PathBuf| dir.as_ref().join(format!("{prefix}{}", file_name.to_string_lossy()));
+    let tile_path = prefixed(format.tile_file_name(index));
+    // fall back to the unpadded file name used by some third-party tools
+    if ! tile_path.exists() {
+        let unpadded_tile_path = prefixed(format.unpadded_tile_file_name(index));
+        if unpadded_tile_path.exists() {
+            return unpadded_tile_path;
+        }
+    }
+    tile_path
+}
+
+fn load_tile_file(tile_path: &Path, format: TileFileFormat, context: &IoContext) -> Result<Option<Tile>, LoadTilesFromDirError> {
+    match Tile::load_file_with_context(tile_path, format, context) {
+        Ok(loaded_tile) => Ok(Some(loaded_tile)),
+        Err(error) if error.because_file_is_missing() => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// number of tile files read at once, the observer can only cancel the loading between batches
+const LOAD_BATCH_SIZE: usize = 64;
+
+fn load_indexed_tile_file(dir: &Path, prefix: &str, index: usize, format: TileFileFormat, context: &IoContext)
+        -> (PathBuf, Result<Option<Tile>, LoadTilesFromDirError>) {
+    let tile_path = tile_file_path(dir, prefix, index, format);
+    let tile = load_tile_file(&tile_path, format, context);
+    (tile_path, tile)
+}
+
+#[cfg(feature = "rayon")]
+fn load_tile_files(dir: &Path, prefix: &str, indices: Range<usize>, format: TileFileFormat, context: &IoContext)
+        -> Vec<(PathBuf, Result<Option<Tile>, LoadTilesFromDirError>)> {
+    indices.into_par_iter().map(|index| load_indexed_tile_file(dir, prefix, index, format, context)).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn load_tile_files(dir: &Path, prefix: &str, indices: Range<usize>, format: TileFileFormat, context: &IoContext)
+        -> Vec<(PathBuf, Result<Option<Tile>, LoadTilesFromDirError>)> {
+    indices.map(|index| load_indexed_tile_file(dir, prefix, index, format, context)).collect()
+}
+
 pub fn load_tiles_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    load_tiles_from_dir_with_format(path, max_tiles, TileFileFormat::Png)
+}
+
+pub fn load_tiles_from_dir_with_format<P: AsRef<Path>>(path: P, max_tiles: usize, format: TileFileFormat) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    load_tiles_from_dir_with_observer(path, max_tiles, format, &mut NoopObserver)
+}
+
+pub fn load_tiles_from_dir_with_context<P: AsRef<Path>>(path: P, max_tiles: usize, format: TileFileFormat, context: &IoContext) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    load_prefixed_tiles_from_dir(path, "", max_tiles, format, &mut NoopObserver, context)
+}
+
+/// Loads the tiles of the files of a directory whose names start with `prefix` followed by the tile index
+pub fn load_tiles_from_dir_with_prefix<P: AsRef<Path>>(path: P, prefix: &str, max_tiles: usize, format: TileFileFormat) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    load_prefixed_tiles_from_dir(path, prefix, max_tiles, format, &mut NoopObserver, &IoContext::default())
+}
+
+pub fn load_tiles_from_dir_with_observer<P: AsRef<Path>>(path: P, max_tiles: usize, format: TileFileFormat, observer: &mut dyn LoadObserver) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    load_prefixed_tiles_from_dir(path, "", max_tiles, format, observer, &IoContext::default())
+}
+
+fn load_prefixed_tiles_from_dir<P: AsRef<Path>>(path: P, prefix: &str, max_tiles: usize, format: TileFileFormat, observer: &mut dyn LoadObserver,
+        context: &IoContext) -> Result<Vec<Tile>, LoadTilesFromDirError> {
     let mut tiles = vec![];
     let mut tile_kind = None;
 
-    for index in 0..max_tiles {
-        let tile_path: PathBuf = [path.as_ref(), Path::new(&format!("{:03}.png", index))].iter().collect();
-        let tile = match Tile::load_image_file(tile_path) {
-            Ok(loaded_tile) => Some(loaded_tile),
-            Err(error) => match &error {
-                TileLoadError::ImageReadError(ImageReadError::OpenError { file_path: _, error: open_error }) =>
-                    match open_error.kind() {
-                        std::io::ErrorKind::NotFound => None,
-                        _ => return Err(error.into()),
-                    },
-                _ => return Err(error.into())
-            },
-        };
-
-        match (&tile, &tile_kind) {
-
-            // first loaded tile: record the kind of tile
-            (Some(tile), None) => {
-                log::info!("detected {} kind of tiles in {}", tile.kind(), path.as_ref().to_string_lossy());
-                tile_kind = Some(tile.kind());
-            },
-
-            // we have already loaded a tile before, check that the new tile kind is matching what had recorded
-            (Some(tile), Some(tile_kind)) => if tile.kind() != *tile_kind {
-                return Err(LoadTilesFromDirError::kind_mismatch(&path))
-            },
-
-            _ => {}
-
+    // the files of a batch are loaded in parallel then checked in index order so that the error reported is the one of
+    // the lowest index like when loading them one after the other
+    for batch_start in (0..max_tiles).step_by(LOAD_BATCH_SIZE) {
+        if observer.should_cancel() {
+            return Err(LoadTilesFromDirError::Cancelled(path.as_ref().to_path_buf()));
         }
+        let indices = batch_start..(batch_start + LOAD_BATCH_SIZE).min(max_tiles);
+        for (index, (tile_path, tile)) in indices.clone().zip(load_tile_files(path.as_ref(), prefix, indices, format, context)) {
+            let tile = tile?;
+
+            match (&tile, &tile_kind) {
+
+                // first loaded tile: record the kind of tile
+                (Some(tile), None) => {
+                    context.emit_detection(&path, tile.kind(), DetectionEvidence::ImageDimensions {
+                        file_path: tile_path,
+                        dimensions: tile.kind().dimensions()
+                    });
+                    tile_kind = Some(tile.kind());
+                },
+
+                // we have already loaded a tile before, check that the new tile kind is matching what had recorded
+                (Some(tile), Some(tile_kind)) if tile.kind() != *tile_kind => return Err(LoadTilesFromDirError::kind_mismatch(&path)),
 
-        tiles.push(tile);
+                _ => {}
+
+            }
+
+            tiles.push(tile);
+            observer.on_item(index, max_tiles);
+        }
     }
 
-    let tiles = match tile_kind {
+    let tiles: Vec<Tile> = match tile_kind {
         Some(tile_kind) => {
             let last_some_index = tiles.iter().rposition(Option::is_some).unwrap();
             tiles[0..=last_some_index].iter().map(|tile| tile.clone().unwrap_or_else(|| Tile::new(tile_kind))).collect()
@@ -79,5 +161,61 @@ pub fn load_tiles_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize) -> Result<
         None => return Err(LoadTilesFromDirError::no_tile_found(&path)),
     };
 
+    observer.on_done(tiles.len());
     Ok(tiles)
 }
+
+/// Loads the tiles of a directory which may contain both kinds of tiles, partitioned by kind in SD then HD order
+///
+/// Only the kinds which have been found are returned, a directory with a single kind of tiles gives a single partition.
+pub fn load_tiles_from_dir_split_kinds<P: AsRef<Path>>(path: P, max_tiles: usize, format: TileFileFormat) -> Result<Vec<KindPartition>, LoadTilesFromDirError> {
+    load_tiles_from_dir_split_kinds_with_context(path, max_tiles, format, &IoContext::default())
+}
+
+pub fn load_tiles_from_dir_split_kinds_with_context<P: AsRef<Path>>(path: P, max_tiles: usize, format: TileFileFormat, context: &IoContext)
+        -> Result<Vec<KindPartition>, LoadTilesFromDirError> {
+    let mut indexed_tiles = vec![];
+    for index in 0..max_tiles {
+        if let Some(tile) = load_tile_file(&tile_file_path(&path, "", index, format), format, context)? {
+            indexed_tiles.push((index, tile));
+        }
+    }
+
+    let partitions: Vec<KindPartition> = [TileKind::SD, TileKind::HD].into_iter().filter_map(|tile_kind| {
+        let indices: Vec<usize> = indexed_tiles.iter().filter(|(_, tile)| tile.kind() == tile_kind).map(|(index, _)| *index).collect();
+        let tile_count = indices.last()? + 1;
+        let mut tiles = vec![Tile::new(tile_kind); tile_count];
+        for (index, tile) in indexed_tiles.iter().filter(|(_, tile)| tile.kind() == tile_kind) {
+            tiles[*index] = tile.clone();
+        }
+        Some(KindPartition { tile_kind, tiles, indices })
+    }).collect();
+
+    if partitions.is_empty() {
+        return Err(LoadTilesFromDirError::no_tile_found(&path));
+    }
+    Ok(partitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{file_format::FileFormat as TileFileFormat, Kind as TileKind, Tile};
+
+    use super::{load_tiles_from_dir_split_kinds, load_tiles_from_dir_with_format, LoadTilesFromDirError};
+
+    #[test]
+    fn split_kinds() {
+        let temp_dir = TempDir::new().unwrap();
+        for (index, tile_kind) in [(0, TileKind::SD), (1, TileKind::HD), (3, TileKind::SD)] {
+            Tile::new(tile_kind).save_file(temp_dir.path().join(TileFileFormat::Png.tile_file_name(index)), TileFileFormat::Png).unwrap();
+        }
+        assert!(matches!(load_tiles_from_dir_with_format(temp_dir.path(), 8, TileFileFormat::Png), Err(LoadTilesFromDirError::KindMismatch(_))));
+
+        let partitions = load_tiles_from_dir_split_kinds(temp_dir.path(), 8, TileFileFormat::Png).unwrap();
+        assert_eq!(partitions.len(), 2);
+        assert_eq!((partitions[0].tile_kind, partitions[0].indices.clone(), partitions[0].tiles.len()), (TileKind::SD, vec![0, 3], 4));
+        assert_eq!((partitions[1].tile_kind, partitions[1].indices.clone(), partitions[1].tiles.len()), (TileKind::HD, vec![1], 2));
+    }
+}
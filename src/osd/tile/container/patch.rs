@@ -0,0 +1,173 @@
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::image_encode::ImageEncodeOptions;
+use crate::osd::tile::{
+    Tile,
+    Kind as TileKind,
+    grid::{self, Grid as TileGrid, LoadError as GridLoadError},
+};
+use super::into_tile_grid::IntoTileGrid;
+use super::uniq_tile_kind::{TileKindError, UniqTileKind};
+
+
+const INDICES_METADATA_KEY: &str = "hd_fpv_osd_font_tool:patch_indices";
+const BASE_SHA256_METADATA_KEY: &str = "hd_fpv_osd_font_tool:patch_base_sha256";
+
+#[derive(Debug, Error)]
+pub enum SavePatchError {
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+    #[error("failed to write patch file {file_path}: {error}")]
+    WriteError { file_path: PathBuf, error: png::EncodingError },
+}
+
+#[derive(Debug, Error)]
+pub enum LoadPatchError {
+    #[error(transparent)]
+    GridLoadError(#[from] GridLoadError),
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+    #[error("{0} is not a patch file: it has no patch metadata")]
+    MissingMetadata(PathBuf),
+    #[error("invalid patch metadata in {0}")]
+    InvalidMetadata(PathBuf),
+}
+
+#[derive(Debug, Error)]
+#[error("cannot apply a patch of {patch} tiles to a collection of {collection} tiles")]
+pub struct PatchTileKindMismatchError {
+    pub patch: TileKind,
+    pub collection: TileKind,
+}
+
+fn tiles_sha256(tiles: &[Tile]) -> String {
+    let mut hasher = Sha256::new();
+    for tile in tiles {
+        hasher.update(tile.as_raw());
+    }
+    hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Tiles which differ from a base collection along with their indices
+///
+/// Patch files are grid images of the changed tiles with the indices and the checksum of the base stored in the image
+/// metadata, so that small customizations can be distributed and applied to new versions of the base collection.
+#[derive(Debug, Clone)]
+pub struct Patch {
+    tile_kind: TileKind,
+    tiles: Vec<(usize, Tile)>,
+    base_sha256: String,
+}
+
+/// Result of applying a patch
+pub struct Patched {
+    pub tiles: Vec<Tile>,
+    /// whether the collection the patch has been applied to is the one the patch has been made from
+    pub base_matches: bool,
+}
+
+impl Patch {
+
+    /// Tiles of `modified` which differ from `base`, tiles missing from `modified` are recorded as empty tiles
+    pub fn diff(base: &[Tile], modified: &[Tile]) -> Result<Self, TileKindError> {
+        let tile_kind = [base, modified].concat().tile_kind()?;
+        let empty_tile = Tile::new(tile_kind);
+        let tiles = (0..base.len().max(modified.len()))
+            .filter_map(|index| {
+                let modified_tile = modified.get(index).unwrap_or(&empty_tile);
+                let base_tile = base.get(index).unwrap_or(&empty_tile);
+                (modified_tile.as_raw() != base_tile.as_raw()).then(|| (index, modified_tile.clone()))
+            })
+            .collect();
+        Ok(Self { tile_kind, tiles, base_sha256: tiles_sha256(base) })
+    }
+
+    pub fn tile_kind(&self) -> TileKind {
+        self.tile_kind
+    }
+
+    pub fn tiles(&self) -> &[(usize, Tile)] {
+        &self.tiles
+    }
+
+    /// Replaces the tiles of the collection by the ones of the patch, the collection is extended with empty tiles if
+    /// the patch contains tiles beyond its end
+    pub fn apply(&self, base: &[Tile]) -> Result<Patched, PatchTileKindMismatchError> {
+        if let Some(base_tile) = base.first() {
+            if base_tile.kind() != self.tile_kind {
+                return Err(PatchTileKindMismatchError { patch: self.tile_kind, collection: base_tile.kind() });
+            }
+        }
+        let mut tiles = base.to_vec();
+        for (index, tile) in &self.tiles {
+            if *index >= tiles.len() {
+                tiles.resize(index + 1, Tile::new(self.tile_kind));
+            }
+            tiles[*index] = tile.clone();
+        }
+        Ok(Patched { tiles, base_matches: tiles_sha256(base) == self.base_sha256 })
+    }
+
+    pub fn save_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SavePatchError> {
+        let mut tiles: Vec<Tile> = self.tiles.iter().map(|(_, tile)| tile.clone()).collect();
+        // an empty patch is saved as a single empty tile for the image to carry the tile kind
+        if tiles.is_empty() {
+            tiles.push(Tile::new(self.tile_kind));
+        }
+        let image = tiles.as_slice().into_tile_grid().generate_image()?;
+        let indices = self.tiles.iter().map(|(index, _)| index.to_string()).collect::<Vec<_>>().join(",");
+        let text_chunks = [(INDICES_METADATA_KEY, indices), (BASE_SHA256_METADATA_KEY, self.base_sha256.clone())];
+        grid::write_png_with_text_chunks(&path, &image, &text_chunks, &ImageEncodeOptions::default())
+            .map_err(|error| SavePatchError::WriteError { file_path: path.as_ref().to_path_buf(), error })
+    }
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadPatchError> {
+        let file_path = path.as_ref().to_path_buf();
+        let indices = grid::read_png_text_chunk(&path, INDICES_METADATA_KEY).ok_or_else(|| LoadPatchError::MissingMetadata(file_path.clone()))?;
+        let base_sha256 = grid::read_png_text_chunk(&path, BASE_SHA256_METADATA_KEY).ok_or_else(|| LoadPatchError::MissingMetadata(file_path.clone()))?;
+        let indices = indices.split(',').filter(|index| ! index.is_empty())
+            .map(|index| index.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| LoadPatchError::InvalidMetadata(file_path.clone()))?;
+        let grid_tiles = TileGrid::load_from_image(&path)?;
+        if indices.len() > grid_tiles.len() {
+            return Err(LoadPatchError::InvalidMetadata(file_path));
+        }
+        let tile_kind = grid_tiles.tile_kind()?;
+        Ok(Self { tile_kind, tiles: indices.into_iter().zip(grid_tiles.iter().cloned()).collect(), base_sha256 })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::Patch;
+
+    #[test]
+    fn round_trip() {
+        let base = vec![Tile::new(TileKind::SD); 4];
+        let mut modified = base.clone();
+        modified[2].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+
+        let patch = Patch::diff(&base, &modified).unwrap();
+        assert_eq!(patch.tiles().len(), 1);
+        assert_eq!(patch.tiles()[0].0, 2);
+
+        let temp_dir = TempDir::new().unwrap();
+        let patch_path = temp_dir.child("patch.png");
+        patch.save_file(&patch_path).unwrap();
+        let patched = Patch::load_file(&patch_path).unwrap().apply(&base).unwrap();
+        assert!(patched.base_matches);
+        assert!(patched.tiles.iter().zip(&modified).all(|(patched, modified)| patched.as_raw() == modified.as_raw()));
+        assert!(! Patch::load_file(&patch_path).unwrap().apply(&modified).unwrap().base_matches);
+    }
+}
@@ -0,0 +1,122 @@
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use fs_err::File;
+use image::imageops::{self, FilterType};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::symbol::Symbol;
+
+use crate::create_path::{create_path, CreatePathError};
+use crate::image::{WriteImageFile, WriteError as ImageWriteError};
+
+
+pub const METADATA_FILE_NAME: &str = "sequence.json";
+
+#[derive(Debug, From, Error)]
+pub enum SavePngSequenceError {
+    #[error(transparent)]
+    CreatePathError(CreatePathError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+    #[error("failed to write sequence metadata file: {0}")]
+    MetadataWriteError(IOError),
+    #[error("failed to serialize sequence metadata: {0}")]
+    MetadataSerializeError(serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SequenceFrame {
+    pub frame: usize,
+    pub tile_index: usize,
+    pub span: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Content of the metadata file describing which symbol each frame of the sequence is
+#[derive(Debug, Clone, Serialize)]
+pub struct SequenceMetadata {
+    /// printf style pattern of the frame file names as given to `ffmpeg -i`
+    pub file_pattern: String,
+    /// scale factors of the frames, each scale other than 1 is stored in a `<scale>x` sub-directory
+    pub scales: Vec<u32>,
+    pub frames: Vec<SequenceFrame>,
+}
+
+pub fn frame_file_name(frame: usize) -> PathBuf {
+    PathBuf::from(format!("symbol_{frame:04}.png"))
+}
+
+pub fn scale_dir_path<P: AsRef<Path>>(dir: P, scale: u32) -> PathBuf {
+    match scale {
+        1 => dir.as_ref().to_path_buf(),
+        scale => dir.as_ref().join(format!("{scale}x")),
+    }
+}
+
+pub trait SaveSymbolsToPngSequence {
+    /// Saves each symbol as a frame of a contiguously numbered PNG sequence, the original size images are always written
+    fn save_to_png_sequence<P: AsRef<Path>>(&self, path: P, scales: &[u32]) -> Result<SequenceMetadata, SavePngSequenceError>;
+}
+
+impl<T> SaveSymbolsToPngSequence for T
+where
+    for<'any> &'any T: IntoIterator<Item = &'any Symbol>,
+{
+    fn save_to_png_sequence<P: AsRef<Path>>(&self, path: P, scales: &[u32]) -> Result<SequenceMetadata, SavePngSequenceError> {
+        let mut all_scales = vec![1];
+        all_scales.extend(scales.iter().copied().filter(|scale| *scale > 1));
+        all_scales.sort_unstable();
+        all_scales.dedup();
+        for scale in &all_scales {
+            create_path(scale_dir_path(&path, *scale))?;
+        }
+
+        let mut tile_index = 0;
+        let mut frames = vec![];
+        for (frame, symbol) in self.into_iter().enumerate() {
+            let image = symbol.generate_image();
+            for scale in &all_scales {
+                let file_path = scale_dir_path(&path, *scale).join(frame_file_name(frame));
+                match *scale {
+                    1 => image.write_image_file(file_path)?,
+                    scale => imageops::resize(&image, image.width() * scale, image.height() * scale, FilterType::Nearest).write_image_file(file_path)?,
+                }
+            }
+            frames.push(SequenceFrame { frame, tile_index, span: symbol.span(), width: image.width(), height: image.height() });
+            tile_index += symbol.span();
+        }
+
+        let metadata = SequenceMetadata { file_pattern: "symbol_%04d.png".to_owned(), scales: all_scales, frames };
+        serde_json::to_writer_pretty(File::create(path.as_ref().join(METADATA_FILE_NAME))?, &metadata)?;
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{container::symbol::Symbol, Kind as TileKind, Tile};
+
+    use super::{frame_file_name, scale_dir_path, SaveSymbolsToPngSequence, METADATA_FILE_NAME};
+
+    #[test]
+    fn save() {
+        let temp_dir = TempDir::new().unwrap();
+        let symbols = vec![
+            Symbol::from(Tile::new(TileKind::SD)),
+            Symbol::try_from(vec![Tile::new(TileKind::SD); 3]).unwrap(),
+            Symbol::from(Tile::new(TileKind::SD)),
+        ];
+        let metadata = symbols.save_to_png_sequence(temp_dir.path(), &[2]).unwrap();
+        assert_eq!(metadata.frames.iter().map(|frame| frame.tile_index).collect::<Vec<_>>(), vec![0, 1, 4]);
+        assert!(temp_dir.child(METADATA_FILE_NAME).is_file());
+        let scaled_dimensions = image::image_dimensions(scale_dir_path(temp_dir.path(), 2).join(frame_file_name(1))).unwrap();
+        assert_eq!(scaled_dimensions, (2 * 3 * 36, 2 * 54));
+    }
+}
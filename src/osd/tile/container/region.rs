@@ -0,0 +1,180 @@
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::image_encode::ImageEncodeOptions;
+use crate::osd::tile::{
+    Tile,
+    grid::{self, Grid as TileGrid, GridStyle, LoadError as GridLoadError},
+};
+use super::into_tile_grid::IntoTileGrid;
+use super::uniq_tile_kind::{TileKindError, UniqTileKind};
+
+
+const REGION_METADATA_KEY: &str = "hd_fpv_osd_font_tool:region";
+
+#[derive(Debug, Error)]
+pub enum RegionError {
+    #[error("region {region} does not fit the {grid_width} tiles wide grid")]
+    OutOfGrid { region: Region, grid_width: usize },
+    #[error("region {region} contains {} tiles but {tile_count} tiles were given", region.tile_count())]
+    WrongTileCount { region: Region, tile_count: usize },
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+}
+
+#[derive(Debug, Error)]
+pub enum SaveRegionImageError {
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+    #[error("failed to write region image {file_path}: {error}")]
+    WriteError { file_path: PathBuf, error: png::EncodingError },
+}
+
+#[derive(Debug, Error)]
+pub enum LoadRegionImageError {
+    #[error(transparent)]
+    GridLoadError(#[from] GridLoadError),
+    #[error(transparent)]
+    InvalidRegion(#[from] InvalidRegionError),
+    #[error("{0} is not a region image: it has no region metadata, the region has to be given")]
+    MissingMetadata(PathBuf),
+}
+
+#[derive(Debug, Error)]
+#[error("invalid region `{0}`, expected `x,y,width,height` in tiles")]
+pub struct InvalidRegionError(String);
+
+/// Rectangle of tiles of the grid layout of a collection, in tiles from the top left tile
+///
+/// Region images are grid images of the tiles of a region laid out as in the region, the region is stored in the image
+/// metadata so that the tiles can be put back at the same place once edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+
+    pub fn tile_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Indices of the tiles of the region in the collection, row by row
+    pub fn tile_indices(&self, grid_width: usize) -> Result<Vec<usize>, RegionError> {
+        if self.x + self.width > grid_width {
+            return Err(RegionError::OutOfGrid { region: *self, grid_width });
+        }
+        Ok((self.y..self.y + self.height).flat_map(|y| (self.x..self.x + self.width).map(move |x| x + y * grid_width)).collect())
+    }
+
+    /// Tiles of the region of the collection in the default grid layout, the tiles past the end of the collection are
+    /// empty tiles
+    pub fn extract(&self, tiles: &[Tile]) -> Result<Vec<Tile>, RegionError> {
+        let empty_tile = Tile::new(tiles.tile_kind()?);
+        let indices = self.tile_indices(GridStyle::default().width())?;
+        Ok(indices.into_iter().map(|index| tiles.get(index).unwrap_or(&empty_tile).clone()).collect())
+    }
+
+    /// Replaces the tiles of the region of the collection, the collection is extended with empty tiles if the region
+    /// goes past its end
+    pub fn insert(&self, tiles: &[Tile], region_tiles: &[Tile]) -> Result<Vec<Tile>, RegionError> {
+        if region_tiles.len() != self.tile_count() {
+            return Err(RegionError::WrongTileCount { region: *self, tile_count: region_tiles.len() });
+        }
+        let tile_kind = [tiles, region_tiles].concat().tile_kind()?;
+        let mut tiles = tiles.to_vec();
+        for (index, region_tile) in self.tile_indices(GridStyle::default().width())?.into_iter().zip(region_tiles) {
+            if index >= tiles.len() {
+                tiles.resize(index + 1, Tile::new(tile_kind));
+            }
+            tiles[index] = region_tile.clone();
+        }
+        Ok(tiles)
+    }
+
+    fn image_style(&self) -> GridStyle {
+        GridStyle::new(self.width, GridStyle::default().separator_thickness())
+    }
+
+    pub fn save_image<P: AsRef<Path>>(&self, region_tiles: &[Tile], path: P) -> Result<(), SaveRegionImageError> {
+        let image = region_tiles.into_tile_grid().generate_image_with_style(&self.image_style())?;
+        grid::write_png_with_text_chunks(&path, &image, &[(REGION_METADATA_KEY, self.to_string())], &ImageEncodeOptions::default())
+            .map_err(|error| SaveRegionImageError::WriteError { file_path: path.as_ref().to_path_buf(), error })
+    }
+
+    /// Loads the tiles of a region image along with the region recorded in it, `region` overrides the recorded one
+    pub fn load_image<P: AsRef<Path>>(path: P, region: Option<Region>) -> Result<(Region, Vec<Tile>), LoadRegionImageError> {
+        let region = match region {
+            Some(region) => region,
+            None => grid::read_png_text_chunk(&path, REGION_METADATA_KEY)
+                .ok_or_else(|| LoadRegionImageError::MissingMetadata(path.as_ref().to_path_buf()))?
+                .parse()?,
+        };
+        let tiles = TileGrid::load_from_image_with_style(&path, &region.image_style())?;
+        Ok((region, tiles.iter().cloned().collect()))
+    }
+
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl FromStr for Region {
+    type Err = InvalidRegionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s.split(',').map(|value| value.trim().parse::<usize>()).collect::<Result<Vec<_>, _>>()
+            .map_err(|_| InvalidRegionError(s.to_owned()))?;
+        match values.as_slice() {
+            [x, y, width, height] if *width > 0 && *height > 0 => Ok(Self { x: *x, y: *y, width: *width, height: *height }),
+            _ => Err(InvalidRegionError(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::Region;
+
+    #[test]
+    fn export_and_import() {
+        let region: Region = "2,1,3,2".parse().unwrap();
+        assert_eq!(region.tile_indices(16).unwrap(), vec![18, 19, 20, 34, 35, 36]);
+        assert!("14,0,3,1".parse::<Region>().unwrap().tile_indices(16).is_err());
+        assert!("1,2,0,1".parse::<Region>().is_err());
+
+        let mut tiles = vec![Tile::new(TileKind::SD); 40];
+        tiles[19].put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        let region_tiles = region.extract(&tiles).unwrap();
+        assert_eq!(region_tiles[1].as_raw(), tiles[19].as_raw());
+        assert!(region_tiles[5].is_empty());
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("region.png");
+        region.save_image(&region_tiles, &path).unwrap();
+        let (loaded_region, loaded_tiles) = Region::load_image(&path, None).unwrap();
+        assert_eq!(loaded_region, region);
+
+        let mut base = vec![Tile::new(TileKind::SD); 20];
+        base[0].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let imported = region.insert(&base, &loaded_tiles).unwrap();
+        assert_eq!(imported.len(), 37);
+        assert_eq!(imported[19].as_raw(), tiles[19].as_raw());
+        assert_eq!(imported[0].as_raw(), base[0].as_raw());
+    }
+}
@@ -0,0 +1,80 @@
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::osd::tile::file_format::FileFormat as TileFileFormat;
+
+
+#[derive(Debug, Error)]
+pub enum ReindexTileDirError {
+    #[error("failed to read tile directory {dir}: {error}")]
+    ReadDirError { dir: PathBuf, error: IOError },
+    #[error("tile file {file_path} would be moved before the first index by an offset of {offset}")]
+    NegativeIndex { file_path: PathBuf, offset: isize },
+    #[error("failed to rename {from} to {to}: {error}")]
+    RenameError { from: PathBuf, to: PathBuf, error: IOError },
+}
+
+fn rename(from: PathBuf, to: PathBuf) -> Result<(), ReindexTileDirError> {
+    fs_err::rename(&from, &to).map_err(|error| ReindexTileDirError::RenameError { from, to, error })
+}
+
+/// Renames the tile files of a directory so that the tile at `index + offset` ends up at `index`, returns the number
+/// of renamed files
+///
+/// Nothing is renamed if a tile would be moved before the first index. Files are first moved to temporary names so
+/// that renaming never overwrites a tile which has not been moved yet.
+pub fn reindex_tile_dir<P: AsRef<Path>>(dir: P, format: TileFileFormat, offset: isize) -> Result<usize, ReindexTileDirError> {
+    let dir = dir.as_ref();
+    let read_dir_error = |error| ReindexTileDirError::ReadDirError { dir: dir.to_path_buf(), error };
+    let mut moves = vec![];
+    for entry in fs_err::read_dir(dir).map_err(read_dir_error)? {
+        let file_path = entry.map_err(read_dir_error)?.path();
+        let index = match file_path.file_name().and_then(|file_name| format.tile_file_index(&file_name.to_string_lossy())) {
+            Some(index) => index,
+            None => continue,
+        };
+        let new_index = index as isize - offset;
+        if new_index < 0 {
+            return Err(ReindexTileDirError::NegativeIndex { file_path, offset });
+        }
+        moves.push((file_path, dir.join(format.tile_file_name(new_index as usize))));
+    }
+
+    let temporary_path = |file_path: &Path| {
+        let mut path = file_path.as_os_str().to_owned();
+        path.push(".reindex");
+        PathBuf::from(path)
+    };
+    for (file_path, _) in &moves {
+        rename(file_path.clone(), temporary_path(file_path))?;
+    }
+    for (file_path, new_path) in &moves {
+        rename(temporary_path(file_path), new_path.clone())?;
+    }
+    Ok(moves.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::file_format::FileFormat as TileFileFormat;
+
+    use super::reindex_tile_dir;
+
+    #[test]
+    fn reindex() {
+        let temp_dir = TempDir::new().unwrap();
+        for (file_name, content) in [("002.rgba", "a"), ("3.rgba", "b"), ("notes.txt", "c")] {
+            std::fs::write(temp_dir.child(file_name), content).unwrap();
+        }
+        assert!(reindex_tile_dir(temp_dir.path(), TileFileFormat::Rgba, 3).is_err());
+        assert_eq!(reindex_tile_dir(temp_dir.path(), TileFileFormat::Rgba, 2).unwrap(), 2);
+        assert_eq!(std::fs::read_to_string(temp_dir.child("000.rgba")).unwrap(), "a");
+        assert_eq!(std::fs::read_to_string(temp_dir.child("001.rgba")).unwrap(), "b");
+        assert!(temp_dir.child("notes.txt").exists());
+    }
+}
@@ -0,0 +1,77 @@
+
+use crate::osd::tile::Tile;
+use super::uniq_tile_kind::{TileKindError, UniqTileKind};
+
+
+pub struct Repaged {
+    pub tiles: Vec<Tile>,
+    /// indices of the non-empty tiles which were dropped by truncating the collection
+    pub dropped_non_empty_tiles: Vec<usize>,
+}
+
+/// Pads the collection with empty tiles or truncates it so that it contains exactly `target_count` tiles
+pub fn repage(tiles: &[Tile], target_count: usize) -> Result<Repaged, TileKindError> {
+    let tile_kind = tiles.tile_kind()?;
+    let dropped_non_empty_tiles = tiles.iter().enumerate()
+        .skip(target_count)
+        .filter(|(_, tile)| ! tile.is_empty())
+        .map(|(index, _)| index)
+        .collect();
+    let mut repaged_tiles: Vec<Tile> = tiles.iter().take(target_count).cloned().collect();
+    repaged_tiles.resize(target_count, Tile::new(tile_kind));
+    Ok(Repaged { tiles: repaged_tiles, dropped_non_empty_tiles })
+}
+
+/// Splits the collection in pages of `page_size` tiles, the last page is padded with empty tiles
+pub fn split_pages(tiles: &[Tile], page_size: usize) -> Result<Vec<Vec<Tile>>, TileKindError> {
+    assert!(page_size > 0, "page size must not be 0");
+    let tile_kind = tiles.tile_kind()?;
+    let pages = tiles.chunks(page_size).map(|page_tiles| {
+        let mut page = page_tiles.to_vec();
+        page.resize(page_size, Tile::new(tile_kind));
+        page
+    }).collect();
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{repage, split_pages};
+
+    fn tiles(count: usize) -> Vec<Tile> {
+        let mut tiles = vec![Tile::new(TileKind::SD); count];
+        for tile in tiles.iter_mut() {
+            tile.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        }
+        tiles
+    }
+
+    #[test]
+    fn pad() {
+        let repaged = repage(&tiles(10), 256).unwrap();
+        assert_eq!(repaged.tiles.len(), 256);
+        assert!(repaged.tiles[10..].iter().all(Tile::is_empty));
+        assert!(repaged.dropped_non_empty_tiles.is_empty());
+    }
+
+    #[test]
+    fn truncate() {
+        let mut tiles = tiles(512);
+        tiles[300] = Tile::new(TileKind::SD);
+        let repaged = repage(&tiles, 256).unwrap();
+        assert_eq!(repaged.tiles.len(), 256);
+        assert_eq!(repaged.dropped_non_empty_tiles.len(), 255);
+        assert!(! repaged.dropped_non_empty_tiles.contains(&300));
+    }
+
+    #[test]
+    fn split() {
+        let pages = split_pages(&tiles(300), 256).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert!(pages.iter().all(|page| page.len() == 256));
+    }
+}
@@ -6,6 +6,7 @@ use super::symbol::Symbol;
 
 use crate::create_path::{create_path, CreatePathError};
 use crate::image::{WriteImageFile, WriteError as ImageWriteError};
+use crate::image_encode::ImageEncodeOptions;
 
 
 #[derive(Debug, Error, Display, From)]
@@ -15,25 +16,38 @@ pub enum SaveSymbolsToDirError {
 }
 
 pub trait SaveSymbolsToDir {
-    fn save_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveSymbolsToDirError>;
+    fn save_to_dir_with_options<P: AsRef<Path>>(&self, path: P, encode_options: &ImageEncodeOptions) -> Result<(), SaveSymbolsToDirError>;
+
+    fn save_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveSymbolsToDirError> {
+        self.save_to_dir_with_options(path, &ImageEncodeOptions::default())
+    }
 }
 
 impl<T> SaveSymbolsToDir for T
 where
     for<'any> &'any T: IntoIterator<Item = &'any Symbol>,
 {
-    fn save_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveSymbolsToDirError> {
-        create_path(&path)?;
-        let mut tile_index = 0;
-        for symbol in self {
-            let file_name = match symbol.span() {
-                1 => format!("{tile_index:03}.png"),
-                span => format!("{tile_index:03}-{:03}.png", tile_index + span - 1)
-            };
-            let file_path: PathBuf = [path.as_ref(), Path::new(&file_name)].iter().collect();
-            symbol.generate_image().write_image_file(file_path)?;
-            tile_index += symbol.span();
-        }
-        Ok(())
+    fn save_to_dir_with_options<P: AsRef<Path>>(&self, path: P, encode_options: &ImageEncodeOptions) -> Result<(), SaveSymbolsToDirError> {
+        save_symbols_to_dir_with_prefix(self, path, "", encode_options)
+    }
+}
+
+/// Saves the symbols to files whose names are the tile indices prefixed with `prefix`, e.g. sd_030-032.png
+pub fn save_symbols_to_dir_with_prefix<'a, I, P>(symbols: I, path: P, prefix: &str, encode_options: &ImageEncodeOptions) -> Result<(), SaveSymbolsToDirError>
+where
+    I: IntoIterator<Item = &'a Symbol>,
+    P: AsRef<Path>,
+{
+    create_path(&path)?;
+    let mut tile_index = 0;
+    for symbol in symbols {
+        let file_name = match symbol.span() {
+            1 => format!("{prefix}{tile_index:03}.png"),
+            span => format!("{prefix}{tile_index:03}-{:03}.png", tile_index + span - 1)
+        };
+        let file_path: PathBuf = [path.as_ref(), Path::new(&file_name)].iter().collect();
+        symbol.generate_image().write_image_file_with_options(file_path, encode_options)?;
+        tile_index += symbol.span();
     }
+    Ok(())
 }
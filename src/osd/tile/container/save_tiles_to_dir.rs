@@ -1,11 +1,17 @@
 
 use derive_more::{Error, Display, From};
 use image::ImageError;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::path::{Path, PathBuf};
 
 use crate::{
-    osd::tile::Tile,
-    create_path::{create_path, CreatePathError}
+    osd::tile::{
+        Tile,
+        file_format::{FileFormat as TileFileFormat, SaveError as TileSaveError, SaveOptions as TileSaveOptions},
+    },
+    create_path::{create_path, CreatePathError},
+    io_context::IoContext,
 };
 
 
@@ -13,24 +19,62 @@ use crate::{
 pub enum SaveTilesToDirError {
     CreatePathError(CreatePathError),
     ImageError(ImageError),
+    TileSaveError(TileSaveError),
 }
 
 pub trait SaveTilesToDir {
-    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToDirError>;
+    fn save_tiles_to_dir_with_options<P: AsRef<Path>>(&self, path: P, options: &TileSaveOptions) -> Result<(), SaveTilesToDirError>;
+
+    fn save_tiles_to_dir_with_format<P: AsRef<Path>>(&self, path: P, format: TileFileFormat) -> Result<(), SaveTilesToDirError> {
+        self.save_tiles_to_dir_with_options(path, &format.into())
+    }
+
+    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToDirError> {
+        self.save_tiles_to_dir_with_format(path, TileFileFormat::Png)
+    }
 }
 
 impl<T> SaveTilesToDir for T
 where
     for<'any> &'any T: IntoIterator<Item = &'any Tile>,
 {
-    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToDirError> {
-        create_path(&path)?;
+    fn save_tiles_to_dir_with_options<P: AsRef<Path>>(&self, path: P, options: &TileSaveOptions) -> Result<(), SaveTilesToDirError> {
+        save_tiles_to_dir_with_prefix(self, path, "", options)
+    }
+}
 
-        for (index, tile) in self.into_iter().enumerate() {
-            let path: PathBuf = [path.as_ref(), Path::new(&format!("{:03}.png", index))].iter().collect();
-            tile.save(path)?;
-        }
+/// Saves the tiles to files whose names are the tile index prefixed with `prefix`, e.g. sd_011.png
+///
+/// Tiles are encoded and written in parallel with the `rayon` feature, the error reported is the one of the lowest failing
+/// tile index.
+pub fn save_tiles_to_dir_with_prefix<'a, I, P>(tiles: I, path: P, prefix: &str, options: &TileSaveOptions) -> Result<(), SaveTilesToDirError>
+where
+    I: IntoIterator<Item = &'a Tile>,
+    P: AsRef<Path>,
+{
+    save_tiles_to_dir_with_context(tiles, path, prefix, options, &IoContext::default())
+}
 
-        Ok(())
-    }
+/// Saves the tiles like [`save_tiles_to_dir_with_prefix`], writing the files with the retry policy of the context
+pub fn save_tiles_to_dir_with_context<'a, I, P>(tiles: I, path: P, prefix: &str, options: &TileSaveOptions, context: &IoContext)
+        -> Result<(), SaveTilesToDirError>
+where
+    I: IntoIterator<Item = &'a Tile>,
+    P: AsRef<Path>,
+{
+    create_path(&path)?;
+
+    let dir = path.as_ref();
+    let tiles: Vec<&Tile> = tiles.into_iter().collect();
+    let save_tile = |(index, tile): (usize, &&Tile)| {
+        let path: PathBuf = dir.join(format!("{prefix}{}", options.format.tile_file_name(index).to_string_lossy()));
+        tile.save_file_with_context(path, options, context)
+    };
+    #[cfg(feature = "rayon")]
+    let results: Vec<Result<(), TileSaveError>> = tiles.par_iter().enumerate().map(save_tile).collect();
+    #[cfg(not(feature = "rayon"))]
+    let results: Vec<Result<(), TileSaveError>> = tiles.iter().enumerate().map(save_tile).collect();
+    results.into_iter().collect::<Result<(), _>>()?;
+
+    Ok(())
 }
\ No newline at end of file
@@ -6,6 +6,7 @@ use std::{
 
 use derive_more::{Error, Display, From};
 use crate::{osd::{tile::{Tile, grid::Grid as TileGrid}, bin_file::{self, BinFileWriter}}, prelude::bin_file::FontPart, create_path::{CreatePathError, create_path}};
+use crate::io_context::IoContext;
 use super::uniq_tile_kind::{TileKindError, UniqTileKind};
 
 
@@ -23,18 +24,23 @@ pub trait SaveToBinFile {
     fn save_to_bin_file_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, part: FontPart) -> Result<(), SaveTilesToBinFileError>;
 }
 
-impl SaveToBinFile for &[Tile] {
-    fn save_to_bin_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToBinFileError> {
-        self.tile_kind()?;
-        let mut writer = BinFileWriter::create(path)?;
+/// Saves the tiles to a bin file, creating the file with the retry policy of the context
+pub fn save_to_bin_file_with_context<P: AsRef<Path>>(tiles: &[Tile], path: P, context: &IoContext) -> Result<(), SaveTilesToBinFileError> {
+    tiles.tile_kind()?;
+    let mut writer = BinFileWriter::create_with_context(path, context)?;
 
-        for tile in self.iter() {
-            writer.write_tile(tile)?;
-        }
+    for tile in tiles {
+        writer.write_tile(tile)?;
+    }
+
+    writer.fill_remaining_space()?;
+    writer.finish()?;
+    Ok(())
+}
 
-        writer.fill_remaining_space()?;
-        writer.finish()?;
-        Ok(())
+impl SaveToBinFile for &[Tile] {
+    fn save_to_bin_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToBinFileError> {
+        save_to_bin_file_with_context(self, path, &IoContext::default())
     }
 
     fn save_to_bin_file_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, part: FontPart) -> Result<(), SaveTilesToBinFileError> {
@@ -68,15 +74,26 @@ pub trait SaveToBinFiles {
     fn save_to_bin_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveTilesToBinFileError>;
 }
 
+// tiles of the base and extended bin files, the tiles missing from a collection shorter than both files are empty
+fn bin_files_tiles(tiles: &[Tile]) -> Result<(Vec<Tile>, Vec<Tile>), TileKindError> {
+    let tile_kind = tiles.tile_kind()?;
+    let mut base_tiles = tiles.iter().take(2 * bin_file::TILE_COUNT).cloned().collect::<Vec<_>>();
+    base_tiles.resize(2 * bin_file::TILE_COUNT, Tile::new(tile_kind));
+    let ext_tiles = base_tiles.split_off(bin_file::TILE_COUNT);
+    Ok((base_tiles, ext_tiles))
+}
+
 impl SaveToBinFiles for &[Tile] {
     fn save_to_bin_files<P: AsRef<Path>>(&self, path1: P, path2: P) -> Result<(), SaveTilesToBinFileError> {
-        (&self[0..bin_file::TILE_COUNT]).save_to_bin_file(path1)?;
-        (&self[bin_file::TILE_COUNT..2 * bin_file::TILE_COUNT]).save_to_bin_file(path2)
+        let (base_tiles, ext_tiles) = bin_files_tiles(self)?;
+        base_tiles.save_to_bin_file(path1)?;
+        ext_tiles.save_to_bin_file(path2)
     }
 
     fn save_to_bin_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveTilesToBinFileError> {
-        (&self[0..bin_file::TILE_COUNT]).save_to_bin_file_norm(&dir, ident, FontPart::Base)?;
-        (&self[bin_file::TILE_COUNT..2 * bin_file::TILE_COUNT]).save_to_bin_file_norm(&dir, ident, FontPart::Ext)
+        let (base_tiles, ext_tiles) = bin_files_tiles(self)?;
+        base_tiles.save_to_bin_file_norm(&dir, ident, FontPart::Base)?;
+        ext_tiles.save_to_bin_file_norm(&dir, ident, FontPart::Ext)
     }
 }
 
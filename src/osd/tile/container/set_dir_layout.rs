@@ -0,0 +1,163 @@
+
+//! Layouts of the directories holding the tiles or symbols of both kinds of a set
+//!
+//! The default layout has one subdirectory per tile kind, `SD` and `HD`. The names of the subdirectories can be changed
+//! and may contain `/` to nest them deeper. The tiles of both kinds can instead be stored in a single directory with the
+//! file names prefixed by the tile kind, e.g. `sd_011.png` and `hd_011.png`.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::osd::tile::Kind as TileKind;
+
+
+#[derive(Debug, Error)]
+#[error("invalid set directory layout `{0}`, expected dirs:<SD dir>,<HD dir> or prefix:<SD prefix>,<HD prefix>")]
+pub struct InvalidSetDirLayoutError(String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetDirLayout {
+    /// one subdirectory per tile kind, relative to the set directory
+    Subdirs { sd: String, hd: String },
+    /// both kinds in the set directory, file names prefixed by the tile kind
+    Prefixed { sd: String, hd: String },
+}
+
+impl SetDirLayout {
+
+    fn names(&self) -> (&str, &str) {
+        match self {
+            Self::Subdirs { sd, hd } | Self::Prefixed { sd, hd } => (sd, hd),
+        }
+    }
+
+    fn name(&self, tile_kind: TileKind) -> &str {
+        let (sd, hd) = self.names();
+        match tile_kind {
+            TileKind::SD => sd,
+            TileKind::HD => hd,
+        }
+    }
+
+    /// Directory holding the files of the tiles of the given kind
+    pub fn tile_kind_dir<P: AsRef<Path>>(&self, set_dir: P, tile_kind: TileKind) -> PathBuf {
+        match self {
+            Self::Subdirs { .. } => self.name(tile_kind).split('/').fold(set_dir.as_ref().to_path_buf(), |path, name| path.join(name)),
+            Self::Prefixed { .. } => set_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Prefix of the names of the files of the tiles of the given kind
+    pub fn file_prefix(&self, tile_kind: TileKind) -> &str {
+        match self {
+            Self::Subdirs { .. } => "",
+            Self::Prefixed { .. } => self.name(tile_kind),
+        }
+    }
+
+    fn candidates() -> Vec<Self> {
+        let subdirs = |sd: &str, hd: &str| Self::Subdirs { sd: sd.to_owned(), hd: hd.to_owned() };
+        let prefixed = |sd: &str, hd: &str| Self::Prefixed { sd: sd.to_owned(), hd: hd.to_owned() };
+        vec![
+            subdirs("SD", "HD"), subdirs("sd", "hd"),
+            prefixed("sd_", "hd_"), prefixed("SD_", "HD_"), prefixed("sd-", "hd-"),
+        ]
+    }
+
+    fn matches(&self, set_dir: &Path) -> bool {
+        match self {
+            Self::Subdirs { .. } => TileKind::iter().any(|tile_kind| self.tile_kind_dir(set_dir, tile_kind).is_dir()),
+            Self::Prefixed { .. } => {
+                let file_names = match fs_err::read_dir(set_dir) {
+                    Ok(entries) => entries.filter_map(Result::ok).map(|entry| entry.file_name().to_string_lossy().into_owned()).collect::<Vec<_>>(),
+                    Err(_) => return false,
+                };
+                TileKind::iter().any(|tile_kind| {
+                    let prefix = self.file_prefix(tile_kind);
+                    file_names.iter().any(|file_name| file_name.strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with(|char: char| char.is_ascii_digit())))
+                })
+            },
+        }
+    }
+
+    /// Finds the layout of an existing set directory among the common ones, `None` if none matches
+    pub fn detect<P: AsRef<Path>>(set_dir: P) -> Option<Self> {
+        Self::candidates().into_iter().find(|layout| layout.matches(set_dir.as_ref()))
+    }
+
+    /// Layout of an existing set directory, the default one if it cannot be detected
+    pub fn detect_or_default<P: AsRef<Path>>(set_dir: P) -> Self {
+        Self::detect(&set_dir).unwrap_or_default()
+    }
+
+}
+
+impl Default for SetDirLayout {
+    fn default() -> Self {
+        Self::Subdirs { sd: TileKind::SD.set_dir_name().to_owned(), hd: TileKind::HD.set_dir_name().to_owned() }
+    }
+}
+
+impl FromStr for SetDirLayout {
+    type Err = InvalidSetDirLayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || InvalidSetDirLayoutError(s.to_owned());
+        let (layout_kind, names) = s.split_once(':').ok_or_else(error)?;
+        let (sd, hd) = names.split_once(',').ok_or_else(error)?;
+        if sd.is_empty() || hd.is_empty() || sd == hd {
+            return Err(error());
+        }
+        let (sd, hd) = (sd.to_owned(), hd.to_owned());
+        match layout_kind {
+            "dirs" => Ok(Self::Subdirs { sd, hd }),
+            "prefix" => Ok(Self::Prefixed { sd, hd }),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl Display for SetDirLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Subdirs { sd, hd } => write!(f, "dirs:{sd},{hd}"),
+            Self::Prefixed { sd, hd } => write!(f, "prefix:{sd},{hd}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile, container::tile_set::TileSet};
+
+    use super::SetDirLayout;
+
+    #[test]
+    fn layouts() {
+        let layout: SetDirLayout = "dirs:fonts/sd,fonts/hd".parse().unwrap();
+        assert_eq!(layout.tile_kind_dir("set", TileKind::HD), Path::new("set/fonts/hd"));
+        assert_eq!(layout.to_string(), "dirs:fonts/sd,fonts/hd");
+        assert!("prefix:sd_".parse::<SetDirLayout>().is_err());
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut sd_tiles = vec![Tile::new(TileKind::SD); 4];
+        sd_tiles[2].put_pixel(1, 1, image::Rgba([255, 255, 255, 255]));
+        let tile_set = TileSet::try_from_tiles(sd_tiles, vec![Tile::new(TileKind::HD); 4]).unwrap();
+        let layout: SetDirLayout = "prefix:sd-,hd-".parse().unwrap();
+        tile_set.save_tiles_to_dir_with_layout(temp_dir.path(), &layout, &Default::default()).unwrap();
+        assert!(temp_dir.child("hd-003.png").is_file());
+
+        assert_eq!(SetDirLayout::detect(temp_dir.path()), Some(layout));
+        let loaded = TileSet::load_from_dir_with_layout(temp_dir.path(), 4, &SetDirLayout::detect_or_default(temp_dir.path())).unwrap();
+        assert_eq!(loaded.sd_tiles()[2].as_raw(), tile_set.sd_tiles()[2].as_raw());
+    }
+}
@@ -1,6 +1,8 @@
 
 pub mod spec;
 pub mod set;
+pub mod spec_diff;
+pub mod spec_header;
 
 use std::fmt::Display;
 use std::path::Path;
@@ -9,6 +11,7 @@ use getset::CopyGetters;
 use image::{ImageBuffer, Rgba, GenericImage, GenericImageView};
 
 use crate::dimensions;
+use spec::Spec;
 use crate::osd::tile::{
     Tile,
     Kind as TileKind,
@@ -18,9 +21,18 @@ use crate::osd::tile::{
         UniqTileKind
     }
 };
-use crate::image::{read_image_file, ReadError as ImageReadError};
+use crate::image::{read_image_file_with_context, ReadError as ImageReadError, WriteImageFile, WriteError as ImageWriteError};
+use crate::io_context::IoContext;
 
 
+#[derive(Debug, thiserror::Error)]
+pub enum ExtractError {
+    #[error("symbol spanning tiles {start_index}-{end_index} is outside of the collection of {tile_count} tiles")]
+    OutOfRange { start_index: usize, end_index: usize, tile_count: usize },
+    #[error(transparent)]
+    TileKindError(TileKindError),
+}
+
 #[derive(Debug, From, Error)]
 pub enum LoadError {
     ImageReadError(ImageReadError),
@@ -60,7 +72,11 @@ impl Symbol {
     }
 
     pub fn load_image_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
-        let image = read_image_file(&path)?;
+        Self::load_image_file_with_context(path, &IoContext::default())
+    }
+
+    pub fn load_image_file_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Self, LoadError> {
+        let image = read_image_file_with_context(&path, context)?;
         let (image_width, image_height) = image.dimensions();
         let tile_kind = TileKind::for_height(image_height)?;
         let tile_dimensions = tile_kind.dimensions();
@@ -77,6 +93,17 @@ impl Symbol {
         Ok(Self { tile_kind, tiles })
     }
 
+    pub fn extract(tiles: &[Tile], spec: &Spec) -> Result<Self, ExtractError> {
+        if spec.end_tile_index() > tiles.len() {
+            return Err(ExtractError::OutOfRange { start_index: spec.start_tile_index(), end_index: spec.end_tile_index() - 1, tile_count: tiles.len() });
+        }
+        Self::try_from(Vec::from(&tiles[spec.tile_index_range()])).map_err(ExtractError::TileKindError)
+    }
+
+    pub fn save_image_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageWriteError> {
+        self.generate_image().write_image_file(path)
+    }
+
     pub fn span(&self) -> usize {
         self.tiles.len()
     }
@@ -6,11 +6,12 @@ use derive_more::{From, Display, Error};
 use getset::Getters;
 use strum::IntoEnumIterator;
 
+use crate::image_encode::ImageEncodeOptions;
 use crate::osd::tile::Kind as TileKind;
-use crate::osd::tile::container::load_symbols_from_dir::{load_symbols_from_dir, LoadSymbolsFromDirError};
-use crate::osd::tile::container::save_symbols_to_dir::SaveSymbolsToDirError;
+use crate::osd::tile::container::load_symbols_from_dir::{load_symbols_from_dir_with_prefix, LoadSymbolsFromDirError};
+use crate::osd::tile::container::save_symbols_to_dir::{save_symbols_to_dir_with_prefix, SaveSymbolsToDirError};
+use crate::osd::tile::container::set_dir_layout::SetDirLayout;
 use crate::osd::tile::container::uniq_tile_kind::{UniqTileKind, TileKindError};
-use crate::prelude::SaveSymbolsToDir;
 use super::Symbol;
 
 
@@ -25,6 +26,8 @@ pub enum LoadFromDirError {
 pub struct Set {
     pub(crate) sd_symbols: Vec<Symbol>,
     pub(crate) hd_symbols: Vec<Symbol>,
+    /// ident of the normalized files the set originates from
+    pub(crate) ident: Option<String>,
 }
 
 impl Set {
@@ -40,20 +43,27 @@ impl Set {
     pub fn try_from_symbols(sd_symbols: Vec<Symbol>, hd_symbols: Vec<Symbol>) -> Result<Self, TileKindError> {
         Self::check_collection_kind(&sd_symbols, TileKind::SD)?;
         Self::check_collection_kind(&hd_symbols, TileKind::HD)?;
-        Ok(Self { sd_symbols, hd_symbols })
+        Ok(Self { sd_symbols, hd_symbols, ident: None })
     }
 
     pub fn save_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<(), SaveSymbolsToDirError> {
-        for tile_kind in TileKind::iter() {
-            self[tile_kind].save_to_dir(tile_kind.set_dir_path(&dir))?;
+        self.save_to_dir_with_layout(dir, &SetDirLayout::default(), &ImageEncodeOptions::default())
+    }
+
+    pub fn save_to_dir_with_layout<P: AsRef<Path>>(&self, dir: P, layout: &SetDirLayout, encode_options: &ImageEncodeOptions) -> Result<(), SaveSymbolsToDirError> {
+        for tile_kind in TileKind::iter().filter(|tile_kind| ! self[*tile_kind].is_empty()) {
+            save_symbols_to_dir_with_prefix(&self[tile_kind], layout.tile_kind_dir(&dir, tile_kind), layout.file_prefix(tile_kind), encode_options)?;
         }
         Ok(())
     }
 
-    pub fn load_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) -> Result<Self, LoadFromDirError> {
-        let sd_symbols = load_symbols_from_dir(TileKind::SD.set_dir_path(&dir_path), max_symbols)?;
-        let hd_symbols = load_symbols_from_dir(TileKind::HD.set_dir_path(&dir_path), max_symbols)?;
-        Ok(Self::try_from_symbols(sd_symbols, hd_symbols)?)
+    pub fn load_from_dir<P: AsRef<Path>>(dir_path: P, max_tiles: usize) -> Result<Self, LoadFromDirError> {
+        Self::load_from_dir_with_layout(dir_path, max_tiles, &SetDirLayout::default())
+    }
+
+    pub fn load_from_dir_with_layout<P: AsRef<Path>>(dir_path: P, max_tiles: usize, layout: &SetDirLayout) -> Result<Self, LoadFromDirError> {
+        let load = |tile_kind| load_symbols_from_dir_with_prefix(layout.tile_kind_dir(&dir_path, tile_kind), layout.file_prefix(tile_kind), max_tiles);
+        Ok(Self::try_from_symbols(load(TileKind::SD)?, load(TileKind::HD)?)?)
     }
 
 }
@@ -1,6 +1,7 @@
 
 use std::{
     collections::HashMap,
+    fmt::Write,
     io::Error as IOError,
     ops::Range,
     path::{
@@ -10,25 +11,74 @@ use std::{
 };
 
 use derive_more::{From, Deref};
-use getset::CopyGetters;
+use getset::{CopyGetters, Getters};
 use parse_int::parse;
 use regex::Regex;
 use lazy_static::lazy_static;
+use serde::Deserialize;
 use thiserror::Error;
-use fs_err::File;
 
+use crate::migrate::{self, Migration};
+use crate::osd::tile::index::TILE_COUNT;
 
-#[derive(Debug, CopyGetters)]
-#[getset(get_copy = "pub")]
+use super::Symbol;
+
+
+/// Schema version of the symbol specs files
+///
+/// 1. map of the symbol names to their `<start tile index>:<span>` spec
+/// 2. the map is under the `symbols` key next to the `version` key
+pub const FILE_VERSION: u32 = 2;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpecsFile {
+    Versioned { version: u32, symbols: HashMap<String, String> },
+    Version1(HashMap<String, String>),
+}
+
+impl SpecsFile {
+    fn version(&self) -> u32 {
+        match self {
+            Self::Versioned { version, .. } => *version,
+            Self::Version1(_) => 1,
+        }
+    }
+
+    fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadSpecsFileError> {
+        let content = fs_err::read_to_string(&path)?;
+        Self::parse(path, &content)
+    }
+
+    fn parse<P: AsRef<Path>>(path: P, content: &str) -> Result<Self, LoadSpecsFileError> {
+        let file: Self = serde_yaml::from_str(content)
+            .map_err(|error| LoadSpecsFileError::file_structure(&path, error))?;
+        if file.version() > FILE_VERSION {
+            return Err(LoadSpecsFileError::UnsupportedVersion { file_path: path.as_ref().to_path_buf(), version: file.version() });
+        }
+        Ok(file)
+    }
+}
+
+
+#[derive(Debug, Getters, CopyGetters)]
 pub struct Spec {
+    #[getset(get = "pub")]
+    name: Option<String>,
+    #[getset(get_copy = "pub")]
     start_tile_index: usize,
+    #[getset(get_copy = "pub")]
     span: usize
 }
 
 impl Spec {
 
     pub fn new(start_tile_index: usize, span: usize) -> Self {
-        Self { start_tile_index, span }
+        Self { name: None, start_tile_index, span }
+    }
+
+    pub fn named(name: &str, start_tile_index: usize, span: usize) -> Self {
+        Self { name: Some(name.to_owned()), start_tile_index, span }
     }
 
     pub fn end_tile_index(&self) -> usize {
@@ -47,20 +97,15 @@ pub struct Specs(Vec<Spec>);
 impl Specs {
 
     pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadSpecsFileError> {
-        let file_content: HashMap<String, String> = serde_yaml::from_reader(File::open(&path)?)
-            .map_err(|error| LoadSpecsFileError::file_structure(&path, error))?;
-        lazy_static! {
-            static ref SPEC_RE: Regex = Regex::new(r"\A(?P<start_tile_index>0x[\da-zA-Z]+|\d+):(?P<span>\d+)\z").unwrap();
-        }
-        let mut spec_vec = Vec::with_capacity(file_content.len());
-        for (symbol_name, spec) in file_content {
-            match SPEC_RE.captures(&spec) {
-                Some(captures) => {
-                    let (start_tile_index, span) = (captures.name("start_tile_index").unwrap(), captures.name("span").unwrap());
-                    let spec = Spec::new(parse(start_tile_index.as_str()).unwrap(), parse(span.as_str()).unwrap());
-                    spec_vec.push(spec);
-                },
-                None => return Err(LoadSpecsFileError::invalid_symbol_spec(&path, &symbol_name, &spec)),
+        let content = fs_err::read_to_string(&path)?;
+        let symbols = match SpecsFile::parse(&path, &content)? {
+            SpecsFile::Versioned { symbols, .. } | SpecsFile::Version1(symbols) => symbols,
+        };
+        let mut spec_vec = Vec::with_capacity(symbols.len());
+        for (symbol_name, spec) in symbols {
+            match parse_spec(&spec) {
+                Ok((start_tile_index, span)) => spec_vec.push(Spec::named(&symbol_name, start_tile_index, span)),
+                Err(reason) => return Err(LoadSpecsFileError::invalid_symbol_spec(&path, symbol_line(&content, &symbol_name), &symbol_name, &spec, reason)),
             }
         }
         Ok(spec_vec.into())
@@ -70,6 +115,86 @@ impl Specs {
         self.iter().find(|sym_spec| sym_spec.start_tile_index() == start_tile_index)
     }
 
+    /// Spec of the symbol the tile at `tile_index` is part of
+    pub fn find_containing_index(&self, tile_index: usize) -> Option<&Spec> {
+        self.iter().find(|sym_spec| sym_spec.tile_index_range().contains(&tile_index))
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&Spec> {
+        self.iter().find(|sym_spec| sym_spec.name().as_deref() == Some(name))
+    }
+
+    /// Named specs ordered by start tile index
+    pub fn named_specs(&self) -> Vec<&Spec> {
+        let mut named_specs: Vec<&Spec> = self.iter().filter(|sym_spec| sym_spec.name().is_some()).collect();
+        named_specs.sort_by_key(|sym_spec| sym_spec.start_tile_index());
+        named_specs
+    }
+
+    /// Unnamed specs of the multi-tile symbols of consecutive symbols starting at tile index 0, as loaded from a symbol
+    /// directory, the single tile symbols do not need a spec to be saved back to a symbol directory
+    pub fn from_symbols(symbols: &[Symbol]) -> Self {
+        let mut start_tile_index = 0;
+        let mut spec_vec = vec![];
+        for symbol in symbols {
+            if symbol.span() > 1 {
+                spec_vec.push(Spec::new(start_tile_index, symbol.span()));
+            }
+            start_tile_index += symbol.span();
+        }
+        spec_vec.into()
+    }
+
+    /// Content of a symbol specs file of the current version defining these specs ordered by start tile index, the
+    /// unnamed specs are named `SYMBOL_<start tile index>`
+    pub fn file_content(&self) -> String {
+        let mut specs: Vec<&Spec> = self.iter().collect();
+        specs.sort_by_key(|sym_spec| sym_spec.start_tile_index());
+        let mut content = format!("version: {FILE_VERSION}\nsymbols:\n");
+        for sym_spec in specs {
+            let name = sym_spec.name().clone().unwrap_or_else(|| format!("SYMBOL_0x{:02X}", sym_spec.start_tile_index()));
+            writeln!(content, "  {name}: '0x{:02X}:{}'", sym_spec.start_tile_index(), sym_spec.span()).unwrap();
+        }
+        content
+    }
+
+}
+
+/// Why a `<start tile index>:<span>` symbol spec is invalid
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidSpecReason {
+    #[error("expected `<start tile index>:<span>` with a decimal or `0x` prefixed hexadecimal start tile index and a decimal span")]
+    Syntax,
+    #[error("the span can't be 0")]
+    ZeroSpan,
+    #[error("the symbol goes past the last tile index {}", TILE_COUNT - 1)]
+    OutOfRange,
+}
+
+fn parse_spec(spec: &str) -> Result<(usize, usize), InvalidSpecReason> {
+    lazy_static! {
+        static ref SPEC_RE: Regex = Regex::new(r"\A(?P<start_tile_index>0x[\da-fA-F]+|\d+):(?P<span>\d+)\z").unwrap();
+    }
+    let captures = SPEC_RE.captures(spec).ok_or(InvalidSpecReason::Syntax)?;
+    // the numbers only fail to parse when they overflow
+    let start_tile_index: usize = parse(&captures["start_tile_index"]).map_err(|_| InvalidSpecReason::OutOfRange)?;
+    let span: usize = parse(&captures["span"]).map_err(|_| InvalidSpecReason::OutOfRange)?;
+    if span == 0 {
+        return Err(InvalidSpecReason::ZeroSpan);
+    }
+    match start_tile_index.checked_add(span) {
+        Some(end_tile_index) if end_tile_index <= TILE_COUNT => Ok((start_tile_index, span)),
+        _ => Err(InvalidSpecReason::OutOfRange),
+    }
+}
+
+// line number from 1 of the key of a symbol, serde_yaml does not report the positions of the parsed values
+fn symbol_line(content: &str, symbol_name: &str) -> Option<usize> {
+    let keys = [symbol_name.to_owned(), format!("'{symbol_name}'"), format!("\"{symbol_name}\"")];
+    content.lines().position(|line| {
+        let line = line.trim_start();
+        keys.iter().any(|key| line.strip_prefix(key.as_str()).is_some_and(|rest| rest.trim_start().starts_with(':')))
+    }).map(|index| index + 1)
 }
 
 impl From<Vec<Spec>> for Specs {
@@ -84,8 +209,13 @@ pub enum LoadSpecsFileError {
     OpenError(IOError),
     #[error("failed to parse symbol specs file {file_path}: {error}")]
     FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
-    #[error("invalid spec for symbol {symbol_name} in file {file_path}: {spec}")]
-    InvalidSymbolSpec { file_path: PathBuf, symbol_name: String, spec: String },
+    #[error("invalid spec `{spec}` for symbol {symbol_name} in file {}{}: {reason}",
+        .file_path.to_string_lossy(), .line.map(|line| format!(" line {line}")).unwrap_or_default())]
+    InvalidSymbolSpec { file_path: PathBuf, line: Option<usize>, symbol_name: String, spec: String, reason: InvalidSpecReason },
+    #[error("symbol specs file {file_path} has version {version} which is newer than the supported version {FILE_VERSION}")]
+    UnsupportedVersion { file_path: PathBuf, version: u32 },
+    #[error("failed to write migrated symbol specs file {file_path}: {error}")]
+    WriteError { file_path: PathBuf, error: IOError },
 }
 
 impl LoadSpecsFileError {
@@ -93,7 +223,94 @@ impl LoadSpecsFileError {
         Self::FileStructureError { file_path: file_path.as_ref().to_path_buf(), error }
     }
 
-    pub fn invalid_symbol_spec<P: AsRef<Path>>(file_path: P, symbol_name: &str, spec: &str) -> Self {
-        Self::InvalidSymbolSpec { file_path: file_path.as_ref().to_path_buf(), symbol_name: symbol_name.to_owned(), spec: spec.to_owned() }
+    pub fn invalid_symbol_spec<P: AsRef<Path>>(file_path: P, line: Option<usize>, symbol_name: &str, spec: &str, reason: InvalidSpecReason) -> Self {
+        Self::InvalidSymbolSpec { file_path: file_path.as_ref().to_path_buf(), line, symbol_name: symbol_name.to_owned(), spec: spec.to_owned(), reason }
+    }
+}
+
+/// Upgrades a symbol specs file to the current version keeping its comments, returns `None` if it is already current
+pub fn migrate_file<P: AsRef<Path>>(path: P) -> Result<Option<Migration>, LoadSpecsFileError> {
+    let version = SpecsFile::load(&path)?.version();
+    if version == FILE_VERSION {
+        return Ok(None);
+    }
+    let content = fs_err::read_to_string(&path)?;
+    let mut migrated = format!("version: {FILE_VERSION}\nsymbols:\n");
+    for line in content.lines().filter(|line| line.trim() != "---") {
+        match line.trim().is_empty() {
+            true => migrated.push('\n'),
+            false => migrated.push_str(&format!("  {line}\n")),
+        }
+    }
+    let migration = migrate::write_migrated(&path, version, FILE_VERSION, &migrated).map_err(|error| LoadSpecsFileError::WriteError { file_path: path.as_ref().to_path_buf(), error })?;
+    Ok(Some(migration))
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::migrate::backup_path;
+    use crate::osd::tile::{Kind as TileKind, Tile, container::symbol::Symbol};
+
+    use super::{migrate_file, parse_spec, InvalidSpecReason, LoadSpecsFileError, Spec, Specs, FILE_VERSION};
+
+    #[test]
+    fn spec_boundaries() {
+        assert_eq!(parse_spec("0x1FF:1"), Ok((0x1FF, 1)));
+        assert_eq!(parse_spec("0:512"), Ok((0, 512)));
+        assert_eq!(parse_spec("0x1FF:2"), Err(InvalidSpecReason::OutOfRange));
+        assert_eq!(parse_spec("99999999999999999999999:1"), Err(InvalidSpecReason::OutOfRange));
+        assert_eq!(parse_spec("10:99999999999999999999999"), Err(InvalidSpecReason::OutOfRange));
+        assert_eq!(parse_spec("10:0"), Err(InvalidSpecReason::ZeroSpan));
+        for spec in ["0xZZ:1", "0x:1", "-1:1", "12", "12:", ":3", " 1:1", "1:1x"] {
+            assert_eq!(parse_spec(spec), Err(InvalidSpecReason::Syntax), "{spec}");
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("specs.yaml");
+        std::fs::write(&path, "GPS: '0x1E:2'\n'mAh/km': '0xZZ:1'\n").unwrap();
+        match Specs::load_file(&path) {
+            Err(LoadSpecsFileError::InvalidSymbolSpec { line, symbol_name, reason, .. }) =>
+                assert_eq!((line, symbol_name.as_str(), reason), (Some(2), "mAh/km", InvalidSpecReason::Syntax)),
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn migrate_version_1() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("specs.yaml");
+        std::fs::write(&path, "---\n# arrows\nGPS: '0x1E:2'\nHOME: '5:1'\n").unwrap();
+        assert_eq!(Specs::load_file(&path).unwrap().len(), 2);
+
+        let migration = migrate_file(&path).unwrap().unwrap();
+        assert_eq!((migration.from_version, migration.to_version), (1, FILE_VERSION));
+        assert!(backup_path(&path, 1).is_file());
+        assert!(std::fs::read_to_string(&path).unwrap().contains("  # arrows"));
+        assert_eq!(Specs::load_file(&path).unwrap().find_by_name("GPS").unwrap().start_tile_index(), 0x1E);
+        assert!(migrate_file(&path).unwrap().is_none());
+
+        std::fs::write(&path, "version: 99\nsymbols: {}\n").unwrap();
+        assert!(Specs::load_file(&path).is_err());
+    }
+
+    #[test]
+    fn specs_from_symbols() {
+        let symbols = vec![
+            Symbol::new(TileKind::HD),
+            Symbol::try_from(vec![Tile::new(TileKind::HD); 3]).unwrap(),
+            Symbol::new(TileKind::HD),
+            Symbol::try_from(vec![Tile::new(TileKind::HD); 2]).unwrap(),
+        ];
+        let specs = Specs::from_symbols(&symbols);
+        assert_eq!(specs.iter().map(Spec::tile_index_range).collect::<Vec<_>>(), vec![1..4, 5..7]);
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("sym_specs.yaml");
+        std::fs::write(&path, specs.file_content()).unwrap();
+        let loaded_specs = Specs::load_file(&path).unwrap();
+        assert_eq!(loaded_specs.find_by_name("SYMBOL_0x05").unwrap().tile_index_range(), 5..7);
+        assert_eq!(loaded_specs.len(), 2);
     }
 }
\ No newline at end of file
@@ -0,0 +1,150 @@
+
+use std::fmt::Display;
+use std::ops::Range;
+
+use image::{GenericImage, Rgba};
+
+use crate::osd::tile::{Tile, container::uniq_tile_kind::UniqTileKind};
+use super::{ExtractError, Image, Symbol, spec::{Spec, Specs}};
+
+
+/// Difference between two symbol specifications files for a single symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added { name: String, range: Range<usize> },
+    Removed { name: String, range: Range<usize> },
+    /// the same tiles are now known under another name
+    Renamed { old_name: String, new_name: String, range: Range<usize> },
+    /// the symbol now starts at another index or spans another number of tiles
+    Moved { name: String, old_range: Range<usize>, new_range: Range<usize> },
+}
+
+impl Change {
+
+    pub fn old_range(&self) -> Option<&Range<usize>> {
+        match self {
+            Change::Added { .. } => None,
+            Change::Removed { range, .. } | Change::Renamed { range, .. } => Some(range),
+            Change::Moved { old_range, .. } => Some(old_range),
+        }
+    }
+
+    pub fn new_range(&self) -> Option<&Range<usize>> {
+        match self {
+            Change::Removed { .. } => None,
+            Change::Added { range, .. } | Change::Renamed { range, .. } => Some(range),
+            Change::Moved { new_range, .. } => Some(new_range),
+        }
+    }
+
+    fn sort_key(&self) -> usize {
+        self.old_range().or_else(|| self.new_range()).map_or(0, |range| range.start)
+    }
+
+}
+
+struct DisplayRange<'a>(&'a Range<usize>);
+
+impl Display for DisplayRange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{:02X}:{}", self.0.start, self.0.len())
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added { name, range } => write!(f, "+ {name} {}", DisplayRange(range)),
+            Change::Removed { name, range } => write!(f, "- {name} {}", DisplayRange(range)),
+            Change::Renamed { old_name, new_name, range } => write!(f, "~ {old_name} -> {new_name} {}", DisplayRange(range)),
+            Change::Moved { name, old_range, new_range } => write!(f, "~ {name} {} -> {}", DisplayRange(old_range), DisplayRange(new_range)),
+        }
+    }
+}
+
+fn named(specs: &Specs) -> Vec<(&str, Range<usize>)> {
+    specs.named_specs().into_iter().map(|spec| (spec.name().as_deref().unwrap(), spec.tile_index_range())).collect()
+}
+
+/// Lists the changes needed to go from the `old` specifications to the `new` ones ordered by tile index
+///
+/// Symbols are matched by name first, a removed symbol whose tiles are used by an added symbol is reported as renamed.
+pub fn diff(old: &Specs, new: &Specs) -> Vec<Change> {
+    let old_named = named(old);
+    let new_named = named(new);
+    let mut changes = vec![];
+
+    let mut added: Vec<(&str, Range<usize>)> = new_named.iter()
+        .filter(|(name, _)| ! old_named.iter().any(|(old_name, _)| old_name == name))
+        .cloned()
+        .collect();
+
+    for (name, old_range) in &old_named {
+        match new_named.iter().find(|(new_name, _)| new_name == name) {
+            Some((_, new_range)) => if new_range != old_range {
+                changes.push(Change::Moved { name: name.to_string(), old_range: old_range.clone(), new_range: new_range.clone() });
+            },
+            None => match added.iter().position(|(_, range)| range == old_range) {
+                Some(position) => {
+                    let (new_name, range) = added.remove(position);
+                    changes.push(Change::Renamed { old_name: name.to_string(), new_name: new_name.to_owned(), range });
+                },
+                None => changes.push(Change::Removed { name: name.to_string(), range: old_range.clone() }),
+            },
+        }
+    }
+
+    changes.extend(added.into_iter().map(|(name, range)| Change::Added { name: name.to_owned(), range }));
+    changes.sort_by_key(Change::sort_key);
+    changes
+}
+
+const VISUAL_DIFF_MARGIN: u32 = 4;
+
+fn render_range(tiles: &[Tile], range: &Range<usize>) -> Result<Image, ExtractError> {
+    Ok(Symbol::extract(tiles, &Spec::new(range.start, range.len()))?.generate_image())
+}
+
+/// Renders one row per change with the tiles designated by the old specifications on the left and the ones designated
+/// by the new specifications on the right, using `tiles` as reference font
+pub fn render_visual_diff(changes: &[Change], tiles: &[Tile]) -> Result<Image, ExtractError> {
+    let tile_dimensions = tiles.tile_kind().map_err(ExtractError::TileKindError)?.dimensions();
+    let max_span = changes.iter()
+        .flat_map(|change| [change.old_range(), change.new_range()])
+        .flatten()
+        .map(|range| range.len())
+        .max().unwrap_or(1) as u32;
+    let column_width = max_span * tile_dimensions.width + VISUAL_DIFF_MARGIN;
+    let row_height = tile_dimensions.height + VISUAL_DIFF_MARGIN;
+    let mut image = Image::from_pixel(2 * column_width + VISUAL_DIFF_MARGIN, changes.len().max(1) as u32 * row_height + VISUAL_DIFF_MARGIN,
+        Rgba([0x40, 0x40, 0x40, 255]));
+
+    for (row, change) in changes.iter().enumerate() {
+        let y = VISUAL_DIFF_MARGIN + row as u32 * row_height;
+        for (column, range) in [change.old_range(), change.new_range()].into_iter().enumerate() {
+            if let Some(range) = range {
+                image.copy_from(&render_range(tiles, range)?, VISUAL_DIFF_MARGIN + column as u32 * column_width, y).unwrap();
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::spec::{Spec, Specs};
+    use super::{diff, Change};
+
+    #[test]
+    fn changes() {
+        let old = Specs::from(vec![Spec::named("GPS", 0x1E, 2), Spec::named("mAh/km", 0x9D, 2), Spec::named("HOME", 0x10, 1)]);
+        let new = Specs::from(vec![Spec::named("GPS", 0x20, 2), Spec::named("MAH_KM", 0x9D, 2), Spec::named("WIND", 0x40, 3)]);
+        assert_eq!(diff(&old, &new), vec![
+            Change::Removed { name: "HOME".to_owned(), range: 0x10..0x11 },
+            Change::Moved { name: "GPS".to_owned(), old_range: 0x1E..0x20, new_range: 0x20..0x22 },
+            Change::Added { name: "WIND".to_owned(), range: 0x40..0x43 },
+            Change::Renamed { old_name: "mAh/km".to_owned(), new_name: "MAH_KM".to_owned(), range: 0x9D..0x9F },
+        ]);
+    }
+}
@@ -0,0 +1,149 @@
+
+//! Symbol specs generated from firmware headers defining the index of each glyph, like the `osd_symbols.h` of Betaflight
+//! and INAV
+//!
+//! Each `#define <prefix><NAME> <index>` line gives a single tile symbol named `<NAME>`. Ranges of glyphs given by a
+//! `<NAME>_START` index and a `<NAME>_COUNT` are merged into a single `<NAME>` symbol spanning `COUNT` tiles. Values may
+//! be decimal or hexadecimal numbers, possibly parenthesized, or the name of another define of the header.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use lazy_static::lazy_static;
+use parse_int::parse;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::osd::tile::index::TILE_COUNT;
+
+use super::spec::FILE_VERSION;
+
+
+/// Prefix of the symbol defines of the Betaflight and INAV headers
+pub const DEFAULT_PREFIX: &str = "SYM_";
+
+#[derive(Debug, From, Error)]
+pub enum HeaderSpecsError {
+    #[error("failed to read header file: {0}")]
+    ReadError(IOError),
+    #[error("no symbol defined with the `{prefix}` prefix in header file {file_path}")]
+    NoSymbols { file_path: PathBuf, prefix: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderSymbol {
+    pub name: String,
+    pub start_tile_index: usize,
+    pub span: usize,
+}
+
+// strips the comments, parentheses and integer suffixes around the value of a define
+fn define_value(value: &str) -> &str {
+    let value = value.split("//").next().unwrap().split("/*").next().unwrap().trim();
+    let value = value.trim_start_matches('(').trim_end_matches(')').trim();
+    value.trim_end_matches(['u', 'U', 'l', 'L'])
+}
+
+/// Parses the symbol defines of a header, the symbols are ordered by start tile index
+///
+/// Defines whose value is not a tile index are skipped with a warning.
+pub fn parse_header(content: &str, prefix: &str) -> Vec<HeaderSymbol> {
+    lazy_static! {
+        static ref DEFINE_RE: Regex = Regex::new(r"^\s*#\s*define\s+(?P<name>[A-Za-z_]\w*)\s+(?P<value>.+)$").unwrap();
+    }
+    let mut values: HashMap<String, usize> = HashMap::new();
+    let mut names = vec![];
+    for (line_index, line) in content.lines().enumerate() {
+        let captures = match DEFINE_RE.captures(line) {
+            Some(captures) => captures,
+            None => continue,
+        };
+        let name = match captures["name"].strip_prefix(prefix) {
+            Some(name) if ! name.is_empty() => name.to_owned(),
+            _ => continue,
+        };
+        let value = define_value(&captures["value"]);
+        let number = parse::<usize>(value).ok()
+            .or_else(|| value.strip_prefix(prefix).and_then(|referenced| values.get(referenced).copied()));
+        match number {
+            Some(number) => {
+                if values.insert(name.clone(), number).is_none() {
+                    names.push(name);
+                }
+            },
+            None => log::warn!("line {}: skipping {}{name}, `{value}` is not a tile index", line_index + 1, prefix),
+        }
+    }
+
+    let mut symbols = vec![];
+    for name in &names {
+        if let Some(range_name) = name.strip_suffix("_COUNT") {
+            if values.contains_key(&format!("{range_name}_START")) {
+                continue;
+            }
+        }
+        let (name, start_tile_index, span) = match name.strip_suffix("_START") {
+            Some(range_name) => match values.get(&format!("{range_name}_COUNT")) {
+                Some(count) => (range_name, values[name], *count),
+                None => (name.as_str(), values[name], 1),
+            },
+            None => (name.as_str(), values[name], 1),
+        };
+        if span == 0 || start_tile_index.saturating_add(span) > TILE_COUNT {
+            log::warn!("skipping {prefix}{name}, tiles {start_tile_index}..{} are out of the font", start_tile_index.saturating_add(span));
+            continue;
+        }
+        symbols.push(HeaderSymbol { name: name.to_owned(), start_tile_index, span });
+    }
+    symbols.sort_by_key(|symbol| symbol.start_tile_index);
+    symbols
+}
+
+/// Content of a symbol specs file defining the symbols parsed from a header
+pub fn specs_file_content(symbols: &[HeaderSymbol], header_path: &Path) -> String {
+    let mut content = format!("# generated from {}\nversion: {FILE_VERSION}\nsymbols:\n", header_path.to_string_lossy());
+    for symbol in symbols {
+        writeln!(content, "  {}: '0x{:02X}:{}'", symbol.name, symbol.start_tile_index, symbol.span).unwrap();
+    }
+    content
+}
+
+/// Generates the content of a symbol specs file from the symbol defines of a firmware header
+pub fn generate_specs_from_header<P: AsRef<Path>>(header_path: P, prefix: &str) -> Result<String, HeaderSpecsError> {
+    let content = fs_err::read_to_string(&header_path)?;
+    let symbols = parse_header(&content, prefix);
+    if symbols.is_empty() {
+        return Err(HeaderSpecsError::NoSymbols { file_path: header_path.as_ref().to_path_buf(), prefix: prefix.to_owned() });
+    }
+    Ok(specs_file_content(&symbols, header_path.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::container::symbol::spec::Specs;
+
+    use super::{parse_header, specs_file_content, HeaderSymbol, DEFAULT_PREFIX};
+
+    #[test]
+    fn header_symbols() {
+        let header = "#pragma once\n#define OSD_SYMBOLS_H\n#define SYM_RSSI 0x01 // RSSI\n#define SYM_LQ (0x02)\n\
+            #define SYM_AH_START 0x80U\n#define SYM_AH_COUNT 9\n#define SYM_LINK_QUALITY SYM_LQ\n#define SYM_MACRO(x) (x)\n\
+            #define SYM_BIG 0x200\n";
+        let symbols = parse_header(header, DEFAULT_PREFIX);
+        let symbol = |name: &str, start_tile_index, span| HeaderSymbol { name: name.to_owned(), start_tile_index, span };
+        assert_eq!(symbols, vec![symbol("RSSI", 1, 1), symbol("LQ", 2, 1), symbol("LINK_QUALITY", 2, 1), symbol("AH", 0x80, 9)]);
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("specs.yaml");
+        std::fs::write(&path, specs_file_content(&symbols, Path::new("osd_symbols.h"))).unwrap();
+        let specs = Specs::load_file(&path).unwrap();
+        assert_eq!(specs.find_by_name("AH").unwrap().tile_index_range(), 0x80..0x89);
+    }
+}
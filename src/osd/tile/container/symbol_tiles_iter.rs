@@ -35,11 +35,11 @@ impl<'a> Iterator for SymbolTilesIter<'a> {
 }
 
 pub trait IntoSymbolsTilesIter {
-    fn tiles_iter(&self) -> SymbolTilesIter;
+    fn tiles_iter(&self) -> SymbolTilesIter<'_>;
 }
 
 impl IntoSymbolsTilesIter for &[Symbol] {
-    fn tiles_iter(&self) -> SymbolTilesIter {
+    fn tiles_iter(&self) -> SymbolTilesIter<'_> {
         SymbolTilesIter::new(self)
     }
 }
@@ -0,0 +1,126 @@
+
+//! Index arrangement of an output collection differing from the one of its source
+//!
+//! The mapping file lists the source index of each reordered destination index, indices can be given in decimal, in
+//! hexadecimal with the `0x` prefix or as `<page>/<offset>`:
+//!
+//! ```yaml
+//! # destination: source
+//! 0x30: 0x130
+//! 0x130: 0x30
+//! ```
+//!
+//! The destinations which are not listed keep their tile.
+
+use std::collections::BTreeMap;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+use thiserror::Error;
+
+use super::transform_map::TileIndexOutOfBoundsError;
+
+use crate::osd::tile::{index::parse_index, Tile};
+
+
+#[derive(Debug, Error)]
+pub enum LoadTileOrderError {
+    #[error("failed to read tile order file {file_path}: {error}")]
+    ReadError { file_path: PathBuf, error: IOError },
+    #[error("failed to parse tile order file {file_path}: {error}")]
+    FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
+    #[error("invalid tile index `{index}` in tile order file {file_path}")]
+    InvalidIndex { file_path: PathBuf, index: String },
+}
+
+/// Source index of each reordered destination index
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TileOrder(BTreeMap<usize, usize>);
+
+fn value_index(value: &Value) -> Option<usize> {
+    match value {
+        Value::Number(number) => number.as_u64().map(|index| index as usize),
+        Value::String(index) => parse_index(index).ok(),
+        _ => None,
+    }
+}
+
+impl TileOrder {
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadTileOrderError> {
+        let file_path = path.as_ref().to_path_buf();
+        let content = fs_err::read_to_string(&path)
+            .map_err(|error| LoadTileOrderError::ReadError { file_path: file_path.clone(), error })?;
+        let entries: Mapping = match content.trim().is_empty() {
+            true => Mapping::new(),
+            false => serde_yaml::from_str(&content)
+                .map_err(|error| LoadTileOrderError::FileStructureError { file_path: file_path.clone(), error })?,
+        };
+        let index = |value: &Value| value_index(value).ok_or_else(|| LoadTileOrderError::InvalidIndex {
+            file_path: file_path.clone(),
+            index: serde_yaml::to_string(value).unwrap_or_default().trim().to_owned(),
+        });
+        let mut mapping = BTreeMap::new();
+        for (destination, source) in &entries {
+            mapping.insert(index(destination)?, index(source)?);
+        }
+        Ok(Self(mapping))
+    }
+
+    pub fn mapping(&self) -> &BTreeMap<usize, usize> {
+        &self.0
+    }
+
+    /// Arranges the tiles in the new order, the source tiles are taken before any of them is moved so that tiles can be
+    /// swapped
+    pub fn apply(&self, tiles: &[Tile]) -> Result<Vec<Tile>, TileIndexOutOfBoundsError> {
+        let tile_count = tiles.len();
+        let out_of_bounds = self.0.iter()
+            .flat_map(|(destination, source)| [*destination, *source])
+            .find(|index| *index >= tile_count);
+        if let Some(index) = out_of_bounds {
+            return Err(TileIndexOutOfBoundsError { index, tile_count });
+        }
+        let mut ordered = tiles.to_vec();
+        for (destination, source) in &self.0 {
+            ordered[*destination] = tiles[*source].clone();
+        }
+        Ok(ordered)
+    }
+
+}
+
+impl From<BTreeMap<usize, usize>> for TileOrder {
+    fn from(mapping: BTreeMap<usize, usize>) -> Self {
+        Self(mapping)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::TileOrder;
+
+    #[test]
+    fn swap_tiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let order_file = temp_dir.child("order.yaml");
+        std::fs::write(&order_file, "0: 0x2\n\"2\": 0\n").unwrap();
+        let order = TileOrder::load_file(&order_file).unwrap();
+
+        let mut tiles = vec![Tile::new(TileKind::SD); 3];
+        tiles[2].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let ordered = order.apply(&tiles).unwrap();
+        assert_eq!(ordered[0].as_raw(), tiles[2].as_raw());
+        assert_eq!(ordered[2].as_raw(), tiles[0].as_raw());
+        assert!(order.apply(&tiles[..2]).is_err());
+
+        std::fs::write(&order_file, "0: gps\n").unwrap();
+        assert!(TileOrder::load_file(&order_file).is_err());
+    }
+}
@@ -7,14 +7,15 @@ use getset::Getters;
 use strum::IntoEnumIterator;
 
 use crate::osd::tile::container::UniqTileKind;
-use crate::osd::tile::{Kind as TileKind, Tile};
+use crate::osd::tile::{Kind as TileKind, Tile, alpha_mode::AlphaMode, file_format::{FileFormat as TileFileFormat, SaveOptions as TileSaveOptions}};
 use crate::osd::tile::grid::{Grid as TileGrid, LoadError as GridLoadError};
 use super::save_to_bin_file::{SaveToBinFiles, SaveTilesToBinFileError};
 use super::save_to_grid::SaveToGridImage;
 use super::uniq_tile_kind::TileKindError;
 use super::{IntoTilesVec, ToSymbols};
-use super::load_tiles_from_dir::{load_tiles_from_dir, LoadTilesFromDirError};
-use super::save_tiles_to_dir::{SaveTilesToDir, SaveTilesToDirError};
+use super::load_tiles_from_dir::{load_tiles_from_dir_with_prefix, LoadTilesFromDirError};
+use super::save_tiles_to_dir::{save_tiles_to_dir_with_prefix, SaveTilesToDir, SaveTilesToDirError};
+use super::set_dir_layout::SetDirLayout;
 use super::symbol::set::Set as SymbolSet;
 use super::symbol::spec::Specs as SymbolSpecs;
 use crate::osd::tile::grid::SaveImageError as SaveGridImageError;
@@ -37,6 +38,8 @@ pub enum LoadFromTileGridsError {
 pub struct TileSet {
     pub(crate) sd_tiles: Vec<Tile>,
     pub(crate) hd_tiles: Vec<Tile>,
+    /// ident of the normalized files the set has been loaded from, used by default when saving to normalized files
+    pub(crate) ident: Option<String>,
 }
 
 impl TileSet {
@@ -52,13 +55,44 @@ impl TileSet {
     pub fn try_from_tiles(sd_tiles: Vec<Tile>, hd_tiles: Vec<Tile>) -> Result<Self, TileKindError> {
         Self::check_collection_kind(&sd_tiles, TileKind::SD)?;
         Self::check_collection_kind(&hd_tiles, TileKind::HD)?;
-        Ok(Self { sd_tiles, hd_tiles })
+        Ok(Self { sd_tiles, hd_tiles, ident: None })
+    }
+
+    /// Tile kinds without any tile, only partially loaded sets can have some
+    pub fn missing_tile_kinds(&self) -> Vec<TileKind> {
+        TileKind::iter().filter(|tile_kind| self[*tile_kind].is_empty()).collect()
+    }
+
+    /// Applies the alpha mode to every tile of the set, see [`Tile::apply_alpha_mode`]
+    pub fn apply_alpha_mode(&mut self, alpha_mode: AlphaMode) {
+        for tile in self.sd_tiles.iter_mut().chain(self.hd_tiles.iter_mut()) {
+            tile.apply_alpha_mode(alpha_mode);
+        }
+    }
+
+    pub fn with_ident(self, ident: Option<&str>) -> Self {
+        Self { ident: ident.map(str::to_owned), ..self }
+    }
+
+    // the ident given explicitly takes precedence over the one of the set
+    fn norm_ident<'a>(&'a self, ident: &Option<&'a str>) -> Option<&'a str> {
+        ident.or(self.ident.as_deref())
     }
 
     pub fn load_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize) -> Result<Self, LoadTileSetTilesFromDirError> {
-        let sd_tiles = load_tiles_from_dir(TileKind::SD.set_dir_path(&path), max_tiles)?;
-        let hd_tiles = load_tiles_from_dir(TileKind::HD.set_dir_path(&path), max_tiles)?;
-        Ok(Self::try_from_tiles(sd_tiles, hd_tiles)?)
+        Self::load_from_dir_with_layout(path, max_tiles, &SetDirLayout::default())
+    }
+
+    pub fn load_from_dir_with_layout<P: AsRef<Path>>(path: P, max_tiles: usize, layout: &SetDirLayout) -> Result<Self, LoadTileSetTilesFromDirError> {
+        let load = |tile_kind| load_tiles_from_dir_with_prefix(layout.tile_kind_dir(&path, tile_kind), layout.file_prefix(tile_kind), max_tiles, TileFileFormat::Png);
+        Ok(Self::try_from_tiles(load(TileKind::SD)?, load(TileKind::HD)?)?)
+    }
+
+    pub fn save_tiles_to_dir_with_layout<P: AsRef<Path>>(&self, path: P, layout: &SetDirLayout, options: &TileSaveOptions) -> Result<(), SaveTilesToDirError> {
+        for tile_kind in TileKind::iter().filter(|tile_kind| ! self[*tile_kind].is_empty()) {
+            save_tiles_to_dir_with_prefix(&self[tile_kind], layout.tile_kind_dir(&path, tile_kind), layout.file_prefix(tile_kind), options)?;
+        }
+        Ok(())
     }
 
     pub fn load_from_tile_grids<P: AsRef<Path>>(sd_grid_path: P, hd_grid_path: P) -> Result<Self, LoadFromTileGridsError> {
@@ -70,28 +104,51 @@ impl TileSet {
     pub fn into_symbol_set(self, specs: &SymbolSpecs) -> Result<SymbolSet, TileKindError> {
         Ok(SymbolSet {
             sd_symbols: self.sd_tiles.to_symbols(specs)?,
-            hd_symbols: self.hd_tiles.to_symbols(specs)?
+            hd_symbols: self.hd_tiles.to_symbols(specs)?,
+            ident: self.ident,
         })
     }
 
     pub fn save_to_bin_files<P: AsRef<Path>>(&self, sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P) -> Result<(), SaveTilesToBinFileError> {
-        self.sd_tiles.save_to_bin_files(sd_path, sd_2_path)?;
-        self.hd_tiles.save_to_bin_files(hd_path, hd_2_path)
+        if ! self.sd_tiles.is_empty() {
+            self.sd_tiles.save_to_bin_files(sd_path, sd_2_path)?;
+        }
+        if ! self.hd_tiles.is_empty() {
+            self.hd_tiles.save_to_bin_files(hd_path, hd_2_path)?;
+        }
+        Ok(())
     }
 
     pub fn save_to_bin_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveTilesToBinFileError> {
-        self.sd_tiles.save_to_bin_files_norm(&dir, ident)?;
-        self.hd_tiles.save_to_bin_files_norm(&dir, ident)
+        let ident = &self.norm_ident(ident);
+        if ! self.sd_tiles.is_empty() {
+            self.sd_tiles.save_to_bin_files_norm(&dir, ident)?;
+        }
+        if ! self.hd_tiles.is_empty() {
+            self.hd_tiles.save_to_bin_files_norm(&dir, ident)?;
+        }
+        Ok(())
     }
 
     pub fn save_to_grids<P: AsRef<Path>>(&self, sd_path: P, hd_path: P) -> Result<(), SaveGridImageError> {
-        self.sd_tiles.save_to_grid_image(sd_path)?;
-        self.hd_tiles.save_to_grid_image(hd_path)
+        if ! self.sd_tiles.is_empty() {
+            self.sd_tiles.save_to_grid_image(sd_path)?;
+        }
+        if ! self.hd_tiles.is_empty() {
+            self.hd_tiles.save_to_grid_image(hd_path)?;
+        }
+        Ok(())
     }
 
     pub fn save_to_grids_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveGridImageError> {
-        self.sd_tiles.save_to_grid_image_norm(&dir, ident)?;
-        self.hd_tiles.save_to_grid_image_norm(&dir, ident)
+        let ident = &self.norm_ident(ident);
+        if ! self.sd_tiles.is_empty() {
+            self.sd_tiles.save_to_grid_image_norm(&dir, ident)?;
+        }
+        if ! self.hd_tiles.is_empty() {
+            self.hd_tiles.save_to_grid_image_norm(&dir, ident)?;
+        }
+        Ok(())
     }
 
 }
@@ -108,11 +165,8 @@ impl Index<TileKind> for TileSet {
 }
 
 impl SaveTilesToDir for TileSet {
-    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToDirError> {
-        for tile_kind in TileKind::iter() {
-            self[tile_kind].save_tiles_to_dir(tile_kind.set_dir_path(&path))?;
-        }
-        Ok(())
+    fn save_tiles_to_dir_with_options<P: AsRef<Path>>(&self, path: P, options: &TileSaveOptions) -> Result<(), SaveTilesToDirError> {
+        self.save_tiles_to_dir_with_layout(path, &SetDirLayout::default(), options)
     }
 }
 
@@ -120,7 +174,8 @@ impl From<SymbolSet> for TileSet {
     fn from(symbol_set: SymbolSet) -> Self {
         Self {
             sd_tiles: symbol_set.sd_symbols.into_tiles_vec(),
-            hd_tiles: symbol_set.hd_symbols.into_tiles_vec()
+            hd_tiles: symbol_set.hd_symbols.into_tiles_vec(),
+            ident: symbol_set.ident,
         }
     }
 }
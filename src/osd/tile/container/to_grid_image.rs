@@ -0,0 +1,104 @@
+
+//! Grid images of collections generated in memory
+//!
+//! This is the supported way for applications like GUIs to show previews of collections without going through
+//! temporary files: the images are the ones the `save_to_grid*` methods would write.
+
+use crate::osd::tile::{grid::Grid as TileGrid, Image, Tile};
+use super::symbol::{set::Set as SymbolSet, Symbol};
+use super::symbol_tiles_iter::IntoSymbolsTilesIter;
+use super::tile_set::TileSet;
+use super::uniq_tile_kind::TileKindError;
+
+
+pub trait ToGridImage {
+    /// Fails if the collection is empty or contains both kinds of tiles
+    fn to_grid_image(&self) -> Result<Image, TileKindError>;
+}
+
+impl ToGridImage for TileGrid {
+    fn to_grid_image(&self) -> Result<Image, TileKindError> {
+        self.generate_image()
+    }
+}
+
+impl ToGridImage for [Tile] {
+    fn to_grid_image(&self) -> Result<Image, TileKindError> {
+        TileGrid::from(self).generate_image()
+    }
+}
+
+impl ToGridImage for Vec<Tile> {
+    fn to_grid_image(&self) -> Result<Image, TileKindError> {
+        self.as_slice().to_grid_image()
+    }
+}
+
+/// The tiles of the symbols are laid out in the grid at their tile indices
+impl ToGridImage for [Symbol] {
+    fn to_grid_image(&self) -> Result<Image, TileKindError> {
+        self.tiles_iter().cloned().collect::<Vec<_>>().to_grid_image()
+    }
+}
+
+impl ToGridImage for Vec<Symbol> {
+    fn to_grid_image(&self) -> Result<Image, TileKindError> {
+        self.as_slice().to_grid_image()
+    }
+}
+
+/// Grid images of both kinds of a set, `None` for the kinds without any tile in partially loaded sets
+#[derive(Debug, Clone)]
+pub struct GridImages {
+    pub sd: Option<Image>,
+    pub hd: Option<Image>,
+}
+
+fn non_empty_grid_image<T: ToGridImage + ?Sized>(collection: &T, is_empty: bool) -> Result<Option<Image>, TileKindError> {
+    match is_empty {
+        true => Ok(None),
+        false => collection.to_grid_image().map(Some),
+    }
+}
+
+impl TileSet {
+    pub fn to_grid_images(&self) -> Result<GridImages, TileKindError> {
+        Ok(GridImages {
+            sd: non_empty_grid_image(self.sd_tiles.as_slice(), self.sd_tiles.is_empty())?,
+            hd: non_empty_grid_image(self.hd_tiles.as_slice(), self.hd_tiles.is_empty())?,
+        })
+    }
+}
+
+impl SymbolSet {
+    pub fn to_grid_images(&self) -> Result<GridImages, TileKindError> {
+        Ok(GridImages {
+            sd: non_empty_grid_image(self.sd_symbols.as_slice(), self.sd_symbols.is_empty())?,
+            hd: non_empty_grid_image(self.hd_symbols.as_slice(), self.hd_symbols.is_empty())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::{container::{symbol::Symbol, tile_set::TileSet}, grid::Grid as TileGrid, Kind as TileKind, Tile};
+
+    use super::ToGridImage;
+
+    #[test]
+    fn grid_images() {
+        let mut tiles = vec![Tile::new(TileKind::SD); 4];
+        tiles[2].put_pixel(1, 1, image::Rgba([255, 255, 255, 255]));
+        let image = tiles.to_grid_image().unwrap();
+        assert_eq!(image.as_raw(), TileGrid::from(tiles.as_slice()).generate_image().unwrap().as_raw());
+
+        let symbols = vec![Symbol::try_from(tiles[0..3].to_vec()).unwrap(), Symbol::from(tiles[3].clone())];
+        assert_eq!(symbols.to_grid_image().unwrap().as_raw(), image.as_raw());
+
+        let tile_set = TileSet::try_from_tiles(tiles, vec![Tile::new(TileKind::HD); 4]).unwrap();
+        let grid_images = tile_set.to_grid_images().unwrap();
+        assert_eq!(grid_images.sd.unwrap().as_raw(), image.as_raw());
+        assert!(grid_images.hd.is_some());
+        assert!(Vec::<Tile>::new().to_grid_image().is_err());
+    }
+}
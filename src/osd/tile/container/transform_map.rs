@@ -0,0 +1,93 @@
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use thiserror::Error;
+
+use crate::osd::tile::{index::parse_index, Tile, transform::Transform};
+
+
+#[derive(Debug, Error)]
+pub enum LoadTransformMapError {
+    #[error("failed to read transform map file {file_path}: {error}")]
+    ReadError { file_path: PathBuf, error: IOError },
+    #[error("invalid line {line} in transform map file {file_path}: expected `<source index> <destination index> <transform>`")]
+    InvalidLine { file_path: PathBuf, line: usize },
+}
+
+#[derive(Debug, Error)]
+#[error("transform map refers to tile {index} but the collection only contains {tile_count} tiles")]
+pub struct TileIndexOutOfBoundsError {
+    pub index: usize,
+    pub tile_count: usize,
+}
+
+/// Destination tile generated by transforming a source tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransformMapping {
+    pub source_index: usize,
+    pub destination_index: usize,
+    pub transform: Transform,
+}
+
+/// List of tiles to generate from other tiles of the same collection, e.g. to create mirrored arrows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransformMap(Vec<TransformMapping>);
+
+impl TransformMap {
+
+    /// Loads a file with one `<source index> <destination index> <transform>` mapping per line, indices can be given
+    /// in hexadecimal with the `0x` prefix or as `<page>/<offset>` and lines starting with `#` are ignored
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadTransformMapError> {
+        let file_path = path.as_ref().to_path_buf();
+        let content = fs_err::read_to_string(&path)
+            .map_err(|error| LoadTransformMapError::ReadError { file_path: file_path.clone(), error })?;
+        let mut mappings = vec![];
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = || LoadTransformMapError::InvalidLine { file_path: file_path.clone(), line: line_index + 1 };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                return Err(invalid_line());
+            }
+            mappings.push(TransformMapping {
+                source_index: parse_index(fields[0]).map_err(|_| invalid_line())?,
+                destination_index: parse_index(fields[1]).map_err(|_| invalid_line())?,
+                transform: Transform::from_str(fields[2], false).map_err(|_| invalid_line())?,
+            });
+        }
+        Ok(Self(mappings))
+    }
+
+    pub fn mappings(&self) -> &[TransformMapping] {
+        &self.0
+    }
+
+    /// Replaces the destination tiles by the transformed source tiles, the source tiles are taken before any mapping is
+    /// applied so that the order of the mappings does not matter
+    pub fn apply(&self, tiles: &mut [Tile]) -> Result<(), TileIndexOutOfBoundsError> {
+        let tile_count = tiles.len();
+        let out_of_bounds = self.0.iter()
+            .flat_map(|mapping| [mapping.source_index, mapping.destination_index])
+            .find(|index| *index >= tile_count);
+        if let Some(index) = out_of_bounds {
+            return Err(TileIndexOutOfBoundsError { index, tile_count });
+        }
+        let transformed: Vec<Tile> = self.0.iter().map(|mapping| tiles[mapping.source_index].transformed(mapping.transform)).collect();
+        for (mapping, tile) in self.0.iter().zip(transformed) {
+            tiles[mapping.destination_index] = tile;
+        }
+        Ok(())
+    }
+
+}
+
+impl From<Vec<TransformMapping>> for TransformMap {
+    fn from(mappings: Vec<TransformMapping>) -> Self {
+        Self(mappings)
+    }
+}
@@ -0,0 +1,186 @@
+
+//! Alternative styles of the digit glyphs
+//!
+//! The digits are drawn from strokes at the indices of the ASCII digits, `0x30` to `0x39`, where the ArduPilot and
+//! INAV fonts have them. They are white with a black outline like the stock glyphs.
+
+use clap::ValueEnum;
+use image::{imageops, Rgba};
+use strum::Display;
+
+use super::{Image, Kind as TileKind, Tile};
+use super::container::uniq_tile_kind::{TileKindError, UniqTileKind};
+
+
+pub const DIGITS_START_INDEX: usize = 0x30;
+pub const DIGIT_COUNT: usize = 10;
+
+const INK: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const OUTLINE: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const PREVIEW_BACKGROUND: Rgba<u8> = Rgba([128, 128, 128, 255]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, ValueEnum)]
+pub enum DigitStyle {
+    #[strum(serialize = "bold")]
+    Bold,
+    #[value(name = "seven-segment")]
+    #[strum(serialize = "seven-segment")]
+    SevenSegment,
+    #[strum(serialize = "thin")]
+    Thin,
+}
+
+type Point = (f32, f32);
+
+// strokes of the digits on a grid 4 units wide and 6 units high, each stroke is a polyline
+const DIGIT_STROKES: [&[&[Point]]; DIGIT_COUNT] = [
+    &[&[(1., 0.), (3., 0.), (4., 1.), (4., 5.), (3., 6.), (1., 6.), (0., 5.), (0., 1.), (1., 0.)]],
+    &[&[(1., 1.), (2., 0.), (2., 6.)], &[(1., 6.), (3., 6.)]],
+    &[&[(0., 1.), (1., 0.), (3., 0.), (4., 1.), (4., 2.), (0., 6.), (4., 6.)]],
+    &[&[(0., 0.), (4., 0.), (2., 2.5), (3., 2.5), (4., 3.5), (4., 5.), (3., 6.), (1., 6.), (0., 5.)]],
+    &[&[(3., 6.), (3., 0.), (0., 4.), (4., 4.)]],
+    &[&[(4., 0.), (0., 0.), (0., 2.5), (3., 2.5), (4., 3.5), (4., 5.), (3., 6.), (0., 6.)]],
+    &[&[(3.5, 0.), (1., 0.), (0., 1.), (0., 5.), (1., 6.), (3., 6.), (4., 5.), (4., 4.), (3., 3.), (0., 3.)]],
+    &[&[(0., 0.), (4., 0.), (1., 6.)]],
+    &[&[(1., 3.), (0., 2.), (0., 1.), (1., 0.), (3., 0.), (4., 1.), (4., 2.), (3., 3.), (1., 3.), (0., 4.), (0., 5.), (1., 6.), (3., 6.), (4., 5.), (4., 4.), (3., 3.)]],
+    &[&[(4., 3.), (1., 3.), (0., 2.), (0., 1.), (1., 0.), (3., 0.), (4., 1.), (4., 5.), (3., 6.), (0.5, 6.)]],
+];
+
+// segments a to g of the seven-segment digits
+const SEGMENTS: [(Point, Point); 7] = [
+    ((0., 0.), (4., 0.)),
+    ((4., 0.), (4., 3.)),
+    ((4., 3.), (4., 6.)),
+    ((0., 6.), (4., 6.)),
+    ((0., 3.), (0., 6.)),
+    ((0., 0.), (0., 3.)),
+    ((0., 3.), (4., 3.)),
+];
+
+// bit 0 is segment a
+const DIGIT_SEGMENTS: [u8; DIGIT_COUNT] = [0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, 0x7F, 0x6F];
+
+// units between the lit segments of the seven-segment digits
+const SEGMENT_GAP: f32 = 0.35;
+
+fn distance_to_segment(point: Point, (start, end): (Point, Point)) -> f32 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0. {
+        0.
+    } else {
+        (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length_squared).clamp(0., 1.)
+    };
+    let (nearest_x, nearest_y) = (start.0 + t * dx, start.1 + t * dy);
+    ((point.0 - nearest_x).powi(2) + (point.1 - nearest_y).powi(2)).sqrt()
+}
+
+fn shortened((start, end): (Point, Point), by: f32) -> (Point, Point) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = (dx / length * by, dy / length * by);
+    ((start.0 + ux, start.1 + uy), (end.0 - ux, end.1 - uy))
+}
+
+fn digit_segments(digit: usize, style: DigitStyle) -> Vec<(Point, Point)> {
+    match style {
+        DigitStyle::SevenSegment => SEGMENTS.iter().enumerate()
+            .filter(|(segment, _)| DIGIT_SEGMENTS[digit] & (1 << segment) != 0)
+            .map(|(_, segment)| shortened(*segment, SEGMENT_GAP))
+            .collect(),
+        DigitStyle::Bold | DigitStyle::Thin => DIGIT_STROKES[digit].iter()
+            .flat_map(|polyline| polyline.windows(2).map(|points| (points[0], points[1])))
+            .collect(),
+    }
+}
+
+impl DigitStyle {
+
+    // stroke width in pixels for tiles `tile_width` pixels wide
+    fn stroke_width(&self, tile_width: u32) -> f32 {
+        let tile_width = tile_width as f32;
+        match self {
+            Self::Bold => tile_width / 6.,
+            Self::SevenSegment => tile_width / 9.,
+            Self::Thin => (tile_width / 18.).max(1.5),
+        }
+    }
+
+    /// Tile of the digit drawn in this style, `digit` is from 0 to 9
+    pub fn render_digit(&self, digit: usize, tile_kind: TileKind) -> Tile {
+        assert!(digit < DIGIT_COUNT, "digit must be from 0 to 9");
+        let dimensions = tile_kind.dimensions();
+        let (width, height) = (dimensions.width() as f32, dimensions.height() as f32);
+        let outline_width = (dimensions.width() / 18).max(1) as f32;
+        let stroke_radius = self.stroke_width(dimensions.width()) / 2.;
+
+        // the glyph box is centered, leaving room for the strokes and the outline around it
+        let margin = stroke_radius + outline_width + 1.;
+        let scale = ((width - 2. * margin) / 4.).min((height - 2. * margin) / 6.);
+        let origin = ((width - 4. * scale) / 2., (height - 6. * scale) / 2.);
+        let segments: Vec<(Point, Point)> = digit_segments(digit, *self).into_iter()
+            .map(|(start, end)| {
+                let to_pixels = |(x, y): Point| (origin.0 + x * scale, origin.1 + y * scale);
+                (to_pixels(start), to_pixels(end))
+            })
+            .collect();
+
+        let mut tile = Tile::new(tile_kind);
+        for (x, y, pixel) in tile.enumerate_pixels_mut() {
+            let center = (x as f32 + 0.5, y as f32 + 0.5);
+            let distance = segments.iter().map(|segment| distance_to_segment(center, *segment)).fold(f32::INFINITY, f32::min);
+            if distance <= stroke_radius {
+                *pixel = INK;
+            } else if distance <= stroke_radius + outline_width {
+                *pixel = OUTLINE;
+            }
+        }
+        tile
+    }
+
+    /// Replaces the digits of the collection with the digits of this style, the collection is padded with empty tiles
+    /// if it ends before the last digit
+    pub fn apply(&self, tiles: &[Tile]) -> Result<Vec<Tile>, TileKindError> {
+        let tile_kind = tiles.tile_kind()?;
+        let mut tiles = tiles.to_vec();
+        if tiles.len() < DIGITS_START_INDEX + DIGIT_COUNT {
+            tiles.resize(DIGITS_START_INDEX + DIGIT_COUNT, Tile::new(tile_kind));
+        }
+        for digit in 0..DIGIT_COUNT {
+            tiles[DIGITS_START_INDEX + digit] = self.render_digit(digit, tile_kind);
+        }
+        Ok(tiles)
+    }
+
+    /// Image of the digits from 0 to 9 in a row on a grey background
+    pub fn preview(&self, tile_kind: TileKind) -> Image {
+        let dimensions = tile_kind.dimensions();
+        let mut image = Image::from_pixel(dimensions.width() * DIGIT_COUNT as u32, dimensions.height(), PREVIEW_BACKGROUND);
+        for digit in 0..DIGIT_COUNT {
+            imageops::overlay(&mut image, self.render_digit(digit, tile_kind).image(), (digit as u32 * dimensions.width()) as i64, 0);
+        }
+        image
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{DigitStyle, DIGITS_START_INDEX};
+
+    #[test]
+    fn digits() {
+        let tiles = DigitStyle::SevenSegment.apply(&vec![Tile::new(TileKind::HD); 256]).unwrap();
+        assert_eq!(tiles.len(), 256);
+        assert!(tiles[..DIGITS_START_INDEX].iter().all(Tile::is_empty));
+        // 8 lights every segment, 1 only two of them
+        let ink = |tile: &Tile| tile.pixels().filter(|pixel| pixel.0 == [255, 255, 255, 255]).count();
+        assert!(ink(&tiles[DIGITS_START_INDEX + 8]) > 2 * ink(&tiles[DIGITS_START_INDEX + 1]));
+
+        let bold = DigitStyle::Bold.render_digit(0, TileKind::SD);
+        assert!(ink(&bold) > ink(&DigitStyle::Thin.render_digit(0, TileKind::SD)));
+        assert_eq!(DigitStyle::Thin.preview(TileKind::SD).dimensions(), (360, 54));
+    }
+}
@@ -0,0 +1,203 @@
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use image::{
+    DynamicImage,
+    ImageError,
+    codecs::png::CompressionType,
+};
+use strum::{EnumIter, IntoEnumIterator};
+use thiserror::Error;
+
+use super::{Tile, LoadError};
+use crate::{image_encode::{self, ImageEncodeOptions}, io_context::IoContext};
+
+
+#[derive(Debug, Error)]
+#[error("invalid tile file extension `{0}`: expected png, rgba or ppm")]
+pub struct InvalidFileFormatError(String);
+
+/// Format of the files holding individual tiles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter)]
+pub enum FileFormat {
+    /// PNG image
+    #[default]
+    Png,
+    /// raw RGBA bytes without any header, the tile kind is deduced from the file size
+    Rgba,
+    /// binary PPM image: the alpha channel is dropped when saving and loaded tiles are fully opaque
+    Ppm,
+}
+
+impl FileFormat {
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Png => "png",
+            FileFormat::Rgba => "rgba",
+            FileFormat::Ppm => "ppm",
+        }
+    }
+
+    /// Name of the file holding the tile with the given index in a tile directory, e.g. 011.png
+    pub fn tile_file_name(&self, index: usize) -> PathBuf {
+        PathBuf::from(format!("{index:03}.{}", self.extension()))
+    }
+
+    /// Name without zero padding used by some third-party tools, e.g. 11.png
+    pub fn unpadded_tile_file_name(&self, index: usize) -> PathBuf {
+        PathBuf::from(format!("{index}.{}", self.extension()))
+    }
+
+    /// Index of the tile held by a file of a tile directory if its name is a padded or unpadded tile file name
+    pub fn tile_file_index(&self, file_name: &str) -> Option<usize> {
+        let (stem, extension) = file_name.rsplit_once('.')?;
+        if extension != self.extension() || stem.is_empty() || ! stem.chars().all(|char| char.is_ascii_digit()) {
+            return None;
+        }
+        let index = stem.parse().ok()?;
+        let file_name = Path::new(file_name);
+        if file_name == self.tile_file_name(index) || file_name == self.unpadded_tile_file_name(index) {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+}
+
+impl FromStr for FileFormat {
+    type Err = InvalidFileFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::iter().find(|format| format.extension() == s).ok_or_else(|| InvalidFileFormatError(s.to_owned()))
+    }
+}
+
+/// Trade-off between the size of the PNG files and the time taken to encode them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PngCompression {
+    Fast,
+    #[default]
+    Default,
+    Best,
+}
+
+impl From<PngCompression> for CompressionType {
+    fn from(compression: PngCompression) -> Self {
+        match compression {
+            PngCompression::Fast => CompressionType::Fast,
+            PngCompression::Default => CompressionType::Default,
+            PngCompression::Best => CompressionType::Best,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveOptions {
+    pub format: FileFormat,
+    /// only used for the PNG format
+    pub encode: ImageEncodeOptions,
+}
+
+impl From<FileFormat> for SaveOptions {
+    fn from(format: FileFormat) -> Self {
+        Self { format, ..Default::default() }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("failed to write tile image {file_path}: {error}")]
+    ImageError { file_path: PathBuf, error: ImageError },
+    #[error("failed to write tile file {file_path}: {error}")]
+    FileError { file_path: PathBuf, error: std::io::Error },
+}
+
+impl Tile {
+
+    pub fn load_file<P: AsRef<Path>>(path: P, format: FileFormat) -> Result<Self, LoadError> {
+        Self::load_file_with_context(path, format, &IoContext::default())
+    }
+
+    pub fn load_file_with_context<P: AsRef<Path>>(path: P, format: FileFormat, context: &IoContext) -> Result<Self, LoadError> {
+        match format {
+            FileFormat::Png | FileFormat::Ppm => Self::load_image_file_with_context(path, context),
+            FileFormat::Rgba => {
+                let bytes = context.retry_io(path.as_ref(), || fs_err::read(&path))
+                    .map_err(|error| LoadError::FileError { file_path: path.as_ref().to_path_buf(), error })?;
+                let size = bytes.len() as u64;
+                Self::try_from(bytes).map_err(|_| LoadError::InvalidRawSize { file_path: path.as_ref().to_path_buf(), size })
+            },
+        }
+    }
+
+    pub fn save_file<P: AsRef<Path>>(&self, path: P, format: FileFormat) -> Result<(), SaveError> {
+        self.save_file_with_options(path, &format.into())
+    }
+
+    pub fn save_file_with_options<P: AsRef<Path>>(&self, path: P, options: &SaveOptions) -> Result<(), SaveError> {
+        self.save_file_with_context(path, options, &IoContext::default())
+    }
+
+    pub fn save_file_with_context<P: AsRef<Path>>(&self, path: P, options: &SaveOptions, context: &IoContext) -> Result<(), SaveError> {
+        let file_path = path.as_ref().to_path_buf();
+        match options.format {
+            FileFormat::Png => {
+                let file = context.retry_io(path.as_ref(), || fs_err::File::create(path.as_ref())).map_err(|error| SaveError::FileError { file_path: file_path.clone(), error })?;
+                image_encode::write_png(std::io::BufWriter::new(file), &options.encode, (self.width(), self.height()), png::ColorType::Rgba, self.as_raw(), &[])
+                    .map_err(|error| SaveError::ImageError { file_path, error: image_encode::image_error(error) })
+            },
+            FileFormat::Rgba => context.retry_io(path.as_ref(), || fs_err::write(&path, self.as_raw())).map_err(|error| SaveError::FileError { file_path, error }),
+            FileFormat::Ppm => DynamicImage::ImageRgba8(self.image().clone()).to_rgb8().save(&path)
+                .map_err(|error| SaveError::ImageError { file_path, error }),
+        }
+    }
+
+}
+
+impl LoadError {
+    /// The tile file does not exist, which is not an error for sparse tile directories
+    pub fn because_file_is_missing(&self) -> bool {
+        use crate::image::ReadError as ImageReadError;
+        let error = match self {
+            LoadError::FileError { file_path: _, error } => error,
+            LoadError::ImageReadError(ImageReadError::OpenError { file_path: _, error }) => error,
+            _ => return false,
+        };
+        error.kind() == std::io::ErrorKind::NotFound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::FileFormat;
+
+    #[test]
+    fn raw_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut tile = Tile::new(TileKind::SD);
+        tile.put_pixel(3, 4, image::Rgba([10, 20, 30, 40]));
+        let path = temp_dir.child("003.rgba");
+        tile.save_file(&path, FileFormat::Rgba).unwrap();
+        let loaded = Tile::load_file(&path, FileFormat::Rgba).unwrap();
+        assert_eq!(loaded.kind(), TileKind::SD);
+        assert_eq!(loaded.as_raw(), tile.as_raw());
+        assert!(Tile::load_file(temp_dir.child("004.rgba"), FileFormat::Rgba).unwrap_err().because_file_is_missing());
+    }
+
+    #[test]
+    fn tile_file_index() {
+        assert_eq!(FileFormat::Png.tile_file_index("011.png"), Some(11));
+        assert_eq!(FileFormat::Png.tile_file_index("11.png"), Some(11));
+        assert_eq!(FileFormat::Png.tile_file_index("0011.png"), None);
+        assert_eq!(FileFormat::Png.tile_file_index("011.ppm"), None);
+        assert_eq!(FileFormat::Png.tile_file_index("symbol.png"), None);
+    }
+}
@@ -1,28 +1,42 @@
 
+pub mod annotation;
+pub mod pages;
+pub mod review;
+
 use std::ops::Index;
 use std::path::{Path, PathBuf};
 
 use derive_more::{Deref, Display, From, IntoIterator};
 use thiserror::Error;
-use getset::Getters;
-use image::{ImageBuffer, Rgba, GenericImage, GenericImageView};
+use getset::{CopyGetters, Getters};
+use image::{ImageBuffer, Rgba, GenericImageView};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use strum::IntoEnumIterator;
 
 use super::{
     Tile,
     Kind as TileKind,
+    KindSwapPolicy,
+    SwappedKindsError,
     container::{
+        symbol::spec::Specs as SymbolSpecs,
         tile_set::TileSet,
         uniq_tile_kind::{UniqTileKind, TileKindError},
     },
+    index::format_index,
 };
 
 use crate::{
+    background::Background,
     create_path::{create_path, CreatePathError},
+    diagnostics::DetectionEvidence,
+    image_encode::{self, ImageEncodeOptions},
+    io_context::IoContext,
     dimensions,
     osd::tile,
     image::{
-        read_image_file,
+        read_image_file_with_context,
         WriteImageFile,
         ReadError as ImageLoadError,
         WriteError as ImageWriteError,
@@ -39,6 +53,7 @@ pub enum LoadError {
     ImageLoadError(ImageLoadError),
     InvalidImageDimensions(InvalidImageDimensionsError),
     TileKindError(TileKindError),
+    SwappedKinds(SwappedKindsError),
 }
 
 #[derive(Debug, From, Error, Display)]
@@ -46,6 +61,14 @@ pub enum SaveImageError {
     CreatePathError(CreatePathError),
     ImageWriteError(ImageWriteError),
     TileKindError(TileKindError),
+    CroppedImageWriteError(CroppedImageWriteError),
+}
+
+#[derive(Debug, Error)]
+#[error("failed to write cropped grid image {file_path}: {error}")]
+pub struct CroppedImageWriteError {
+    file_path: PathBuf,
+    error: png::EncodingError,
 }
 
 pub type ImageDimensions = dimensions::Dimensions<u32>;
@@ -53,36 +76,59 @@ pub type ImageDimensions = dimensions::Dimensions<u32>;
 const WIDTH: usize = 16;
 const SEPARATOR_THICKNESS: u32 = 2;
 
+// PNG text chunk recording the number of tiles of a grid saved cropped to its populated rows
+const TILE_COUNT_METADATA_KEY: &str = "hd_fpv_osd_font_tool:tile_count";
+
 pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
 
-#[derive(Deref, IntoIterator)]
-pub struct Grid(Vec<Tile>);
+/// Layout of the tiles in a grid image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct GridStyle {
+    /// number of tiles per row
+    width: usize,
+    /// thickness in pixels of the separators between tiles
+    separator_thickness: u32,
+}
 
-impl Grid {
+impl GridStyle {
 
-    pub fn index_to_grid_coordinates(index: usize) -> (usize, usize) {
-        (index % WIDTH, index / WIDTH)
+    pub const fn new(width: usize, separator_thickness: u32) -> Self {
+        Self { width, separator_thickness }
     }
 
-    fn grid_coordinates_to_index(x: usize, y: usize) -> usize {
-        assert!(x < WIDTH);
-        x + y * WIDTH
+    pub fn index_to_grid_coordinates(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
     }
 
-    fn image_tile_position(tile_kind: &tile::Kind, x: u32, y: u32) -> (u32, u32) {
+    fn grid_coordinates_to_index(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width);
+        x + y * self.width
+    }
+
+    fn image_tile_position(&self, tile_kind: &tile::Kind, x: u32, y: u32) -> (u32, u32) {
         let tile_dimensions = tile_kind.dimensions();
         (
-            x * (SEPARATOR_THICKNESS + tile_dimensions.width()),
-            y * (SEPARATOR_THICKNESS + tile_dimensions.height())
+            x * (self.separator_thickness + tile_dimensions.width()),
+            y * (self.separator_thickness + tile_dimensions.height())
         )
     }
 
-    pub fn image_tile_kind_and_grid_height(image_dimensions: ImageDimensions) -> Result<(tile::Kind, usize), InvalidImageDimensionsError> {
+    pub fn grid_height(&self, tile_count: usize) -> usize {
+        let h_full_width = tile_count / self.width;
+        if tile_count % self.width == 0 {
+            h_full_width
+        } else {
+            h_full_width + 1
+        }
+    }
+
+    pub fn image_tile_kind_and_grid_height(&self, image_dimensions: ImageDimensions) -> Result<(tile::Kind, usize), InvalidImageDimensionsError> {
         for tile_kind in tile::Kind::iter() {
-            let expected_width = (WIDTH as u32 - 1) * SEPARATOR_THICKNESS + WIDTH as u32 * tile_kind.dimensions().width;
+            let expected_width = (self.width as u32 - 1) * self.separator_thickness + self.width as u32 * tile_kind.dimensions().width;
             if image_dimensions.width == expected_width {
-                if (image_dimensions.height - tile_kind.dimensions().height) % (tile_kind.dimensions().height + SEPARATOR_THICKNESS) == 0 {
-                    let grid_height = (image_dimensions.height - tile_kind.dimensions().height) / (tile_kind.dimensions().height + SEPARATOR_THICKNESS) + 1;
+                if (image_dimensions.height - tile_kind.dimensions().height) % (tile_kind.dimensions().height + self.separator_thickness) == 0 {
+                    let grid_height = (image_dimensions.height - tile_kind.dimensions().height) / (tile_kind.dimensions().height + self.separator_thickness) + 1;
                     return Ok((tile_kind, grid_height as usize));
                 } else {
                     return Err(InvalidImageDimensionsError(image_dimensions))
@@ -92,22 +138,71 @@ impl Grid {
         Err(InvalidImageDimensionsError(image_dimensions))
     }
 
+    pub fn image_dimensions(&self, tile_kind: &tile::Kind, height: usize) -> ImageDimensions {
+        let tile_dimensions = tile_kind.dimensions();
+        ImageDimensions {
+            width: self.width as u32 * tile_dimensions.width() + (self.width as u32 - 1) * self.separator_thickness,
+            height: height as u32 * tile_dimensions.height() + (height as u32 - 1) * self.separator_thickness
+        }
+    }
+
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        Self::new(WIDTH, SEPARATOR_THICKNESS)
+    }
+}
+
+#[derive(Deref, IntoIterator)]
+pub struct Grid(Vec<Tile>);
+
+impl Grid {
+
+    pub fn index_to_grid_coordinates(index: usize) -> (usize, usize) {
+        GridStyle::default().index_to_grid_coordinates(index)
+    }
+
+    pub fn image_tile_kind_and_grid_height(image_dimensions: ImageDimensions) -> Result<(tile::Kind, usize), InvalidImageDimensionsError> {
+        GridStyle::default().image_tile_kind_and_grid_height(image_dimensions)
+    }
+
     pub fn load_from_image<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
-        let image = read_image_file(&path)?;
+        Self::load_from_image_with_style(path, &GridStyle::default())
+    }
+
+    pub fn load_from_image_with_style<P: AsRef<Path>>(path: P, style: &GridStyle) -> Result<Self, LoadError> {
+        Self::load_from_image_with_context(path, style, &IoContext::default())
+    }
+
+    pub fn load_from_image_with_context<P: AsRef<Path>>(path: P, style: &GridStyle, context: &IoContext) -> Result<Self, LoadError> {
+        let image = read_image_file_with_context(&path, context)?;
         let (img_dim_width, img_dim_height) = image.dimensions();
-        let (tile_kind, grid_height) = Self::image_tile_kind_and_grid_height(ImageDimensions { width: img_dim_width, height: img_dim_height })?;
-        log::info!("detected {tile_kind} kind of tiles in a {WIDTH}x{grid_height} grid in {}", path.as_ref().to_string_lossy());
+        let (tile_kind, grid_height) = style.image_tile_kind_and_grid_height(ImageDimensions { width: img_dim_width, height: img_dim_height })?;
+        context.emit_detection(&path, tile_kind, DetectionEvidence::GridImageDimensions {
+            dimensions: ImageDimensions { width: img_dim_width, height: img_dim_height },
+            grid_width: style.width(),
+            grid_height,
+            separator_thickness: style.separator_thickness(),
+        });
         let tile_dimensions = tile_kind.dimensions();
-        let mut tiles_container = Vec::with_capacity(WIDTH * grid_height);
+        let mut tiles_container = Vec::with_capacity(style.width() * grid_height);
 
         for y in 0..grid_height {
-            for x in 0..WIDTH {
-                let (tile_pos_x, tile_pos_y) = Self::image_tile_position(&tile_kind, x as u32, y as u32);
+            for x in 0..style.width() {
+                let (tile_pos_x, tile_pos_y) = style.image_tile_position(&tile_kind, x as u32, y as u32);
                 let tile_view = image.view(tile_pos_x, tile_pos_y, tile_dimensions.width, tile_dimensions.height).to_image();
                 tiles_container.push(Tile::try_from(tile_view.clone()).unwrap());
             }
         }
 
+        // re-expand grids which have been cropped to their populated rows
+        if let Some(tile_count) = read_tile_count_metadata(&path) {
+            if tile_count > tiles_container.len() {
+                tiles_container.resize(tile_count, Tile::new(tile_kind));
+            }
+        }
+
         Ok(Self(tiles_container))
     }
 
@@ -115,33 +210,42 @@ impl Grid {
         Self::load_from_image(normalized_image_file_path(dir, tile_kind, ident))
     }
 
-    fn image_dimensions(tile_kind: &tile::Kind, height: usize) -> ImageDimensions {
-        let tile_dimensions = tile_kind.dimensions();
-        ImageDimensions {
-            width: WIDTH as u32 * tile_dimensions.width() + (WIDTH as u32 - 1) * SEPARATOR_THICKNESS,
-            height: height as u32 * tile_dimensions.height() + (height as u32 - 1) * SEPARATOR_THICKNESS
-        }
+    pub fn load_from_image_norm_with_style<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, style: &GridStyle) -> Result<Self, LoadError> {
+        Self::load_from_image_with_style(normalized_image_file_path(dir, tile_kind, ident), style)
     }
 
     pub fn height(&self) -> usize {
-        let h_full_width = self.0.len() / WIDTH;
-        if self.0.len() % WIDTH == 0 {
-            h_full_width
-        } else {
-            h_full_width + 1
-        }
+        GridStyle::default().grid_height(self.0.len())
     }
 
     pub fn generate_image(&self) -> Result<Image, TileKindError> {
+        self.generate_image_with_style(&GridStyle::default())
+    }
+
+    pub fn generate_image_with_style(&self, style: &GridStyle) -> Result<Image, TileKindError> {
         let tile_kind = self.tile_kind()?;
-        let img_dim = Self::image_dimensions(&tile_kind, self.height());
+        let tile_dimensions = tile_kind.dimensions();
+        let img_dim = style.image_dimensions(&tile_kind, style.grid_height(self.0.len()));
         let mut image = Image::from_pixel(img_dim.width(), img_dim.height(), Rgba::from([0, 0, 0, 255]));
 
-        for (index, tile) in self.0.iter().enumerate() {
-            let (x, y) = Self::index_to_grid_coordinates(index);
-            let (tile_x_position, tile_y_position) = Self::image_tile_position(&tile_kind, x as u32, y as u32);
-            image.copy_from(tile.image(), tile_x_position, tile_y_position).unwrap();
-        }
+        // each row of the grid is composed in its own band of the image, in parallel with the `rayon` feature, a band
+        // being the pixel rows of the tiles followed by the separator below them
+        let image_row_bytes = img_dim.width() as usize * 4;
+        let tile_row_bytes = tile_dimensions.width() as usize * 4;
+        let band_bytes = (tile_dimensions.height() + style.separator_thickness()) as usize * image_row_bytes;
+        let compose_band = |(band, row_tiles): (&mut [u8], &[Tile])| {
+            for (x, tile) in row_tiles.iter().enumerate() {
+                let (tile_x_position, _) = style.image_tile_position(&tile_kind, x as u32, 0);
+                for (tile_y, tile_row) in tile.as_raw().chunks(tile_row_bytes).enumerate() {
+                    let offset = tile_y * image_row_bytes + tile_x_position as usize * 4;
+                    band[offset..offset + tile_row_bytes].copy_from_slice(tile_row);
+                }
+            }
+        };
+        #[cfg(feature = "rayon")]
+        image.par_chunks_mut(band_bytes).zip(self.0.par_chunks(style.width())).for_each(compose_band);
+        #[cfg(not(feature = "rayon"))]
+        image.chunks_mut(band_bytes).zip(self.0.chunks(style.width())).for_each(compose_band);
 
         Ok(image)
     }
@@ -150,18 +254,109 @@ impl Grid {
         Ok(normalized_image_file_name(self.tile_kind()?, ident))
     }
 
+    /// HTML page displaying the grid image with an image map showing the index and symbol name of the hovered tile
+    ///
+    /// `image_src` is the URL of the grid image relative to the HTML file.
+    pub fn html_image_map(&self, image_src: &str, style: &GridStyle, symbol_specs: Option<&SymbolSpecs>) -> Result<String, TileKindError> {
+        let tile_kind = self.tile_kind()?;
+        let tile_dimensions = tile_kind.dimensions();
+        let mut html = format!(concat!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{image_src}</title>\n</head>\n<body>\n",
+            "<img src=\"{image_src}\" usemap=\"#tiles\" alt=\"{image_src}\">\n<map name=\"tiles\">\n"
+        ), image_src = html_escape(image_src));
+        for index in 0..self.len() {
+            let (x, y) = style.index_to_grid_coordinates(index);
+            let (left, top) = style.image_tile_position(&tile_kind, x as u32, y as u32);
+            let symbol_name = symbol_specs
+                .and_then(|specs| specs.iter().find(|spec| spec.tile_index_range().contains(&index)))
+                .and_then(|spec| spec.name().as_deref());
+            let title = match symbol_name {
+                Some(symbol_name) => format!("{}: {}", format_index(index), html_escape(symbol_name)),
+                None => format_index(index),
+            };
+            html.push_str(&format!("<area shape=\"rect\" coords=\"{left},{top},{},{}\" title=\"{title}\" alt=\"{title}\">\n",
+                left + tile_dimensions.width(), top + tile_dimensions.height()));
+        }
+        html.push_str("</map>\n</body>\n</html>\n");
+        Ok(html)
+    }
+
     pub fn normalized_image_file_path<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<PathBuf, TileKindError> {
         Ok(normalized_image_file_path(dir, self.tile_kind()?, ident))
     }
 
     pub fn save_image<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveImageError> {
-        self.generate_image()?.write_image_file(path)?;
+        self.save_image_with_style(path, &GridStyle::default(), &ImageEncodeOptions::default())
+    }
+
+    pub fn save_image_with_style<P: AsRef<Path>>(&self, path: P, style: &GridStyle, encode_options: &ImageEncodeOptions) -> Result<(), SaveImageError> {
+        self.generate_image_with_style(style)?.write_image_file_with_options(path, encode_options)?;
+        Ok(())
+    }
+
+    /// Grid without its trailing rows containing only empty tiles, at least one row is kept
+    pub fn cropped_to_populated_rows(&self, style: &GridStyle) -> Self {
+        let populated_tile_count = self.0.iter().rposition(|tile| ! tile.is_empty()).map_or(1, |index| index + 1);
+        let kept_tile_count = (style.grid_height(populated_tile_count) * style.width()).min(self.0.len());
+        Self(self.0[..kept_tile_count].to_vec())
+    }
+
+    /// Saves only the rows containing non-empty tiles, the number of tiles is recorded in the image metadata so that
+    /// the grid is re-expanded to its original size when loaded
+    pub fn save_image_cropped_with_style<P: AsRef<Path>>(&self, path: P, style: &GridStyle, encode_options: &ImageEncodeOptions) -> Result<(), SaveImageError> {
+        let image = self.cropped_to_populated_rows(style).generate_image_with_style(style)?;
+        write_png_with_text_chunks(&path, &image, &[(TILE_COUNT_METADATA_KEY, self.0.len().to_string())], encode_options)
+            .map_err(|error| CroppedImageWriteError { file_path: path.as_ref().to_path_buf(), error })?;
+        Ok(())
+    }
+
+    /// Saves the grid image composited over a background, the saved image is meant for viewing and cannot be loaded back losslessly
+    pub fn save_image_with_background<P: AsRef<Path>>(&self, path: P, style: &GridStyle, background: &Background, encode_options: &ImageEncodeOptions)
+            -> Result<(), SaveImageError> {
+        background.composite(&self.generate_image_with_style(style)?).write_image_file_with_options(path, encode_options)?;
+        Ok(())
+    }
+
+    /// Saves the grid image with the tiles of the symbols of the specs outlined and labeled, composited over the
+    /// background if any, the saved image is meant for viewing and cannot be loaded back losslessly
+    pub fn save_annotated_image<P: AsRef<Path>>(&self, path: P, style: &GridStyle, background: Option<&Background>, symbol_specs: &SymbolSpecs,
+            encode_options: &ImageEncodeOptions) -> Result<(), SaveImageError> {
+        let image = self.generate_image_with_style(style)?;
+        let mut image = match background {
+            Some(background) => background.composite(&image),
+            None => image,
+        };
+        annotation::annotate_symbols(&mut image, self.tile_kind()?, self.0.len(), style, symbol_specs);
+        image.write_image_file_with_options(path, encode_options)?;
+        Ok(())
+    }
+
+    /// Saves the review sheet of the grid with the notes marked on their tiles and listed below the grid, composited
+    /// over the background if any, see [`review`]
+    pub fn save_review_sheet<P: AsRef<Path>>(&self, path: P, style: &GridStyle, background: Option<&Background>, notes: &[review::ReviewNote]) -> Result<(), SaveImageError> {
+        let image = self.generate_image_with_style(style)?;
+        let image = match background {
+            Some(background) => background.composite(&image),
+            None => image,
+        };
+        review::review_sheet(&image, self.tile_kind()?, self.0.len(), style, notes).write_image_file(path)?;
         Ok(())
     }
 
     pub fn save_image_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveImageError> {
+        self.save_image_norm_with_style(dir, ident, &GridStyle::default(), &ImageEncodeOptions::default())
+    }
+
+    pub fn save_image_norm_with_background<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, style: &GridStyle, background: &Background,
+            encode_options: &ImageEncodeOptions) -> Result<(), SaveImageError> {
+        create_path(&dir)?;
+        self.save_image_with_background(self.normalized_image_file_path(&dir, ident)?, style, background, encode_options)
+    }
+
+    pub fn save_image_norm_with_style<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, style: &GridStyle, encode_options: &ImageEncodeOptions)
+            -> Result<(), SaveImageError> {
         create_path(&dir)?;
-        self.save_image(self.normalized_image_file_path(&dir, ident)?)
+        self.save_image_with_style(self.normalized_image_file_path(&dir, ident)?, style, encode_options)
     }
 
 }
@@ -170,7 +365,7 @@ impl Index<(usize, usize)> for Grid {
     type Output = Tile;
 
     fn index(&self, index: (usize, usize)) -> &Self::Output {
-        &self.0[Self::grid_coordinates_to_index(index.0, index.1)]
+        &self.0[GridStyle::default().grid_coordinates_to_index(index.0, index.1)]
     }
 }
 
@@ -198,6 +393,29 @@ pub fn normalized_image_file_name(tile_kind: TileKind, ident: &Option<&str>) ->
     PathBuf::from(format!("grid{ident}{tile_kind_str}.png"))
 }
 
+/// Writes an RGBA PNG image with uncompressed text chunks holding metadata
+pub(crate) fn write_png_with_text_chunks<P: AsRef<Path>>(path: P, image: &Image, text_chunks: &[(&str, String)], encode_options: &ImageEncodeOptions)
+        -> Result<(), png::EncodingError> {
+    let file = fs_err::File::create(path.as_ref())?;
+    image_encode::write_png(std::io::BufWriter::new(file), encode_options, image.dimensions(), png::ColorType::Rgba, image.as_raw(), text_chunks)
+}
+
+/// Text of the uncompressed text chunk of a PNG image with the given keyword
+pub(crate) fn read_png_text_chunk<P: AsRef<Path>>(path: P, keyword: &str) -> Option<String> {
+    let file = fs_err::File::open(path.as_ref()).ok()?;
+    let reader = png::Decoder::new(std::io::BufReader::new(file)).read_info().ok()?;
+    let text_chunk = reader.info().uncompressed_latin1_text.iter().find(|chunk| chunk.keyword == keyword)?;
+    Some(text_chunk.text.clone())
+}
+
+fn read_tile_count_metadata<P: AsRef<Path>>(path: P) -> Option<usize> {
+    read_png_text_chunk(path, TILE_COUNT_METADATA_KEY)?.parse().ok()
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
 pub fn normalized_image_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> PathBuf {
     [dir.as_ref().to_path_buf(), normalized_image_file_name(tile_kind, ident)].into_iter().collect()
 }
@@ -219,34 +437,179 @@ impl Set {
         Ok(())
     }
 
-    pub fn load_from_images<P: AsRef<Path>>(sd_grid_image_path: P, hd_grid_image_path: P) -> Result<Self, LoadError> {
-        let sd_grid = Grid::load_from_image(sd_grid_image_path)?;
+    pub fn try_from_grids(sd_grid: Grid, hd_grid: Grid) -> Result<Self, TileKindError> {
         Self::check_grid_kind(&sd_grid, TileKind::SD)?;
-        let hd_grid = Grid::load_from_image(hd_grid_image_path)?;
         Self::check_grid_kind(&hd_grid, TileKind::HD)?;
         Ok(Self { sd_grid, hd_grid })
     }
 
+    pub fn from_tile_set(tile_set: TileSet) -> Self {
+        Self { sd_grid: Grid(tile_set.sd_tiles), hd_grid: Grid(tile_set.hd_tiles) }
+    }
+
+    pub fn load_from_images<P: AsRef<Path>>(sd_grid_image_path: P, hd_grid_image_path: P) -> Result<Self, LoadError> {
+        Self::load_from_images_with_style(sd_grid_image_path, hd_grid_image_path, &GridStyle::default())
+    }
+
+    pub fn load_from_images_with_style<P: AsRef<Path>>(sd_grid_image_path: P, hd_grid_image_path: P, style: &GridStyle) -> Result<Self, LoadError> {
+        Self::load_from_images_with_swap_policy(sd_grid_image_path, hd_grid_image_path, style, KindSwapPolicy::Reject)
+    }
+
+    pub fn load_from_images_with_swap_policy<P: AsRef<Path>>(sd_grid_image_path: P, hd_grid_image_path: P, style: &GridStyle, swap_policy: KindSwapPolicy) -> Result<Self, LoadError> {
+        let sd_grid = Grid::load_from_image_with_style(&sd_grid_image_path, style)?;
+        let hd_grid = Grid::load_from_image_with_style(&hd_grid_image_path, style)?;
+        if sd_grid.tile_kind()? == TileKind::HD && hd_grid.tile_kind()? == TileKind::SD {
+            return match swap_policy {
+                KindSwapPolicy::Reject => Err(SwappedKindsError::new(&sd_grid_image_path, &hd_grid_image_path).into()),
+                KindSwapPolicy::AutoSwap => {
+                    log::warn!("SD and HD grids are swapped, using {} as the SD grid and {} as the HD grid",
+                        hd_grid_image_path.as_ref().to_string_lossy(), sd_grid_image_path.as_ref().to_string_lossy());
+                    Ok(Self { sd_grid: hd_grid, hd_grid: sd_grid })
+                },
+            };
+        }
+        Ok(Self::try_from_grids(sd_grid, hd_grid)?)
+    }
+
     pub fn load_from_images_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<Self, LoadError> {
-        let sd_grid = Grid::load_from_image_norm(&dir, TileKind::SD, ident)?;
-        Self::check_grid_kind(&sd_grid, TileKind::SD)?;
-        let hd_grid = Grid::load_from_image_norm(&dir, TileKind::HD, ident)?;
-        Self::check_grid_kind(&hd_grid, TileKind::HD)?;
-        Ok(Self { sd_grid, hd_grid })
+        Self::load_from_images_norm_with_style(dir, ident, &GridStyle::default())
+    }
+
+    pub fn load_from_images_norm_with_style<P: AsRef<Path>>(dir: P, ident: &Option<&str>, style: &GridStyle) -> Result<Self, LoadError> {
+        Self::load_from_images_norm_with_swap_policy(dir, ident, style, KindSwapPolicy::Reject)
+    }
+
+    pub fn load_from_images_norm_with_swap_policy<P: AsRef<Path>>(dir: P, ident: &Option<&str>, style: &GridStyle, swap_policy: KindSwapPolicy) -> Result<Self, LoadError> {
+        Self::load_from_images_with_swap_policy(
+            normalized_image_file_path(&dir, TileKind::SD, ident),
+            normalized_image_file_path(&dir, TileKind::HD, ident),
+            style,
+            swap_policy
+        )
     }
 
     pub fn save_images<P: AsRef<Path>>(&self, sd_grid_path: P, hd_grid_path: P) -> Result<(), SaveImageError> {
-        self.sd_grid.save_image(sd_grid_path)?;
-        self.hd_grid.save_image(hd_grid_path)
+        self.save_images_with_style(sd_grid_path, hd_grid_path, &GridStyle::default(), &ImageEncodeOptions::default())
+    }
+
+    pub fn save_images_with_style<P: AsRef<Path>>(&self, sd_grid_path: P, hd_grid_path: P, style: &GridStyle, encode_options: &ImageEncodeOptions)
+            -> Result<(), SaveImageError> {
+        if ! self.sd_grid.is_empty() {
+            self.sd_grid.save_image_with_style(sd_grid_path, style, encode_options)?;
+        }
+        if ! self.hd_grid.is_empty() {
+            self.hd_grid.save_image_with_style(hd_grid_path, style, encode_options)?;
+        }
+        Ok(())
     }
 
     pub fn save_images_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveImageError> {
-        self.sd_grid.save_image_norm(&dir, ident)?;
-        self.hd_grid.save_image_norm(&dir, ident)
+        self.save_images_norm_with_style(dir, ident, &GridStyle::default(), &ImageEncodeOptions::default())
+    }
+
+    pub fn save_images_norm_with_style<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, style: &GridStyle, encode_options: &ImageEncodeOptions)
+            -> Result<(), SaveImageError> {
+        if ! self.sd_grid.is_empty() {
+            self.sd_grid.save_image_norm_with_style(&dir, ident, style, encode_options)?;
+        }
+        if ! self.hd_grid.is_empty() {
+            self.hd_grid.save_image_norm_with_style(&dir, ident, style, encode_options)?;
+        }
+        Ok(())
+    }
+
+    pub fn save_images_with_background<P: AsRef<Path>>(&self, sd_grid_path: P, hd_grid_path: P, style: &GridStyle, background: &Background,
+            encode_options: &ImageEncodeOptions) -> Result<(), SaveImageError> {
+        if ! self.sd_grid.is_empty() {
+            self.sd_grid.save_image_with_background(sd_grid_path, style, background, encode_options)?;
+        }
+        if ! self.hd_grid.is_empty() {
+            self.hd_grid.save_image_with_background(hd_grid_path, style, background, encode_options)?;
+        }
+        Ok(())
+    }
+
+    pub fn save_images_norm_with_background<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, style: &GridStyle, background: &Background,
+            encode_options: &ImageEncodeOptions) -> Result<(), SaveImageError> {
+        if ! self.sd_grid.is_empty() {
+            self.sd_grid.save_image_norm_with_background(&dir, ident, style, background, encode_options)?;
+        }
+        if ! self.hd_grid.is_empty() {
+            self.hd_grid.save_image_norm_with_background(&dir, ident, style, background, encode_options)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_tile_set(&self) -> TileSet {
+        TileSet { sd_tiles: self.sd_grid.to_vec(), hd_tiles: self.hd_grid.to_vec(), ident: None }
     }
 
     pub fn into_tile_set(self) -> TileSet {
-        TileSet { sd_tiles: self.sd_grid.0, hd_tiles: self.hd_grid.0 }
+        TileSet { sd_tiles: self.sd_grid.0, hd_tiles: self.hd_grid.0, ident: None }
     }
 
-}
\ No newline at end of file
+}
+
+impl Index<TileKind> for Set {
+    type Output = Grid;
+
+    fn index(&self, tile_kind: TileKind) -> &Self::Output {
+        match tile_kind {
+            TileKind::SD => &self.sd_grid,
+            TileKind::HD => &self.hd_grid,
+        }
+    }
+}
+
+impl From<TileSet> for Set {
+    fn from(tile_set: TileSet) -> Self {
+        Self::from_tile_set(tile_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use image::{GenericImageView, Rgba};
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{Grid, GridStyle};
+
+    fn numbered_tiles(kind: TileKind, count: usize) -> Vec<Tile> {
+        (0..count).map(|index| {
+            let mut tile = Tile::new(kind);
+            tile.put_pixel(0, 0, Rgba([index as u8, (index >> 8) as u8, 0, 255]));
+            tile.put_pixel(kind.dimensions().width() - 1, kind.dimensions().height() - 1, Rgba([255, 255, 255, 255]));
+            tile
+        }).collect()
+    }
+
+    #[test]
+    fn generate_image_places_tiles() {
+        let style = GridStyle::new(4, 2);
+        let tiles = numbered_tiles(TileKind::SD, 10);
+        let image = Grid(tiles.clone()).generate_image_with_style(&style).unwrap();
+        for (index, tile) in tiles.iter().enumerate() {
+            let (x, y) = style.index_to_grid_coordinates(index);
+            let (left, top) = style.image_tile_position(&TileKind::SD, x as u32, y as u32);
+            let dimensions = TileKind::SD.dimensions();
+            assert!(image.view(left, top, dimensions.width(), dimensions.height()).to_image().as_raw() == tile.as_raw(), "tile {index} is misplaced");
+        }
+        // separator after the first tile and missing tiles of the last row
+        assert_eq!(*image.get_pixel(TileKind::SD.dimensions().width(), 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(image.width() - 1, image.height() - 1), Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `cargo test --release -- --ignored --nocapture generate_image_benchmark`"]
+    fn generate_image_benchmark() {
+        let grid = Grid(numbered_tiles(TileKind::HD, 512));
+        let start = Instant::now();
+        for _ in 0..100 {
+            grid.generate_image().unwrap();
+        }
+        println!("generated 512 HD tiles grid image in {:?} on average", start.elapsed() / 100);
+    }
+}
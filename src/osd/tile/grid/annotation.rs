@@ -0,0 +1,222 @@
+
+//! Outlines and labels drawn over grid images to show which tiles make up the symbols of the symbol specs
+//!
+//! The tiles of each symbol are outlined together in a color of their own, the outline following the group when it wraps
+//! to the next row of the grid. The name of the symbol is written once, in the top left corner of its first tile, with a
+//! small built-in 3x5 pixel font: letters are drawn upper case and characters without a glyph as `?`.
+
+use image::Rgba;
+
+use super::{GridStyle, Image};
+use crate::osd::tile::{Kind as TileKind, container::symbol::spec::{Spec as SymbolSpec, Specs as SymbolSpecs}};
+
+
+/// colors the outlines of consecutive symbols cycle through
+pub(super) const OUTLINE_COLORS: [Rgba<u8>; 6] = [
+    Rgba([255, 64, 64, 255]),
+    Rgba([64, 224, 64, 255]),
+    Rgba([64, 160, 255, 255]),
+    Rgba([255, 208, 0, 255]),
+    Rgba([224, 64, 255, 255]),
+    Rgba([0, 224, 224, 255]),
+];
+pub(super) const LABEL_BACKGROUND_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+const GLYPH_WIDTH: u32 = 3;
+pub(super) const GLYPH_HEIGHT: u32 = 5;
+/// horizontal distance between the start of two characters of a label
+pub(super) const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+/// distance of the label from the top left corner of the first tile, inside the outline
+const LABEL_OFFSET: u32 = 2;
+
+// rows of the glyph from top to bottom, the most significant of the 3 bits being the left pixel
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match character.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => [0b111, 0b001, 0b010, 0b000, 0b010],
+    }
+}
+
+pub(super) fn fill_rect(image: &mut Image, left: u32, top: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for y in top..(top + height).min(image.height()) {
+        for x in left..(left + width).min(image.width()) {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+/// Number of characters of a text fitting in `width` pixels
+pub(super) fn fitting_character_count(text: &str, width: u32) -> usize {
+    ((width + 1) / GLYPH_ADVANCE).min(text.chars().count() as u32) as usize
+}
+
+/// Draws the first `character_count` characters of a text in white with its top left corner at `left`, `top`, the
+/// pixels out of the image are dropped
+pub(super) fn draw_text(image: &mut Image, left: u32, top: u32, text: &str, character_count: usize) {
+    for (character_index, character) in text.chars().take(character_count).enumerate() {
+        let character_left = left + character_index as u32 * GLYPH_ADVANCE;
+        for (row_index, row) in glyph(character).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                let (x, y) = (character_left + column, top + row_index as u32);
+                if row & (1 << (GLYPH_WIDTH - 1 - column)) != 0 && x < image.width() && y < image.height() {
+                    image.put_pixel(x, y, TEXT_COLOR);
+                }
+            }
+        }
+    }
+}
+
+struct SymbolOutliner<'a> {
+    style: &'a GridStyle,
+    tile_kind: TileKind,
+    tile_count: usize,
+}
+
+impl<'a> SymbolOutliner<'a> {
+
+    fn tile_position(&self, index: usize) -> (u32, u32) {
+        let (x, y) = self.style.index_to_grid_coordinates(index);
+        self.style.image_tile_position(&self.tile_kind, x as u32, y as u32)
+    }
+
+    // each tile of the symbol gets the edges which do not face another tile of the symbol, the edges are extended
+    // over the separators between the tiles of the symbol so that the outline is continuous
+    fn outline(&self, image: &mut Image, spec: &SymbolSpec, color: Rgba<u8>) {
+        let tile_dimensions = self.tile_kind.dimensions();
+        let (width, height) = (tile_dimensions.width(), tile_dimensions.height());
+        let separator = self.style.separator_thickness();
+        let tile_range = spec.start_tile_index()..spec.end_tile_index().min(self.tile_count);
+        let in_symbol = |index: Option<usize>| index.is_some_and(|index| tile_range.contains(&index));
+        for index in tile_range.clone() {
+            let (x, _) = self.style.index_to_grid_coordinates(index);
+            let (left, top) = self.tile_position(index);
+            let left_neighbor = (x > 0).then(|| index - 1);
+            let right_neighbor = (x + 1 < self.style.width()).then(|| index + 1);
+            let top_neighbor = index.checked_sub(self.style.width());
+            let bottom_neighbor = Some(index + self.style.width());
+            let extended_width = if in_symbol(right_neighbor) { width + separator } else { width };
+            let extended_height = if in_symbol(bottom_neighbor) { height + separator } else { height };
+            if ! in_symbol(top_neighbor) {
+                fill_rect(image, left, top, extended_width, 1, color);
+            }
+            if ! in_symbol(bottom_neighbor) {
+                fill_rect(image, left, top + height - 1, extended_width, 1, color);
+            }
+            if ! in_symbol(left_neighbor) {
+                fill_rect(image, left, top, 1, extended_height, color);
+            }
+            if ! in_symbol(right_neighbor) {
+                fill_rect(image, left + width - 1, top, 1, extended_height, color);
+            }
+        }
+    }
+
+    // the label is truncated to the width of the tiles of the symbol on the row of its first tile
+    fn label(&self, image: &mut Image, spec: &SymbolSpec, name: &str) {
+        let tile_dimensions = self.tile_kind.dimensions();
+        let (first_x, _) = self.style.index_to_grid_coordinates(spec.start_tile_index());
+        let first_row_tile_count = (self.style.width() - first_x).min(spec.span()) as u32;
+        let first_row_width = first_row_tile_count * (tile_dimensions.width() + self.style.separator_thickness()) - self.style.separator_thickness();
+        let max_label_width = first_row_width.saturating_sub(2 * LABEL_OFFSET);
+        let character_count = fitting_character_count(name, max_label_width);
+        if character_count == 0 {
+            return;
+        }
+        let (left, top) = self.tile_position(spec.start_tile_index());
+        let (label_left, label_top) = (left + LABEL_OFFSET, top + LABEL_OFFSET);
+        fill_rect(image, label_left - 1, label_top - 1, character_count as u32 * GLYPH_ADVANCE + 1, GLYPH_HEIGHT + 2, LABEL_BACKGROUND_COLOR);
+        draw_text(image, label_left, label_top, name, character_count);
+    }
+
+}
+
+/// Outlines the tiles of each symbol of the specs present in a grid image of `tile_count` tiles and writes the name of
+/// the named symbols over their first tile
+pub fn annotate_symbols(image: &mut Image, tile_kind: TileKind, tile_count: usize, style: &GridStyle, specs: &SymbolSpecs) {
+    let outliner = SymbolOutliner { style, tile_kind, tile_count };
+    let present_specs = specs.iter().filter(|spec| spec.start_tile_index() < tile_count);
+    for (spec, color) in present_specs.zip(OUTLINE_COLORS.iter().cycle()) {
+        outliner.outline(image, spec, *color);
+        if let Some(name) = spec.name() {
+            outliner.label(image, spec, name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{
+        Kind as TileKind,
+        Tile,
+        container::{into_tile_grid::IntoTileGrid, symbol::spec::{Spec as SymbolSpec, Specs as SymbolSpecs}},
+        grid::GridStyle,
+    };
+
+    use super::{annotate_symbols, OUTLINE_COLORS};
+
+    #[test]
+    fn outline_wrapping_symbol() {
+        let tiles = vec![Tile::new(TileKind::HD); 32];
+        let style = GridStyle::default();
+        let mut image = tiles.as_slice().into_tile_grid().generate_image_with_style(&style).unwrap();
+        // tiles 15 and 16: end of the first row and start of the second one
+        let specs = SymbolSpecs::from(vec![SymbolSpec::named("arrows", 15, 2)]);
+        annotate_symbols(&mut image, TileKind::HD, tiles.len(), &style, &specs);
+
+        let tile_step = 24 + style.separator_thickness();
+        let row_step = 36 + style.separator_thickness();
+        let color = OUTLINE_COLORS[0];
+        assert_eq!(*image.get_pixel(15 * tile_step, 10), color);
+        assert_eq!(*image.get_pixel(15 * tile_step + 23, 10), color);
+        assert_eq!(*image.get_pixel(12, row_step), color);
+        assert_ne!(*image.get_pixel(14 * tile_step + 23, 10), color);
+        // the label does not fit in a single HD tile and is truncated to `ARROW`, the top of the `A` then the
+        // background after the `W`
+        assert_eq!(*image.get_pixel(15 * tile_step + 2 + 1, 2), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(15 * tile_step + 2 + 4 * 4, 2), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(15 * tile_step + 2 + 5 * 4 - 1, 2), Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,190 @@
+
+//! Collections split across several grid images, e.g. 512 tiles stored as two 16×16 sheets instead of a 16×32 one
+//!
+//! The first page is saved to the given path, the following ones next to it with the page number appended to the
+//! file stem: `grid.png`, `grid_2.png`.
+
+use std::fmt::Display as FmtDisplay;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use derive_more::From;
+use getset::CopyGetters;
+use thiserror::Error;
+
+use super::{Grid, GridStyle, LoadError, SaveImageError, SEPARATOR_THICKNESS};
+
+use crate::image_encode::ImageEncodeOptions;
+use crate::io_context::IoContext;
+use crate::osd::tile::{
+    Tile,
+    container::uniq_tile_kind::{TileKindError, UniqTileKind},
+};
+
+
+/// Shape of each page: `columns` tiles per row and `rows` rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct GridLayout {
+    columns: usize,
+    rows: usize,
+}
+
+#[derive(Debug, Error)]
+#[error("invalid grid layout `{0}`, expected <columns>x<rows>, e.g. 16x32")]
+pub struct InvalidGridLayoutError(String);
+
+#[derive(Debug, Error)]
+#[error("{page_path}: {tile_count} tiles do not fit in a page of {capacity} tiles")]
+pub struct PageCapacityError {
+    pub page_path: PathBuf,
+    pub tile_count: usize,
+    pub capacity: usize,
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadPagesError {
+    #[error(transparent)]
+    LoadError(LoadError),
+    #[error(transparent)]
+    PageCapacityError(PageCapacityError),
+}
+
+#[derive(Debug, From, Error)]
+pub enum SavePagesError {
+    #[error(transparent)]
+    SaveImageError(SaveImageError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error(transparent)]
+    PageCapacityError(PageCapacityError),
+}
+
+impl GridLayout {
+
+    /// Two 16×16 pages when the collection spans two pages, a single 16 columns wide page otherwise
+    pub const fn default_for_pages(pages: usize) -> Self {
+        match pages {
+            1 => Self { columns: 16, rows: 32 },
+            _ => Self { columns: 16, rows: 16 },
+        }
+    }
+
+    pub const fn new(columns: usize, rows: usize) -> Self {
+        Self { columns, rows }
+    }
+
+    pub fn page_tile_count(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn style(&self) -> GridStyle {
+        GridStyle::new(self.columns, SEPARATOR_THICKNESS)
+    }
+
+}
+
+impl FromStr for GridLayout {
+    type Err = InvalidGridLayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidGridLayoutError(s.to_owned());
+        let (columns, rows) = s.split_once('x').ok_or_else(invalid)?;
+        let (columns, rows) = (columns.parse().map_err(|_| invalid())?, rows.parse().map_err(|_| invalid())?);
+        if columns == 0 || rows == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { columns, rows })
+    }
+}
+
+impl FmtDisplay for GridLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.columns, self.rows)
+    }
+}
+
+/// Path of the image of a page of a paged grid, pages are numbered from 1
+pub fn page_image_path<P: AsRef<Path>>(path: P, page: usize) -> PathBuf {
+    let path = path.as_ref();
+    if page <= 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{stem}_{page}.{}", extension.to_string_lossy()),
+        None => format!("{stem}_{page}"),
+    };
+    path.with_file_name(file_name)
+}
+
+/// Loads the tiles of the `pages` images of a paged grid, pages smaller than the layout are padded with empty tiles
+/// so that the tiles of the following pages keep their indices
+pub fn load_paged_images<P: AsRef<Path>>(path: P, layout: GridLayout, pages: usize) -> Result<Vec<Tile>, LoadPagesError> {
+    load_paged_images_with_context(path, layout, pages, &IoContext::default())
+}
+
+pub fn load_paged_images_with_context<P: AsRef<Path>>(path: P, layout: GridLayout, pages: usize, context: &IoContext) -> Result<Vec<Tile>, LoadPagesError> {
+    let mut tiles = vec![];
+    for page in 1..=pages {
+        let page_path = page_image_path(&path, page);
+        let mut page_tiles: Vec<Tile> = Grid::load_from_image_with_context(&page_path, &layout.style(), context)?.into_iter().collect();
+        if page_tiles.len() > layout.page_tile_count() {
+            return Err(PageCapacityError { page_path, tile_count: page_tiles.len(), capacity: layout.page_tile_count() }.into());
+        }
+        if page < pages {
+            let tile_kind = page_tiles[0].kind();
+            page_tiles.resize(layout.page_tile_count(), Tile::new(tile_kind));
+        }
+        tiles.append(&mut page_tiles);
+    }
+    Ok(tiles)
+}
+
+/// Saves the tiles to `pages` grid images of the given layout, the last pages are filled with empty tiles
+pub fn save_paged_images<P: AsRef<Path>>(tiles: &[Tile], path: P, layout: GridLayout, pages: usize, encode_options: &ImageEncodeOptions)
+        -> Result<(), SavePagesError> {
+    let tile_kind = tiles.tile_kind()?;
+    let capacity = layout.page_tile_count() * pages;
+    if tiles.len() > capacity {
+        return Err(PageCapacityError { page_path: path.as_ref().to_path_buf(), tile_count: tiles.len(), capacity }.into());
+    }
+    let mut tiles = tiles.to_vec();
+    tiles.resize(capacity, Tile::new(tile_kind));
+    for (page_index, page_tiles) in tiles.chunks(layout.page_tile_count()).enumerate() {
+        Grid::from(page_tiles).save_image_with_style(page_image_path(&path, page_index + 1), &layout.style(), encode_options)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use temp_dir::TempDir;
+
+    use crate::image_encode::ImageEncodeOptions;
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{load_paged_images, page_image_path, save_paged_images, GridLayout};
+
+    #[test]
+    fn two_pages_round_trip() {
+        assert_eq!(page_image_path("fonts/grid.png", 1), Path::new("fonts/grid.png"));
+        assert_eq!(page_image_path("fonts/grid.png", 2), Path::new("fonts/grid_2.png"));
+        assert!("16x".parse::<GridLayout>().is_err());
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("grid.png");
+        let mut tiles = vec![Tile::new(TileKind::SD); 300];
+        tiles[290].put_pixel(1, 1, image::Rgba([255, 255, 255, 255]));
+        let layout: GridLayout = "16x16".parse().unwrap();
+        save_paged_images(&tiles, &path, layout, 2, &ImageEncodeOptions::default()).unwrap();
+        assert_eq!(image::image_dimensions(page_image_path(&path, 2)).unwrap(), image::image_dimensions(&path).unwrap());
+
+        let loaded = load_paged_images(&path, layout, 2).unwrap();
+        assert_eq!(loaded.len(), 512);
+        assert_eq!(loaded[290].as_raw(), tiles[290].as_raw());
+        assert!(save_paged_images(&vec![Tile::new(TileKind::SD); 513], &path, layout, 2, &ImageEncodeOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+
+//! Review sheets: grid images with the notes of a font review marked on the tiles they are about
+//!
+//! The notes are read from CSV files with the tile index in the first column and the note in the second one, as
+//! exported from the spreadsheets review feedback is usually collected in. Each note gets a numbered marker of its own
+//! color in the top right corner of its tile, the markers of the notes of a same tile being stacked, and a line of the
+//! legend written below the grid with the same marker followed by the tile index and the note.
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use image::{GenericImage, Rgba};
+use thiserror::Error;
+
+use super::{GridStyle, Image};
+use super::annotation::{draw_text, fill_rect, fitting_character_count, GLYPH_ADVANCE, GLYPH_HEIGHT, LABEL_BACKGROUND_COLOR, OUTLINE_COLORS};
+use crate::osd::tile::Kind as TileKind;
+
+
+/// height of the markers and of the lines of the legend, the text having a pixel of margin above and below
+const MARKER_HEIGHT: u32 = GLYPH_HEIGHT + 2;
+const LEGEND_LINE_HEIGHT: u32 = MARKER_HEIGHT + 2;
+const LEGEND_MARGIN: u32 = 2;
+
+#[derive(Debug, Error)]
+pub enum LoadReviewNotesError {
+    #[error("failed to read review notes file: {0}")]
+    ReadError(#[from] IOError),
+    #[error("review notes file {file_path} line {line}: expected `<tile index>,<note>`")]
+    InvalidLine { file_path: PathBuf, line: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewNote {
+    pub tile_index: usize,
+    pub note: String,
+}
+
+// quoted fields can hold commas and `""` escaped quotes, they cannot span lines
+fn unquote(field: &str) -> String {
+    let field = field.trim();
+    match field.strip_prefix('"').and_then(|field| field.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\"\"", "\""),
+        None => field.to_owned(),
+    }
+}
+
+/// Parses `<tile index>,<note>` lines, the first line is skipped as a header when it does not start with a tile index
+/// and the columns following the note are ignored
+pub fn parse_review_notes(content: &str) -> Result<Vec<ReviewNote>, usize> {
+    let mut notes = vec![];
+    let lines = content.lines().enumerate().filter(|(_, line)| ! line.trim().is_empty());
+    for (position, (line_index, line)) in lines.enumerate() {
+        let (index, rest) = line.split_once(',').unwrap_or((line, ""));
+        let tile_index = match unquote(index).parse() {
+            Ok(tile_index) => tile_index,
+            Err(_) if position == 0 => continue,
+            Err(_) => return Err(line_index + 1),
+        };
+        // the note ends at the first comma outside of quotes
+        let mut in_quotes = false;
+        let note_end = rest.char_indices()
+            .find(|(_, character)| {
+                if *character == '"' {
+                    in_quotes = ! in_quotes;
+                }
+                *character == ',' && ! in_quotes
+            })
+            .map_or(rest.len(), |(index, _)| index);
+        notes.push(ReviewNote { tile_index, note: unquote(&rest[..note_end]) });
+    }
+    Ok(notes)
+}
+
+pub fn load_review_notes<P: AsRef<Path>>(path: P) -> Result<Vec<ReviewNote>, LoadReviewNotesError> {
+    let content = fs_err::read_to_string(&path)?;
+    parse_review_notes(&content).map_err(|line| LoadReviewNotesError::InvalidLine { file_path: path.as_ref().to_path_buf(), line })
+}
+
+fn marker_width(number: usize) -> u32 {
+    number.to_string().len() as u32 * GLYPH_ADVANCE + 1
+}
+
+fn draw_marker(image: &mut Image, right: u32, top: u32, number: usize, color: Rgba<u8>) {
+    let label = number.to_string();
+    let left = right.saturating_sub(marker_width(number));
+    fill_rect(image, left, top, marker_width(number), MARKER_HEIGHT, color);
+    draw_text(image, left + 1, top + 1, &label, label.len());
+}
+
+/// Grid image of `tile_count` tiles with the notes marked on their tiles and listed in a legend added below the grid,
+/// the notes about tiles past the end of the collection are only listed
+pub fn review_sheet(grid_image: &Image, tile_kind: TileKind, tile_count: usize, style: &GridStyle, notes: &[ReviewNote]) -> Image {
+    let legend_height = if notes.is_empty() { 0 } else { 2 * LEGEND_MARGIN + notes.len() as u32 * LEGEND_LINE_HEIGHT };
+    let mut image = Image::from_pixel(grid_image.width(), grid_image.height() + legend_height, LABEL_BACKGROUND_COLOR);
+    image.copy_from(grid_image, 0, 0).unwrap();
+
+    let tile_width = tile_kind.dimensions().width;
+    // the markers of the legend are right aligned
+    let legend_marker_right = LEGEND_MARGIN + marker_width(notes.len());
+    let mut tile_note_counts = vec![0; tile_count];
+    for (note_index, (note, color)) in notes.iter().zip(OUTLINE_COLORS.iter().cycle()).enumerate() {
+        let number = note_index + 1;
+        if let Some(tile_note_count) = tile_note_counts.get_mut(note.tile_index) {
+            let (x, y) = style.index_to_grid_coordinates(note.tile_index);
+            let (left, top) = style.image_tile_position(&tile_kind, x as u32, y as u32);
+            draw_marker(&mut image, left + tile_width, top + *tile_note_count * (MARKER_HEIGHT + 1), number, *color);
+            *tile_note_count += 1;
+        }
+
+        let line_top = grid_image.height() + LEGEND_MARGIN + note_index as u32 * LEGEND_LINE_HEIGHT + 1;
+        draw_marker(&mut image, legend_marker_right, line_top, number, *color);
+        let text_left = legend_marker_right + GLYPH_ADVANCE;
+        let text = match note.tile_index < tile_count {
+            true => format!("{}: {}", note.tile_index, note.note),
+            false => format!("{} (not in the collection): {}", note.tile_index, note.note),
+        };
+        let character_count = fitting_character_count(&text, image.width().saturating_sub(text_left + LEGEND_MARGIN));
+        draw_text(&mut image, text_left, line_top + 1, &text, character_count);
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile, container::into_tile_grid::IntoTileGrid, grid::GridStyle};
+
+    use super::{parse_review_notes, review_sheet, ReviewNote, LEGEND_LINE_HEIGHT, LEGEND_MARGIN};
+
+    #[test]
+    fn review_notes() {
+        let notes = parse_review_notes("index,note,reviewer\n3,too thin,alex\n\n\"17\",\"arrow, \"\"left\"\" one\"\n").unwrap();
+        assert_eq!(notes, vec![
+            ReviewNote { tile_index: 3, note: "too thin".to_owned() },
+            ReviewNote { tile_index: 17, note: "arrow, \"left\" one".to_owned() },
+        ]);
+        assert_eq!(parse_review_notes("3,ok\nthree,not a tile index\n"), Err(2));
+
+        let tiles = vec![Tile::new(TileKind::HD); 32];
+        let style = GridStyle::default();
+        let grid_image = tiles.as_slice().into_tile_grid().generate_image_with_style(&style).unwrap();
+        let image = review_sheet(&grid_image, TileKind::HD, tiles.len(), &style, &notes);
+        assert_eq!(image.height(), grid_image.height() + 2 * LEGEND_MARGIN + 2 * LEGEND_LINE_HEIGHT);
+        // the marker of the first note in the top right corner of tile 3
+        let tile_step = 24 + style.separator_thickness();
+        assert_eq!(*image.get_pixel(3 * tile_step + 23, 0), Rgba([255, 64, 64, 255]));
+    }
+}
@@ -0,0 +1,123 @@
+
+//! Fonts built from the icons of icon fonts like Material Symbols
+//!
+//! An icon map assigns codepoints of the icon font to tile indices with one `<codepoint> <tile index>` pair per line.
+//! Codepoints are given as `U+E88A`, `0xE88A` or as the character itself and tile indices in any of the forms of
+//! [`parse_index`], e.g. `U+E88A 0x10` puts the home icon of Material Symbols at tile 0x10. Empty lines and lines
+//! starting with `#` are ignored.
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use super::{Kind as TileKind, Tile};
+use super::container::uniq_tile_kind::{TileKindError, UniqTileKind};
+use super::index::{format_index, parse_index, TILE_COUNT};
+use super::ttf::{Font, RasterStyle};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IconMapping {
+    pub codepoint: char,
+    pub tile_index: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum IconMapError {
+    #[error("failed to read icon map file: {0}")]
+    ReadError(#[from] IOError),
+    #[error("invalid icon map {}line {line}: {reason}", .file_path.as_ref().map(|path| format!("file {} ", path.to_string_lossy())).unwrap_or_default())]
+    InvalidLine { file_path: Option<PathBuf>, line: usize, reason: String },
+}
+
+#[derive(Debug, Error)]
+pub enum IconFontError {
+    #[error("the icon font has no glyph for U+{codepoint:04X}")]
+    MissingGlyph { codepoint: u32 },
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+}
+
+fn parse_codepoint(codepoint: &str) -> Option<char> {
+    let hex = codepoint.strip_prefix("U+").or_else(|| codepoint.strip_prefix("u+")).or_else(|| codepoint.strip_prefix("0x"));
+    match hex {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        None => {
+            let mut chars = codepoint.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        },
+    }
+}
+
+pub fn parse_icon_map(content: &str) -> Result<Vec<IconMapping>, IconMapError> {
+    let invalid_line = |line_index: usize, reason: String| IconMapError::InvalidLine { file_path: None, line: line_index + 1, reason };
+    let mut mappings = vec![];
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (codepoint, tile_index) = match fields.as_slice() {
+            [codepoint, tile_index] => (*codepoint, *tile_index),
+            _ => return Err(invalid_line(line_index, "expected `<codepoint> <tile index>`".to_owned())),
+        };
+        let codepoint = parse_codepoint(codepoint)
+            .ok_or_else(|| invalid_line(line_index, format!("invalid codepoint `{codepoint}`, expected `U+<hex>`, `0x<hex>` or a single character")))?;
+        let tile_index = parse_index(tile_index).map_err(|error| invalid_line(line_index, error.to_string()))?;
+        if tile_index >= TILE_COUNT {
+            return Err(invalid_line(line_index, format!("tile index {} is past the last tile of the font", format_index(tile_index))));
+        }
+        mappings.push(IconMapping { codepoint, tile_index });
+    }
+    Ok(mappings)
+}
+
+pub fn load_icon_map<P: AsRef<Path>>(path: P) -> Result<Vec<IconMapping>, IconMapError> {
+    let content = fs_err::read_to_string(&path)?;
+    parse_icon_map(&content).map_err(|error| match error {
+        IconMapError::InvalidLine { line, reason, .. } => IconMapError::InvalidLine { file_path: Some(path.as_ref().to_path_buf()), line, reason },
+        error => error,
+    })
+}
+
+/// Draws the icons over a collection of `tile_kind` tiles at their tile index, the collection may be empty and is padded
+/// with empty tiles up to the highest tile index
+pub fn apply_icons(tiles: &[Tile], tile_kind: TileKind, font: &Font, mappings: &[IconMapping], style: &RasterStyle) -> Result<Vec<Tile>, IconFontError> {
+    if ! tiles.is_empty() && tiles.tile_kind()? != tile_kind {
+        return Err(TileKindError::LoadedDoesNotMatchRequested { requested: tile_kind, loaded: tiles.tile_kind()? }.into());
+    }
+    let mut tiles = tiles.to_vec();
+    if let Some(last_index) = mappings.iter().map(|mapping| mapping.tile_index).max() {
+        if tiles.len() <= last_index {
+            tiles.resize(last_index + 1, Tile::new(tile_kind));
+        }
+    }
+    for mapping in mappings {
+        if ! font.has_glyph(mapping.codepoint) {
+            return Err(IconFontError::MissingGlyph { codepoint: mapping.codepoint as u32 });
+        }
+        // glyphs without an outline, like spaces, give empty tiles
+        tiles[mapping.tile_index] = font.render(mapping.codepoint, tile_kind, style).unwrap_or_else(|| Tile::new(tile_kind));
+    }
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_icon_map, IconMapError, IconMapping};
+
+    #[test]
+    fn icon_map() {
+        let mappings = parse_icon_map("# arrows\nU+E88A 0x10\n\n0xE5D8 2/0x01\n★ 300\n").unwrap();
+        assert_eq!(mappings, vec![
+            IconMapping { codepoint: '\u{E88A}', tile_index: 0x10 },
+            IconMapping { codepoint: '\u{E5D8}', tile_index: 0x101 },
+            IconMapping { codepoint: '★', tile_index: 300 },
+        ]);
+        for content in ["U+E88A", "U+ZZZZ 1", "ab 1", "U+E88A 512", "U+D800 1"] {
+            assert!(matches!(parse_icon_map(content), Err(IconMapError::InvalidLine { line: 1, .. })), "{content}");
+        }
+    }
+}
@@ -0,0 +1,124 @@
+
+//! Tile indices of the firmware fonts
+//!
+//! The fonts are made of two pages of 256 tiles, a tile is addressed either by its linear index from 0 to 511 or by its
+//! page and its offset in the page, e.g. `2/0x10` is the same tile as `0x110`.
+
+use std::fmt::Display as FmtDisplay;
+use std::str::FromStr;
+
+use parse_int::parse;
+use thiserror::Error;
+
+
+pub const PAGE_TILE_COUNT: usize = 256;
+pub const PAGE_COUNT: usize = 2;
+pub const TILE_COUNT: usize = PAGE_COUNT * PAGE_TILE_COUNT;
+
+#[derive(Debug, Error)]
+#[error("invalid tile index `{0}`: expected a decimal index, an hexadecimal index with the `0x` prefix or `<page>/<offset>`")]
+pub struct InvalidIndexError(pub String);
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PageOffsetError {
+    #[error("tile index {0} is out of the {TILE_COUNT} tiles of the font pages")]
+    IndexOutOfRange(usize),
+    #[error("invalid page {0}, pages are numbered from 1 to {PAGE_COUNT}")]
+    InvalidPage(usize),
+    #[error("invalid offset {0}, pages contain {PAGE_TILE_COUNT} tiles")]
+    InvalidOffset(usize),
+}
+
+/// Position of a tile as a page numbered from 1 and an offset in this page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageOffset {
+    page: usize,
+    offset: usize,
+}
+
+impl PageOffset {
+
+    pub fn new(page: usize, offset: usize) -> Result<Self, PageOffsetError> {
+        if ! (1..=PAGE_COUNT).contains(&page) {
+            return Err(PageOffsetError::InvalidPage(page));
+        }
+        if offset >= PAGE_TILE_COUNT {
+            return Err(PageOffsetError::InvalidOffset(offset));
+        }
+        Ok(Self { page, offset })
+    }
+
+    pub fn from_index(index: usize) -> Result<Self, PageOffsetError> {
+        if index >= TILE_COUNT {
+            return Err(PageOffsetError::IndexOutOfRange(index));
+        }
+        Ok(Self { page: index / PAGE_TILE_COUNT + 1, offset: index % PAGE_TILE_COUNT })
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn index(&self) -> usize {
+        (self.page - 1) * PAGE_TILE_COUNT + self.offset
+    }
+
+}
+
+impl FromStr for PageOffset {
+    type Err = InvalidIndexError;
+
+    /// Parses `<page>/<offset>`, the offset can be given in hexadecimal with the `0x` prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidIndexError(s.to_owned());
+        let (page, offset) = s.split_once('/').ok_or_else(invalid)?;
+        let (page, offset) = (parse(page.trim()).map_err(|_| invalid())?, parse(offset.trim()).map_err(|_| invalid())?);
+        Self::new(page, offset).map_err(|_| invalid())
+    }
+}
+
+impl FmtDisplay for PageOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/0x{:02X}", self.page, self.offset)
+    }
+}
+
+/// Parses a linear tile index given in decimal, in hexadecimal with the `0x` prefix or as `<page>/<offset>`
+pub fn parse_index(s: &str) -> Result<usize, InvalidIndexError> {
+    let s = s.trim();
+    if s.contains('/') {
+        return Ok(s.parse::<PageOffset>()?.index());
+    }
+    parse(s).map_err(|_| InvalidIndexError(s.to_owned()))
+}
+
+/// Index for messages in both decimal and hexadecimal, e.g. `272 (0x110)`
+pub fn format_index(index: usize) -> String {
+    format!("{index} (0x{index:03X})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_index, parse_index, PageOffset, PageOffsetError};
+
+    #[test]
+    fn page_addressing() {
+        let page_offset = PageOffset::from_index(0x110).unwrap();
+        assert_eq!((page_offset.page(), page_offset.offset()), (2, 0x10));
+        assert_eq!(page_offset.to_string(), "2/0x10");
+        assert_eq!("2/0x10".parse::<PageOffset>().unwrap().index(), 0x110);
+        assert_eq!(PageOffset::from_index(512), Err(PageOffsetError::IndexOutOfRange(512)));
+        assert!("3/0".parse::<PageOffset>().is_err());
+        assert!("1/256".parse::<PageOffset>().is_err());
+
+        assert_eq!(parse_index("48").unwrap(), 48);
+        assert_eq!(parse_index("0x30").unwrap(), 48);
+        assert_eq!(parse_index("1/0x30").unwrap(), 48);
+        assert!(parse_index("x30").is_err());
+        assert_eq!(format_index(272), "272 (0x110)");
+    }
+}
@@ -0,0 +1,205 @@
+
+use std::fmt::Display as FmtDisplay;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use strum::Display;
+use thiserror::Error;
+
+use super::{index::parse_index, Tile};
+
+
+/// Geometric transform of the content of a tile
+///
+/// Tiles are not square so the quarter turns keep the dimensions of the tile: the rotated content is centered and what
+/// does not fit is clipped. `snap-alpha` is not geometric: it makes the semi-transparent pixels either fully
+/// transparent or fully opaque, which the OSD chips display without artifacts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, ValueEnum)]
+pub enum Transform {
+    #[strum(serialize = "rotate90")]
+    Rotate90,
+    #[strum(serialize = "rotate180")]
+    Rotate180,
+    #[strum(serialize = "rotate270")]
+    Rotate270,
+    #[value(name = "flip-h")]
+    #[strum(serialize = "flip-h")]
+    FlipHorizontal,
+    #[value(name = "flip-v")]
+    #[strum(serialize = "flip-v")]
+    FlipVertical,
+    #[value(name = "snap-alpha")]
+    #[strum(serialize = "snap-alpha")]
+    SnapAlpha,
+}
+
+impl Tile {
+
+    pub fn transformed(&self, transform: Transform) -> Self {
+        if transform == Transform::SnapAlpha {
+            return self.alpha_snapped();
+        }
+        let mut tile = Self::new(self.kind());
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        for (x, y, pixel) in tile.enumerate_pixels_mut() {
+            let (x, y) = (x as i64, y as i64);
+            // pixel of the source landing at (x, y), quarter turns are around the center of the tile
+            let (source_x, source_y) = match transform {
+                Transform::Rotate90 => ((2 * y - height + width) / 2, (height + width) / 2 - 1 - x),
+                Transform::Rotate180 => (width - 1 - x, height - 1 - y),
+                Transform::Rotate270 => ((height + width) / 2 - 1 - y, (2 * x - width + height) / 2),
+                Transform::FlipHorizontal => (width - 1 - x, y),
+                Transform::FlipVertical => (x, height - 1 - y),
+                Transform::SnapAlpha => (x, y),
+            };
+            if (0..width).contains(&source_x) && (0..height).contains(&source_y) {
+                *pixel = *self.get_pixel(source_x as u32, source_y as u32);
+            }
+        }
+        tile
+    }
+
+    /// Pixels at least half opaque become fully opaque, the others fully transparent
+    pub fn alpha_snapped(&self) -> Self {
+        let mut tile = self.clone();
+        for pixel in tile.pixels_mut() {
+            pixel.0[3] = if pixel.0[3] >= 128 { 255 } else { 0 };
+        }
+        tile
+    }
+
+    /// Moves the content of the tile by (dx, dy) pixels, the content moved past the edges is lost
+    pub fn shifted(&self, dx: i32, dy: i32) -> Self {
+        let mut tile = Self::new(self.kind());
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        for (x, y, pixel) in tile.enumerate_pixels_mut() {
+            let (source_x, source_y) = (x as i64 - dx as i64, y as i64 - dy as i64);
+            if (0..width).contains(&source_x) && (0..height).contains(&source_y) {
+                *pixel = *self.get_pixel(source_x as u32, source_y as u32);
+            }
+        }
+        tile
+    }
+
+    fn visible_pixel_count(&self) -> usize {
+        self.pixels().filter(|pixel| pixel.0[3] != 0).count()
+    }
+
+}
+
+#[derive(Debug, Error)]
+#[error("invalid shift `{0}`: expected `<index>[-<last index>]:<dx>,<dy>`")]
+pub struct InvalidShiftError(String);
+
+#[derive(Debug, Error)]
+#[error("shift {shift} goes past the end of the collection of {tile_count} tiles")]
+pub struct ShiftOutOfBoundsError {
+    pub shift: Shift,
+    pub tile_count: usize,
+}
+
+/// Offset applied to the content of a range of tiles, e.g. to move digits one pixel up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shift {
+    pub first_index: usize,
+    pub last_index: usize,
+    pub dx: i32,
+    pub dy: i32,
+}
+
+impl Shift {
+
+    /// Shifts the tiles of the range and returns the indices of the tiles whose content has been clipped
+    pub fn apply(&self, tiles: &mut [Tile]) -> Result<Vec<usize>, ShiftOutOfBoundsError> {
+        if self.last_index >= tiles.len() {
+            return Err(ShiftOutOfBoundsError { shift: *self, tile_count: tiles.len() });
+        }
+        let mut clipped = vec![];
+        for (index, tile) in (self.first_index..).zip(&mut tiles[self.first_index..=self.last_index]) {
+            let shifted = tile.shifted(self.dx, self.dy);
+            if shifted.visible_pixel_count() < tile.visible_pixel_count() {
+                clipped.push(index);
+            }
+            *tile = shifted;
+        }
+        Ok(clipped)
+    }
+
+}
+
+impl FromStr for Shift {
+    type Err = InvalidShiftError;
+
+    /// Parses `<index>[-<last index>]:<dx>,<dy>`, indices can be given in hexadecimal with the `0x` prefix
+    /// or as `<page>/<offset>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidShiftError(s.to_owned());
+        let (range, offset) = s.split_once(':').ok_or_else(invalid)?;
+        let (first_index, last_index) = match range.split_once('-') {
+            Some((first_index, last_index)) => (parse_index(first_index).map_err(|_| invalid())?, parse_index(last_index).map_err(|_| invalid())?),
+            None => {
+                let index = parse_index(range).map_err(|_| invalid())?;
+                (index, index)
+            },
+        };
+        let (dx, dy) = offset.split_once(',').ok_or_else(invalid)?;
+        let (dx, dy) = (dx.trim().parse().map_err(|_| invalid())?, dy.trim().parse().map_err(|_| invalid())?);
+        if first_index > last_index {
+            return Err(invalid());
+        }
+        Ok(Self { first_index, last_index, dx, dy })
+    }
+}
+
+impl FmtDisplay for Shift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}:{},{}", self.first_index, self.last_index, self.dx, self.dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{Shift, Transform};
+
+    #[test]
+    fn transforms() {
+        let mut tile = Tile::new(TileKind::SD);
+        let opaque = Rgba([255, 255, 255, 255]);
+        // in the square centered in the tile so that the quarter turns keep them
+        tile.put_pixel(5, 10, opaque);
+        tile.put_pixel(6, 10, opaque);
+
+        let (width, height) = (TileKind::SD.dimensions().width(), TileKind::SD.dimensions().height());
+        let flipped = tile.transformed(Transform::FlipHorizontal);
+        assert_eq!(*flipped.get_pixel(width - 1 - 5, 10), opaque);
+        assert_eq!(*flipped.get_pixel(width - 1 - 6, 10), opaque);
+        assert_eq!(*flipped.get_pixel(5, 10), Rgba([0, 0, 0, 0]));
+        assert_eq!(tile.transformed(Transform::FlipVertical).get_pixel(5, height - 1 - 10), &opaque);
+
+        // a quarter turn in each direction gives back the original content
+        let rotated = tile.transformed(Transform::Rotate90).transformed(Transform::Rotate270);
+        assert!(rotated.as_raw() == tile.as_raw());
+        let rotated = tile.transformed(Transform::Rotate180).transformed(Transform::Rotate180);
+        assert!(rotated.as_raw() == tile.as_raw());
+    }
+
+    #[test]
+    fn shifts() {
+        let shift: Shift = "0x30-0x39:0,-1".parse().unwrap();
+        assert_eq!(shift, Shift { first_index: 0x30, last_index: 0x39, dx: 0, dy: -1 });
+        assert!("5-4:0,1".parse::<Shift>().is_err());
+
+        let opaque = Rgba([255, 255, 255, 255]);
+        let mut tiles = vec![Tile::new(TileKind::SD); 3];
+        tiles[1].put_pixel(5, 0, opaque);
+        tiles[2].put_pixel(5, 8, opaque);
+        let clipped = "1-2:0,-1".parse::<Shift>().unwrap().apply(&mut tiles).unwrap();
+        assert_eq!(clipped, vec![1]);
+        assert_eq!(*tiles[2].get_pixel(5, 7), opaque);
+        assert!("2-3:0,1".parse::<Shift>().unwrap().apply(&mut tiles).is_err());
+    }
+}
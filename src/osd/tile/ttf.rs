@@ -0,0 +1,112 @@
+
+//! Rasterization of the glyphs of TrueType and OpenType fonts at tile resolution
+//!
+//! Glyphs are scaled to fit the tile inside a margin, centered and thresholded so that the tiles stay crisp pixel art.
+//! They are filled in white with a black stroke around them like the stock glyphs.
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use ab_glyph::{Font as _, FontVec, GlyphId};
+use derive_more::From;
+use image::Rgba;
+use thiserror::Error;
+
+use super::{Kind as TileKind, Tile};
+
+
+// size at which the glyphs are outlined to measure them, the outlines scale linearly
+const REFERENCE_SCALE: f32 = 100.;
+
+// minimum coverage of a pixel by the outline for it to be filled
+const COVERAGE_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, From, Error)]
+pub enum LoadFontError {
+    #[error("failed to read font file: {0}")]
+    ReadError(IOError),
+    #[error("invalid font file {file_path}: {error}")]
+    InvalidFont { file_path: PathBuf, error: ab_glyph::InvalidFont },
+}
+
+/// How glyphs are drawn in the tiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RasterStyle {
+    /// pixels left empty around the stroked glyph
+    pub margin: u32,
+    /// width in pixels of the stroke around the glyph, 0 for none
+    pub stroke: u32,
+    pub fill: Rgba<u8>,
+    pub stroke_color: Rgba<u8>,
+}
+
+impl Default for RasterStyle {
+    fn default() -> Self {
+        Self { margin: 1, stroke: 1, fill: Rgba([255, 255, 255, 255]), stroke_color: Rgba([0, 0, 0, 255]) }
+    }
+}
+
+pub struct Font(FontVec);
+
+impl Font {
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadFontError> {
+        let bytes = fs_err::read(&path)?;
+        FontVec::try_from_vec(bytes)
+            .map(Self)
+            .map_err(|error| LoadFontError::InvalidFont { file_path: path.as_ref().to_path_buf(), error })
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, ab_glyph::InvalidFont> {
+        FontVec::try_from_vec(bytes).map(Self)
+    }
+
+    fn glyph_id(&self, codepoint: char) -> Option<GlyphId> {
+        Some(self.0.glyph_id(codepoint)).filter(|glyph_id| glyph_id.0 != 0)
+    }
+
+    pub fn has_glyph(&self, codepoint: char) -> bool {
+        self.glyph_id(codepoint).is_some()
+    }
+
+    /// Tile of the glyph of `codepoint`, `None` if the font has no glyph or an empty glyph for it
+    pub fn render(&self, codepoint: char, tile_kind: TileKind, style: &RasterStyle) -> Option<Tile> {
+        let glyph_id = self.glyph_id(codepoint)?;
+        let dimensions = tile_kind.dimensions();
+        let (width, height) = (dimensions.width(), dimensions.height());
+        let inset = 2 * (style.margin + style.stroke);
+        let (box_width, box_height) = (width.saturating_sub(inset).max(1) as f32, height.saturating_sub(inset).max(1) as f32);
+
+        let reference_bounds = self.0.outline_glyph(glyph_id.with_scale(REFERENCE_SCALE))?.px_bounds();
+        if reference_bounds.width() <= 0. || reference_bounds.height() <= 0. {
+            return None;
+        }
+        let scale = REFERENCE_SCALE * (box_width / reference_bounds.width()).min(box_height / reference_bounds.height());
+        let outlined = self.0.outline_glyph(glyph_id.with_scale(scale))?;
+        let bounds = outlined.px_bounds();
+        let offset_x = ((width as f32 - bounds.width()) / 2.).round() as i64;
+        let offset_y = ((height as f32 - bounds.height()) / 2.).round() as i64;
+
+        let mut filled = vec![false; (width * height) as usize];
+        outlined.draw(|x, y, coverage| {
+            let (x, y) = (x as i64 + offset_x, y as i64 + offset_y);
+            if coverage >= COVERAGE_THRESHOLD && (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                filled[(y * width as i64 + x) as usize] = true;
+            }
+        });
+
+        let stroke = style.stroke as i64;
+        let is_filled = |x: i64, y: i64| (0..width as i64).contains(&x) && (0..height as i64).contains(&y) && filled[(y * width as i64 + x) as usize];
+        let mut tile = Tile::new(tile_kind);
+        for (x, y, pixel) in tile.enumerate_pixels_mut() {
+            let (x, y) = (x as i64, y as i64);
+            if is_filled(x, y) {
+                *pixel = style.fill;
+            } else if (-stroke..=stroke).any(|dy| (-stroke..=stroke).any(|dx| is_filled(x + dx, y + dy))) {
+                *pixel = style.stroke_color;
+            }
+        }
+        Some(tile)
+    }
+
+}
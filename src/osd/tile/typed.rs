@@ -0,0 +1,144 @@
+
+//! Tiles with their kind encoded in the type, e.g. `Tile<SD>`, for code which must never mix SD and HD tiles
+//!
+//! The typed tiles convert from and to the dynamic [`Tile`](super::Tile) used by the rest of the library, the kind
+//! is only checked once when converting from a dynamic tile.
+
+use std::marker::PhantomData;
+
+use derive_more::{Deref, DerefMut};
+use thiserror::Error;
+
+use super::{Dimensions, Image, InvalidDimensionsError, Kind, Tile as DynTile};
+
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Marker type of a tile kind
+pub trait KindMarker: sealed::Sealed {
+    const KIND: Kind;
+}
+
+/// Marker type of SD tiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SD;
+
+/// Marker type of HD tiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HD;
+
+impl sealed::Sealed for SD {}
+impl sealed::Sealed for HD {}
+
+impl KindMarker for SD {
+    const KIND: Kind = Kind::SD;
+}
+
+impl KindMarker for HD {
+    const KIND: Kind = Kind::HD;
+}
+
+#[derive(Debug, Error)]
+#[error("expected a {expected} tile, got a {actual} tile")]
+pub struct KindMismatchError {
+    pub expected: Kind,
+    pub actual: Kind,
+}
+
+#[derive(Debug, Error)]
+pub enum TryFromImageError {
+    #[error(transparent)]
+    InvalidDimensions(InvalidDimensionsError),
+    #[error(transparent)]
+    KindMismatch(KindMismatchError),
+}
+
+#[derive(Deref, DerefMut, Clone, Debug)]
+pub struct Tile<K: KindMarker> {
+    #[deref]
+    #[deref_mut]
+    tile: DynTile,
+    kind: PhantomData<K>,
+}
+
+impl<K: KindMarker> Tile<K> {
+
+    pub fn new() -> Self {
+        Self { tile: DynTile::new(K::KIND), kind: PhantomData }
+    }
+
+    pub const fn kind() -> Kind {
+        K::KIND
+    }
+
+    pub const fn dimensions() -> Dimensions {
+        K::KIND.dimensions()
+    }
+
+    pub fn into_inner(self) -> DynTile {
+        self.tile
+    }
+
+    /// Converts a whole collection of dynamic tiles, fails on the first tile which is not of the kind `K`
+    pub fn try_from_tiles<I: IntoIterator<Item = DynTile>>(tiles: I) -> Result<Vec<Self>, KindMismatchError> {
+        tiles.into_iter().map(Self::try_from).collect()
+    }
+
+}
+
+impl<K: KindMarker> Default for Tile<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KindMarker> TryFrom<DynTile> for Tile<K> {
+    type Error = KindMismatchError;
+
+    fn try_from(tile: DynTile) -> Result<Self, Self::Error> {
+        if tile.kind() != K::KIND {
+            return Err(KindMismatchError { expected: K::KIND, actual: tile.kind() });
+        }
+        Ok(Self { tile, kind: PhantomData })
+    }
+}
+
+impl<K: KindMarker> TryFrom<Image> for Tile<K> {
+    type Error = TryFromImageError;
+
+    fn try_from(image: Image) -> Result<Self, Self::Error> {
+        let tile = DynTile::try_from(image).map_err(TryFromImageError::InvalidDimensions)?;
+        Self::try_from(tile).map_err(TryFromImageError::KindMismatch)
+    }
+}
+
+impl<K: KindMarker> From<Tile<K>> for DynTile {
+    fn from(tile: Tile<K>) -> Self {
+        tile.tile
+    }
+}
+
+impl<K: KindMarker> AsRef<DynTile> for Tile<K> {
+    fn as_ref(&self) -> &DynTile {
+        &self.tile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::{Dimensions, Kind, Tile as DynTile};
+
+    use super::{Tile, HD, SD};
+
+    #[test]
+    fn conversions() {
+        let tile = Tile::<SD>::try_from(DynTile::new(Kind::SD)).unwrap();
+        assert_eq!(DynTile::from(tile).kind(), Kind::SD);
+        let error = Tile::<HD>::try_from(DynTile::new(Kind::SD)).unwrap_err();
+        assert_eq!((error.expected, error.actual), (Kind::HD, Kind::SD));
+        assert!(Tile::<HD>::try_from_tiles(vec![DynTile::new(Kind::HD), DynTile::new(Kind::SD)]).is_err());
+        assert_eq!(Dimensions::from(Tile::<HD>::new().image().dimensions()), Tile::<HD>::dimensions());
+    }
+}
@@ -0,0 +1,137 @@
+
+//! Font folders of the walksnail-osd-tool DVR overlay program
+//!
+//! A folder contains `font_sd.png` with the 36 pixels wide SD tiles, `font_hd.png` with the 24 pixels wide HD tiles and
+//! `font_hd_2x.png` with the HD tiles scaled to 48 pixels wide used for 4K recordings. Each file is a single column of
+//! tiles. The scaled file is generated when saving and ignored when loading.
+
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use image::{imageops::{self, FilterType}, GenericImage, GenericImageView, ImageBuffer, Rgba};
+use thiserror::Error;
+
+use super::tile::{
+    Tile,
+    Kind as TileKind,
+    container::{
+        tile_set::TileSet,
+        uniq_tile_kind::{TileKindError, UniqTileKind},
+    },
+};
+
+use crate::{
+    create_path::{create_path, CreatePathError},
+    dimensions,
+    image_encode::ImageEncodeOptions,
+    image::{
+        read_image_file,
+        ReadError as ImageReadError,
+        WriteImageFile,
+        WriteError as ImageWriteError,
+    },
+};
+
+
+pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+pub type ImageDimensions = dimensions::Dimensions<u32>;
+
+pub const SD_FILE_NAME: &str = "font_sd.png";
+pub const HD_FILE_NAME: &str = "font_hd.png";
+pub const SCALED_HD_FILE_NAME: &str = "font_hd_2x.png";
+
+#[derive(Debug, From, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    ImageReadError(ImageReadError),
+    #[error("invalid font image dimensions in file {file_path}: {dimensions}, expected a single column of {tile_kind} tiles")]
+    InvalidDimensions { file_path: PathBuf, tile_kind: TileKind, dimensions: ImageDimensions },
+    #[error(transparent)]
+    TileKindError(TileKindError),
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    CreatePathError(CreatePathError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+}
+
+pub fn file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind) -> PathBuf {
+    match tile_kind {
+        TileKind::SD => dir.as_ref().join(SD_FILE_NAME),
+        TileKind::HD => dir.as_ref().join(HD_FILE_NAME),
+    }
+}
+
+fn load_font_image<P: AsRef<Path>>(path: P, tile_kind: TileKind) -> Result<Vec<Tile>, LoadError> {
+    let image = read_image_file(&path)?.into_rgba8();
+    let tile_dimensions = tile_kind.dimensions();
+    if image.width() != tile_dimensions.width || image.height() == 0 || image.height() % tile_dimensions.height != 0 {
+        return Err(LoadError::InvalidDimensions {
+            file_path: path.as_ref().to_path_buf(),
+            tile_kind,
+            dimensions: ImageDimensions::from(image.dimensions())
+        });
+    }
+    let tiles = (0..image.height() / tile_dimensions.height)
+        .map(|tile_index| {
+            let tile_image = image.view(0, tile_index * tile_dimensions.height, tile_dimensions.width, tile_dimensions.height).to_image();
+            Tile::try_from(tile_image).unwrap()
+        })
+        .collect();
+    Ok(tiles)
+}
+
+fn generate_font_image(tiles: &[Tile]) -> Result<Image, TileKindError> {
+    let tile_dimensions = tiles.tile_kind()?.dimensions();
+    let mut image = Image::new(tile_dimensions.width, tiles.len() as u32 * tile_dimensions.height);
+    for (tile_index, tile) in tiles.iter().enumerate() {
+        image.copy_from(tile.image(), 0, tile_index as u32 * tile_dimensions.height).unwrap();
+    }
+    Ok(image)
+}
+
+pub fn load_set<P: AsRef<Path>>(dir: P) -> Result<TileSet, LoadError> {
+    let sd_tiles = load_font_image(file_path(&dir, TileKind::SD), TileKind::SD)?;
+    let hd_tiles = load_font_image(file_path(&dir, TileKind::HD), TileKind::HD)?;
+    Ok(TileSet::try_from_tiles(sd_tiles, hd_tiles)?)
+}
+
+pub fn save_set<P: AsRef<Path>>(tile_set: &TileSet, dir: P, encode_options: &ImageEncodeOptions) -> Result<(), SaveError> {
+    create_path(&dir)?;
+    let sd_image = generate_font_image(tile_set.sd_tiles())?;
+    sd_image.write_image_file_with_options(file_path(&dir, TileKind::SD), encode_options)?;
+    let hd_image = generate_font_image(tile_set.hd_tiles())?;
+    hd_image.write_image_file_with_options(file_path(&dir, TileKind::HD), encode_options)?;
+    let scaled_hd_image = imageops::resize(&hd_image, hd_image.width() * 2, hd_image.height() * 2, FilterType::Nearest);
+    scaled_hd_image.write_image_file_with_options(dir.as_ref().join(SCALED_HD_FILE_NAME), encode_options)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::image_encode::ImageEncodeOptions;
+    use crate::osd::tile::{container::tile_set::TileSet, Kind as TileKind, Tile};
+
+    use super::{load_set, save_set, SCALED_HD_FILE_NAME};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut sd_tiles = vec![Tile::new(TileKind::SD); 256];
+        sd_tiles[10].put_pixel(3, 3, image::Rgba([255, 255, 255, 255]));
+        let tile_set = TileSet::try_from_tiles(sd_tiles, vec![Tile::new(TileKind::HD); 256]).unwrap();
+        save_set(&tile_set, temp_dir.path(), &ImageEncodeOptions::default()).unwrap();
+        assert_eq!(image::image_dimensions(temp_dir.child(SCALED_HD_FILE_NAME)).unwrap(), (48, 256 * 72));
+
+        let loaded = load_set(temp_dir.path()).unwrap();
+        assert_eq!(loaded.sd_tiles().len(), 256);
+        assert_eq!(loaded.sd_tiles()[10].as_raw(), tile_set.sd_tiles()[10].as_raw());
+    }
+}
@@ -0,0 +1,212 @@
+
+//! Font files of msp-osd, the OSD overlay of the WTFOS rooted DJI goggles
+//!
+//! A font file is a PNG image of a single kind of tiles with the 4 pages of 256 tiles side by side, each page being a
+//! column of tiles: the tile at index `i` is at row `i % 256` of column `i / 256`. The SD and HD fonts of a set are
+//! stored in separate files named like the bin files: `font[_<ident>].png` and `font[_<ident>]_hd.png`.
+
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use image::{GenericImage, GenericImageView, ImageBuffer, Rgba};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use super::tile::{
+    Tile,
+    Kind as TileKind,
+    InvalidDimensionsError,
+    container::{
+        tile_set::TileSet,
+        uniq_tile_kind::{TileKindError, UniqTileKind},
+    },
+};
+
+use crate::{
+    create_path::{create_path, CreatePathError},
+    diagnostics::DetectionEvidence,
+    dimensions,
+    image_encode::ImageEncodeOptions,
+    io_context::IoContext,
+    image::{
+        read_image_file_with_context,
+        ReadError as ImageReadError,
+        WriteImageFile,
+        WriteError as ImageWriteError,
+    },
+};
+
+
+pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
+pub type ImageDimensions = dimensions::Dimensions<u32>;
+
+pub const PAGE_TILE_COUNT: usize = 256;
+pub const PAGE_COUNT: usize = 4;
+pub const TILE_COUNT: usize = PAGE_COUNT * PAGE_TILE_COUNT;
+
+impl TileKind {
+
+    pub const fn wtfos_image_dimensions(&self) -> ImageDimensions {
+        let tile_dimensions = self.dimensions();
+        ImageDimensions { width: PAGE_COUNT as u32 * tile_dimensions.width, height: PAGE_TILE_COUNT as u32 * tile_dimensions.height }
+    }
+
+    pub fn for_wtfos_image_dimensions(dimensions: ImageDimensions) -> Result<Self, InvalidDimensionsError> {
+        Self::iter().find(|kind| kind.wtfos_image_dimensions() == dimensions).ok_or(InvalidDimensionsError { dimensions })
+    }
+
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    ImageReadError(ImageReadError),
+    #[from(ignore)]
+    #[error("file {file_path} has dimensions ({dimensions}) which do not match any msp-osd font image")]
+    InvalidDimensionsError {
+        file_path: PathBuf,
+        dimensions: ImageDimensions
+    },
+    #[error(transparent)]
+    TileKindError(TileKindError),
+}
+
+impl LoadError {
+    pub fn invalid_dimensions<P: AsRef<Path>>(file_path: P, dimensions: ImageDimensions) -> Self {
+        Self::InvalidDimensionsError { file_path: file_path.as_ref().to_path_buf(), dimensions }
+    }
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    CreatePathError(CreatePathError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+}
+
+fn tile_position(tile_index: usize, tile_kind: TileKind) -> (u32, u32) {
+    let tile_dimensions = tile_kind.dimensions();
+    ((tile_index / PAGE_TILE_COUNT) as u32 * tile_dimensions.width, (tile_index % PAGE_TILE_COUNT) as u32 * tile_dimensions.height)
+}
+
+/// Loads the tiles of a font file, the trailing pages without any visible pixel are dropped but the first page is
+/// always loaded
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
+    load_with_context(path, &IoContext::default())
+}
+
+pub fn load_with_context<P: AsRef<Path>>(path: P, context: &IoContext) -> Result<Vec<Tile>, LoadError> {
+    let image = read_image_file_with_context(&path, context)?;
+    let tile_kind = TileKind::for_wtfos_image_dimensions(image.dimensions().into())
+        .map_err(|InvalidDimensionsError { dimensions }| LoadError::invalid_dimensions(&path, dimensions))?;
+    context.emit_detection(&path, tile_kind, DetectionEvidence::WtfosImageDimensions { dimensions: image.dimensions().into() });
+    let tile_dimensions = tile_kind.dimensions();
+    let mut tiles = vec![Tile::new(tile_kind); TILE_COUNT];
+    for (tile_index, tile) in tiles.iter_mut().enumerate() {
+        let (x, y) = tile_position(tile_index, tile_kind);
+        tile.copy_from(&image.view(x, y, tile_dimensions.width, tile_dimensions.height).to_image(), 0, 0).unwrap();
+    }
+    let page_count = tiles.chunks(PAGE_TILE_COUNT).rposition(|page| page.iter().any(|tile| ! tile.is_empty())).unwrap_or(0) + 1;
+    tiles.truncate(page_count * PAGE_TILE_COUNT);
+    Ok(tiles)
+}
+
+/// Saves the tiles to a font file, the pages past the end of the collection are left empty
+pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveError> {
+    save_with_options(tiles, path, &ImageEncodeOptions::default())
+}
+
+pub fn save_with_options<P: AsRef<Path>>(tiles: &[Tile], path: P, encode_options: &ImageEncodeOptions) -> Result<(), SaveError> {
+    if tiles.len() > TILE_COUNT {
+        log::warn!("msp-osd font files can only contain {TILE_COUNT} tiles but the source collection contains {}", tiles.len());
+    }
+    let tile_kind = tiles.tile_kind()?;
+    let dimensions = tile_kind.wtfos_image_dimensions();
+    let mut image = Image::new(dimensions.width, dimensions.height);
+    for (tile_index, tile) in tiles.iter().take(TILE_COUNT).enumerate() {
+        let (x, y) = tile_position(tile_index, tile_kind);
+        image.copy_from(tile.image(), x, y).unwrap();
+    }
+    image.write_image_file_with_options(path, encode_options)?;
+    Ok(())
+}
+
+pub fn normalized_file_name(tile_kind: TileKind, ident: &Option<&str>) -> PathBuf {
+    let ident = match ident {
+        Some(ident) => format!("_{ident}"),
+        None => "".to_owned(),
+    };
+    let kind_suffix = match tile_kind {
+        TileKind::SD => "",
+        TileKind::HD => "_hd",
+    };
+    PathBuf::from(format!("font{ident}{kind_suffix}.png"))
+}
+
+pub fn normalized_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> PathBuf {
+    dir.as_ref().join(normalized_file_name(tile_kind, ident))
+}
+
+/// Loads the SD and HD fonts of a set, the trailing empty pages dropped by [`load`] are kept so that both collections
+/// hold all the pages of the files
+pub fn load_set_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<TileSet, LoadError> {
+    load_set_norm_with_context(dir, ident, &IoContext::default())
+}
+
+pub fn load_set_norm_with_context<P: AsRef<Path>>(dir: P, ident: &Option<&str>, context: &IoContext) -> Result<TileSet, LoadError> {
+    let mut sd_tiles = load_with_context(normalized_file_path(&dir, TileKind::SD, ident), context)?;
+    let mut hd_tiles = load_with_context(normalized_file_path(&dir, TileKind::HD, ident), context)?;
+    sd_tiles.resize(TILE_COUNT, Tile::new(TileKind::SD));
+    hd_tiles.resize(TILE_COUNT, Tile::new(TileKind::HD));
+    Ok(TileSet::try_from_tiles(sd_tiles, hd_tiles)?)
+}
+
+pub fn save_set_norm<P: AsRef<Path>>(tile_set: &TileSet, dir: P, ident: &Option<&str>, encode_options: &ImageEncodeOptions) -> Result<(), SaveError> {
+    create_path(&dir)?;
+    save_with_options(tile_set.sd_tiles(), normalized_file_path(&dir, TileKind::SD, ident), encode_options)?;
+    save_with_options(tile_set.hd_tiles(), normalized_file_path(&dir, TileKind::HD, ident), encode_options)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::bin_file::{self, FontPart};
+    use crate::osd::tile::{Kind as TileKind, Tile, container::save_to_bin_file::SaveToBinFiles};
+
+    use super::{load, load_set_norm, normalized_file_path, save, PAGE_TILE_COUNT, TILE_COUNT};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font_hd.png");
+        let mut tiles = vec![Tile::new(TileKind::HD); 2 * PAGE_TILE_COUNT];
+        tiles[PAGE_TILE_COUNT + 3].put_pixel(2, 2, image::Rgba([255, 255, 255, 255]));
+        save(&tiles, &path).unwrap();
+        assert_eq!(image::image_dimensions(&path).unwrap(), (4 * 24, 256 * 36));
+        assert_eq!(image::open(&path).unwrap().into_rgba8().get_pixel(24 + 2, 3 * 36 + 2).0, [255, 255, 255, 255]);
+
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.len(), 2 * PAGE_TILE_COUNT);
+        assert_eq!(loaded[PAGE_TILE_COUNT + 3].as_raw(), tiles[PAGE_TILE_COUNT + 3].as_raw());
+    }
+
+    #[test]
+    fn single_page_set_to_bin_files() {
+        let temp_dir = TempDir::new().unwrap();
+        for tile_kind in [TileKind::SD, TileKind::HD] {
+            save(&vec![Tile::new(tile_kind); PAGE_TILE_COUNT], normalized_file_path(temp_dir.path(), tile_kind, &None)).unwrap();
+        }
+        let tile_set = load_set_norm(temp_dir.path(), &None).unwrap();
+        assert_eq!((tile_set.sd_tiles().len(), tile_set.hd_tiles().len()), (TILE_COUNT, TILE_COUNT));
+
+        // a collection shorter than the two bin files is padded with empty tiles
+        let bin_dir = temp_dir.child("bin");
+        load(normalized_file_path(temp_dir.path(), TileKind::SD, &None)).unwrap().save_to_bin_files_norm(&bin_dir, &None).unwrap();
+        assert!(bin_file::normalized_file_path(&bin_dir, TileKind::SD, &None, FontPart::Ext).exists());
+    }
+}
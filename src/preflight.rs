@@ -0,0 +1,293 @@
+
+use std::fs::OpenOptions;
+use std::io::{Error as IOError, ErrorKind as IOErrorKind};
+use std::path::{Path, PathBuf};
+
+use image::ImageError;
+use thiserror::Error;
+
+use crate::dimensions::Dimensions;
+use crate::osd::tile::{Kind as TileKind, KindSwapPolicy};
+use crate::osd::tile::grid::GridStyle;
+
+
+#[cfg(unix)]
+const READ_ONLY_FILESYSTEM_OS_ERROR: Option<i32> = Some(30); // EROFS
+#[cfg(windows)]
+const READ_ONLY_FILESYSTEM_OS_ERROR: Option<i32> = Some(19); // ERROR_WRITE_PROTECT
+#[cfg(not(any(unix, windows)))]
+const READ_ONLY_FILESYSTEM_OS_ERROR: Option<i32> = None;
+
+#[derive(Debug, Error)]
+pub enum DestinationError {
+    #[error("destination {path} is on a read-only filesystem: if it is an SD card check its write-protect switch, otherwise remount it read-write or choose another destination")]
+    ReadOnlyFilesystem { path: PathBuf },
+    #[error("permission denied writing to {path}: fix the permissions of {probed_path} or choose another destination")]
+    PermissionDenied { path: PathBuf, probed_path: PathBuf },
+    #[error("destination {path} is not writable: {error}")]
+    NotWritable { path: PathBuf, error: IOError },
+    #[error("writing {path} needs about {required} bytes but only {available} bytes are available on its filesystem: free some space or choose another destination")]
+    InsufficientSpace { path: PathBuf, required: u64, available: u64 },
+}
+
+impl DestinationError {
+    fn from_probe_error<P: AsRef<Path>, Q: AsRef<Path>>(path: P, probed_path: Q, error: IOError) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if error.raw_os_error().is_some() && error.raw_os_error() == READ_ONLY_FILESYSTEM_OS_ERROR {
+            Self::ReadOnlyFilesystem { path }
+        } else if error.kind() == std::io::ErrorKind::PermissionDenied {
+            Self::PermissionDenied { path, probed_path: probed_path.as_ref().to_path_buf() }
+        } else {
+            Self::NotWritable { path, error }
+        }
+    }
+}
+
+pub(crate) fn nearest_existing_ancestor(path: &Path) -> PathBuf {
+    let mut ancestor = path;
+    while ! ancestor.exists() {
+        match ancestor.parent() {
+            Some(parent) if ! parent.as_os_str().is_empty() => ancestor = parent,
+            _ => return PathBuf::from("."),
+        }
+    }
+    ancestor.to_path_buf()
+}
+
+/// Checks that `path` can be written before starting a conversion
+///
+/// Existing files are opened for writing without being modified, otherwise a probe file is created and removed in the
+/// nearest existing ancestor directory.
+pub fn check_destination_writable<P: AsRef<Path>>(path: P) -> Result<(), DestinationError> {
+    let probed_path = nearest_existing_ancestor(path.as_ref());
+    if probed_path.is_file() {
+        OpenOptions::new().write(true).open(&probed_path)
+            .map_err(|error| DestinationError::from_probe_error(&path, &probed_path, error))?;
+        return Ok(());
+    }
+    let probe_file_path = probed_path.join(format!(".hd_fpv_osd_font_tool_write_test_{}", std::process::id()));
+    OpenOptions::new().write(true).create_new(true).open(&probe_file_path)
+        .map_err(|error| DestinationError::from_probe_error(&path, &probed_path, error))?;
+    std::fs::remove_file(&probe_file_path)
+        .map_err(|error| DestinationError::from_probe_error(&path, &probed_path, error))?;
+    Ok(())
+}
+
+// each file uses a whole number of allocation units, 32KiB is the cluster size of the FAT32 SD cards of 32GB
+const ALLOCATION_UNIT_BYTES: u64 = 32 * 1024;
+
+/// Estimated space used by an output made of one file per tile or symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceEstimate {
+    /// size of the content of the files, PNG tiles are assumed to compress to half their raw size
+    pub data_bytes: u64,
+    /// space allocated to the files by the filesystem
+    pub allocated_bytes: u64,
+}
+
+impl std::ops::Add for SpaceEstimate {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { data_bytes: self.data_bytes + other.data_bytes, allocated_bytes: self.allocated_bytes + other.allocated_bytes }
+    }
+}
+
+pub fn estimate_tile_files_size(tile_count: usize, tile_kind: TileKind) -> SpaceEstimate {
+    let file_bytes = tile_kind.raw_rgba_size_bytes() as u64 / 2;
+    let allocated_file_bytes = file_bytes.div_ceil(ALLOCATION_UNIT_BYTES) * ALLOCATION_UNIT_BYTES;
+    SpaceEstimate { data_bytes: tile_count as u64 * file_bytes, allocated_bytes: tile_count as u64 * allocated_file_bytes }
+}
+
+/// Checks that the filesystem of `path` has room for an output before writing it
+///
+/// Fails when even the content of the files does not fit and warns when the space allocated to them may not, nothing
+/// is checked when the available space can not be determined.
+pub fn check_available_space<P: AsRef<Path>>(path: P, estimate: SpaceEstimate) -> Result<(), DestinationError> {
+    let probed_path = nearest_existing_ancestor(path.as_ref());
+    let available = match fs2::available_space(&probed_path) {
+        Ok(available) => available,
+        Err(error) => {
+            log::debug!("could not determine the space available for {}: {error}", path.as_ref().to_string_lossy());
+            return Ok(());
+        },
+    };
+    if estimate.data_bytes > available {
+        return Err(DestinationError::InsufficientSpace { path: path.as_ref().to_path_buf(), required: estimate.data_bytes, available });
+    }
+    if estimate.allocated_bytes > available {
+        log::warn!("writing {} may need up to {} bytes while {available} bytes are available, it may run out of space",
+            path.as_ref().to_string_lossy(), estimate.allocated_bytes);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum InputProblem {
+    #[error("{path}: file does not exist")]
+    Missing { path: PathBuf },
+    #[error("{path}: failed to read: {error}")]
+    Unreadable { path: PathBuf, error: IOError },
+    #[error("{path}: failed to read image: {error}")]
+    UnreadableImage { path: PathBuf, error: ImageError },
+    #[error("{path}: invalid bin file size: {size} bytes")]
+    InvalidBinFileSize { path: PathBuf, size: u64 },
+    #[error("{path}: invalid grid image dimensions: {dimensions}")]
+    InvalidGridImageDimensions { path: PathBuf, dimensions: Dimensions<u32> },
+    #[error("{path}: contains {found} tiles, expected {expected} tiles")]
+    UnexpectedTileKind { path: PathBuf, expected: TileKind, found: TileKind },
+    #[error("{path}: ident `{found}` differs from ident `{expected}` of {reference_path}")]
+    IdentMismatch { path: PathBuf, found: String, reference_path: PathBuf, expected: String },
+}
+
+/// All the problems found in the inputs of a set conversion
+#[derive(Debug, Error)]
+#[error("inconsistent inputs, nothing has been written:{}", .0.iter().map(|problem| format!("\n  {problem}")).collect::<String>())]
+pub struct InputsError(pub Vec<InputProblem>);
+
+impl InputsError {
+    fn result(problems: Vec<InputProblem>) -> Result<(), Self> {
+        if problems.is_empty() { Ok(()) } else { Err(Self(problems)) }
+    }
+}
+
+// ident of a normalized file name `<prefix>[_<ident>]<suffix>`, `None` if the name is not normalized
+fn file_name_ident(path: &Path, prefix: &str, suffixes: &[&str]) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let rest = file_name.strip_prefix(prefix)?;
+    let rest = suffixes.iter().find_map(|suffix| rest.strip_suffix(suffix))?;
+    match rest {
+        "" => Some(String::new()),
+        rest => rest.strip_prefix('_').map(str::to_owned),
+    }
+}
+
+fn check_idents(paths: &[&Path], prefix: &str, suffixes: &[&str], problems: &mut Vec<InputProblem>) {
+    let mut reference: Option<(&Path, String)> = None;
+    for path in paths {
+        let ident = match file_name_ident(path, prefix, suffixes) {
+            Some(ident) => ident,
+            None => continue,
+        };
+        match &reference {
+            Some((reference_path, expected)) if *expected != ident => problems.push(InputProblem::IdentMismatch {
+                path: path.to_path_buf(), found: ident, reference_path: reference_path.to_path_buf(), expected: expected.clone()
+            }),
+            Some(_) => {},
+            None => reference = Some((path, ident)),
+        }
+    }
+}
+
+// pushes a problem for each file whose tile kind is not the expected one unless the kinds are all swapped and
+// the policy allows it
+fn check_kinds(found_kinds: Vec<(&Path, TileKind, TileKind)>, swap_policy: KindSwapPolicy, problems: &mut Vec<InputProblem>) {
+    let all_swapped = found_kinds.iter().all(|(_, expected, found)| expected != found);
+    if all_swapped && swap_policy == KindSwapPolicy::AutoSwap {
+        return;
+    }
+    for (path, expected, found) in found_kinds {
+        if expected != found {
+            problems.push(InputProblem::UnexpectedTileKind { path: path.to_path_buf(), expected, found });
+        }
+    }
+}
+
+fn file_size(path: &Path) -> Result<u64, InputProblem> {
+    std::fs::metadata(path).map(|metadata| metadata.len()).map_err(|error| match error.kind() {
+        IOErrorKind::NotFound => InputProblem::Missing { path: path.to_path_buf() },
+        _ => InputProblem::Unreadable { path: path.to_path_buf(), error },
+    })
+}
+
+/// Checks that the four bin files of a set exist, have valid sizes matching their kinds and, when their names are
+/// normalized, the same ident
+///
+/// Only the file metadata is read so that all the problems are reported at once before anything is loaded or written.
+pub fn check_bin_file_set_inputs<P: AsRef<Path>>(sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P, swap_policy: KindSwapPolicy) -> Result<(), InputsError> {
+    let paths = [sd_path.as_ref(), sd_2_path.as_ref(), hd_path.as_ref(), hd_2_path.as_ref()];
+    let expected_kinds = [TileKind::SD, TileKind::SD, TileKind::HD, TileKind::HD];
+    let mut problems = vec![];
+    let mut found_kinds = vec![];
+    for (path, expected_kind) in paths.into_iter().zip(expected_kinds) {
+        match file_size(path) {
+            Ok(size) => match TileKind::for_bin_file_size_bytes(size) {
+                Ok(found_kind) => found_kinds.push((path, expected_kind, found_kind)),
+                Err(_) => problems.push(InputProblem::InvalidBinFileSize { path: path.to_path_buf(), size }),
+            },
+            Err(problem) => problems.push(problem),
+        }
+    }
+    check_kinds(found_kinds, swap_policy, &mut problems);
+    check_idents(&paths, "font", &["_hd_2.bin", "_hd.bin", "_2.bin", ".bin"], &mut problems);
+    InputsError::result(problems)
+}
+
+/// Checks that the two grid images of a set exist, have valid dimensions matching their kinds and, when their names
+/// are normalized, the same ident
+pub fn check_grid_set_inputs<P: AsRef<Path>>(sd_path: P, hd_path: P, style: &GridStyle, swap_policy: KindSwapPolicy) -> Result<(), InputsError> {
+    let paths = [sd_path.as_ref(), hd_path.as_ref()];
+    let mut problems = vec![];
+    let mut found_kinds = vec![];
+    for (path, expected_kind) in paths.into_iter().zip([TileKind::SD, TileKind::HD]) {
+        if ! path.exists() {
+            problems.push(InputProblem::Missing { path: path.to_path_buf() });
+            continue;
+        }
+        match image::image_dimensions(path) {
+            Ok(dimensions) => {
+                let dimensions = Dimensions::from(dimensions);
+                match style.image_tile_kind_and_grid_height(dimensions) {
+                    Ok((found_kind, _)) => found_kinds.push((path, expected_kind, found_kind)),
+                    Err(_) => problems.push(InputProblem::InvalidGridImageDimensions { path: path.to_path_buf(), dimensions }),
+                }
+            },
+            Err(error) => problems.push(InputProblem::UnreadableImage { path: path.to_path_buf(), error }),
+        }
+    }
+    check_kinds(found_kinds, swap_policy, &mut problems);
+    check_idents(&paths, "grid", &["_sd.png", "_hd.png"], &mut problems);
+    InputsError::result(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, KindSwapPolicy};
+
+    use super::{check_available_space, check_bin_file_set_inputs, check_destination_writable, estimate_tile_files_size, DestinationError, InputProblem, SpaceEstimate};
+
+    #[test]
+    fn available_space() {
+        let estimate = estimate_tile_files_size(512, TileKind::SD);
+        assert_eq!(estimate, SpaceEstimate { data_bytes: 512 * 36 * 54 * 2, allocated_bytes: 512 * 32 * 1024 });
+        let temp_dir = TempDir::new().unwrap();
+        check_available_space(temp_dir.child("tiles"), estimate).unwrap();
+        let too_large = SpaceEstimate { data_bytes: u64::MAX, allocated_bytes: u64::MAX };
+        assert!(matches!(check_available_space(temp_dir.child("tiles"), too_large), Err(DestinationError::InsufficientSpace { .. })));
+    }
+
+    #[test]
+    fn writable_destinations() {
+        let temp_dir = TempDir::new().unwrap();
+        check_destination_writable(temp_dir.path()).unwrap();
+        check_destination_writable(temp_dir.child("not/yet/created/font.bin")).unwrap();
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn bin_file_set_inputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let write = |name: &str, tile_kind: TileKind| std::fs::write(temp_dir.child(name), vec![0; tile_kind.bin_file_size_bytes()]).unwrap();
+        write("font_ardu.bin", TileKind::SD);
+        write("font_inav_2.bin", TileKind::SD);
+        write("font_ardu_hd.bin", TileKind::SD);
+        let paths = ["font_ardu.bin", "font_inav_2.bin", "font_ardu_hd.bin", "font_ardu_hd_2.bin"].map(|name| temp_dir.child(name));
+        let problems = check_bin_file_set_inputs(&paths[0], &paths[1], &paths[2], &paths[3], KindSwapPolicy::AutoSwap).unwrap_err().0;
+        assert_eq!(problems.len(), 3);
+        assert!(matches!(problems[0], InputProblem::Missing { .. }));
+        assert!(matches!(problems[1], InputProblem::UnexpectedTileKind { expected: TileKind::HD, found: TileKind::SD, .. }));
+        assert!(matches!(&problems[2], InputProblem::IdentMismatch { found, .. } if found == "inav"));
+    }
+}
@@ -5,14 +5,21 @@ pub use crate::osd::{
         LoadError as BinFileLoadError,
     },
     avatar_file::load as load_avatar_file,
+    font_profile::FontProfile,
     tile::{
         self,
         Tile,
         Dimensions as TileDimensions,
+        KindSwapPolicy,
+        file_format::{
+            FileFormat as TileFileFormat,
+            PngCompression,
+            SaveOptions as TileSaveOptions,
+        },
         container::{
             into_tile_grid::IntoTileGrid,
             load_symbols_from_dir::load_symbols_from_dir,
-            load_tiles_from_dir::load_tiles_from_dir,
+            load_tiles_from_dir::{load_tiles_from_dir, load_tiles_from_dir_split_kinds, load_tiles_from_dir_with_format},
             save_symbols_to_dir::SaveSymbolsToDir,
             save_tiles_to_dir::SaveTilesToDir,
             save_to_bin_file::{
@@ -24,6 +31,8 @@ pub use crate::osd::{
                 SaveTilesToAvatarFile,
             },
             save_to_grid::SaveToGridImage,
+            to_grid_image::{GridImages, ToGridImage},
+            repage::{repage, split_pages},
             symbol::{
                 set::Set as SymbolSet,
                 spec::Specs as SymbolSpecs,
@@ -35,6 +44,7 @@ pub use crate::osd::{
         grid::{
             Grid as TileGrid,
             Set as TileGridSet,
+            GridStyle as TileGridStyle,
             LoadError as GridLoadError,
             SaveImageError as GridSaveImageError,
         },
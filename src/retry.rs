@@ -0,0 +1,136 @@
+
+use std::io::{Error as IOError, ErrorKind as IOErrorKind};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::diagnostics::{Event, RetryEvent};
+
+
+// EIO, EBUSY and ESTALE are returned by SD card readers and network filesystems for errors which often go away
+#[cfg(unix)]
+const TRANSIENT_OS_ERRORS: &[i32] = &[5, 16, 116];
+#[cfg(not(unix))]
+const TRANSIENT_OS_ERRORS: &[i32] = &[];
+
+/// How many times and how long apart file operations failing with a transient IO error are attempted
+///
+/// The delay between two attempts doubles after each failure up to `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+
+    /// Operations are attempted only once, used by the one-shot commands of the command line tool
+    pub const NONE: Self = Self { max_attempts: 1, initial_delay: Duration::ZERO, max_delay: Duration::ZERO };
+
+    pub const fn new(max_attempts: u32, initial_delay: Duration, max_delay: Duration) -> Self {
+        Self { max_attempts, initial_delay, max_delay }
+    }
+
+    /// Policy of the batch jobs attempting the operations `retries` more times, 100ms apart then up to 5s apart
+    pub const fn batch(retries: u32) -> Self {
+        Self::new(retries.saturating_add(1), Duration::from_millis(100), Duration::from_secs(5))
+    }
+
+    /// Delay before the attempt following the given failed attempt, starting from 1
+    pub fn delay(&self, failed_attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(failed_attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    pub fn is_transient(error: &IOError) -> bool {
+        matches!(error.kind(), IOErrorKind::Interrupted | IOErrorKind::TimedOut | IOErrorKind::WouldBlock)
+            || error.raw_os_error().is_some_and(|code| TRANSIENT_OS_ERRORS.contains(&code))
+    }
+
+    /// Runs the operation on the file at `path` again after a delay while it fails with a transient error, each retry is
+    /// reported to `diagnostics`
+    pub fn retry_io<T, F: FnMut() -> Result<T, IOError>>(&self, path: &Path, diagnostics: &dyn Fn(&Event), mut operation: F) -> Result<T, IOError> {
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(error) if attempt < self.max_attempts && Self::is_transient(&error) => {
+                    let delay = self.delay(attempt);
+                    diagnostics(&Event::Retry(RetryEvent { path: path.to_path_buf(), failed_attempt: attempt, delay, error: error.to_string() }));
+                    thread::sleep(delay);
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error as IOError, ErrorKind as IOErrorKind};
+    use std::path::Path;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use crate::diagnostics::{self, Event};
+    use crate::io_context::IoContext;
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn retries_transient_errors() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(2));
+        assert_eq!(policy.delay(1), Duration::from_millis(1));
+        assert_eq!(policy.delay(5), Duration::from_millis(2));
+
+        let mut attempts = 0;
+        let result = policy.retry_io(Path::new("font.bin"), &diagnostics::log_event, || {
+            attempts += 1;
+            if attempts < 3 { Err(IOError::from(IOErrorKind::Interrupted)) } else { Ok(attempts) }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut attempts = 0;
+        let result: Result<(), _> = policy.retry_io(Path::new("font.bin"), &diagnostics::log_event, || {
+            attempts += 1;
+            Err(IOError::from(IOErrorKind::NotFound))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn retry_reported_to_context() {
+        let failed_attempts = Mutex::new(vec![]);
+        let record_retry = |event: &Event| if let Event::Retry(retry) = event {
+            failed_attempts.lock().unwrap().push(retry.failed_attempt);
+        };
+
+        // the operation fails once with a transient error then succeeds
+        let context = IoContext::new(RetryPolicy::batch(2), &record_retry);
+        let mut attempts = 0;
+        let result = context.retry_io(Path::new("font.bin"), || {
+            attempts += 1;
+            if attempts == 1 { Err(IOError::from(IOErrorKind::TimedOut)) } else { Ok(attempts) }
+        });
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(*failed_attempts.lock().unwrap(), [1]);
+
+        // the default context does not retry
+        let mut attempts = 0;
+        let result: Result<(), _> = IoContext::default().retry_io(Path::new("font.bin"), || {
+            attempts += 1;
+            Err(IOError::from(IOErrorKind::TimedOut))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
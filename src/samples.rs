@@ -0,0 +1,75 @@
+
+//! Small generated sample tiles and fonts to exercise the APIs without any file, enabled with the `samples` feature
+//!
+//! ```
+//! use hd_fpv_osd_font_tool::prelude::*;
+//! use hd_fpv_osd_font_tool::samples;
+//!
+//! let tiles = samples::font(tile::Kind::SD);
+//! let tile_grid = tiles.as_slice().into_tile_grid();
+//! assert_eq!(tile_grid.to_vec().len(), bin_file::TILE_COUNT);
+//! assert!(tile_grid.to_vec()[42].as_raw() == Tile::sample_numbered(tile::Kind::SD, 42).as_raw());
+//! ```
+
+use image::Rgba;
+
+use crate::osd::{
+    bin_file,
+    tile::{Kind as TileKind, Tile},
+    tile::container::tile_set::TileSet,
+};
+
+
+const FRAME_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const BIT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const NUMBER_BIT_COUNT: u32 = 9;
+
+impl Tile {
+
+    /// Sample tile of the given kind: a black frame
+    pub fn sample(kind: TileKind) -> Self {
+        let mut tile = Self::new(kind);
+        let (width, height) = tile.dimensions();
+        for (x, y, pixel) in tile.enumerate_pixels_mut() {
+            if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                *pixel = FRAME_COLOR;
+            }
+        }
+        tile
+    }
+
+    /// Sample tile which is different for each number below 512: a black frame with the bits of the number drawn in white
+    /// across the middle of the tile
+    pub fn sample_numbered(kind: TileKind, number: usize) -> Self {
+        let mut tile = Self::sample(kind);
+        let y = tile.height() / 2;
+        for bit in 0..NUMBER_BIT_COUNT {
+            if (number >> bit) & 1 == 1 {
+                tile.put_pixel(bit + 1, y, BIT_COLOR);
+            }
+        }
+        tile
+    }
+
+}
+
+/// `count` different sample tiles, see [`Tile::sample_numbered`]
+pub fn tiles(kind: TileKind, count: usize) -> Vec<Tile> {
+    (0..count).map(|number| Tile::sample_numbered(kind, number)).collect()
+}
+
+/// Sample font of the size of a bin file
+pub fn font(kind: TileKind) -> Vec<Tile> {
+    tiles(kind, bin_file::TILE_COUNT)
+}
+
+/// Content of a bin file holding the sample font
+pub fn bin_file_bytes(kind: TileKind) -> Vec<u8> {
+    font(kind).iter().flat_map(|tile| tile.as_raw().clone()).collect()
+}
+
+/// Sample tile set of two bin files per tile kind
+pub fn tile_set() -> TileSet {
+    TileSet::try_from_tiles(tiles(TileKind::SD, 2 * bin_file::TILE_COUNT), tiles(TileKind::HD, 2 * bin_file::TILE_COUNT))
+        .expect("sample tiles have the requested kind")
+}
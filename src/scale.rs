@@ -0,0 +1,140 @@
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use derive_more::From;
+use image::imageops::{self, FilterType};
+use thiserror::Error;
+
+use crate::create_path::{create_path, CreatePathError};
+use crate::image::{read_image_file, ReadError as ImageReadError, WriteImageFile, WriteError as ImageWriteError};
+use crate::image_encode::ImageEncodeOptions;
+use crate::osd::tile::{Kind as TileKind, Tile};
+
+
+/// Filter used to compute the pixels of scaled images, `nearest` keeps the pixel art look of the tiles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ScaleFilter {
+    #[default]
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    Lanczos3,
+}
+
+impl From<ScaleFilter> for FilterType {
+    fn from(filter: ScaleFilter) -> Self {
+        match filter {
+            ScaleFilter::Nearest => FilterType::Nearest,
+            ScaleFilter::Triangle => FilterType::Triangle,
+            ScaleFilter::CatmullRom => FilterType::CatmullRom,
+            ScaleFilter::Gaussian => FilterType::Gaussian,
+            ScaleFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+impl Tile {
+
+    /// Copy of the tile scaled to the dimensions of another kind of tiles, SD and HD tiles have the same aspect ratio
+    pub fn resize_to(&self, kind: TileKind, filter: ScaleFilter) -> Self {
+        if kind == self.kind() {
+            return self.clone();
+        }
+        let dimensions = kind.dimensions();
+        Self::try_from(imageops::resize(self.image(), dimensions.width(), dimensions.height(), filter.into()))
+            .expect("resized to the dimensions of a tile kind")
+    }
+
+}
+
+#[derive(Debug, From, Error)]
+pub enum ScaleError {
+    #[error(transparent)]
+    ImageReadError(ImageReadError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+    #[error(transparent)]
+    CreatePathError(CreatePathError),
+    #[error("failed to read directory: {0}")]
+    ReadDirError(IOError),
+}
+
+/// Path of the scaled variant of a file or directory: `@<factor>x` is appended to the file stem, e.g. grid@2x.png
+pub fn scaled_variant_path<P: AsRef<Path>>(path: P, factor: u32) -> PathBuf {
+    let path = path.as_ref();
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{stem}@{factor}x.{}", extension.to_string_lossy()),
+        None => format!("{stem}@{factor}x"),
+    };
+    path.with_file_name(file_name)
+}
+
+fn save_scaled_image(path: &Path, scaled_path: &Path, factor: u32, filter: ScaleFilter, encode_options: &ImageEncodeOptions) -> Result<(), ScaleError> {
+    let image = read_image_file(path)?.into_rgba8();
+    imageops::resize(&image, image.width() * factor, image.height() * factor, filter.into()).write_image_file_with_options(scaled_path, encode_options)?;
+    Ok(())
+}
+
+/// Writes a copy of an image file scaled by `factor` next to it
+pub fn save_scaled_image_copy<P: AsRef<Path>>(path: P, factor: u32, filter: ScaleFilter, encode_options: &ImageEncodeOptions) -> Result<PathBuf, ScaleError> {
+    let scaled_path = scaled_variant_path(&path, factor);
+    save_scaled_image(path.as_ref(), &scaled_path, factor, filter, encode_options)?;
+    Ok(scaled_path)
+}
+
+/// Writes a copy of a directory with each of its PNG files scaled by `factor` next to it
+pub fn save_scaled_dir_copy<P: AsRef<Path>>(dir: P, factor: u32, filter: ScaleFilter, encode_options: &ImageEncodeOptions) -> Result<PathBuf, ScaleError> {
+    let scaled_dir = scaled_variant_path(&dir, factor);
+    create_path(&scaled_dir)?;
+    for entry in fs_err::read_dir(dir.as_ref())? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "png") {
+            save_scaled_image(&path, &scaled_dir.join(path.file_name().unwrap()), factor, filter, encode_options)?;
+        }
+    }
+    Ok(scaled_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::image::WriteImageFile;
+    use crate::image_encode::ImageEncodeOptions;
+    use crate::osd::tile::{Kind as TileKind, Tile};
+
+    use super::{save_scaled_image_copy, scaled_variant_path, ScaleFilter};
+
+    #[test]
+    fn scaled_copy() {
+        assert_eq!(scaled_variant_path("fonts/grid.png", 3), Path::new("fonts/grid@3x.png"));
+        assert_eq!(scaled_variant_path("fonts/tiles", 2), Path::new("fonts/tiles@2x"));
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("image.png");
+        let mut image = image::RgbaImage::new(2, 3);
+        image.put_pixel(1, 2, Rgba([255, 0, 0, 255]));
+        image.write_image_file(&path).unwrap();
+        let scaled_path = save_scaled_image_copy(&path, 2, ScaleFilter::Nearest, &ImageEncodeOptions::default()).unwrap();
+        let scaled = image::open(scaled_path).unwrap().into_rgba8();
+        assert_eq!(scaled.dimensions(), (4, 6));
+        assert_eq!(*scaled.get_pixel(3, 5), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn resize_tile() {
+        let mut tile = Tile::new(TileKind::SD);
+        tile.put_pixel(35, 53, Rgba([255, 0, 0, 255]));
+        let resized = tile.resize_to(TileKind::HD, ScaleFilter::Nearest);
+        assert_eq!(resized.kind(), TileKind::HD);
+        assert_eq!(*resized.get_pixel(23, 35), Rgba([255, 0, 0, 255]));
+        assert_eq!(resized.resize_to(TileKind::SD, ScaleFilter::Lanczos3).dimensions(), (36, 54));
+    }
+}
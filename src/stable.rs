@@ -0,0 +1,145 @@
+
+//! Curated API with semver guarantees for applications depending on this crate
+//!
+//! Everything reachable from this module only changes in a backward incompatible way with a major version bump, unlike
+//! the rest of the crate including [`prelude`](crate::prelude) which follows the needs of the command line tool. Errors
+//! are reported through the opaque [`Error`] type so that the internal error types can evolve freely. Items are only
+//! ever removed from this module after having been deprecated for at least one minor release.
+//!
+//! [`Tile`] wraps the tile type of the crate so that only its own methods and trait implementations are covered. The
+//! other types are re-exported as is and only the following parts of them are covered:
+//!
+//! - [`TileKind`]: its variants and its `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq` and `Display` implementations
+//! - [`FontProfile`]: its `DJI_2PAGE`, `AVATAR_1PAGE`, `HDZERO` and `PRESETS` constants, its `name`, `tile_count`,
+//!   `tile_kinds` and `supports_tile_kind` methods and its `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq`, `Default`
+//!   and `Display` implementations
+//!
+//! Any other method or trait implementation of these types, like the `ValueEnum` implementation of `FontProfile`
+//! used by the command line, can change with a minor release.
+
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::osd::{
+    avatar_file,
+    bin_file,
+    tile,
+    tile::container::{
+        load_tiles_from_dir::load_tiles_from_dir,
+        save_tiles_to_dir::SaveTilesToDir,
+        save_to_avatar_file::SaveToAvatarFile,
+        save_to_bin_file::SaveToBinFile,
+        save_to_grid::SaveToGridImage,
+    },
+    tile::grid::Grid as TileGrid,
+};
+
+pub use crate::osd::{
+    font_profile::FontProfile,
+    tile::Kind as TileKind,
+};
+
+
+/// Number of tiles of a bin file
+pub const BIN_FILE_TILE_COUNT: usize = bin_file::TILE_COUNT;
+
+/// Error of any of the functions of this module, its message and source are meant for display only
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(Box<dyn std::error::Error + Send + Sync + 'static>);
+
+impl Error {
+    fn new<E: std::error::Error + Send + Sync + 'static>(error: E) -> Self {
+        Self(Box::new(error))
+    }
+}
+
+/// Tile of a font, its pixels are stored as RGBA bytes row by row
+#[derive(Debug, Clone)]
+pub struct Tile(tile::Tile);
+
+impl Tile {
+
+    /// Fully transparent tile
+    pub fn new(kind: TileKind) -> Self {
+        Self(tile::Tile::new(kind))
+    }
+
+    /// Tile of the kind matching the number of RGBA bytes
+    pub fn from_rgba_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        tile::Tile::try_from(bytes).map(Self).map_err(Error::new)
+    }
+
+    pub fn kind(&self) -> TileKind {
+        self.0.kind()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.0.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.0.height()
+    }
+
+    pub fn rgba_bytes(&self) -> &[u8] {
+        self.0.as_raw()
+    }
+
+    /// A tile is empty when all its pixels are fully transparent
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+}
+
+impl PartialEq for Tile {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind() && self.rgba_bytes() == other.rgba_bytes()
+    }
+}
+
+impl Eq for Tile {}
+
+fn wrap_tiles(tiles: Vec<tile::Tile>) -> Vec<Tile> {
+    tiles.into_iter().map(Tile).collect()
+}
+
+fn inner_tiles(tiles: &[Tile]) -> Vec<tile::Tile> {
+    tiles.iter().map(|tile| tile.0.clone()).collect()
+}
+
+pub fn load_bin_file<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, Error> {
+    bin_file::load(path).map(wrap_tiles).map_err(Error::new)
+}
+
+pub fn save_bin_file<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), Error> {
+    inner_tiles(tiles).save_to_bin_file(path).map_err(Error::new)
+}
+
+pub fn load_avatar_file<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, Error> {
+    avatar_file::load(path).map(wrap_tiles).map_err(Error::new)
+}
+
+pub fn save_avatar_file<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), Error> {
+    inner_tiles(tiles).save_to_avatar_file(path).map_err(Error::new)
+}
+
+pub fn load_grid_image<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, Error> {
+    Ok(wrap_tiles(TileGrid::load_from_image(path).map_err(Error::new)?.to_vec()))
+}
+
+pub fn save_grid_image<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), Error> {
+    inner_tiles(tiles).save_to_grid_image(path).map_err(Error::new)
+}
+
+/// Loads at most `max_tiles` tiles from a directory holding one PNG file per tile
+pub fn load_tile_dir<P: AsRef<Path>>(path: P, max_tiles: usize) -> Result<Vec<Tile>, Error> {
+    load_tiles_from_dir(path, max_tiles).map(wrap_tiles).map_err(Error::new)
+}
+
+/// Saves the tiles to a directory, one PNG file per tile
+pub fn save_tile_dir<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), Error> {
+    inner_tiles(tiles).save_tiles_to_dir(path).map_err(Error::new)
+}
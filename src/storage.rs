@@ -0,0 +1,189 @@
+
+//! Storage backends the collections are read from and written to, for services which do not keep the fonts on a
+//! local filesystem
+//!
+//! A [`Storage`] reads and writes whole objects given by a location, a path for the [`FileSystem`] default backend,
+//! a URL or a path relative to a base URL for the HTTP backend (`http` feature) and a key for the S3 backend (`s3`
+//! feature). Bin files are decoded from and encoded to memory, the collections of the other single file formats go
+//! through a temporary workspace since their loaders and savers work on files. The collections made of several files,
+//! directories and atlases, are not supported.
+
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "s3")]
+pub mod s3;
+
+use std::io::{Cursor, Error as IOError};
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::conversion::{self, CollectionFormat, CollectionSpec, ConversionError, ConversionOptions};
+use crate::diagnostics::Event;
+use crate::io_context::IoContext;
+use crate::osd::{
+    bin_file::{self, BinFileWriter},
+    tile::{Tile, container::{save_to_bin_file::SaveTilesToBinFileError, uniq_tile_kind::UniqTileKind}},
+};
+use crate::workspace::{self, Workspace};
+
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("{location}: {error}")]
+    FileError { location: String, error: IOError },
+    #[error("request for {location} failed: {message}")]
+    RequestError { location: String, message: String },
+    #[error("invalid storage backend configuration: {0}")]
+    ConfigurationError(String),
+    #[error("`{0}` collections are made of several files and cannot be read from or written to a storage backend")]
+    UnsupportedFormat(&'static str),
+}
+
+impl StorageError {
+
+    pub fn file_error(location: &str, error: IOError) -> Self {
+        Self::FileError { location: location.to_owned(), error }
+    }
+
+    pub fn request_error<E: ToString>(location: &str, error: E) -> Self {
+        Self::RequestError { location: location.to_owned(), message: error.to_string() }
+    }
+
+}
+
+/// Backend reading and writing whole objects
+pub trait Storage {
+    fn read(&self, location: &str) -> Result<Vec<u8>, StorageError>;
+    /// Replaces the object at `location` with `content`
+    fn write(&self, location: &str, content: &[u8]) -> Result<(), StorageError>;
+}
+
+/// Default backend, the locations are paths and the files are written atomically
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileSystem;
+
+impl Storage for FileSystem {
+
+    fn read(&self, location: &str) -> Result<Vec<u8>, StorageError> {
+        fs_err::read(location).map_err(|error| StorageError::file_error(location, error))
+    }
+
+    fn write(&self, location: &str, content: &[u8]) -> Result<(), StorageError> {
+        workspace::write_file(location, content).map_err(|error| StorageError::file_error(location, error))
+    }
+
+}
+
+fn check_single_file(format: CollectionFormat) -> Result<(), StorageError> {
+    use CollectionFormat::*;
+    match format {
+        TileDir { .. } | SymbolDir | Atlas => Err(StorageError::UnsupportedFormat(format.prefix())),
+        _ => Ok(()),
+    }
+}
+
+// the file name of the location is kept so that the loaders and savers selecting the image format from the extension
+// handle the staged file the same way
+fn staging_workspace(location: &str) -> Result<(Workspace, PathBuf), StorageError> {
+    let workspace = Workspace::create_in(std::env::temp_dir()).map_err(|error| StorageError::file_error(location, error))?;
+    let staging_path = workspace.staging_path(location);
+    Ok((workspace, staging_path))
+}
+
+/// Loads a collection of which the path of the specification is a location of `storage`, the diagnostics events of the
+/// load are sent to `diagnostics`
+pub fn load_tiles(storage: &dyn Storage, from: &CollectionSpec, options: &ConversionOptions, diagnostics: &(dyn Fn(&Event) + Sync))
+        -> Result<Vec<Tile>, ConversionError> {
+    check_single_file(from.format)?;
+    let location = from.path.to_string_lossy();
+    let content = storage.read(&location)?;
+    if from.format == CollectionFormat::DjiBin {
+        return Ok(bin_file::load_from_reader_with_context(Cursor::new(content), &IoContext::new(options.retry, diagnostics))?);
+    }
+    let (_workspace, staging_path) = staging_workspace(&location)?;
+    fs_err::write(&staging_path, content).map_err(|error| StorageError::file_error(&location, error))?;
+    conversion::load_tiles(&CollectionSpec::new(from.format, &staging_path), options, diagnostics)
+}
+
+fn encode_bin_file(tiles: &[Tile]) -> Result<Vec<u8>, SaveTilesToBinFileError> {
+    tiles.tile_kind()?;
+    let mut writer = BinFileWriter::from_writer(vec![]);
+    for tile in tiles {
+        writer.write_tile(tile)?;
+    }
+    writer.fill_remaining_space()?;
+    Ok(writer.into_inner()?)
+}
+
+/// Saves a collection to the location of `storage` given by the path of the specification, the diagnostics events of
+/// the save are sent to `diagnostics`
+pub fn save_tiles(storage: &dyn Storage, tiles: &[Tile], to: &CollectionSpec, options: &ConversionOptions, diagnostics: &(dyn Fn(&Event) + Sync))
+        -> Result<(), ConversionError> {
+    check_single_file(to.format)?;
+    let location = to.path.to_string_lossy();
+    let content = match to.format {
+        CollectionFormat::DjiBin => encode_bin_file(tiles)?,
+        format => {
+            let (_workspace, staging_path) = staging_workspace(&location)?;
+            conversion::save_tiles(tiles, &CollectionSpec::new(format, &staging_path), options, diagnostics)?;
+            fs_err::read(&staging_path).map_err(|error| StorageError::file_error(&location, error))?
+        },
+    };
+    Ok(storage.write(&location, &content)?)
+}
+
+/// Converts a collection read from `source` to another written to `destination`, returns the number of tiles converted
+pub fn convert(source: &dyn Storage, from: &CollectionSpec, destination: &dyn Storage, to: &CollectionSpec, options: &ConversionOptions,
+        diagnostics: &(dyn Fn(&Event) + Sync)) -> Result<usize, ConversionError> {
+    let tiles = load_tiles(source, from, options, diagnostics)?;
+    options.font_profile.check_tile_kind(tiles.tile_kind()?)?;
+    save_tiles(destination, &tiles, to, options, diagnostics)?;
+    Ok(tiles.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use crate::conversion::{CollectionFormat, CollectionSpec, ConversionOptions};
+    use crate::diagnostics;
+    use crate::osd::bin_file;
+
+    use super::{convert, load_tiles, Storage, StorageError};
+
+    #[derive(Default)]
+    struct MemoryStorage(Mutex<HashMap<String, Vec<u8>>>);
+
+    impl Storage for MemoryStorage {
+
+        fn read(&self, location: &str) -> Result<Vec<u8>, StorageError> {
+            self.0.lock().unwrap().get(location).cloned().ok_or_else(|| StorageError::request_error(location, "not found"))
+        }
+
+        fn write(&self, location: &str, content: &[u8]) -> Result<(), StorageError> {
+            self.0.lock().unwrap().insert(location.to_owned(), content.to_vec());
+            Ok(())
+        }
+
+    }
+
+    #[test]
+    fn memory_storage() {
+        let storage = MemoryStorage::default();
+        storage.write("uploads/font_hd.bin", &std::fs::read("test_files/djibinsetnorm/font_hd.bin").unwrap()).unwrap();
+        let options = ConversionOptions::default();
+        let bin_spec = CollectionSpec::new(CollectionFormat::DjiBin, "uploads/font_hd.bin");
+        let grid_spec = CollectionSpec::new(CollectionFormat::TileGrid, "artifacts/grid_hd.png");
+        assert_eq!(convert(&storage, &bin_spec, &storage, &grid_spec, &options, &diagnostics::log_event).unwrap(), 256);
+
+        let copy_spec = CollectionSpec::new(CollectionFormat::DjiBin, "artifacts/font_hd.bin");
+        convert(&storage, &grid_spec, &storage, &copy_spec, &options, &diagnostics::log_event).unwrap();
+        assert_eq!(storage.read("artifacts/font_hd.bin").unwrap(), storage.read("uploads/font_hd.bin").unwrap());
+        assert_eq!(load_tiles(&storage, &copy_spec, &options, &diagnostics::log_event).unwrap().len(), bin_file::load("test_files/djibinsetnorm/font_hd.bin").unwrap().len());
+
+        let dir_spec = CollectionSpec::new(CollectionFormat::SymbolDir, "artifacts/symbols");
+        assert!(load_tiles(&storage, &dir_spec, &options, &diagnostics::log_event).is_err());
+    }
+}
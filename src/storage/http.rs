@@ -0,0 +1,94 @@
+
+//! HTTP storage backend: objects are read with `GET` requests and written with `PUT` requests, as accepted by most
+//! artifact repositories and by presigned object store URLs
+
+use std::io::Read;
+use std::time::Duration;
+
+use super::{Storage, StorageError};
+
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct HttpStorage {
+    base_url: Option<String>,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+}
+
+impl HttpStorage {
+
+    /// Backend of which the locations are full URLs
+    pub fn new() -> Self {
+        Self { base_url: None, headers: vec![], timeout: DEFAULT_TIMEOUT }
+    }
+
+    /// Backend of which the locations are paths relative to `base_url`, full URLs are still accepted
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self { base_url: Some(base_url.trim_end_matches('/').to_owned()), ..Self::new() }
+    }
+
+    /// Adds a header sent with every request, e.g. `Authorization`
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn url(&self, location: &str) -> String {
+        match &self.base_url {
+            Some(base_url) if ! location.contains("://") => format!("{base_url}/{}", location.trim_start_matches('/')),
+            _ => location.to_owned(),
+        }
+    }
+
+    fn request(&self, method: &str, location: &str) -> ureq::Request {
+        self.headers.iter().fold(
+            ureq::request(method, &self.url(location))
+                .set("User-Agent", concat!("hd_fpv_osd_font_tool/", env!("CARGO_PKG_VERSION")))
+                .timeout(self.timeout),
+            |request, (name, value)| request.set(name, value)
+        )
+    }
+
+}
+
+impl Default for HttpStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Storage for HttpStorage {
+
+    fn read(&self, location: &str) -> Result<Vec<u8>, StorageError> {
+        let response = self.request("GET", location).call().map_err(|error| StorageError::request_error(location, error))?;
+        let mut content = vec![];
+        response.into_reader().read_to_end(&mut content).map_err(|error| StorageError::file_error(location, error))?;
+        Ok(content)
+    }
+
+    fn write(&self, location: &str, content: &[u8]) -> Result<(), StorageError> {
+        self.request("PUT", location).send_bytes(content).map_err(|error| StorageError::request_error(location, error))?;
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HttpStorage;
+
+    #[test]
+    fn urls() {
+        let storage = HttpStorage::with_base_url("https://fonts.example.com/builds/");
+        assert_eq!(storage.url("/42/font_hd.bin"), "https://fonts.example.com/builds/42/font_hd.bin");
+        assert_eq!(storage.url("https://cdn.example.com/font.bin"), "https://cdn.example.com/font.bin");
+        assert_eq!(HttpStorage::new().url("https://cdn.example.com/font.bin"), "https://cdn.example.com/font.bin");
+    }
+}
@@ -0,0 +1,145 @@
+
+//! Summary files describing the provenance of the outputs of a conversion
+//!
+//! The summary of a file output is written next to it as `<file>.summary.yaml`, the summary of a directory output is
+//! written inside it as `summary.yaml`.
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use derive_more::From;
+use fs_err::File;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+
+pub const FILE_EXTENSION: &str = "summary.yaml";
+pub const DIR_FILE_NAME: &str = "summary.yaml";
+
+#[derive(Debug, From, Error)]
+pub enum SummaryError {
+    #[error(transparent)]
+    IOError(IOError),
+    #[error("failed to parse summary file {file_path}: {error}")]
+    FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
+}
+
+impl SummaryError {
+    pub fn file_structure<P: AsRef<Path>>(file_path: P, error: serde_yaml::Error) -> Self {
+        Self::FileStructureError { file_path: file_path.as_ref().to_path_buf(), error }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    pub tool_version: String,
+    /// seconds since the UNIX epoch
+    pub created_at: u64,
+    pub source: String,
+    /// checksum of the source files, `None` if they could not be read
+    pub source_sha256: Option<String>,
+    pub destination: String,
+    /// command line arguments of the conversion, options included
+    pub arguments: Vec<String>,
+    pub symbol_specs_file: Option<PathBuf>,
+    pub symbol_specs_sha256: Option<String>,
+}
+
+/// Path of the summary file of the output at `output_path`
+pub fn summary_path<P: AsRef<Path>>(output_path: P) -> PathBuf {
+    let output_path = output_path.as_ref();
+    if output_path.is_dir() {
+        return output_path.join(DIR_FILE_NAME);
+    }
+    let mut path = output_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(FILE_EXTENSION);
+    path.into()
+}
+
+fn hash_path(hasher: &mut Sha256, path: &Path) -> Result<(), IOError> {
+    if path.is_dir() {
+        let mut file_paths = fs_err::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        file_paths.retain(|file_path| file_path.is_file() && ! file_path.to_string_lossy().ends_with(FILE_EXTENSION));
+        file_paths.sort();
+        for file_path in file_paths {
+            std::io::copy(&mut File::open(file_path)?, hasher)?;
+        }
+    } else {
+        std::io::copy(&mut File::open(path)?, hasher)?;
+    }
+    Ok(())
+}
+
+/// Checksum of the content of files and of the files of directories, in order
+pub fn paths_sha256<P: AsRef<Path>>(paths: &[P]) -> Result<String, IOError> {
+    let mut hasher = Sha256::new();
+    for path in paths {
+        hash_path(&mut hasher, path.as_ref())?;
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+impl Summary {
+
+    /// Collects the provenance of a conversion from `source` read from `source_paths` to `destination`
+    pub fn new<P: AsRef<Path>>(source: &str, source_paths: &[P], destination: &str, symbol_specs_file: Option<&Path>) -> Self {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_owned(),
+            created_at,
+            source: source.to_owned(),
+            source_sha256: paths_sha256(source_paths).ok(),
+            destination: destination.to_owned(),
+            arguments: std::env::args().skip(1).collect(),
+            symbol_specs_file: symbol_specs_file.map(Path::to_path_buf),
+            symbol_specs_sha256: symbol_specs_file.and_then(|path| paths_sha256(&[path]).ok()),
+        }
+    }
+
+    /// Saves the summary of the output at `output_path`, returns the path of the summary file
+    pub fn write<P: AsRef<Path>>(&self, output_path: P) -> Result<PathBuf, SummaryError> {
+        let path = summary_path(output_path);
+        let content = serde_yaml::to_string(self).map_err(|error| SummaryError::file_structure(&path, error))?;
+        fs_err::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Loads the summary of the output at `output_path`, returns `None` if there is no summary file
+    pub fn load<P: AsRef<Path>>(output_path: P) -> Result<Option<Self>, SummaryError> {
+        let path = summary_path(output_path);
+        if ! path.is_file() {
+            return Ok(None);
+        }
+        let summary = serde_yaml::from_reader(File::open(&path)?)
+            .map_err(|error| SummaryError::file_structure(&path, error))?;
+        Ok(Some(summary))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::{paths_sha256, summary_path, Summary, DIR_FILE_NAME};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let bin_file_path = temp_dir.child("font.bin");
+        std::fs::write(&bin_file_path, [0u8; 16]).unwrap();
+        assert_eq!(summary_path(&bin_file_path), temp_dir.child("font.bin.summary.yaml"));
+        assert_eq!(summary_path(temp_dir.path()), temp_dir.child(DIR_FILE_NAME));
+
+        assert!(Summary::load(&bin_file_path).unwrap().is_none());
+        let summary = Summary::new("djibin:font.bin", &[&bin_file_path], "tilegrid:grid.png", None);
+        assert_eq!(summary.source_sha256, Some(paths_sha256(&[temp_dir.path()]).unwrap()));
+        summary.write(&bin_file_path).unwrap();
+        assert_eq!(Summary::load(&bin_file_path).unwrap(), Some(summary));
+    }
+}
@@ -0,0 +1,203 @@
+
+//! Inline display of images in terminals
+//!
+//! Images are drawn with the kitty graphics protocol or sixel graphics by the terminals supporting them, other
+//! terminals get unicode half block characters with 24-bit colors drawing two pixels per character.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use clap::ValueEnum;
+use image::{imageops::{self, FilterType}, Rgba, RgbaImage};
+
+
+// Pixels less opaque than this are left to the terminal background
+const OPAQUE_THRESHOLD: u8 = 128;
+const KITTY_CHUNK_SIZE: usize = 4096;
+const SIXEL_MAX_COLORS: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Protocol {
+    /// detect the protocol supported by the terminal from the environment
+    #[default]
+    Auto,
+    Kitty,
+    Sixel,
+    /// unicode half block characters, works in any terminal supporting 24-bit colors
+    Blocks,
+}
+
+impl Protocol {
+
+    /// Protocol supported by the terminal according to the environment variables, `Blocks` if none is recognized
+    pub fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let (term, term_program) = (var("TERM"), var("TERM_PROGRAM"));
+        if std::env::var_os("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" || term == "xterm-ghostty"
+                || term_program == "WezTerm" || term_program == "ghostty" {
+            return Self::Kitty;
+        }
+        if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") || term_program == "iTerm.app" {
+            return Self::Sixel;
+        }
+        Self::Blocks
+    }
+
+    fn resolve(self) -> Self {
+        match self {
+            Self::Auto => Self::detect(),
+            protocol => protocol,
+        }
+    }
+
+}
+
+fn is_opaque(pixel: &Rgba<u8>) -> bool {
+    pixel[3] >= OPAQUE_THRESHOLD
+}
+
+/// Kitty graphics protocol escape sequences transmitting and displaying the image at the cursor position
+pub fn kitty(image: &RgbaImage) -> String {
+    let data = BASE64.encode(image.as_raw());
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    let mut output = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        match index {
+            0 => write!(output, "\x1b_Gf=32,s={},v={},a=T,m={more};{chunk}\x1b\\", image.width(), image.height()).unwrap(),
+            _ => write!(output, "\x1b_Gm={more};{chunk}\x1b\\").unwrap(),
+        }
+    }
+    output.push('\n');
+    output
+}
+
+// colors are reduced to 6 levels per channel when the image has more colors than a sixel palette can hold
+fn sixel_palette(image: &RgbaImage) -> (Vec<[u8; 3]>, HashMap<[u8; 3], usize>, bool) {
+    let mut colors: Vec<[u8; 3]> = image.pixels().filter(|pixel| is_opaque(pixel)).map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+    colors.sort_unstable();
+    colors.dedup();
+    let reduced = colors.len() > SIXEL_MAX_COLORS;
+    if reduced {
+        colors = colors.iter().map(|color| color.map(reduce_channel)).collect();
+        colors.sort_unstable();
+        colors.dedup();
+    }
+    let indices = colors.iter().enumerate().map(|(index, color)| (*color, index)).collect();
+    (colors, indices, reduced)
+}
+
+fn reduce_channel(value: u8) -> u8 {
+    ((value as u16 + 25) / 51 * 51) as u8
+}
+
+fn percent(value: u8) -> u32 {
+    (value as u32 * 100 + 127) / 255
+}
+
+fn push_sixel_run(output: &mut String, sixel: u8, count: usize) {
+    let char = char::from(63 + sixel);
+    match count {
+        0 => (),
+        1..=3 => (0..count).for_each(|_| output.push(char)),
+        count => write!(output, "!{count}{char}").unwrap(),
+    }
+}
+
+/// Sixel escape sequence drawing the image at the cursor position, transparent pixels are not drawn
+pub fn sixel(image: &RgbaImage) -> String {
+    let (colors, indices, reduced) = sixel_palette(image);
+    let color_index = |pixel: &Rgba<u8>| {
+        let color = [pixel[0], pixel[1], pixel[2]];
+        indices[&if reduced { color.map(reduce_channel) } else { color }]
+    };
+
+    let mut output = format!("\x1bP0;1;0q\"1;1;{};{}", image.width(), image.height());
+    for (index, color) in colors.iter().enumerate() {
+        write!(output, "#{index};2;{};{};{}", percent(color[0]), percent(color[1]), percent(color[2])).unwrap();
+    }
+
+    for band_y in (0..image.height()).step_by(6) {
+        let band_height = (image.height() - band_y).min(6);
+        let mut band_colors = vec![vec![0u8; image.width() as usize]; colors.len()];
+        for x in 0..image.width() {
+            for row in 0..band_height {
+                let pixel = image.get_pixel(x, band_y + row);
+                if is_opaque(pixel) {
+                    band_colors[color_index(pixel)][x as usize] |= 1 << row;
+                }
+            }
+        }
+        for (index, sixels) in band_colors.iter().enumerate() {
+            if sixels.iter().all(|sixel| *sixel == 0) {
+                continue;
+            }
+            write!(output, "#{index}").unwrap();
+            let mut run = (sixels[0], 0);
+            for sixel in sixels {
+                if *sixel != run.0 {
+                    push_sixel_run(&mut output, run.0, run.1);
+                    run = (*sixel, 0);
+                }
+                run.1 += 1;
+            }
+            push_sixel_run(&mut output, run.0, run.1);
+            output.push('$');
+        }
+        output.push('-');
+    }
+    output.push_str("\x1b\\\n");
+    output
+}
+
+/// Unicode half block characters drawing two vertical pixels per character with 24-bit colors
+pub fn blocks(image: &RgbaImage) -> String {
+    let mut output = String::new();
+    for y in (0..image.height()).step_by(2) {
+        for x in 0..image.width() {
+            let top = Some(image.get_pixel(x, y)).filter(|pixel| is_opaque(pixel));
+            let bottom = Some(y + 1).filter(|y| *y < image.height()).map(|y| image.get_pixel(x, y)).filter(|pixel| is_opaque(pixel));
+            match (top, bottom) {
+                (None, None) => output.push_str("\x1b[0m "),
+                (Some(top), None) => write!(output, "\x1b[0;38;2;{};{};{}m▀", top[0], top[1], top[2]).unwrap(),
+                (None, Some(bottom)) => write!(output, "\x1b[0;38;2;{};{};{}m▄", bottom[0], bottom[1], bottom[2]).unwrap(),
+                (Some(top), Some(bottom)) => write!(output, "\x1b[0;38;2;{};{};{};48;2;{};{};{}m▀",
+                    top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]).unwrap(),
+            }
+        }
+        output.push_str("\x1b[0m\n");
+    }
+    output
+}
+
+/// Escape sequences displaying the image with the protocol, scaled by `scale` for the kitty and sixel protocols
+pub fn render(image: &RgbaImage, protocol: Protocol, scale: u32) -> String {
+    let scaled = || imageops::resize(image, image.width() * scale, image.height() * scale, FilterType::Nearest);
+    match protocol.resolve() {
+        Protocol::Kitty => kitty(&scaled()),
+        Protocol::Sixel => sixel(&scaled()),
+        Protocol::Blocks | Protocol::Auto => blocks(image),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::{blocks, sixel};
+
+    #[test]
+    fn render() {
+        let mut image = RgbaImage::new(2, 3);
+        image.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        image.put_pixel(0, 1, Rgba([0, 0, 0, 255]));
+        image.put_pixel(1, 2, Rgba([255, 255, 255, 255]));
+        assert_eq!(blocks(&image), concat!(
+            "\x1b[0;38;2;255;255;255;48;2;0;0;0m▀\x1b[0m \x1b[0m\n",
+            "\x1b[0m \x1b[0;38;2;255;255;255m▀\x1b[0m\n",
+        ));
+        assert_eq!(sixel(&image), "\x1bP0;1;0q\"1;1;2;3#0;2;0;0;0#1;2;100;100;100#0A?$#1@C$-\x1b\\\n");
+    }
+}
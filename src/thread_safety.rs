@@ -0,0 +1,24 @@
+
+//! Compile-time checks that the containers can be shared between worker threads
+//!
+//! Loaded fonts are shared across threads by batch jobs, a field making one of these types `!Send` or `!Sync` fails the
+//! build of the crate here rather than the build of the applications depending on it.
+
+use crate::osd::tile::{
+    container::{symbol::{set::Set as SymbolSet, spec::Specs as SymbolSpecs, Symbol}, tile_set::TileSet},
+    grid::{Grid as TileGrid, Set as TileGridSet},
+    Tile,
+};
+
+
+const fn assert_send_sync<T: Send + Sync>() {}
+
+const _: () = {
+    assert_send_sync::<Tile>();
+    assert_send_sync::<TileGrid>();
+    assert_send_sync::<TileGridSet>();
+    assert_send_sync::<TileSet>();
+    assert_send_sync::<Symbol>();
+    assert_send_sync::<SymbolSet>();
+    assert_send_sync::<SymbolSpecs>();
+};
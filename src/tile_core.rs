@@ -0,0 +1,178 @@
+
+//! Pixel and tile math shared with firmware-side and embedded tools
+//!
+//! This module only depends on `core` and `alloc` so that it can be built in a `no_std` environment with an allocator,
+//! the rest of the library extends its types with the file and image related functionality. `tests/tile_core_no_std.rs`
+//! builds it in a `no_std` crate.
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::ops::Mul;
+
+use derive_more::{From, Sub, Div};
+use getset::CopyGetters;
+use strum::{EnumIter, IntoEnumIterator};
+
+
+#[derive(CopyGetters, PartialEq, Eq, PartialOrd, Ord, From, Debug, Clone, Copy, Div, Sub)]
+#[getset(get_copy = "pub")]
+pub struct Dimensions<T: PartialEq + Eq + PartialOrd + Ord + Copy + Clone + Display> {
+    pub width: T,
+    pub height: T
+}
+
+impl<T: PartialEq + Eq + PartialOrd + Ord + Copy + Display> Dimensions<T> {
+    pub const fn new(width: T, height: T) -> Self {
+        Self { width, height }
+    }
+}
+
+impl<T: PartialEq + Eq + PartialOrd + Ord + Copy + Display> Display for Dimensions<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl<T: PartialOrd + Ord + Copy + Mul<Output = T> + Display> Mul<T> for Dimensions<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self { width: self.width * rhs, height: self.height * rhs }
+    }
+}
+
+impl<T: PartialOrd + Ord + Copy + Mul<Output = T> + Display> Mul for Dimensions<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self { width: self.width * rhs.width, height: self.height * rhs.height }
+    }
+}
+
+pub const SD_DIMENSIONS: Dimensions<u32> = Dimensions::new(36, 54);
+pub const HD_DIMENSIONS: Dimensions<u32> = Dimensions::new(24, 36);
+
+#[derive(Debug, Copy, Clone, EnumIter, PartialEq, Eq, strum::Display)]
+pub enum Kind {
+    SD,
+    HD
+}
+
+impl Kind {
+
+    pub const fn dimensions(&self) -> Dimensions<u32> {
+        match self {
+            Kind::SD => SD_DIMENSIONS,
+            Kind::HD => HD_DIMENSIONS,
+        }
+    }
+
+    pub const fn raw_rgba_size_bytes(&self) -> usize {
+        let Dimensions { width, height } = self.dimensions();
+        width as usize * height as usize * 4
+    }
+
+    pub fn for_raw_rgba_size_bytes(bytes: u64) -> Option<Self> {
+        Self::iter().find(|kind| bytes == kind.raw_rgba_size_bytes() as u64)
+    }
+
+    pub fn for_dimensions(dimensions: Dimensions<u32>) -> Option<Self> {
+        Self::iter().find(|kind| kind.dimensions() == dimensions)
+    }
+
+}
+
+/// Splits raw RGBA bytes into pixels, trailing bytes not making a whole pixel are ignored
+pub fn unpack_rgba(bytes: &[u8]) -> Vec<[u8; 4]> {
+    bytes.chunks_exact(4).map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]]).collect()
+}
+
+pub fn pack_rgba(pixels: &[[u8; 4]]) -> Vec<u8> {
+    pixels.iter().flatten().copied().collect()
+}
+
+/// Pixel of the 2 bits per pixel format of the MAX7456 style analog OSD chips
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Pixel2bpp {
+    Black,
+    White,
+    Transparent,
+}
+
+impl Pixel2bpp {
+
+    const fn bits(&self) -> u8 {
+        match self {
+            Pixel2bpp::Black => 0b00,
+            Pixel2bpp::White => 0b10,
+            Pixel2bpp::Transparent => 0b01,
+        }
+    }
+
+    // both 01 and 11 are transparent
+    const fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => Pixel2bpp::Black,
+            0b10 => Pixel2bpp::White,
+            _ => Pixel2bpp::Transparent,
+        }
+    }
+
+    /// Mostly transparent pixels are transparent, the others are black or white depending on their luma
+    pub fn from_rgba([red, green, blue, alpha]: [u8; 4]) -> Self {
+        let luma = (299 * red as u32 + 587 * green as u32 + 114 * blue as u32) / 1000;
+        if alpha < 128 {
+            Pixel2bpp::Transparent
+        } else if luma >= 128 {
+            Pixel2bpp::White
+        } else {
+            Pixel2bpp::Black
+        }
+    }
+
+    pub const fn to_rgba(&self) -> [u8; 4] {
+        match self {
+            Pixel2bpp::Black => [0, 0, 0, 255],
+            Pixel2bpp::White => [255, 255, 255, 255],
+            Pixel2bpp::Transparent => [0, 0, 0, 0],
+        }
+    }
+
+}
+
+/// Packs 4 pixels per byte, the first pixel in the most significant bits, the last byte is padded with transparent pixels
+pub fn pack_2bpp(pixels: &[Pixel2bpp]) -> Vec<u8> {
+    pixels.chunks(4)
+        .map(|chunk| (0..4).fold(0, |byte, position| {
+            let pixel = chunk.get(position).copied().unwrap_or(Pixel2bpp::Transparent);
+            byte | pixel.bits() << (6 - 2 * position)
+        }))
+        .collect()
+}
+
+pub fn unpack_2bpp(bytes: &[u8], pixel_count: usize) -> Vec<Pixel2bpp> {
+    (0..pixel_count.min(bytes.len() * 4))
+        .map(|index| Pixel2bpp::from_bits(bytes[index / 4] >> (6 - 2 * (index % 4))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{pack_2bpp, pack_rgba, unpack_2bpp, unpack_rgba, Kind, Pixel2bpp};
+
+    #[test]
+    fn packing() {
+        assert_eq!(Kind::for_raw_rgba_size_bytes(24 * 36 * 4), Some(Kind::HD));
+
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(pack_rgba(&unpack_rgba(&bytes)), bytes);
+
+        let pixels = [Pixel2bpp::White, Pixel2bpp::Black, Pixel2bpp::Transparent, Pixel2bpp::White, Pixel2bpp::Black];
+        let packed = pack_2bpp(&pixels);
+        assert_eq!(packed, vec![0b1000_0110, 0b0001_0101]);
+        assert_eq!(unpack_2bpp(&packed, pixels.len()), pixels);
+        assert_eq!(Pixel2bpp::from_rgba(Pixel2bpp::White.to_rgba()), Pixel2bpp::White);
+    }
+}
@@ -0,0 +1,120 @@
+
+//! Opt-in check for newer releases of the tool
+//!
+//! Only the list of the releases of the project is downloaded, nothing is installed. The releases newer than the running
+//! version are reported with the lines of their notes about file formats so that users can tell whether upgrading
+//! brings support for the files they are working with.
+
+use std::io::Error as IOError;
+use std::time::Duration;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+
+pub const RELEASES_URL: &str = "https://api.github.com/repos/shellixyz/hd_fpv_osd_font_tool/releases";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// words of the lines of the release notes reported as format changes
+const FORMAT_CHANGE_KEYWORDS: &[&str] = &["format", "support"];
+
+#[derive(Debug, Error)]
+pub enum UpdateCheckError {
+    #[error("failed to query the release feed {RELEASES_URL}: {0}")]
+    RequestError(Box<ureq::Error>),
+    #[error("invalid release feed {RELEASES_URL}: {0}")]
+    FeedError(IOError),
+}
+
+/// `major.minor.patch` version, missing numbers are 0 and pre-release suffixes are ignored
+pub type Version = [u32; 3];
+
+pub fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(|char| char == '-' || char == '+').next().unwrap();
+    let mut numbers = [0; 3];
+    for (index, number) in version.split('.').enumerate() {
+        *numbers.get_mut(index)? = number.parse().ok()?;
+    }
+    Some(numbers)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+}
+
+impl Release {
+
+    pub fn version(&self) -> Option<Version> {
+        parse_version(&self.tag_name)
+    }
+
+    /// Lines of the release notes mentioning format changes, without their list markers
+    pub fn format_changes(&self) -> Vec<&str> {
+        self.body.as_deref().unwrap_or_default().lines()
+            .map(|line| line.trim().trim_start_matches(|char| char == '-' || char == '*').trim())
+            .filter(|line| {
+                let line = line.to_lowercase();
+                FORMAT_CHANGE_KEYWORDS.iter().any(|keyword| line.contains(keyword))
+            })
+            .collect()
+    }
+
+}
+
+/// Published releases newer than `current_version`, newest first
+pub fn newer_releases(releases: Vec<Release>, current_version: &str) -> Vec<Release> {
+    let current_version = parse_version(current_version).unwrap_or_default();
+    let mut newer: Vec<Release> = releases.into_iter()
+        .filter(|release| ! release.draft && ! release.prerelease && release.version().is_some_and(|version| version > current_version))
+        .collect();
+    newer.sort_by_key(|release| std::cmp::Reverse(release.version()));
+    newer
+}
+
+/// Queries the release feed of the project for releases newer than the running version
+pub fn check_for_updates() -> Result<Vec<Release>, UpdateCheckError> {
+    let releases: Vec<Release> = ureq::get(RELEASES_URL)
+        .set("User-Agent", concat!("hd_fpv_osd_font_tool/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT)
+        .call()
+        .map_err(|error| UpdateCheckError::RequestError(Box::new(error)))?
+        .into_json()
+        .map_err(UpdateCheckError::FeedError)?;
+    Ok(newer_releases(releases, env!("CARGO_PKG_VERSION")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{newer_releases, parse_version, Release};
+
+    fn release(tag_name: &str, body: &str, prerelease: bool) -> Release {
+        Release { tag_name: tag_name.to_owned(), html_url: String::new(), body: Some(body.to_owned()), draft: false, prerelease }
+    }
+
+    #[test]
+    fn newer_releases_order() {
+        assert_eq!(parse_version("v1.2"), Some([1, 2, 0]));
+        assert_eq!(parse_version("1.2.3-rc1"), Some([1, 2, 3]));
+        assert_eq!(parse_version("1.2.3.4"), None);
+
+        let releases = vec![
+            release("v1.0.0", "", false),
+            release("v1.2.0", "- Added HDZero font format support\n- Fixed a crash", false),
+            release("v1.3.0", "", true),
+            release("v1.1.1", "", false),
+        ];
+        let newer = newer_releases(releases, "1.1.0");
+        assert_eq!(newer.iter().map(|release| release.tag_name.as_str()).collect::<Vec<_>>(), vec!["v1.2.0", "v1.1.1"]);
+        assert_eq!(newer[0].format_changes(), vec!["Added HDZero font format support"]);
+    }
+}
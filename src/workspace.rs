@@ -0,0 +1,336 @@
+//! Workspaces holding the files being written until they are complete
+//!
+//! A conversion stages each of its outputs in a hidden workspace directory created next to the destinations and moves
+//! them in place together once all of them are fully written, so that a conversion which fails or is cancelled leaves
+//! neither truncated outputs nor clobbered previous versions behind. The files written next to an output, like its
+//! summary or scaled variants, are part of the output. A workspace is deleted with whatever it still holds when it is
+//! dropped.
+//!
+//! Each workspace holds a lock file locked for its lifetime: the workspaces left behind by a process which crashed or
+//! was killed are told apart by their free lock and deleted the first time a workspace is created in their directory.
+//! The lock file is locked under a temporary name before being renamed, so that it never appears unlocked.
+//!
+//! The outputs of a conversion written to several directories, which can be on different filesystems, are staged in
+//! [`Workspaces`]: one workspace per directory so that each output is moved in place without crossing filesystems.
+
+use std::collections::HashSet;
+use std::io::{Error as IOError, ErrorKind as IOErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use fs2::FileExt;
+use fs_err::File;
+use lazy_static::lazy_static;
+
+use crate::preflight::nearest_existing_ancestor;
+
+
+const DIR_NAME_PREFIX: &str = ".hd_fpv_osd_font_tool-";
+const DIR_NAME_SUFFIX: &str = ".workspace";
+const LOCK_FILE_NAME: &str = ".lock";
+const UNPUBLISHED_LOCK_FILE_NAME: &str = ".lock.new";
+// name of the staged output of which the destination has no file name, like `.`
+const UNNAMED_OUTPUT_NAME: &str = "output";
+
+static WORKSPACE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    // directories already checked for orphan workspaces by this process
+    static ref RECOVERED_DIRS: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+fn is_workspace_dir_name(name: &str) -> bool {
+    name.starts_with(DIR_NAME_PREFIX) && name.ends_with(DIR_NAME_SUFFIX)
+}
+
+// a workspace without lock file is still being created by another process
+fn is_orphan(workspace_dir: &Path) -> bool {
+    match File::open(workspace_dir.join(LOCK_FILE_NAME)) {
+        Ok(lock_file) => lock_file.file().try_lock_exclusive().is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Deletes the workspaces of `dir` left behind by the processes which did not exit cleanly, returns their paths
+pub fn recover_orphans<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, IOError> {
+    let mut removed = vec![];
+    for entry in fs_err::read_dir(dir.as_ref())? {
+        let entry = entry?;
+        if ! entry.file_name().to_str().is_some_and(is_workspace_dir_name) {
+            continue;
+        }
+        let path = entry.path();
+        if is_orphan(&path) {
+            fs_err::remove_dir_all(&path)?;
+            log::warn!("removed workspace {} left behind by an interrupted run", path.to_string_lossy());
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+// the other threads wait for the recovery to be done before creating their workspaces in the same directory
+fn recover_orphans_once(dir: &Path) {
+    let mut recovered_dirs = RECOVERED_DIRS.lock().unwrap();
+    if recovered_dirs.insert(dir.to_path_buf()) {
+        if let Err(error) = recover_orphans(dir) {
+            log::warn!("failed to look for workspaces left behind in {}: {error}", dir.to_string_lossy());
+        }
+    }
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if ! parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+// the directory of the destination, or its nearest existing ancestor when the directory is still to be created
+fn destination_workspace_dir(destination: &Path) -> PathBuf {
+    nearest_existing_ancestor(parent_dir(destination))
+}
+
+fn sorted_entry_paths(dir: &Path) -> Result<Vec<PathBuf>, IOError> {
+    let mut paths = fs_err::read_dir(dir)?.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+// the directories are merged into the existing ones, the files replace the existing ones
+fn move_into_place(staged_path: &Path, destination: &Path, moved_paths: &mut Vec<PathBuf>) -> Result<(), IOError> {
+    if staged_path.is_dir() {
+        fs_err::create_dir_all(destination)?;
+        for entry_path in sorted_entry_paths(staged_path)? {
+            move_into_place(&entry_path, &destination.join(entry_path.file_name().unwrap_or_default()), moved_paths)?;
+        }
+    } else {
+        fs_err::rename(staged_path, destination)?;
+        moved_paths.push(destination.to_path_buf());
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct Workspace {
+    dir: PathBuf,
+    // locked for the lifetime of the workspace, only taken to be closed before deleting the workspace
+    lock_file: Option<File>,
+    // staged path and destination of each output
+    outputs: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Workspace {
+
+    /// Creates a workspace in `dir`, which has to be on the same filesystem as the destinations of the files for them
+    /// to be moved in place atomically
+    pub fn create_in<P: AsRef<Path>>(dir: P) -> Result<Self, IOError> {
+        recover_orphans_once(dir.as_ref());
+        let name = format!("{DIR_NAME_PREFIX}{}-{}{DIR_NAME_SUFFIX}", std::process::id(), WORKSPACE_COUNTER.fetch_add(1, Ordering::Relaxed));
+        let workspace_dir = dir.as_ref().join(name);
+        fs_err::create_dir(&workspace_dir)?;
+        // deleted on drop if the lock file cannot be set up
+        let mut workspace = Self { dir: workspace_dir, lock_file: None, outputs: vec![] };
+        let unpublished_lock_file_path = workspace.dir.join(UNPUBLISHED_LOCK_FILE_NAME);
+        let lock_file = File::create(&unpublished_lock_file_path)?;
+        lock_file.file().try_lock_exclusive()?;
+        fs_err::rename(&unpublished_lock_file_path, workspace.dir.join(LOCK_FILE_NAME))?;
+        workspace.lock_file = Some(lock_file);
+        Ok(workspace)
+    }
+
+    /// Creates a workspace in the directory of `destination`, or in its nearest existing ancestor when the directory is
+    /// still to be created
+    pub fn for_destination<P: AsRef<Path>>(destination: P) -> Result<Self, IOError> {
+        Self::create_in(destination_workspace_dir(destination.as_ref()))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of a scratch file in the workspace, the file name of `destination` is kept so that the format of image
+    /// files can still be selected from their extension
+    pub fn staging_path<P: AsRef<Path>>(&self, destination: P) -> PathBuf {
+        self.dir.join(destination.as_ref().file_name().unwrap_or_default())
+    }
+
+    /// Stages an output, returns the path to write it to instead of `destination`
+    ///
+    /// Each output gets its own directory of the workspace: the files written next to the staged path are moved next
+    /// to `destination`. The staged directory is created when `destination` is an existing directory.
+    pub fn stage<P: AsRef<Path>>(&mut self, destination: P) -> Result<PathBuf, IOError> {
+        let destination = destination.as_ref();
+        let output_dir = self.dir.join(self.outputs.len().to_string());
+        fs_err::create_dir(&output_dir)?;
+        let staged_path = output_dir.join(destination.file_name().unwrap_or_else(|| UNNAMED_OUTPUT_NAME.as_ref()));
+        if destination.is_dir() {
+            fs_err::create_dir(&staged_path)?;
+        }
+        self.outputs.push((staged_path.clone(), destination.to_path_buf()));
+        Ok(staged_path)
+    }
+
+    /// Moves the staged outputs in place once all of them are written, returns the paths of the files moved
+    pub fn commit(self) -> Result<Vec<PathBuf>, IOError> {
+        let mut moved_paths = vec![];
+        for (staged_path, destination) in &self.outputs {
+            let destination_dir = parent_dir(destination);
+            fs_err::create_dir_all(destination_dir)?;
+            for entry_path in sorted_entry_paths(parent_dir(staged_path))? {
+                match &entry_path == staged_path {
+                    true => move_into_place(&entry_path, destination, &mut moved_paths)?,
+                    false => move_into_place(&entry_path, &destination_dir.join(entry_path.file_name().unwrap_or_default()), &mut moved_paths)?,
+                }
+            }
+        }
+        Ok(moved_paths)
+    }
+
+}
+
+impl Drop for Workspace {
+
+    fn drop(&mut self) {
+        drop(self.lock_file.take());
+        match fs_err::remove_dir_all(&self.dir) {
+            Err(error) if error.kind() != IOErrorKind::NotFound => log::warn!("failed to remove workspace: {error}"),
+            _ => {},
+        }
+    }
+
+}
+
+/// Workspaces of the outputs of a conversion, each output is staged in the workspace of the directory of its destination
+#[derive(Debug, Default)]
+pub struct Workspaces {
+    // directory each workspace is created in
+    workspaces: Vec<(PathBuf, Workspace)>,
+}
+
+impl Workspaces {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stages an output in the workspace created for the directory of `destination`, see [`Workspace::stage`]
+    pub fn stage<P: AsRef<Path>>(&mut self, destination: P) -> Result<PathBuf, IOError> {
+        let dir = destination_workspace_dir(destination.as_ref());
+        let index = match self.workspaces.iter().position(|(workspace_dir, _)| *workspace_dir == dir) {
+            Some(index) => index,
+            None => {
+                let workspace = Workspace::create_in(&dir)?;
+                self.workspaces.push((dir, workspace));
+                self.workspaces.len() - 1
+            },
+        };
+        self.workspaces[index].1.stage(destination)
+    }
+
+    /// Moves the staged outputs in place once all of them are written, returns the paths of the files moved
+    pub fn commit(self) -> Result<Vec<PathBuf>, IOError> {
+        let mut moved_paths = vec![];
+        for (_, workspace) in self.workspaces {
+            moved_paths.extend(workspace.commit()?);
+        }
+        Ok(moved_paths)
+    }
+
+}
+
+/// Writes a single file output through a workspace in the directory of `destination`
+///
+/// `write` is given the path to write the file to, the file is moved to `destination` once it returned successfully.
+/// `io_error` converts the errors of the workspace operations.
+pub fn write_atomically<P, T, E, W, M>(destination: P, write: W, io_error: M) -> Result<T, E>
+where
+    P: AsRef<Path>,
+    W: FnOnce(&Path) -> Result<T, E>,
+    M: Fn(IOError) -> E,
+{
+    let mut workspace = Workspace::for_destination(&destination).map_err(&io_error)?;
+    let staged_path = workspace.stage(&destination).map_err(&io_error)?;
+    let result = write(&staged_path)?;
+    workspace.commit().map_err(io_error)?;
+    Ok(result)
+}
+
+/// Atomic replacement for `fs::write`
+pub fn write_file<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, content: C) -> Result<(), IOError> {
+    write_atomically(path, |staging_path| fs_err::write(staging_path, content), |error| error)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::{recover_orphans, write_atomically, Workspace, Workspaces};
+
+    #[test]
+    fn workspace_lifecycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let destination = temp_dir.child("font.mcm");
+
+        let result: Result<(), std::io::Error> = write_atomically(&destination, |staging_path| {
+            std::fs::write(staging_path, "partial").unwrap();
+            Err(std::io::Error::other("cancelled"))
+        }, |error| error);
+        assert!(result.is_err());
+        assert!(! destination.exists());
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+
+        super::write_file(&destination, "MAX7456").unwrap();
+        assert_eq!(std::fs::read_to_string(&destination).unwrap(), "MAX7456");
+
+        // a workspace of a killed process is still there but its lock is free
+        let orphan_path = temp_dir.child(".hd_fpv_osd_font_tool-0-0.workspace");
+        std::fs::create_dir(&orphan_path).unwrap();
+        std::fs::write(orphan_path.join(".lock"), []).unwrap();
+        let workspace = Workspace::create_in(temp_dir.path()).unwrap();
+        assert_eq!(recover_orphans(temp_dir.path()).unwrap(), vec![orphan_path]);
+        assert!(workspace.path().join(".lock").is_file());
+    }
+
+    #[test]
+    fn outputs_committed_together() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.child("tiles")).unwrap();
+        std::fs::write(temp_dir.child("tiles").join("001.png"), "previous").unwrap();
+        std::fs::write(temp_dir.child("tiles").join("002.png"), "kept").unwrap();
+
+        let mut workspace = Workspace::for_destination(temp_dir.child("tiles")).unwrap();
+        let staged_dir = workspace.stage(temp_dir.child("tiles")).unwrap();
+        std::fs::write(staged_dir.join("001.png"), "tile").unwrap();
+        std::fs::write(staged_dir.with_file_name("tiles@2x"), "scaled").unwrap();
+        let staged_file = workspace.stage(temp_dir.child("fonts/font.bin")).unwrap();
+        std::fs::write(&staged_file, "font").unwrap();
+        assert!(! temp_dir.child("fonts").exists());
+
+        assert_eq!(workspace.commit().unwrap(), vec![temp_dir.child("tiles").join("001.png"), temp_dir.child("tiles@2x"),
+            temp_dir.child("fonts/font.bin")]);
+        assert_eq!(std::fs::read_to_string(temp_dir.child("tiles").join("001.png")).unwrap(), "tile");
+        assert_eq!(std::fs::read_to_string(temp_dir.child("tiles").join("002.png")).unwrap(), "kept");
+        assert_eq!(std::fs::read_to_string(temp_dir.child("fonts/font.bin")).unwrap(), "font");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn outputs_staged_next_to_their_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.child("sd")).unwrap();
+        std::fs::create_dir(temp_dir.child("hd")).unwrap();
+
+        let mut workspaces = Workspaces::new();
+        let staged_paths = ["sd/font.png", "hd/font_hd.png"].map(|destination| workspaces.stage(temp_dir.child(destination)).unwrap());
+        for (staged_path, dir) in staged_paths.iter().zip(["sd", "hd"]) {
+            assert!(staged_path.starts_with(temp_dir.child(dir)));
+            std::fs::write(staged_path, dir).unwrap();
+        }
+
+        assert_eq!(workspaces.commit().unwrap(), vec![temp_dir.child("sd/font.png"), temp_dir.child("hd/font_hd.png")]);
+        assert_eq!(std::fs::read_dir(temp_dir.child("sd")).unwrap().count(), 1);
+        assert_eq!(std::fs::read_to_string(temp_dir.child("hd/font_hd.png")).unwrap(), "hd");
+    }
+}
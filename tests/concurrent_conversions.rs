@@ -0,0 +1,43 @@
+
+// Converts fonts from several threads of one process sharing the loaded fonts, as batch jobs do
+
+use std::sync::Arc;
+use std::thread;
+
+use hd_fpv_osd_font_tool::prelude::*;
+use temp_dir::TempDir;
+
+const THREAD_COUNT: usize = 8;
+
+fn same_tiles(tiles: &[Tile], other_tiles: &[Tile]) -> bool {
+    tiles.len() == other_tiles.len() && tiles.iter().zip(other_tiles).all(|(tile, other_tile)| tile.as_raw() == other_tile.as_raw())
+}
+
+#[test]
+fn concurrent_conversions() {
+    let tile_set = Arc::new(TileSet::load_bin_files_norm("test_files/djibinsetnorm", &None).unwrap());
+
+    let workers: Vec<_> = (0..THREAD_COUNT).map(|worker| {
+        let tile_set = Arc::clone(&tile_set);
+        thread::spawn(move || {
+            let temp_dir = TempDir::new().unwrap();
+            let (sd_grid_path, hd_grid_path) = (temp_dir.child("sd.png"), temp_dir.child("hd.png"));
+            tile_set.save_to_grids(&sd_grid_path, &hd_grid_path).unwrap();
+            let grid_tile_set = TileSet::load_from_tile_grids(&sd_grid_path, &hd_grid_path).unwrap();
+            assert!(same_tiles(grid_tile_set.sd_tiles(), tile_set.sd_tiles()), "worker {worker}: SD tiles differ");
+            assert!(same_tiles(grid_tile_set.hd_tiles(), tile_set.hd_tiles()), "worker {worker}: HD tiles differ");
+
+            grid_tile_set.save_to_bin_files_norm(temp_dir.path(), &None).unwrap();
+            let bin_tile_set = TileSet::load_bin_files_norm(temp_dir.path(), &None).unwrap();
+            assert!(same_tiles(bin_tile_set.hd_tiles(), tile_set.hd_tiles()), "worker {worker}: bin file round trip differs");
+
+            let tile_dir = temp_dir.child("tiles");
+            tile_set.hd_tiles().save_tiles_to_dir(&tile_dir).unwrap();
+            assert!(same_tiles(&load_tiles_from_dir(&tile_dir, 512).unwrap(), tile_set.hd_tiles()), "worker {worker}: tile directory round trip differs");
+        })
+    }).collect();
+
+    for worker in workers {
+        worker.join().unwrap();
+    }
+}
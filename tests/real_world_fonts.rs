@@ -0,0 +1,115 @@
+
+// Conversion tests on real-world fonts which are not stored in the repository
+//
+// The fixtures are listed with their pinned hash in `test_files/real_world/fixtures.txt` and fetched with
+// `just fetch-fixtures`, the tests are ignored by default and can be run with `just test-real-world`
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use hd_fpv_osd_font_tool::osd::tile::container::uniq_tile_kind::UniqTileKind;
+use hd_fpv_osd_font_tool::prelude::*;
+use sha2::{Digest, Sha256};
+use temp_dir::TempDir;
+
+const FIXTURES_DIR: &str = "test_files/real_world";
+const MANIFEST_FILE_NAME: &str = "fixtures.txt";
+const DOWNLOADS_DIR_NAME: &str = "downloads";
+const SYMBOL_SPECS_FILE: &str = "symbol_specs/ardu.yaml";
+
+struct Fixture {
+    sha256: String,
+    format: String,
+    file_name: String,
+}
+
+impl Fixture {
+    fn path(&self) -> PathBuf {
+        [Path::new(FIXTURES_DIR), Path::new(DOWNLOADS_DIR_NAME), Path::new(&self.file_name)].iter().collect()
+    }
+}
+
+fn fixtures() -> Vec<Fixture> {
+    let manifest = fs::read_to_string(Path::new(FIXTURES_DIR).join(MANIFEST_FILE_NAME)).unwrap();
+    manifest.lines()
+        .map(str::trim)
+        .filter(|line| ! line.is_empty() && ! line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(fields.len(), 4, "invalid fixture manifest line: {line}");
+            Fixture { sha256: fields[0].to_lowercase(), format: fields[1].to_owned(), file_name: fields[2].to_owned() }
+        })
+        .collect()
+}
+
+fn file_sha256<P: AsRef<Path>>(path: P) -> String {
+    let mut hasher = Sha256::new();
+    let mut file = fs::File::open(path).unwrap();
+    io::copy(&mut file, &mut hasher).unwrap();
+    hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn load_fixture(fixture: &Fixture) -> Vec<Tile> {
+    let path = fixture.path();
+    assert!(path.is_file(), "fixture {} is missing, run `just fetch-fixtures`", path.to_string_lossy());
+    assert_eq!(file_sha256(&path), fixture.sha256, "fixture {} does not match its pinned hash", path.to_string_lossy());
+    match fixture.format.as_str() {
+        "djibin" => bin_file::load(&path).unwrap(),
+        "avatar" => load_avatar_file(&path).unwrap(),
+        "tilegrid" => TileGrid::load_from_image(&path).unwrap().to_vec(),
+        format => panic!("unsupported fixture format: {format}"),
+    }
+}
+
+fn tile_is_empty(tile: &Tile) -> bool {
+    tile.as_raw().iter().all(|byte| *byte == 0)
+}
+
+// collections can be padded or trimmed with empty tiles depending on the format
+fn assert_same_tiles(expected: &[Tile], actual: &[Tile], context: &str) {
+    for index in 0..expected.len().max(actual.len()) {
+        match (expected.get(index), actual.get(index)) {
+            (Some(expected), Some(actual)) => assert!(expected.as_raw() == actual.as_raw(), "{context}: tile {index} differs"),
+            (Some(tile), None) | (None, Some(tile)) => assert!(tile_is_empty(tile), "{context}: tile {index} is missing"),
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs the real-world fixtures, run `just fetch-fixtures`"]
+fn real_world_fonts_conversion_matrix() {
+    let symbol_specs = SymbolSpecs::load_file(SYMBOL_SPECS_FILE).unwrap();
+    let temp_dir = TempDir::new().unwrap();
+
+    for fixture in fixtures() {
+        let tiles = load_fixture(&fixture);
+        let tile_kind = tiles.tile_kind().unwrap();
+        println!("testing {} ({}, {tile_kind}, {} tiles)", fixture.file_name, fixture.format, tiles.len());
+
+        if tiles.len() <= bin_file::TILE_COUNT {
+            let path = temp_dir.child(format!("{}.bin", fixture.file_name));
+            tiles.save_to_bin_file(&path).unwrap();
+            assert_same_tiles(&tiles, &bin_file::load(&path).unwrap(), "djibin");
+        }
+
+        if tiles.len() >= bin_file::TILE_COUNT {
+            let path = temp_dir.child(format!("{}_avatar.png", fixture.file_name));
+            tiles.save_to_avatar_file(&path).unwrap();
+            assert_same_tiles(&tiles[0..bin_file::TILE_COUNT], &load_avatar_file(&path).unwrap(), "avatar");
+        }
+
+        let path = temp_dir.child(format!("{}_grid.png", fixture.file_name));
+        tiles.save_to_grid_image(&path).unwrap();
+        assert_same_tiles(&tiles, &TileGrid::load_from_image(&path).unwrap(), "tilegrid");
+
+        let path = temp_dir.child(format!("{}_tiledir", fixture.file_name));
+        tiles.save_tiles_to_dir(&path).unwrap();
+        assert_same_tiles(&tiles, &load_tiles_from_dir(&path, 512).unwrap(), "tiledir");
+
+        let path = temp_dir.child(format!("{}_symdir", fixture.file_name));
+        tiles.to_symbols(&symbol_specs).unwrap().save_to_dir(&path).unwrap();
+        assert_same_tiles(&tiles, &load_symbols_from_dir(&path, 512).unwrap().into_tiles_vec(), "symdir");
+    }
+}
@@ -0,0 +1,55 @@
+
+// Compile-time checks of the signatures of the `stable` module: any change breaking this file is a breaking change of
+// the public API and requires a major version bump
+
+use std::path::PathBuf;
+
+use hd_fpv_osd_font_tool::stable::{self, Error, FontProfile, Tile, TileKind, BIN_FILE_TILE_COUNT};
+use temp_dir::TempDir;
+
+#[allow(clippy::type_complexity)]
+const _SIGNATURES: (
+    fn(PathBuf) -> Result<Vec<Tile>, Error>,
+    fn(&[Tile], PathBuf) -> Result<(), Error>,
+    fn(PathBuf) -> Result<Vec<Tile>, Error>,
+    fn(&[Tile], PathBuf) -> Result<(), Error>,
+    fn(PathBuf) -> Result<Vec<Tile>, Error>,
+    fn(&[Tile], PathBuf) -> Result<(), Error>,
+    fn(PathBuf, usize) -> Result<Vec<Tile>, Error>,
+    fn(&[Tile], PathBuf) -> Result<(), Error>,
+) = (
+    stable::load_bin_file::<PathBuf>,
+    stable::save_bin_file::<PathBuf>,
+    stable::load_avatar_file::<PathBuf>,
+    stable::save_avatar_file::<PathBuf>,
+    stable::load_grid_image::<PathBuf>,
+    stable::save_grid_image::<PathBuf>,
+    stable::load_tile_dir::<PathBuf>,
+    stable::save_tile_dir::<PathBuf>,
+);
+
+fn assert_error_is_sendable<E: std::error::Error + Send + Sync + 'static>() {}
+
+#[test]
+fn stable_api() {
+    assert_error_is_sendable::<Error>();
+    assert_eq!(BIN_FILE_TILE_COUNT, 256);
+    assert!(FontProfile::default().supports_tile_kind(TileKind::SD));
+    assert!(Tile::new(TileKind::HD).is_empty());
+    assert!(Tile::from_rgba_bytes(vec![0; 4]).is_err());
+
+    let tiles = stable::load_bin_file("test_files/djibinsetnorm/font.bin").unwrap();
+    let temp_dir = TempDir::new().unwrap();
+    let grid_path = temp_dir.child("grid.png");
+    stable::save_grid_image(&tiles, &grid_path).unwrap();
+    assert_eq!(stable::load_grid_image(&grid_path).unwrap(), tiles);
+    assert!(stable::load_bin_file(temp_dir.child("missing.bin")).is_err());
+}
+
+// the pass cases use the API the way applications do, the fail cases use what is kept out of the semver policy
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/pass/*.rs");
+    cases.compile_fail("tests/ui/fail/*.rs");
+}
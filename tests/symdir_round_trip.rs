@@ -0,0 +1,71 @@
+
+// Conversions between tile directories and symbol directories must keep every tile at its index, including the tiles
+// not covered by the symbol specs and the ones of a symbol truncated by the end of the collection
+
+use std::fs;
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::prelude::*;
+use image::Rgba;
+use temp_dir::TempDir;
+
+const TILE_COUNT: usize = 20;
+const SPECS: &str = "version: 2\nsymbols:\n  arrow: '2:3'\n  home: '0x0A:2'\n  truncated: '18:4'\n";
+
+// a single opaque pixel at a different position in each tile, tile 5 is left empty
+fn tiles() -> Vec<Tile> {
+    (0..TILE_COUNT).map(|index| {
+        let mut tile = Tile::new(tile::Kind::SD);
+        if index != 5 {
+            tile.put_pixel(index as u32, 1, Rgba([255, 255, 255, 255]));
+        }
+        tile
+    }).collect()
+}
+
+fn assert_same_tiles(tiles: &[Tile], other_tiles: &[Tile]) {
+    assert_eq!(tiles.len(), other_tiles.len());
+    for (index, (tile, other_tile)) in tiles.iter().zip(other_tiles).enumerate() {
+        assert!(tile.as_raw() == other_tile.as_raw(), "tile {index} differs");
+    }
+}
+
+fn file_names<P: AsRef<Path>>(dir: P) -> Vec<String> {
+    let mut file_names: Vec<String> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned()).collect();
+    file_names.sort();
+    file_names
+}
+
+#[test]
+fn tiledir_symdir_round_trips() {
+    let temp_dir = TempDir::new().unwrap();
+    let specs_path = temp_dir.child("specs.yaml");
+    fs::write(&specs_path, SPECS).unwrap();
+    let specs = SymbolSpecs::load_file(&specs_path).unwrap();
+    let tiles = tiles();
+
+    // tiledir -> symdir -> tiledir
+    tiles.save_tiles_to_dir(temp_dir.child("tiles")).unwrap();
+    let loaded_tiles = load_tiles_from_dir(temp_dir.child("tiles"), 512).unwrap();
+    assert_same_tiles(&loaded_tiles, &tiles);
+    loaded_tiles.to_symbols(&specs).unwrap().save_to_dir(temp_dir.child("symbols")).unwrap();
+    let symbol_dir_file_names = file_names(temp_dir.child("symbols"));
+    for file_name in ["001.png", "002-004.png", "005.png", "010-011.png", "018.png", "019.png"] {
+        assert!(symbol_dir_file_names.iter().any(|name| name == file_name), "{file_name} is missing");
+    }
+    assert_eq!(symbol_dir_file_names.len(), TILE_COUNT - 2 - 1);
+    let symbol_tiles = load_symbols_from_dir(temp_dir.child("symbols"), 512).unwrap().into_tiles_vec();
+    assert_same_tiles(&symbol_tiles, &tiles);
+
+    // symdir -> tiledir -> symdir
+    symbol_tiles.save_tiles_to_dir(temp_dir.child("tiles_2")).unwrap();
+    let loaded_tiles = load_tiles_from_dir(temp_dir.child("tiles_2"), 512).unwrap();
+    loaded_tiles.to_symbols(&specs).unwrap().save_to_dir(temp_dir.child("symbols_2")).unwrap();
+    assert_eq!(file_names(temp_dir.child("symbols_2")), symbol_dir_file_names);
+    for file_name in &symbol_dir_file_names {
+        assert_eq!(fs::read(temp_dir.child("symbols").join(file_name)).unwrap(), fs::read(temp_dir.child("symbols_2").join(file_name)).unwrap());
+    }
+
+    // the symbols are loaded up to a tile count, not a symbol count
+    assert_eq!(load_symbols_from_dir(temp_dir.child("symbols"), 12).unwrap().into_tiles_vec().len(), 12);
+}
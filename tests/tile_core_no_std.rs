@@ -0,0 +1,21 @@
+// Build check of the `tile_core` module in a `no_std` crate: any use of `std` in the module fails to compile this file
+
+#![no_std]
+
+extern crate alloc;
+// only for the test harness, under another name so that `std` paths do not resolve in the module
+extern crate std as harness_std;
+
+// lints waived in the library for its exported API apply again to a private module
+#[allow(dead_code, clippy::wrong_self_convention)]
+#[path = "../src/tile_core.rs"]
+mod tile_core;
+
+use tile_core::{pack_2bpp, unpack_2bpp, Kind, Pixel2bpp};
+
+#[test]
+fn tile_core_without_std() {
+    assert_eq!(Kind::for_raw_rgba_size_bytes(36 * 54 * 4), Some(Kind::SD));
+    let pixels = [Pixel2bpp::Black, Pixel2bpp::White];
+    assert_eq!(unpack_2bpp(&pack_2bpp(&pixels), pixels.len()), pixels);
+}
@@ -0,0 +1,6 @@
+use hd_fpv_osd_font_tool::stable::{Tile, TileKind};
+
+fn main() {
+    let tile = Tile::new(TileKind::SD);
+    let _ = tile.0;
+}
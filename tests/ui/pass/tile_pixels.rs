@@ -0,0 +1,22 @@
+use hd_fpv_osd_font_tool::stable::{self, Error, Tile, TileKind};
+
+fn opaque_copy(tile: &Tile) -> Result<Tile, Error> {
+    let bytes = tile.rgba_bytes().chunks(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2], u8::MAX]).collect();
+    Tile::from_rgba_bytes(bytes)
+}
+
+// only compiled, there is no font to load when the case is run
+#[allow(dead_code)]
+fn make_opaque(from: &str, to: &str) -> Result<(), Error> {
+    let tiles = stable::load_bin_file(from)?;
+    let opaque_tiles = tiles.iter().map(opaque_copy).collect::<Result<Vec<_>, _>>()?;
+    stable::save_grid_image(&opaque_tiles, to)
+}
+
+fn main() {
+    let tile = Tile::new(TileKind::SD);
+    let opaque_tile = opaque_copy(&tile).unwrap();
+    assert!(tile.is_empty() && ! opaque_tile.is_empty());
+    assert_eq!((opaque_tile.kind(), opaque_tile.width(), opaque_tile.height()), (tile.kind(), tile.width(), tile.height()));
+    assert_eq!(tile.clone(), tile);
+}